@@ -2,7 +2,11 @@
 
 use std::convert::TryFrom;
 use std::fmt::Write;
-use std::path::Path;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::LazyLock;
 use std::time::{
     Duration,
     Instant,
@@ -47,6 +51,131 @@ const SYNC_START_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] = [b'\x1b', b'P', b'=
 /// Start of the DCS sequence for terminating synchronized updates.
 const SYNC_END_ESCAPE_START: [u8; SYNC_ESCAPE_START_LEN] = [b'\x1b', b'P', b'=', b'2', b's'];
 
+/// Parse the semantic prompt mark of an `OSC 133` or `OSC 633` sequence, e.g. `D;1`.
+fn parse_prompt_mark(mark: &[u8], args: &[&[u8]]) -> Option<PromptMark> {
+    match mark {
+        b"A" => Some(PromptMark::PromptStart),
+        b"B" => Some(PromptMark::CommandStart),
+        b"C" => Some(PromptMark::CommandExecuted),
+        b"D" => {
+            let exit_code = args
+                .first()
+                .and_then(|code| str::from_utf8(code).ok())
+                .and_then(|code| code.parse().ok());
+            Some(PromptMark::CommandFinished(exit_code))
+        },
+        _ => None,
+    }
+}
+
+/// Unescape a value of an `OSC 633` sequence, in which `\\` and `\xAB` escapes stand for
+/// backslashes and bytes such as `;`.
+fn unescape_osc_633(value: &[u8]) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut i = 0;
+    while i < value.len() {
+        match value[i..] {
+            [b'\\', b'\\', ..] => {
+                bytes.push(b'\\');
+                i += 2;
+            },
+            [b'\\', b'x', high, low, ..] => {
+                let byte = str::from_utf8(&[high, low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        bytes.push(byte);
+                        i += 4;
+                    },
+                    None => {
+                        bytes.push(b'\\');
+                        i += 1;
+                    },
+                }
+            },
+            _ => {
+                bytes.push(value[i]);
+                i += 1;
+            },
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Name of the local host, to tell local `OSC 7` reports from those of remote shells.
+static HOSTNAME: LazyLock<Option<String>> = LazyLock::new(local_hostname);
+
+#[cfg(unix)]
+fn local_hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: The buffer is valid for `buf.len()` bytes.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    String::from_utf8(buf[..len].to_vec()).ok()
+}
+
+#[cfg(not(unix))]
+fn local_hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+/// Whether the host of a `file://` URL refers to this machine.
+///
+/// Names match if they are equal or either is the short name of the other, as shells
+/// may report the fully qualified name.
+fn is_local_host(host: &str, local: Option<&str>) -> bool {
+    let short = |name: &str| name.split('.').next().unwrap_or(name).to_owned();
+    host.is_empty()
+        || host.eq_ignore_ascii_case("localhost")
+        || local.is_some_and(|local| {
+            host.eq_ignore_ascii_case(local)
+                || ((!host.contains('.') || !local.contains('.')) && short(host).eq_ignore_ascii_case(&short(local)))
+        })
+}
+
+/// Parse the path of a `file://host/path` URL, as reported by `OSC 7`.
+///
+/// Paths on other hosts, e.g. reported by a shell over ssh, are ignored.
+fn parse_file_url(url: &[u8]) -> Option<PathBuf> {
+    let url = url.strip_prefix(b"file://")?;
+    let host_end = url.iter().position(|b| *b == b'/')?;
+    let host = str::from_utf8(&url[..host_end]).ok()?;
+    if !is_local_host(host, HOSTNAME.as_deref()) {
+        return None;
+    }
+    let path = &url[host_end..];
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let escaped = (path[i] == b'%')
+            .then(|| path.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                bytes.push(byte);
+                i += 3;
+            },
+            None => {
+                bytes.push(path[i]);
+                i += 1;
+            },
+        }
+    }
+
+    let path = String::from_utf8(bytes).ok()?;
+    // Windows paths are reported as e.g. `file:///C:/Users`
+    match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => Some(PathBuf::from(&path[1..])),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
 /// Parse colors in XParseColor format.
 fn xparse_color(color: &[u8]) -> Option<Rgb> {
     if !color.is_empty() && color[0] == b'#' {
@@ -524,6 +653,12 @@ pub trait Handler {
     /// Fig OSCUnlock OSC
     fn osc_unlock(&mut self, _: &str) {}
 
+    /// Semantic prompt mark (`OSC 133` and `OSC 633`)
+    fn prompt_mark(&mut self, _: PromptMark) {}
+
+    /// Command line about to be executed (`OSC 633 ; E`)
+    fn command_line(&mut self, _: &str) {}
+
     /// Unhandled `execute` fallthrough
     fn unhandled_execute(&mut self, _byte: u8) -> HandledStatus {
         HandledStatus::Unhandled
@@ -737,6 +872,19 @@ pub enum TabulationClearMode {
     All,
 }
 
+/// Semantic prompt marks of the FinalTerm (`OSC 133`) and VS Code (`OSC 633`) shell integrations.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PromptMark {
+    /// Start of the prompt.
+    PromptStart,
+    /// End of the prompt and start of the command line.
+    CommandStart,
+    /// Command line accepted and start of the command output.
+    CommandExecuted,
+    /// End of the command output, with the exit code of the command if reported.
+    CommandFinished(Option<i32>),
+}
+
 /// Standard colors.
 ///
 /// The order here matters since the enum should be castable to a `usize` for
@@ -1171,6 +1319,44 @@ where
             // Reset text cursor color.
             b"112" => self.handler.reset_color(NamedColor::Cursor as usize),
 
+            // Report the current working directory as a `file://` URL.
+            b"7" => {
+                if params.len() >= 2 {
+                    if let Some(path) = parse_file_url(&params[1..].join(&b';')) {
+                        self.handler.dir(&path);
+                        return;
+                    }
+                }
+                unhandled!();
+            },
+
+            // FinalTerm semantic prompt marks.
+            b"133" => match params.get(1).and_then(|mark| parse_prompt_mark(mark, &params[2..])) {
+                Some(mark) => self.handler.prompt_mark(mark),
+                None => unhandled!(),
+            },
+
+            // VS Code shell integration.
+            b"633" => match params.get(1).copied() {
+                Some(b"E") => match params.get(2).and_then(|command| unescape_osc_633(command)) {
+                    Some(command) => self.handler.command_line(&command),
+                    None => unhandled!(),
+                },
+                Some(b"P") => match params.get(2).and_then(|property| unescape_osc_633(property)) {
+                    Some(property) => match property.strip_prefix("Cwd=") {
+                        Some(cwd) if !cwd.is_empty() => self.handler.dir(Path::new(cwd)),
+                        // Other properties are specific to VS Code
+                        _ => (),
+                    },
+                    None => unhandled!(),
+                },
+                Some(mark) => match parse_prompt_mark(mark, &params[2..]) {
+                    Some(mark) => self.handler.prompt_mark(mark),
+                    None => unhandled!(),
+                },
+                None => unhandled!(),
+            },
+
             // feeg
             b"697" => {
                 if let Some(fig_osc) = params.get(1) {
@@ -1686,6 +1872,9 @@ mod tests {
         index: CharsetIndex,
         charset: StandardCharset,
         attr: Option<Attr>,
+        prompt_marks: Vec<PromptMark>,
+        command_line: Option<String>,
+        dir: Option<PathBuf>,
     }
 
     impl Handler for MockHandler {
@@ -1705,6 +1894,18 @@ mod tests {
         fn reset_state(&mut self) {
             *self = Self::default();
         }
+
+        fn prompt_mark(&mut self, mark: PromptMark) {
+            self.prompt_marks.push(mark);
+        }
+
+        fn command_line(&mut self, command_line: &str) {
+            self.command_line = Some(command_line.to_owned());
+        }
+
+        fn dir(&mut self, dir: &Path) {
+            self.dir = Some(dir.to_path_buf());
+        }
     }
 
     impl Default for MockHandler {
@@ -1713,6 +1914,9 @@ mod tests {
                 index: CharsetIndex::G0,
                 charset: StandardCharset::Ascii,
                 attr: None,
+                prompt_marks: Vec::new(),
+                command_line: None,
+                dir: None,
             }
         }
     }
@@ -1773,6 +1977,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_semantic_prompt_marks() {
+        static BYTES: &[u8] = b"\x1b]133;A;cl=m\x07$ \x1b]133;B\x07ls\x1b]133;C\x07\x1b]133;D;1\x07\x1b]133;D\x1b\\";
+
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in BYTES {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.prompt_marks, vec![
+            PromptMark::PromptStart,
+            PromptMark::CommandStart,
+            PromptMark::CommandExecuted,
+            PromptMark::CommandFinished(Some(1)),
+            PromptMark::CommandFinished(None),
+        ]);
+    }
+
+    #[test]
+    fn parse_vscode_shell_integration() {
+        static BYTES: &[u8] =
+            b"\x1b]633;P;Cwd=/tmp/a\\x3bb\x07\x1b]633;E;echo a\\x3b echo \\\\n;nonce\x07\x1b]633;C\x07\x1b]633;D;0\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in BYTES {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.dir, Some(PathBuf::from("/tmp/a;b")));
+        assert_eq!(handler.command_line.as_deref(), Some("echo a; echo \\n"));
+        assert_eq!(handler.prompt_marks, vec![
+            PromptMark::CommandExecuted,
+            PromptMark::CommandFinished(Some(0))
+        ]);
+    }
+
+    #[test]
+    fn parse_cwd_report() {
+        static BYTES: &[u8] = b"\x1b]7;file://localhost/home/user/My%20Files\x07";
+
+        let mut parser = Processor::new();
+        let mut handler = MockHandler::default();
+
+        for byte in BYTES {
+            parser.advance(&mut handler, *byte);
+        }
+
+        assert_eq!(handler.dir, Some(PathBuf::from("/home/user/My Files")));
+        assert_eq!(parse_file_url(b"file:///C:/Users"), Some(PathBuf::from("C:/Users")));
+        assert_eq!(parse_file_url(b"file://host"), None);
+        assert_eq!(parse_file_url(b"/home/user"), None);
+        assert_eq!(parse_file_url(b"file://remote.example.com/home/user"), None);
+    }
+
+    #[test]
+    fn local_host_names() {
+        assert!(is_local_host("", None));
+        assert!(is_local_host("LocalHost", None));
+        assert!(is_local_host("laptop", Some("laptop")));
+        assert!(is_local_host("laptop.example.com", Some("laptop")));
+        assert!(is_local_host("laptop", Some("laptop.example.com")));
+        assert!(!is_local_host("laptop", None));
+        assert!(!is_local_host("server", Some("laptop")));
+        assert!(!is_local_host("laptop.other.com", Some("laptop.example.com")));
+    }
+
     #[test]
     fn parse_designate_g0_as_line_drawing() {
         static BYTES: &[u8] = &[0x1b, b'(', b'0'];
@@ -37,6 +37,7 @@ use crate::ansi::{
     Color,
     Handler,
     NamedColor,
+    PromptMark,
     StandardCharset,
 };
 use crate::event::{
@@ -72,6 +73,9 @@ use crate::term::color::{
 pub mod cell;
 pub mod color;
 
+/// Maximum number of lines of output kept for a command.
+const COMMAND_OUTPUT_MAX_LINES: usize = 1000;
+
 /// Minimum number of columns.
 ///
 /// A minimum of 2 is necessary to hold fullwidth unicode characters.
@@ -169,6 +173,13 @@ pub struct CommandInfo {
     pub end_time: Option<SystemTime>,
     pub username: Option<String>,
     pub exit_code: Option<i32>,
+    /// Output of the command as written to the screen, without escape sequences
+    #[serde(default)]
+    pub output: Option<String>,
+    /// If the beginning of the output was dropped, because it was too long or scrolled out of
+    /// the history
+    #[serde(default)]
+    pub output_truncated: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -203,6 +214,8 @@ pub struct ShellState {
     pub preexec: bool,
     /// Position of start of cmd
     pub cmd_cursor: Option<Point>,
+    /// Position of start of the output of the running command
+    pub output_cursor: Option<Point>,
     /// Fish suggestion color
     pub fish_suggestion_color: Option<shell_color::SuggestionColor>,
     /// Zsh autosuggestion color
@@ -219,6 +232,12 @@ pub struct ShellState {
     pub fig_log_level: Option<String>,
     /// OSC Lock
     pub osc_lock: bool,
+    /// If the Fig shell integration marked the current prompt, in which case the standard semantic
+    /// prompt marks are ignored as they would duplicate the Fig ones, unless a nested shell prompts
+    pub fig_integration: bool,
+    /// Command line reported by the shell before executing it, which takes precedence over the
+    /// one read from the screen
+    pub command_line: Option<String>,
 }
 
 impl ShellState {
@@ -509,6 +528,9 @@ impl<T> Term<T> {
         if let Some(ref mut cursor) = self.shell_state.cmd_cursor {
             cursor.line += lines as i32;
         }
+        if let Some(ref mut cursor) = self.shell_state.output_cursor {
+            cursor.line += lines as i32;
+        }
 
         lines = min(lines, (self.scroll_region.end - self.scroll_region.start).0 as usize);
         lines = min(lines, (self.scroll_region.end - origin).0 as usize);
@@ -530,6 +552,9 @@ impl<T> Term<T> {
         if let Some(ref mut cursor) = self.shell_state.cmd_cursor {
             cursor.line -= lines as i32;
         }
+        if let Some(ref mut cursor) = self.shell_state.output_cursor {
+            cursor.line -= lines as i32;
+        }
 
         lines = min(lines, (self.scroll_region.end - self.scroll_region.start).0 as usize);
 
@@ -848,10 +873,12 @@ impl<T> Term<T> {
         }
 
         self.shell_state.preexec = false;
+        self.shell_state.command_line = None;
 
         self.event_proxy.send_event(Event::Prompt, &self.shell_state);
         trace!("Prompt event sent");
 
+        self.capture_command_output();
         if let Some(command) = self.shell_state.command_info.take() {
             self.event_proxy
                 .send_event(Event::CommandInfo(&command), &self.shell_state);
@@ -859,6 +886,61 @@ impl<T> Term<T> {
         }
     }
 
+    /// Read the output of the running command from the screen into its [`CommandInfo`], once the
+    /// command finished
+    fn capture_command_output(&mut self) {
+        let Some(start) = self.shell_state.output_cursor.take() else {
+            return;
+        };
+        // The output of full screen applications isn't kept on the primary screen
+        if self.shell_state.command_info.is_none() || self.mode.contains(TermMode::ALT_SCREEN) {
+            return;
+        }
+
+        let end = self.grid.cursor.point;
+        let first_line = max(
+            max(start.line, self.topmost_line()),
+            end.line - (COMMAND_OUTPUT_MAX_LINES as i32 - 1),
+        );
+        let truncated = first_line > start.line;
+
+        let mut output = String::new();
+        let mut line = first_line;
+        while line <= end.line {
+            let row = &self.grid[line];
+            let start_column = if line == start.line { start.column.0 } else { 0 };
+            let end_column = if line == end.line { end.column.0 } else { self.columns() };
+
+            let mut text = String::new();
+            for column in start_column..end_column.min(self.columns()) {
+                let cell = &row[Column(column)];
+                if cell
+                    .flags
+                    .intersects(ShellFlags::WIDE_CHAR_SPACER | ShellFlags::LEADING_WIDE_CHAR_SPACER)
+                {
+                    continue;
+                }
+                text.push(if cell.c == '\0' { ' ' } else { cell.c });
+                if let Some(zerowidth) = cell.zerowidth() {
+                    text.extend(zerowidth);
+                }
+            }
+            output.push_str(text.trim_end());
+
+            // Lines wrapped by the terminal are a single line of output
+            let wrapped = row[self.last_column()].flags.contains(ShellFlags::WRAPLINE);
+            if !wrapped && line < end.line {
+                output.push('\n');
+            }
+            line += 1;
+        }
+
+        if let Some(command) = &mut self.shell_state.command_info {
+            command.output = Some(output.trim_end().to_owned());
+            command.output_truncated = truncated;
+        }
+    }
+
     pub fn get_delayed_events_count(&self) -> usize {
         self.delayed_events.len()
     }
@@ -1464,6 +1546,7 @@ impl<T: EventListener> Handler for Term<T> {
                         Some(ref color) => Some(color),
                         None => self.shell_state().zsh_autosuggestion_color.as_ref(),
                     }),
+                    Some("bash") => Some(self.shell_state().fig_autosuggestion_color.as_ref()),
                     Some("nu") => Some(self.shell_state().nu_hint_color.as_ref()),
                     _ => None,
                 }
@@ -1723,6 +1806,7 @@ impl<T: EventListener> Handler for Term<T> {
 
     #[inline]
     fn new_cmd(&mut self, session_id: &str) {
+        self.shell_state.fig_integration = true;
         self.new_cmd_internal(false, Some(session_id));
     }
 
@@ -1733,6 +1817,7 @@ impl<T: EventListener> Handler for Term<T> {
         }
         trace!("Fig start prompt");
         self.shell_state.has_seen_prompt = true;
+        self.capture_command_output();
 
         self.grid.cursor.template.fig_flags.insert(FigFlags::IN_PROMPT);
     }
@@ -1755,7 +1840,10 @@ impl<T: EventListener> Handler for Term<T> {
         self.event_proxy.send_event(Event::PreExec, &self.shell_state);
         trace!("PreExec event sent");
 
-        let buffer = self.get_current_buffer().map(|b| b.buffer.trim().to_owned());
+        let buffer = match self.shell_state.command_line.take() {
+            Some(command_line) => Some(command_line),
+            None => self.get_current_buffer().map(|b| b.buffer.trim().to_owned()),
+        };
 
         let context = self.shell_state.get_context();
         self.shell_state.command_info = Some(CommandInfo {
@@ -1768,7 +1856,10 @@ impl<T: EventListener> Handler for Term<T> {
             username: context.username.clone(),
             exit_code: None,
             end_time: None,
+            output: None,
+            output_truncated: false,
         });
+        self.shell_state.output_cursor = Some(self.grid.cursor.point);
     }
 
     #[inline]
@@ -1806,6 +1897,7 @@ impl<T: EventListener> Handler for Term<T> {
             return;
         }
         trace!("Fig exit code: {exit_code}");
+        self.capture_command_output();
         if let Some(command) = &mut self.shell_state.command_info {
             command.exit_code = Some(exit_code);
             command.end_time = Some(std::time::SystemTime::now());
@@ -1947,6 +2039,54 @@ impl<T: EventListener> Handler for Term<T> {
 
         self.shell_state.osc_lock = false;
     }
+
+    #[inline]
+    fn prompt_mark(&mut self, mark: PromptMark) {
+        if self.shell_state.osc_lock {
+            return;
+        }
+        if self.shell_state.fig_integration {
+            // A prompt while a command of the Fig integrated shell executes is drawn by a nested
+            // shell, e.g. one started by `sudo -s` or `ssh`, whose marks are followed until the
+            // outer shell takes over again with its next `NewCmd`
+            if mark != PromptMark::PromptStart || !self.shell_state.preexec {
+                return;
+            }
+            trace!("Prompt mark from a nested shell");
+            self.shell_state.fig_integration = false;
+        }
+        trace!("Prompt mark: {mark:?}");
+        match mark {
+            PromptMark::PromptStart => self.start_prompt(),
+            PromptMark::CommandStart => {
+                self.end_prompt();
+                self.new_cmd_internal(false, None);
+            },
+            PromptMark::CommandExecuted => self.pre_exec(),
+            PromptMark::CommandFinished(Some(exit_code)) => self.exit_code(exit_code),
+            PromptMark::CommandFinished(None) => {
+                self.capture_command_output();
+                if let Some(command) = &mut self.shell_state.command_info {
+                    command.end_time = Some(std::time::SystemTime::now());
+                }
+            },
+        }
+    }
+
+    #[inline]
+    fn command_line(&mut self, command_line: &str) {
+        if self.shell_state.osc_lock {
+            return;
+        }
+        let command_line = command_line.trim().to_owned();
+        trace!("Command line: {command_line:?}");
+
+        // Some shells report the command line once it's already executing
+        match &mut self.shell_state.command_info {
+            Some(command) if self.shell_state.preexec => command.command = Some(command_line),
+            _ => self.shell_state.command_line = Some(command_line),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -2335,4 +2475,109 @@ mod tests {
         assert_eq!(term.history_size(), 15);
         assert_eq!(term.grid.cursor.point, Point::new(Line(4), Column(0)));
     }
+
+    /// Listener keeping the info of the commands that finished
+    #[derive(Default)]
+    struct CommandListener(std::cell::RefCell<Vec<CommandInfo>>);
+
+    impl EventListener for CommandListener {
+        fn send_event(&self, event: Event<'_>, _shell_state: &ShellState) {
+            if let Event::CommandInfo(command) = event {
+                self.0.borrow_mut().push(command.clone());
+            }
+        }
+    }
+
+    fn advance<T: EventListener>(term: &mut Term<T>, bytes: &[u8]) {
+        let mut parser = ansi::Processor::new();
+        for byte in bytes {
+            parser.advance(term, *byte);
+        }
+    }
+
+    #[test]
+    fn semantic_prompt_marks_track_commands() {
+        let size = SizeInfo::new(20, 10);
+        let mut term = Term::new_test(size, CommandListener::default(), 10_000);
+
+        advance(&mut term, b"\x1b]133;A\x07$ \x1b]133;B\x07");
+        assert!(term.shell_state().has_seen_prompt);
+        assert_eq!(term.shell_state().cmd_cursor, Some(Point::new(Line(0), Column(2))));
+
+        advance(&mut term, b"ls -a\r\n\x1b]133;C\x07");
+        assert!(term.shell_state().preexec);
+        advance(&mut term, b"file\r\n\x1b]133;D;2\x07\x1b]133;A\x07$ \x1b]133;B\x07");
+        assert!(!term.shell_state().preexec);
+
+        // The command line reported by the shell is preferred over the one on the screen
+        advance(
+            &mut term,
+            b"\x1b]633;E;git log\x07\x1b]633;C\x07\x1b]633;D;0\x07\x1b]633;B\x07",
+        );
+
+        let commands = term.event_proxy.0.borrow();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].command.as_deref(), Some("ls -a"));
+        assert_eq!(commands[0].exit_code, Some(2));
+        assert!(commands[0].end_time.is_some());
+        assert_eq!(commands[0].output.as_deref(), Some("file"));
+        assert_eq!(commands[1].command.as_deref(), Some("git log"));
+        assert_eq!(commands[1].exit_code, Some(0));
+        assert_eq!(commands[1].output.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn command_output_scrolled_into_history() {
+        let size = SizeInfo::new(10, 4);
+        let mut term = Term::new_test(size, CommandListener::default(), 10_000);
+
+        advance(&mut term, b"\x1b]133;A\x07$ \x1b]133;B\x07make\r\n\x1b]133;C\x07");
+        for i in 0..8 {
+            advance(&mut term, format!("line {i}\r\n").as_bytes());
+        }
+        // Lines longer than the screen are wrapped by the terminal
+        advance(
+            &mut term,
+            b"error: a long line\r\n\x1b]133;D;1\x07\x1b]133;A\x07$ \x1b]133;B\x07",
+        );
+
+        let commands = term.event_proxy.0.borrow();
+        let mut expected = (0..8).map(|i| format!("line {i}\n")).collect::<String>();
+        expected.push_str("error: a long line");
+        assert_eq!(commands[0].output, Some(expected));
+        assert!(!commands[0].output_truncated);
+    }
+
+    #[test]
+    fn semantic_prompt_marks_ignored_with_fig_integration() {
+        let size = SizeInfo::new(20, 10);
+        let mut term = Term::new_test(size, VoidListener, 10_000);
+
+        advance(&mut term, b"$ \x1b]697;NewCmd\x07\x1b]133;C\x07");
+        assert!(term.shell_state().fig_integration);
+        assert!(!term.shell_state().preexec);
+
+        advance(&mut term, b"\x1b]697;PreExec\x07");
+        assert!(term.shell_state().preexec);
+    }
+
+    #[test]
+    fn semantic_prompt_marks_of_nested_shell() {
+        let size = SizeInfo::new(20, 10);
+        let mut term = Term::new_test(size, VoidListener, 10_000);
+
+        // The outer shell runs e.g. `sudo -s`, whose shell only emits semantic prompt marks
+        advance(&mut term, b"$ \x1b]697;NewCmd\x07\x1b]697;PreExec\x07\r\n");
+        advance(&mut term, b"\x1b]133;A\x07# \x1b]133;B\x07");
+        assert!(!term.shell_state().fig_integration);
+        assert!(!term.shell_state().preexec);
+
+        advance(&mut term, b"ls\r\n\x1b]133;C\x07");
+        assert!(term.shell_state().preexec);
+
+        // The outer shell prompts again once the nested one exited
+        advance(&mut term, b"\x1b]133;D;0\x07\x1b]697;NewCmd\x07$ ");
+        assert!(term.shell_state().fig_integration);
+        assert!(!term.shell_state().preexec);
+    }
 }
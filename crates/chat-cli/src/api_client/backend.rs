@@ -0,0 +1,72 @@
+//! Chat backends other than the Q Developer streaming API, configured per profile.
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::api_client::ApiClientError;
+use crate::api_client::model::ConversationState;
+use crate::api_client::openai::{
+    OpenAiClient,
+    OpenAiConfig,
+};
+use crate::api_client::send_message_output::SendMessageOutput;
+use crate::database::Secret;
+
+/// The `model_backend` of a profile's context.json, e.g.
+///
+/// ```json
+/// {
+///   "model_backend": {
+///     "type": "openai_compatible",
+///     "base_url": "http://localhost:8000/v1",
+///     "models": ["qwen2.5-coder-32b-instruct"]
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ModelBackendConfig {
+    /// A server implementing the OpenAI chat completions API.
+    OpenaiCompatible(OpenAiConfig),
+}
+
+impl ModelBackendConfig {
+    pub fn models(&self) -> &[String] {
+        match self {
+            Self::OpenaiCompatible(config) => &config.models,
+        }
+    }
+}
+
+/// Key in the secret store of the API key sent to the model backend of `profile`.
+pub fn api_key_secret_key(profile: &str) -> String {
+    format!("chat:model-backend:{profile}:api-key")
+}
+
+#[derive(Debug, Clone)]
+pub enum ModelBackend {
+    OpenAi(OpenAiClient),
+}
+
+impl ModelBackend {
+    #[allow(clippy::result_large_err)]
+    pub fn new(config: &ModelBackendConfig, api_key: Option<Secret>) -> Result<Self, ApiClientError> {
+        Ok(match config {
+            ModelBackendConfig::OpenaiCompatible(config) => Self::OpenAi(OpenAiClient::new(config, api_key)?),
+        })
+    }
+
+    pub fn models(&self) -> &[String] {
+        match self {
+            Self::OpenAi(client) => client.models(),
+        }
+    }
+
+    pub async fn send_message(&self, conversation: ConversationState) -> Result<SendMessageOutput, ApiClientError> {
+        match self {
+            Self::OpenAi(client) => Ok(SendMessageOutput::OpenAi(client.send_message(conversation).await?)),
+        }
+    }
+}
@@ -0,0 +1,634 @@
+//! Record and replay of chat requests against the streaming API.
+//!
+//! A cassette is a JSON file holding every [ConversationState] sent through
+//! [ApiClient::send_message](super::ApiClient::send_message) together with the full
+//! [ChatResponseStream] event sequence (or error) that came back. Recording is enabled with
+//! [RECORD_ENV_VAR], replay with [REPLAY_ENV_VAR]. During replay every request is compared against
+//! the recorded one, so tests can assert the exact request shape of a chat session offline. A
+//! replayed chat session reports mismatched requests and interactions that were never requested
+//! when it ends, and exits with a failure.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::Value;
+use tracing::error;
+
+use crate::api_client::ApiClientError;
+use crate::api_client::model::{
+    ChatResponseStream,
+    ConversationState,
+};
+use crate::api_client::send_message_output::SendMessageOutput;
+use crate::os::Fs;
+
+/// Path of a cassette to record every chat request and response to.
+pub const RECORD_ENV_VAR: &str = "Q_CHAT_CASSETTE_RECORD";
+/// Path of a cassette to serve chat responses from instead of calling the backend.
+pub const REPLAY_ENV_VAR: &str = "Q_CHAT_CASSETTE_REPLAY";
+
+const CASSETTE_VERSION: u32 = 1;
+
+/// Fields that differ between otherwise identical runs and are left out of the recorded request.
+const VOLATILE_FIELDS: &[&str] = &["conversation_id", "message_id", "env_state", "git_state"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cassette {
+    pub version: u32,
+    pub interactions: Vec<Interaction>,
+}
+
+impl Default for Cassette {
+    fn default() -> Self {
+        Self {
+            version: CASSETTE_VERSION,
+            interactions: Vec::new(),
+        }
+    }
+}
+
+impl Cassette {
+    pub async fn load(fs: &Fs, path: impl Into<PathBuf>) -> Result<Self, ApiClientError> {
+        let path = path.into();
+        let contents = fs
+            .read_to_string(&path)
+            .await
+            .map_err(|err| ApiClientError::Cassette(format!("failed to read {}: {err}", path.display())))?;
+        let cassette: Self = serde_json::from_str(&contents)
+            .map_err(|err| ApiClientError::Cassette(format!("failed to parse {}: {err}", path.display())))?;
+
+        if cassette.version != CASSETTE_VERSION {
+            return Err(ApiClientError::Cassette(format!(
+                "unsupported cassette version {} in {}",
+                cassette.version,
+                path.display()
+            )));
+        }
+
+        Ok(cassette)
+    }
+}
+
+/// A single request and the response it received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    /// The request as returned by [request_shape].
+    pub request: Value,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecordedResponse {
+    /// The request succeeded and streamed `events`, optionally failing part way through.
+    Stream {
+        events: Vec<ChatResponseStream>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<RecordedError>,
+    },
+    /// The request itself failed.
+    Error { error: RecordedError },
+}
+
+/// The subset of [ApiClientError] that can be written to a cassette.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecordedError {
+    QuotaBreach {
+        status_code: Option<u16>,
+    },
+    MonthlyLimitReached {
+        status_code: Option<u16>,
+    },
+    ContextWindowOverflow {
+        status_code: Option<u16>,
+    },
+    ModelOverloaded {
+        request_id: Option<String>,
+        status_code: Option<u16>,
+    },
+    Other {
+        message: String,
+        status_code: Option<u16>,
+    },
+    /// The response was not fully received while recording, e.g. because the user pressed Ctrl+C.
+    Interrupted,
+}
+
+impl From<&ApiClientError> for RecordedError {
+    fn from(value: &ApiClientError) -> Self {
+        match value {
+            ApiClientError::QuotaBreach { status_code, .. } => Self::QuotaBreach {
+                status_code: *status_code,
+            },
+            ApiClientError::MonthlyLimitReached { status_code } => Self::MonthlyLimitReached {
+                status_code: *status_code,
+            },
+            ApiClientError::ContextWindowOverflow { status_code } => Self::ContextWindowOverflow {
+                status_code: *status_code,
+            },
+            ApiClientError::ModelOverloadedError {
+                request_id,
+                status_code,
+            } => Self::ModelOverloaded {
+                request_id: request_id.clone(),
+                status_code: *status_code,
+            },
+            other => Self::Other {
+                message: other.to_string(),
+                status_code: other.status_code(),
+            },
+        }
+    }
+}
+
+impl From<RecordedError> for ApiClientError {
+    fn from(value: RecordedError) -> Self {
+        match value {
+            RecordedError::QuotaBreach { status_code } => Self::QuotaBreach {
+                message: "quota has reached its limit",
+                status_code,
+            },
+            RecordedError::MonthlyLimitReached { status_code } => Self::MonthlyLimitReached { status_code },
+            RecordedError::ContextWindowOverflow { status_code } => Self::ContextWindowOverflow { status_code },
+            RecordedError::ModelOverloaded {
+                request_id,
+                status_code,
+            } => Self::ModelOverloadedError {
+                request_id,
+                status_code,
+            },
+            RecordedError::Other { message, status_code } => Self::Replayed { message, status_code },
+            RecordedError::Interrupted => Self::Replayed {
+                message: "the response was interrupted while recording".to_string(),
+                status_code: None,
+            },
+        }
+    }
+}
+
+/// Returns the JSON form of `conversation` that is recorded and compared during replay, with
+/// [VOLATILE_FIELDS] removed and tool specifications sorted.
+pub fn request_shape(conversation: &ConversationState) -> Value {
+    let mut value = serde_json::to_value(conversation).unwrap_or_default();
+    normalize(&mut value);
+    value
+}
+
+fn normalize(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for field in VOLATILE_FIELDS {
+                map.remove(*field);
+            }
+            // Tools are collected from a map, so their order is not stable between runs.
+            if let Some(Value::Array(tools)) = map.get_mut("tools") {
+                tools.sort_by_cached_key(|tool| tool.to_string());
+            }
+            map.values_mut().for_each(normalize);
+        },
+        Value::Array(values) => values.iter_mut().for_each(normalize),
+        _ => (),
+    }
+}
+
+/// Describes the first place where `actual` differs from `expected`, if any.
+fn first_difference(expected: &Value, actual: &Value, path: &str) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected_value) in expected {
+                let path = format!("{path}/{key}");
+                match actual.get(key) {
+                    Some(actual_value) => {
+                        if let Some(diff) = first_difference(expected_value, actual_value, &path) {
+                            return Some(diff);
+                        }
+                    },
+                    None => return Some(format!("{path}: missing, expected {expected_value}")),
+                }
+            }
+            actual
+                .keys()
+                .find(|key| !expected.contains_key(*key))
+                .map(|key| format!("{path}/{key}: unexpected field"))
+        },
+        (Value::Array(expected), Value::Array(actual)) => {
+            if expected.len() != actual.len() {
+                return Some(format!(
+                    "{path}: expected {} elements, got {}",
+                    expected.len(),
+                    actual.len()
+                ));
+            }
+            expected
+                .iter()
+                .zip(actual)
+                .enumerate()
+                .find_map(|(i, (expected, actual))| first_difference(expected, actual, &format!("{path}/{i}")))
+        },
+        (expected, actual) if expected == actual => None,
+        (expected, actual) => Some(format!("{path}: expected {expected}, got {actual}")),
+    }
+}
+
+/// Appends every interaction to a cassette file, rewriting it after each request and each
+/// response that completes.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    fs: Fs,
+    path: PathBuf,
+    cassette: Arc<Mutex<Cassette>>,
+}
+
+impl Recorder {
+    pub fn new(fs: Fs, path: impl Into<PathBuf>) -> Self {
+        Self {
+            fs,
+            path: path.into(),
+            cassette: Arc::new(Mutex::new(Cassette::default())),
+        }
+    }
+
+    /// Writes `request` to the cassette and wraps the result of sending it, such that the
+    /// response is recorded as it is received.
+    ///
+    /// The request is written right away, so the cassette stays aligned with the requests of the
+    /// session even if the response is never fully received, e.g. because the user pressed Ctrl+C.
+    /// Such responses are recorded with the events received so far and
+    /// [RecordedError::Interrupted].
+    pub async fn record(
+        &self,
+        request: Value,
+        result: Result<SendMessageOutput, ApiClientError>,
+    ) -> Result<SendMessageOutput, ApiClientError> {
+        match result {
+            Ok(output) => {
+                let index = self
+                    .push(Interaction {
+                        request,
+                        response: RecordedResponse::Stream {
+                            events: Vec::new(),
+                            error: Some(RecordedError::Interrupted),
+                        },
+                    })
+                    .await;
+                Ok(SendMessageOutput::Recording(Box::new(RecordingOutput {
+                    inner: output,
+                    recorder: self.clone(),
+                    index: Some(index),
+                })))
+            },
+            Err(err) => {
+                self.push(Interaction {
+                    request,
+                    response: RecordedResponse::Error { error: (&err).into() },
+                })
+                .await;
+                Err(err)
+            },
+        }
+    }
+
+    /// Appends `interaction` and writes the cassette, returning the index of the interaction.
+    async fn push(&self, interaction: Interaction) -> usize {
+        let index = {
+            let mut cassette = self.cassette.lock();
+            cassette.interactions.push(interaction);
+            cassette.interactions.len() - 1
+        };
+        self.write().await;
+        index
+    }
+
+    /// Adds `event` to the response of the interaction at `index`, which is written along with the
+    /// next change of the cassette.
+    fn push_event(&self, index: usize, event: ChatResponseStream) {
+        if let Some(Interaction {
+            response: RecordedResponse::Stream { events, .. },
+            ..
+        }) = self.cassette.lock().interactions.get_mut(index)
+        {
+            events.push(event);
+        }
+    }
+
+    /// Marks the response of the interaction at `index` as complete and writes the cassette.
+    async fn finish(&self, index: usize, result: Option<RecordedError>) {
+        if let Some(Interaction {
+            response: RecordedResponse::Stream { error, .. },
+            ..
+        }) = self.cassette.lock().interactions.get_mut(index)
+        {
+            *error = result;
+        }
+        self.write().await;
+    }
+
+    async fn write(&self) {
+        let contents = serde_json::to_string_pretty(&*self.cassette.lock());
+        match contents {
+            Ok(contents) => {
+                if let Err(err) = self.fs.write(&self.path, contents).await {
+                    error!(?err, path = ?self.path, "Failed to write chat cassette");
+                }
+            },
+            Err(err) => error!(?err, "Failed to serialize chat cassette"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RecordingOutput {
+    inner: SendMessageOutput,
+    recorder: Recorder,
+    /// Index of the interaction in the cassette, taken once the response has been fully received.
+    index: Option<usize>,
+}
+
+impl RecordingOutput {
+    pub fn request_id(&self) -> Option<&str> {
+        self.inner.request_id()
+    }
+
+    pub async fn recv(&mut self) -> Result<Option<ChatResponseStream>, ApiClientError> {
+        let result = Box::pin(self.inner.recv()).await;
+        match &result {
+            Ok(Some(event)) => {
+                if let Some(index) = self.index {
+                    self.recorder.push_event(index, event.clone());
+                }
+            },
+            Ok(None) => self.finish(None).await,
+            Err(err) => self.finish(Some(err.into())).await,
+        }
+        result
+    }
+
+    async fn finish(&mut self, error: Option<RecordedError>) {
+        if let Some(index) = self.index.take() {
+            self.recorder.finish(index, error).await;
+        }
+    }
+}
+
+/// Serves the interactions of a cassette in order, checking each request against the recording.
+#[derive(Debug)]
+pub struct Replayer {
+    interactions: VecDeque<Interaction>,
+    index: usize,
+    failures: Vec<String>,
+}
+
+impl Replayer {
+    pub fn new(cassette: Cassette) -> Self {
+        Self {
+            interactions: cassette.interactions.into(),
+            index: 0,
+            failures: Vec::new(),
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn replay(&mut self, conversation: &ConversationState) -> Result<SendMessageOutput, ApiClientError> {
+        let index = self.index;
+        self.index += 1;
+
+        let Some(interaction) = self.interactions.pop_front() else {
+            let message = format!("no recorded interaction left for request {index}");
+            self.failures.push(message.clone());
+            return Err(ApiClientError::Cassette(message));
+        };
+
+        if let Some(diff) = first_difference(&interaction.request, &request_shape(conversation), "") {
+            self.failures.push(format!("request {index}: {diff}"));
+            return Err(ApiClientError::CassetteMismatch { index, diff });
+        }
+
+        match interaction.response {
+            RecordedResponse::Stream { events, error } => Ok(SendMessageOutput::Replay(ReplayOutput {
+                events: events.into(),
+                error,
+            })),
+            RecordedResponse::Error { error } => Err(error.into()),
+        }
+    }
+
+    /// Describes every request that did not match the cassette, and the recorded interactions that
+    /// were never requested.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = self.failures.clone();
+        if !self.interactions.is_empty() {
+            problems.push(format!(
+                "{} recorded chat interaction(s) were never requested",
+                self.interactions.len()
+            ));
+        }
+        problems
+    }
+
+    /// Panics if any request did not match the cassette or if recorded interactions were never
+    /// requested.
+    #[cfg(test)]
+    pub fn assert_finished(&self) {
+        let problems = self.problems();
+        assert!(
+            problems.is_empty(),
+            "chat cassette replay failed:\n{}",
+            problems.join("\n")
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct ReplayOutput {
+    events: VecDeque<ChatResponseStream>,
+    error: Option<RecordedError>,
+}
+
+impl ReplayOutput {
+    #[allow(clippy::result_large_err)]
+    pub fn recv(&mut self) -> Result<Option<ChatResponseStream>, ApiClientError> {
+        match self.events.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => match self.error.take() {
+                Some(error) => Err(error.into()),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_client::model::{
+        UserInputMessage,
+        UserInputMessageContext,
+    };
+
+    fn conversation(content: &str) -> ConversationState {
+        ConversationState {
+            conversation_id: Some(uuid::Uuid::new_v4().to_string()),
+            user_input_message: UserInputMessage {
+                content: content.to_string(),
+                user_input_message_context: Some(UserInputMessageContext {
+                    env_state: Some(Default::default()),
+                    ..Default::default()
+                }),
+                user_intent: None,
+                images: None,
+                model_id: None,
+            },
+            history: None,
+        }
+    }
+
+    #[test]
+    fn test_request_shape_strips_volatile_fields() {
+        let shape = request_shape(&conversation("hello"));
+        assert!(shape.get("conversation_id").is_none());
+        assert!(shape["user_input_message"]["user_input_message_context"]["env_state"].is_null());
+        assert_eq!(shape, request_shape(&conversation("hello")));
+    }
+
+    #[test]
+    fn test_first_difference() {
+        let expected = serde_json::json!({ "a": [1, { "b": "x" }] });
+        assert_eq!(first_difference(&expected, &expected, ""), None);
+        assert_eq!(
+            first_difference(&expected, &serde_json::json!({ "a": [1, { "b": "y" }] }), ""),
+            Some(r#"/a/1/b: expected "x", got "y""#.to_string())
+        );
+        assert_eq!(
+            first_difference(&expected, &serde_json::json!({ "a": [1] }), ""),
+            Some("/a: expected 2 elements, got 1".to_string())
+        );
+        assert_eq!(
+            first_difference(&expected, &serde_json::json!({ "a": [1, { "b": "x" }], "c": 1 }), ""),
+            Some("/c: unexpected field".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        let fs = Fs::new();
+        let recorder = Recorder::new(fs.clone(), "/cassette.json");
+
+        let events = vec![
+            ChatResponseStream::AssistantResponseEvent {
+                content: "Hello".to_string(),
+            },
+            ChatResponseStream::ToolUseEvent {
+                tool_use_id: "1".to_string(),
+                name: "fs_read".to_string(),
+                input: Some("{}".to_string()),
+                stop: Some(true),
+            },
+        ];
+        let mut reversed = events.clone();
+        reversed.reverse();
+        let mut output = recorder
+            .record(
+                request_shape(&conversation("first")),
+                Ok(SendMessageOutput::Mock(reversed)),
+            )
+            .await
+            .unwrap();
+        while output.recv().await.unwrap().is_some() {}
+
+        let err = recorder
+            .record(
+                request_shape(&conversation("second")),
+                Err(ApiClientError::ContextWindowOverflow { status_code: Some(400) }),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ApiClientError::ContextWindowOverflow { .. }));
+
+        let mut replayer = Replayer::new(Cassette::load(&fs, "/cassette.json").await.unwrap());
+        let mut output = replayer.replay(&conversation("first")).unwrap();
+        let mut replayed = Vec::new();
+        while let Some(event) = output.recv().await.unwrap() {
+            replayed.push(event);
+        }
+        assert_eq!(replayed, events);
+
+        assert!(matches!(
+            replayer.replay(&conversation("second")),
+            Err(ApiClientError::ContextWindowOverflow { status_code: Some(400) })
+        ));
+        replayer.assert_finished();
+    }
+
+    #[tokio::test]
+    async fn test_record_interrupted_response() {
+        let fs = Fs::new();
+        let recorder = Recorder::new(fs.clone(), "/cassette.json");
+
+        let event = |content: &str| ChatResponseStream::AssistantResponseEvent {
+            content: content.to_string(),
+        };
+        let mut output = recorder
+            .record(
+                request_shape(&conversation("first")),
+                Ok(SendMessageOutput::Mock(vec![event("world"), event("Hello")])),
+            )
+            .await
+            .unwrap();
+        // The request is on disk before any of the response has been received
+        assert_eq!(
+            Cassette::load(&fs, "/cassette.json").await.unwrap().interactions.len(),
+            1
+        );
+
+        // The response is dropped part way through, e.g. on Ctrl+C
+        output.recv().await.unwrap();
+        drop(output);
+
+        let mut output = recorder
+            .record(
+                request_shape(&conversation("second")),
+                Ok(SendMessageOutput::Mock(vec![event("Bye")])),
+            )
+            .await
+            .unwrap();
+        while output.recv().await.unwrap().is_some() {}
+
+        let mut replayer = Replayer::new(Cassette::load(&fs, "/cassette.json").await.unwrap());
+        let mut output = replayer.replay(&conversation("first")).unwrap();
+        assert_eq!(output.recv().await.unwrap(), Some(event("Hello")));
+        assert!(output.recv().await.is_err());
+
+        let mut output = replayer.replay(&conversation("second")).unwrap();
+        assert_eq!(output.recv().await.unwrap(), Some(event("Bye")));
+        assert_eq!(output.recv().await.unwrap(), None);
+        assert!(replayer.problems().is_empty());
+    }
+
+    #[test]
+    fn test_replay_mismatch() {
+        let mut replayer = Replayer::new(Cassette {
+            version: CASSETTE_VERSION,
+            interactions: vec![Interaction {
+                request: request_shape(&conversation("expected")),
+                response: RecordedResponse::Stream {
+                    events: vec![],
+                    error: None,
+                },
+            }],
+        });
+
+        assert!(matches!(
+            replayer.replay(&conversation("actual")),
+            Err(ApiClientError::CassetteMismatch { index: 0, .. })
+        ));
+        assert_eq!(replayer.problems().len(), 1);
+        assert!(std::panic::catch_unwind(|| replayer.assert_finished()).is_err());
+    }
+}
@@ -93,6 +93,22 @@ pub enum ApiClientError {
     // Credential errors
     #[error("failed to load credentials: {}", .0)]
     Credentials(CredentialsError),
+
+    /// Returned by a model backend configured for the profile, e.g. an OpenAI compatible server.
+    #[error("model backend error: {message}")]
+    ModelBackend { message: String, status_code: Option<u16> },
+
+    // Record and replay errors
+    #[error("chat cassette error: {0}")]
+    Cassette(String),
+
+    /// Returned during replay when a request does not match the one recorded in the cassette.
+    #[error("request {index} does not match the cassette: {diff}")]
+    CassetteMismatch { index: usize, diff: String },
+
+    /// An error recorded in a cassette that has no more specific variant.
+    #[error("{message}")]
+    Replayed { message: String, status_code: Option<u16> },
 }
 
 impl ApiClientError {
@@ -116,6 +132,10 @@ impl ApiClientError {
             Self::ModelOverloadedError { status_code, .. } => *status_code,
             Self::MonthlyLimitReached { status_code } => *status_code,
             Self::Credentials(_e) => None,
+            Self::ModelBackend { status_code, .. } => *status_code,
+            Self::Cassette(_) => None,
+            Self::CassetteMismatch { .. } => None,
+            Self::Replayed { status_code, .. } => *status_code,
         }
     }
 }
@@ -141,6 +161,10 @@ impl ReasonCode for ApiClientError {
             Self::ModelOverloadedError { .. } => "ModelOverloadedError".to_string(),
             Self::MonthlyLimitReached { .. } => "MonthlyLimitReached".to_string(),
             Self::Credentials(_) => "CredentialsError".to_string(),
+            Self::ModelBackend { .. } => "ModelBackendError".to_string(),
+            Self::Cassette(_) => "CassetteError".to_string(),
+            Self::CassetteMismatch { .. } => "CassetteMismatch".to_string(),
+            Self::Replayed { .. } => "ReplayedError".to_string(),
         }
     }
 }
@@ -213,6 +237,19 @@ mod tests {
                 raw_message(),
             )),
             ApiClientError::SmithyBuild(aws_smithy_types::error::operation::BuildError::other("<other>")),
+            ApiClientError::ModelBackend {
+                message: "<message>".into(),
+                status_code: Some(500),
+            },
+            ApiClientError::Cassette("<cassette>".into()),
+            ApiClientError::CassetteMismatch {
+                index: 0,
+                diff: "<diff>".into(),
+            },
+            ApiClientError::Replayed {
+                message: "<message>".into(),
+                status_code: Some(500),
+            },
         ]
     }
 
@@ -1,8 +1,11 @@
+pub mod backend;
+pub mod cassette;
 mod credentials;
 pub mod customization;
 mod endpoints;
 mod error;
 pub mod model;
+pub mod openai;
 mod opt_out;
 pub mod profile;
 pub mod send_message_output;
@@ -37,6 +40,12 @@ use tracing::{
     error,
 };
 
+use crate::api_client::backend::ModelBackend;
+use crate::api_client::cassette::{
+    Cassette,
+    Recorder,
+    Replayer,
+};
 use crate::api_client::credentials::CredentialsChain;
 use crate::api_client::model::{
     ChatResponseStream,
@@ -72,6 +81,10 @@ pub struct ApiClient {
     streaming_client: Option<CodewhispererStreamingClient>,
     sigv4_streaming_client: Option<QDeveloperStreamingClient>,
     mock_client: Option<Arc<Mutex<std::vec::IntoIter<Vec<ChatResponseStream>>>>>,
+    /// Backend configured by the active chat profile, used instead of the streaming clients.
+    model_backend: Arc<Mutex<Option<ModelBackend>>>,
+    recorder: Option<Recorder>,
+    replayer: Option<Arc<Mutex<Replayer>>>,
     profile: Option<AuthProfile>,
 }
 
@@ -105,12 +118,16 @@ impl ApiClient {
                 .build(),
         );
 
-        if cfg!(test) {
+        let replay_path = env.get(cassette::REPLAY_ENV_VAR).ok();
+        if cfg!(test) || replay_path.is_some() {
             let mut this = Self {
                 client,
                 streaming_client: None,
                 sigv4_streaming_client: None,
                 mock_client: None,
+                model_backend: Arc::new(Mutex::new(None)),
+                recorder: None,
+                replayer: None,
                 profile: None,
             };
 
@@ -118,6 +135,12 @@ impl ApiClient {
                 this.set_mock_output(serde_json::from_str(fs.read_to_string(json).await.unwrap().as_str()).unwrap());
             }
 
+            if let Some(path) = replay_path {
+                this.set_cassette_replay(Cassette::load(fs, path).await?);
+            } else if let Ok(path) = env.get(cassette::RECORD_ENV_VAR) {
+                this.set_cassette_record(fs, path);
+            }
+
             return Ok(this);
         }
 
@@ -173,13 +196,22 @@ impl ApiClient {
             },
         };
 
-        Ok(Self {
+        let mut this = Self {
             client,
             streaming_client,
             sigv4_streaming_client,
             mock_client: None,
+            model_backend: Arc::new(Mutex::new(None)),
+            recorder: None,
+            replayer: None,
             profile,
-        })
+        };
+
+        if let Ok(path) = env.get(cassette::RECORD_ENV_VAR) {
+            this.set_cassette_record(fs, path);
+        }
+
+        Ok(this)
     }
 
     pub async fn send_telemetry_event(
@@ -251,6 +283,29 @@ impl ApiClient {
     pub async fn send_message(&self, conversation: ConversationState) -> Result<SendMessageOutput, ApiClientError> {
         debug!("Sending conversation: {:#?}", conversation);
 
+        if let Some(replayer) = &self.replayer {
+            return replayer.lock().replay(&conversation);
+        }
+
+        match &self.recorder {
+            Some(recorder) => {
+                let request = cassette::request_shape(&conversation);
+                let result = self.send_message_to_backend(conversation).await;
+                recorder.record(request, result).await
+            },
+            None => self.send_message_to_backend(conversation).await,
+        }
+    }
+
+    async fn send_message_to_backend(
+        &self,
+        conversation: ConversationState,
+    ) -> Result<SendMessageOutput, ApiClientError> {
+        let model_backend = self.model_backend.lock().clone();
+        if let Some(model_backend) = model_backend {
+            return model_backend.send_message(conversation).await;
+        }
+
         let ConversationState {
             conversation_id,
             user_input_message,
@@ -446,6 +501,46 @@ impl ApiClient {
 
         self.mock_client = Some(Arc::new(Mutex::new(mock.into_iter())));
     }
+
+    /// Sends chat requests to `model_backend` instead of the Q Developer streaming API, or back to
+    /// the streaming API if [None]. Shared by every clone of this client.
+    pub fn set_model_backend(&self, model_backend: Option<ModelBackend>) {
+        *self.model_backend.lock() = model_backend;
+    }
+
+    pub fn model_backend(&self) -> Option<ModelBackend> {
+        self.model_backend.lock().clone()
+    }
+
+    /// Records every request made through [Self::send_message] and its response to a cassette at
+    /// `path`.
+    pub fn set_cassette_record(&mut self, fs: &Fs, path: impl Into<std::path::PathBuf>) {
+        self.recorder = Some(Recorder::new(fs.clone(), path));
+    }
+
+    /// Serves responses to [Self::send_message] from `cassette`, asserting that every request
+    /// matches the recorded one.
+    pub fn set_cassette_replay(&mut self, cassette: Cassette) {
+        self.replayer = Some(Arc::new(Mutex::new(Replayer::new(cassette))));
+    }
+
+    /// Describes the replayed requests that did not match their cassette, and the interactions of
+    /// the cassette that were never requested. Empty if no cassette is replayed.
+    pub fn cassette_problems(&self) -> Vec<String> {
+        self.replayer
+            .as_ref()
+            .map(|replayer| replayer.lock().problems())
+            .unwrap_or_default()
+    }
+
+    /// Panics if a replayed request did not match its cassette or if the cassette has interactions
+    /// that were never requested.
+    #[cfg(test)]
+    pub fn assert_cassette_finished(&self) {
+        if let Some(replayer) = &self.replayer {
+            replayer.lock().assert_finished();
+        }
+    }
 }
 
 fn timeout_config(database: &Database) -> TimeoutConfig {
@@ -92,14 +92,14 @@ pub struct Recommendation {
 // Streaming
 // =========
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ConversationState {
     pub conversation_id: Option<String>,
     pub user_input_message: UserInputMessage,
     pub history: Option<Vec<ChatMessage>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum ChatMessage {
     AssistantResponseMessage(AssistantResponseMessage),
     UserInputMessage(UserInputMessage),
@@ -193,6 +193,13 @@ impl Serialize for FigDocumentRef<'_> {
     }
 }
 
+fn serialize_aws_document<S>(document: &AwsDocument, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    FigDocumentRef(document).serialize(serializer)
+}
+
 impl Serialize for FigDocument {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -424,7 +431,7 @@ impl From<ToolUse> for amzn_qdeveloper_streaming_client::types::ToolUse {
 }
 
 /// A tool result that contains the results for a tool request that was previously made.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ToolResult {
     /// The ID for the tool request.
     pub tool_use_id: String,
@@ -456,9 +463,10 @@ impl From<ToolResult> for amzn_qdeveloper_streaming_client::types::ToolResult {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum ToolResultContentBlock {
     /// A tool result that is JSON format data.
+    #[serde(serialize_with = "serialize_aws_document")]
     Json(AwsDocument),
     /// A tool result that is text.
     Text(String),
@@ -507,7 +515,7 @@ impl From<ToolResultStatus> for amzn_qdeveloper_streaming_client::types::ToolRes
 }
 
 /// Markdown text message.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AssistantResponseMessage {
     /// Unique identifier for the chat message
     pub message_id: Option<String>,
@@ -542,7 +550,7 @@ impl TryFrom<AssistantResponseMessage> for amzn_qdeveloper_streaming_client::typ
 }
 
 #[non_exhaustive]
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChatResponseStream {
     AssistantResponseEvent {
         content: String,
@@ -742,7 +750,7 @@ impl From<EnvironmentVariable> for amzn_qdeveloper_streaming_client::types::Envi
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct GitState {
     pub status: String,
 }
@@ -853,7 +861,7 @@ impl From<ImageBlock> for amzn_qdeveloper_streaming_client::types::ImageBlock {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UserInputMessage {
     pub content: String,
     pub user_input_message_context: Option<UserInputMessageContext>,
@@ -890,7 +898,7 @@ impl From<UserInputMessage> for amzn_qdeveloper_streaming_client::types::UserInp
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct UserInputMessageContext {
     pub env_state: Option<EnvState>,
     pub git_state: Option<GitState>,
@@ -920,7 +928,7 @@ impl From<UserInputMessageContext> for amzn_qdeveloper_streaming_client::types::
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum UserIntent {
     ApplyCommonBestPractices,
 }
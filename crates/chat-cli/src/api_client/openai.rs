@@ -0,0 +1,780 @@
+//! Client for servers implementing the OpenAI chat completions API, such as vLLM or the llama.cpp
+//! server.
+//!
+//! Requests are translated from [ConversationState] and the streamed chunks are translated back
+//! into [ChatResponseStream] events, so the rest of chat is unaware of which backend it talks to.
+//!
+//! Referencing https://platform.openai.com/docs/api-reference/chat/create
+use std::collections::{
+    BTreeMap,
+    VecDeque,
+};
+use std::time::Duration;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use reqwest::header::ACCEPT;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use serde_json::{
+    Value,
+    json,
+};
+use url::Url;
+
+use crate::api_client::ApiClientError;
+use crate::api_client::model::{
+    AssistantResponseMessage,
+    ChatMessage,
+    ChatResponseStream,
+    ConversationState,
+    FigDocument,
+    ImageFormat,
+    ImageSource,
+    Tool,
+    ToolResult,
+    ToolResultContentBlock,
+    UserInputMessage,
+};
+use crate::database::Secret;
+use crate::mcp_client::transport::http::SseParser;
+
+const EVENT_STREAM_MIME: &str = "text/event-stream";
+const DONE_MARKER: &str = "[DONE]";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long to wait for the response headers and then for every chunk of the stream. Generous
+/// since local servers may take a while to process a long prompt before sending anything.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Configuration of an OpenAI compatible backend.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenAiConfig {
+    /// Base url of the API, e.g. `http://localhost:8000/v1`. Requests are sent to
+    /// `<base_url>/chat/completions`.
+    pub base_url: String,
+    /// Models served by the backend, offered by `/model`. The first one is used by default.
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenAiClient {
+    client: reqwest::Client,
+    url: Url,
+    models: Vec<String>,
+    api_key: Option<Secret>,
+    idle_timeout: Duration,
+}
+
+impl OpenAiClient {
+    #[allow(clippy::result_large_err)]
+    pub fn new(config: &OpenAiConfig, api_key: Option<Secret>) -> Result<Self, ApiClientError> {
+        if config.models.is_empty() {
+            return Err(ApiClientError::ModelBackend {
+                message: "no models are configured for the OpenAI compatible backend".to_owned(),
+                status_code: None,
+            });
+        }
+
+        let url = format!("{}/chat/completions", config.base_url.trim_end_matches('/'));
+        let url = Url::parse(&url).map_err(|err| ApiClientError::ModelBackend {
+            message: format!("invalid base url {}: {err}", config.base_url),
+            status_code: None,
+        })?;
+
+        let client = crate::request::client_builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(backend_error)?;
+
+        Ok(Self {
+            client,
+            url,
+            models: config.models.clone(),
+            api_key,
+            idle_timeout: IDLE_TIMEOUT,
+        })
+    }
+
+    pub fn models(&self) -> &[String] {
+        &self.models
+    }
+
+    pub async fn send_message(&self, conversation: ConversationState) -> Result<OpenAiOutput, ApiClientError> {
+        // Fall back to the default model for ids of the Q Developer models, e.g. from a resumed
+        // conversation.
+        let model = conversation
+            .user_input_message
+            .model_id
+            .clone()
+            .filter(|model| self.models.contains(model))
+            .unwrap_or_else(|| self.models[0].clone());
+
+        let mut request = self
+            .client
+            .post(self.url.clone())
+            .header(ACCEPT, EVENT_STREAM_MIME)
+            .json(&build_request(conversation, &model));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(&api_key.0);
+        }
+
+        let response = tokio::time::timeout(self.idle_timeout, request.send())
+            .await
+            .map_err(|_elapsed| idle_timeout_error(self.idle_timeout))?
+            .map_err(backend_error)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(status_error(status.as_u16(), body));
+        }
+
+        let request_id = response
+            .headers()
+            .get("x-request-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned);
+
+        Ok(OpenAiOutput {
+            response,
+            idle_timeout: self.idle_timeout,
+            request_id,
+            parser: SseParser::default(),
+            decoder: StreamDecoder::default(),
+        })
+    }
+}
+
+fn backend_error(err: reqwest::Error) -> ApiClientError {
+    ApiClientError::ModelBackend {
+        message: err.to_string(),
+        status_code: err.status().map(|status| status.as_u16()),
+    }
+}
+
+fn idle_timeout_error(timeout: Duration) -> ApiClientError {
+    ApiClientError::ModelBackend {
+        message: format!("the model backend did not respond within {} seconds", timeout.as_secs()),
+        status_code: None,
+    }
+}
+
+fn status_error(status_code: u16, body: String) -> ApiClientError {
+    let lowercase_body = body.to_lowercase();
+    match status_code {
+        429 => ApiClientError::QuotaBreach {
+            message: "the model backend is rate limiting requests",
+            status_code: Some(status_code),
+        },
+        400 | 413
+            if ["context length", "context window", "maximum context", "too long"]
+                .iter()
+                .any(|needle| lowercase_body.contains(needle)) =>
+        {
+            ApiClientError::ContextWindowOverflow {
+                status_code: Some(status_code),
+            }
+        },
+        _ => ApiClientError::ModelBackend {
+            message: match body.trim() {
+                "" => format!("request failed with status {status_code}"),
+                body => body.to_owned(),
+            },
+            status_code: Some(status_code),
+        },
+    }
+}
+
+/// Builds the body of a streaming chat completions request.
+fn build_request(conversation: ConversationState, model: &str) -> Value {
+    let ConversationState {
+        user_input_message,
+        history,
+        ..
+    } = conversation;
+
+    let tools = user_input_message
+        .user_input_message_context
+        .as_ref()
+        .and_then(|context| context.tools.clone())
+        .unwrap_or_default();
+
+    let mut messages = Vec::new();
+    for message in history.into_iter().flatten() {
+        match message {
+            ChatMessage::UserInputMessage(message) => push_user_message(&mut messages, message),
+            ChatMessage::AssistantResponseMessage(message) => messages.push(assistant_message(message)),
+        }
+    }
+    push_user_message(&mut messages, user_input_message);
+
+    let mut request = json!({
+        "model": model,
+        "messages": messages,
+        "stream": true,
+    });
+    if !tools.is_empty() {
+        request["tools"] = tools.into_iter().map(tool_definition).collect();
+    }
+    request
+}
+
+/// Pushes the tool results of `message` as `tool` messages, followed by the message itself unless
+/// it only carried tool results.
+fn push_user_message(messages: &mut Vec<Value>, message: UserInputMessage) {
+    let tool_results = message
+        .user_input_message_context
+        .and_then(|context| context.tool_results)
+        .unwrap_or_default();
+    let images = message.images.unwrap_or_default();
+
+    let only_tool_results = message.content.is_empty() && images.is_empty() && !tool_results.is_empty();
+    messages.extend(tool_results.into_iter().map(tool_message));
+    if only_tool_results {
+        return;
+    }
+
+    let content = if images.is_empty() {
+        Value::String(message.content)
+    } else {
+        let mut parts = vec![json!({ "type": "text", "text": message.content })];
+        for image in images {
+            let ImageSource::Bytes(bytes) = image.source else {
+                continue;
+            };
+            let mime = match image.format {
+                ImageFormat::Gif => "image/gif",
+                ImageFormat::Jpeg => "image/jpeg",
+                ImageFormat::Png => "image/png",
+                ImageFormat::Webp => "image/webp",
+            };
+            parts.push(json!({
+                "type": "image_url",
+                "image_url": { "url": format!("data:{mime};base64,{}", BASE64.encode(bytes)) },
+            }));
+        }
+        Value::Array(parts)
+    };
+
+    messages.push(json!({ "role": "user", "content": content }));
+}
+
+fn tool_message(result: ToolResult) -> Value {
+    let content = result
+        .content
+        .into_iter()
+        .map(|block| match block {
+            ToolResultContentBlock::Text(text) => text,
+            ToolResultContentBlock::Json(document) => {
+                serde_json::to_string(&FigDocument::from(document)).unwrap_or_default()
+            },
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    json!({
+        "role": "tool",
+        "tool_call_id": result.tool_use_id,
+        "content": content,
+    })
+}
+
+fn assistant_message(message: AssistantResponseMessage) -> Value {
+    let mut value = json!({ "role": "assistant", "content": message.content });
+    if let Some(tool_uses) = message.tool_uses.filter(|tool_uses| !tool_uses.is_empty()) {
+        value["tool_calls"] = tool_uses
+            .into_iter()
+            .map(|tool_use| {
+                json!({
+                    "id": tool_use.tool_use_id,
+                    "type": "function",
+                    "function": {
+                        "name": tool_use.name,
+                        "arguments": serde_json::to_string(&tool_use.input).unwrap_or_default(),
+                    },
+                })
+            })
+            .collect();
+    }
+    value
+}
+
+fn tool_definition(tool: Tool) -> Value {
+    let Tool::ToolSpecification(spec) = tool;
+    json!({
+        "type": "function",
+        "function": {
+            "name": spec.name,
+            "description": spec.description,
+            "parameters": spec
+                .input_schema
+                .json
+                .map_or_else(|| json!({ "type": "object", "properties": {} }), |schema| json!(schema)),
+        },
+    })
+}
+
+#[derive(Debug)]
+pub struct OpenAiOutput {
+    response: reqwest::Response,
+    idle_timeout: Duration,
+    request_id: Option<String>,
+    parser: SseParser,
+    decoder: StreamDecoder,
+}
+
+impl OpenAiOutput {
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub async fn recv(&mut self) -> Result<Option<ChatResponseStream>, ApiClientError> {
+        loop {
+            if let Some(event) = self.decoder.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            if self.decoder.done {
+                return Ok(None);
+            }
+
+            let chunk = tokio::time::timeout(self.idle_timeout, self.response.chunk())
+                .await
+                .map_err(|_elapsed| idle_timeout_error(self.idle_timeout))?
+                .map_err(backend_error)?;
+            match chunk {
+                Some(chunk) => {
+                    for event in self.parser.feed(&chunk) {
+                        self.decoder.decode(&event.data)?;
+                    }
+                },
+                None => self.decoder.finish(),
+            }
+        }
+    }
+}
+
+/// Translates the `data` of streamed chat completion chunks into [ChatResponseStream] events.
+///
+/// Text is forwarded as it arrives. Tool calls are buffered until the choice finishes because
+/// their deltas may interleave, whereas chat expects the events of one tool use to be contiguous.
+#[derive(Debug, Default)]
+struct StreamDecoder {
+    pending: VecDeque<ChatResponseStream>,
+    tool_calls: BTreeMap<u64, PendingToolCall>,
+    done: bool,
+}
+
+#[derive(Debug, Default)]
+struct PendingToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl StreamDecoder {
+    #[allow(clippy::result_large_err)]
+    fn decode(&mut self, data: &str) -> Result<(), ApiClientError> {
+        if data.trim() == DONE_MARKER {
+            self.finish();
+            return Ok(());
+        }
+
+        let chunk: ChatCompletionChunk = serde_json::from_str(data).map_err(|err| ApiClientError::ModelBackend {
+            message: format!("failed to parse chat completion chunk: {err}"),
+            status_code: None,
+        })?;
+
+        if let Some(error) = chunk.error {
+            return Err(ApiClientError::ModelBackend {
+                message: error.message,
+                status_code: None,
+            });
+        }
+
+        for choice in chunk.choices {
+            if let Some(content) = choice.delta.content.filter(|content| !content.is_empty()) {
+                self.pending
+                    .push_back(ChatResponseStream::AssistantResponseEvent { content });
+            }
+
+            for delta in choice.delta.tool_calls.unwrap_or_default() {
+                let tool_call = self.tool_calls.entry(delta.index).or_default();
+                if let Some(id) = delta.id.filter(|id| !id.is_empty()) {
+                    tool_call.id = id;
+                }
+                if let Some(function) = delta.function {
+                    if let Some(name) = function.name.filter(|name| !name.is_empty()) {
+                        tool_call.name = name;
+                    }
+                    if let Some(arguments) = function.arguments {
+                        tool_call.arguments.push_str(&arguments);
+                    }
+                }
+            }
+
+            if choice.finish_reason.is_some() {
+                self.flush_tool_calls();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        self.flush_tool_calls();
+        self.done = true;
+    }
+
+    fn flush_tool_calls(&mut self) {
+        for (_, tool_call) in std::mem::take(&mut self.tool_calls) {
+            let PendingToolCall { id, name, arguments } = tool_call;
+            let tool_use_id = match id.is_empty() {
+                true => uuid::Uuid::new_v4().to_string(),
+                false => id,
+            };
+
+            self.pending.push_back(ChatResponseStream::ToolUseEvent {
+                tool_use_id: tool_use_id.clone(),
+                name: name.clone(),
+                input: None,
+                stop: None,
+            });
+            if !arguments.is_empty() {
+                self.pending.push_back(ChatResponseStream::ToolUseEvent {
+                    tool_use_id: tool_use_id.clone(),
+                    name: name.clone(),
+                    input: Some(arguments),
+                    stop: None,
+                });
+            }
+            self.pending.push_back(ChatResponseStream::ToolUseEvent {
+                tool_use_id,
+                name,
+                input: None,
+                stop: Some(true),
+            });
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    error: Option<ChunkError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: ChunkDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ChunkDelta {
+    content: Option<String>,
+    tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolCallDelta {
+    #[serde(default)]
+    index: u64,
+    id: Option<String>,
+    function: Option<FunctionDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkError {
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use bytes::Bytes;
+    use http_body_util::combinators::BoxBody;
+    use http_body_util::{
+        BodyExt,
+        Full,
+    };
+    use hyper::Request;
+    use hyper::body::Incoming;
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper_util::rt::TokioIo;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::api_client::model::{
+        ToolInputSchema,
+        ToolResultStatus,
+        ToolSpecification,
+        ToolUse,
+        UserInputMessageContext,
+    };
+
+    fn user_message(content: &str, context: Option<UserInputMessageContext>) -> UserInputMessage {
+        UserInputMessage {
+            content: content.to_owned(),
+            user_input_message_context: context,
+            user_intent: None,
+            images: None,
+            model_id: Some("qwen".to_owned()),
+        }
+    }
+
+    fn conversation() -> ConversationState {
+        ConversationState {
+            conversation_id: None,
+            user_input_message: user_message(
+                "",
+                Some(UserInputMessageContext {
+                    tool_results: Some(vec![ToolResult {
+                        tool_use_id: "call_1".to_owned(),
+                        content: vec![ToolResultContentBlock::Text("fn main() {}".to_owned())],
+                        status: ToolResultStatus::Success,
+                    }]),
+                    tools: Some(vec![Tool::ToolSpecification(ToolSpecification {
+                        name: "fs_read".to_owned(),
+                        description: "Reads files".to_owned(),
+                        input_schema: ToolInputSchema {
+                            json: Some(serde_json::from_value(json!({ "type": "object" })).unwrap()),
+                        },
+                    })]),
+                    ..Default::default()
+                }),
+            ),
+            history: Some(vec![
+                ChatMessage::UserInputMessage(user_message("read main.rs", None)),
+                ChatMessage::AssistantResponseMessage(AssistantResponseMessage {
+                    message_id: None,
+                    content: "Reading it".to_owned(),
+                    tool_uses: Some(vec![ToolUse {
+                        tool_use_id: "call_1".to_owned(),
+                        name: "fs_read".to_owned(),
+                        input: serde_json::from_value(json!({ "path": "main.rs" })).unwrap(),
+                    }]),
+                }),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_build_request() {
+        assert_eq!(
+            build_request(conversation(), "qwen"),
+            json!({
+                "model": "qwen",
+                "stream": true,
+                "messages": [
+                    { "role": "user", "content": "read main.rs" },
+                    {
+                        "role": "assistant",
+                        "content": "Reading it",
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": { "name": "fs_read", "arguments": "{\"path\":\"main.rs\"}" },
+                        }],
+                    },
+                    { "role": "tool", "tool_call_id": "call_1", "content": "fn main() {}" },
+                ],
+                "tools": [{
+                    "type": "function",
+                    "function": {
+                        "name": "fs_read",
+                        "description": "Reads files",
+                        "parameters": { "type": "object" },
+                    },
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_stream() {
+        let mut decoder = StreamDecoder::default();
+        for data in [
+            r#"{"choices":[{"delta":{"role":"assistant","content":"Let me"}}]}"#,
+            r#"{"choices":[{"delta":{"content":" check"}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_a","function":{"name":"fs_read","arguments":"{\"pa"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":1,"id":"call_b","function":{"name":"execute_bash","arguments":""}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"th\":\"a\"}"}}]}}]}"#,
+            r#"{"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            DONE_MARKER,
+        ] {
+            decoder.decode(data).unwrap();
+        }
+
+        assert!(decoder.done);
+        assert_eq!(decoder.pending.into_iter().collect::<Vec<_>>(), vec![
+            ChatResponseStream::AssistantResponseEvent {
+                content: "Let me".to_owned()
+            },
+            ChatResponseStream::AssistantResponseEvent {
+                content: " check".to_owned()
+            },
+            ChatResponseStream::ToolUseEvent {
+                tool_use_id: "call_a".to_owned(),
+                name: "fs_read".to_owned(),
+                input: None,
+                stop: None,
+            },
+            ChatResponseStream::ToolUseEvent {
+                tool_use_id: "call_a".to_owned(),
+                name: "fs_read".to_owned(),
+                input: Some(r#"{"path":"a"}"#.to_owned()),
+                stop: None,
+            },
+            ChatResponseStream::ToolUseEvent {
+                tool_use_id: "call_a".to_owned(),
+                name: "fs_read".to_owned(),
+                input: None,
+                stop: Some(true),
+            },
+            ChatResponseStream::ToolUseEvent {
+                tool_use_id: "call_b".to_owned(),
+                name: "execute_bash".to_owned(),
+                input: None,
+                stop: None,
+            },
+            ChatResponseStream::ToolUseEvent {
+                tool_use_id: "call_b".to_owned(),
+                name: "execute_bash".to_owned(),
+                input: None,
+                stop: Some(true),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_decode_error() {
+        let mut decoder = StreamDecoder::default();
+        assert!(matches!(
+            decoder.decode(r#"{"error":{"message":"model crashed"}}"#),
+            Err(ApiClientError::ModelBackend { message, .. }) if message == "model crashed"
+        ));
+    }
+
+    #[test]
+    fn test_status_error() {
+        assert!(matches!(
+            status_error(429, String::new()),
+            ApiClientError::QuotaBreach { .. }
+        ));
+        assert!(matches!(
+            status_error(400, "This model's maximum context length is 8192 tokens".to_owned()),
+            ApiClientError::ContextWindowOverflow { .. }
+        ));
+        assert!(matches!(
+            status_error(401, "invalid api key".to_owned()),
+            ApiClientError::ModelBackend {
+                status_code: Some(401),
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_message() {
+        async fn handle(req: Request<Incoming>) -> Result<hyper::Response<BoxBody<Bytes, Infallible>>, Infallible> {
+            assert_eq!(req.uri().path(), "/v1/chat/completions");
+            assert_eq!(req.headers()["authorization"], "Bearer secret");
+            let body = req.into_body().collect().await.unwrap().to_bytes();
+            let body = serde_json::from_slice::<Value>(&body).unwrap();
+            assert_eq!(body["model"], "qwen");
+
+            let stream = "data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n\
+                          data: {\"choices\":[{\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n\
+                          data: [DONE]\n\n";
+            Ok(hyper::Response::builder()
+                .header("content-type", EVENT_STREAM_MIME)
+                .body(Full::new(Bytes::from(stream)).boxed())
+                .unwrap())
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let _ = http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service_fn(handle))
+                .await;
+        });
+
+        let client = OpenAiClient::new(
+            &OpenAiConfig {
+                base_url: format!("http://{addr}/v1/"),
+                models: vec!["llama".to_owned(), "qwen".to_owned()],
+            },
+            Some("secret".into()),
+        )
+        .unwrap();
+
+        let mut output = client.send_message(conversation()).await.unwrap();
+        let mut events = Vec::new();
+        while let Some(event) = output.recv().await.unwrap() {
+            events.push(event);
+        }
+        assert_eq!(events, vec![ChatResponseStream::AssistantResponseEvent {
+            content: "Hello".to_owned()
+        }]);
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_times_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{
+                AsyncReadExt,
+                AsyncWriteExt,
+            };
+
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).await.unwrap();
+            let data = "data: {\"choices\":[{\"delta\":{\"content\":\"Hello\"}}]}\n\n";
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: {EVENT_STREAM_MIME}\r\ntransfer-encoding: chunked\r\n\r\n{:x}\r\n{data}\r\n",
+                data.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            // Keep the connection open without sending anything else
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        });
+
+        let mut client = OpenAiClient::new(
+            &OpenAiConfig {
+                base_url: format!("http://{addr}/v1"),
+                models: vec!["qwen".to_owned()],
+            },
+            None,
+        )
+        .unwrap();
+        client.idle_timeout = Duration::from_millis(200);
+
+        let mut output = client.send_message(conversation()).await.unwrap();
+        assert_eq!(
+            output.recv().await.unwrap(),
+            Some(ChatResponseStream::AssistantResponseEvent {
+                content: "Hello".to_owned()
+            })
+        );
+        assert!(matches!(
+            output.recv().await,
+            Err(ApiClientError::ModelBackend { status_code: None, .. })
+        ));
+    }
+}
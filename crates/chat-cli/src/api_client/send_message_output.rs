@@ -1,6 +1,11 @@
 use aws_types::request_id::RequestId;
 
 use crate::api_client::ApiClientError;
+use crate::api_client::openai::OpenAiOutput;
+use crate::api_client::cassette::{
+    RecordingOutput,
+    ReplayOutput,
+};
 use crate::api_client::model::ChatResponseStream;
 
 #[derive(Debug)]
@@ -9,7 +14,10 @@ pub enum SendMessageOutput {
         amzn_codewhisperer_streaming_client::operation::generate_assistant_response::GenerateAssistantResponseOutput,
     ),
     QDeveloper(amzn_qdeveloper_streaming_client::operation::send_message::SendMessageOutput),
+    OpenAi(OpenAiOutput),
     Mock(Vec<ChatResponseStream>),
+    Recording(Box<RecordingOutput>),
+    Replay(ReplayOutput),
 }
 
 impl SendMessageOutput {
@@ -17,7 +25,10 @@ impl SendMessageOutput {
         match self {
             SendMessageOutput::Codewhisperer(output) => output.request_id(),
             SendMessageOutput::QDeveloper(output) => output.request_id(),
+            SendMessageOutput::OpenAi(output) => output.request_id(),
             SendMessageOutput::Mock(_) => None,
+            SendMessageOutput::Recording(output) => output.request_id(),
+            SendMessageOutput::Replay(_) => None,
         }
     }
 
@@ -29,7 +40,10 @@ impl SendMessageOutput {
                 .await?
                 .map(|s| s.into())),
             SendMessageOutput::QDeveloper(output) => Ok(output.send_message_response.recv().await?.map(|s| s.into())),
+            SendMessageOutput::OpenAi(output) => output.recv().await,
             SendMessageOutput::Mock(vec) => Ok(vec.pop()),
+            SendMessageOutput::Recording(output) => output.recv().await,
+            SendMessageOutput::Replay(output) => output.recv(),
         }
     }
 }
@@ -39,7 +53,10 @@ impl RequestId for SendMessageOutput {
         match self {
             SendMessageOutput::Codewhisperer(output) => output.request_id(),
             SendMessageOutput::QDeveloper(output) => output.request_id(),
+            SendMessageOutput::OpenAi(output) => output.request_id(),
             SendMessageOutput::Mock(_) => Some("<mock-request-id>"),
+            SendMessageOutput::Recording(output) => output.request_id(),
+            SendMessageOutput::Replay(_) => Some("<replay-request-id>"),
         }
     }
 }
@@ -0,0 +1,181 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use eyre::{
+    Result,
+    bail,
+};
+use time::OffsetDateTime;
+
+use crate::os::Os;
+
+/// The state of a file prior to it being written by `fs_write`.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// The turn of the conversation during which the file was written
+    pub turn: usize,
+    /// The (sanitized) path of the file
+    pub path: PathBuf,
+    /// Contents of the file before the write, [None] if the write created the file
+    pub previous: Option<Vec<u8>>,
+    pub timestamp: OffsetDateTime,
+}
+
+/// What restoring a file amounted to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreAction {
+    /// The file had its previous contents written back
+    Restored,
+    /// The file did not exist prior to the checkpoint and was removed
+    Deleted,
+}
+
+/// Records [Checkpoint]s for the files written during a chat session, grouped by the turn of the
+/// conversation in which they were written, so that the workspace can be rolled back.
+#[derive(Debug, Default)]
+pub struct CheckpointManager {
+    /// The current turn, which is incremented every time the user sends a message
+    turn: usize,
+    /// Checkpoints in the order they were recorded
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl CheckpointManager {
+    /// Marks the start of a new turn of the conversation
+    pub fn begin_turn(&mut self) {
+        self.turn += 1;
+    }
+
+    /// Records the current state of `path`. Must be called before the file is written.
+    pub async fn record(&mut self, os: &Os, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let previous = if os.fs.exists(path) {
+            Some(os.fs.read(path).await?)
+        } else {
+            None
+        };
+        self.checkpoints.push(Checkpoint {
+            turn: self.turn,
+            path: path.to_path_buf(),
+            previous,
+            timestamp: OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc()),
+        });
+        Ok(())
+    }
+
+    /// The recorded checkpoints grouped by turn, oldest first
+    pub fn turns(&self) -> Vec<&[Checkpoint]> {
+        self.checkpoints.chunk_by(|a, b| a.turn == b.turn).collect()
+    }
+
+    /// Reverts the writes of the most recent turn that has any. Returns [None] if there is nothing
+    /// to undo.
+    pub async fn undo(&mut self, os: &Os) -> Result<Option<Vec<(PathBuf, RestoreAction)>>> {
+        match self.turns().len() {
+            0 => Ok(None),
+            n => self.restore(os, n).await.map(Some),
+        }
+    }
+
+    /// Rolls the workspace back to how it was before the `n`th (1-based) group listed by
+    /// [Self::turns], reverting the writes of that turn and of every turn after it. Returns the
+    /// files affected along with what was done to them.
+    pub async fn restore(&mut self, os: &Os, n: usize) -> Result<Vec<(PathBuf, RestoreAction)>> {
+        let turns = self.turns();
+        if n == 0 || n > turns.len() {
+            bail!("No checkpoint {n}, there are {} checkpoints", turns.len());
+        }
+        let first = turns[n - 1][0].turn;
+        let start = self
+            .checkpoints
+            .iter()
+            .position(|c| c.turn == first)
+            .unwrap_or_default();
+
+        // Undo the writes in reverse order such that a file written several times ends up with the
+        // contents it had before the earliest of the writes.
+        let mut affected = Vec::<(PathBuf, RestoreAction)>::new();
+        while self.checkpoints.len() > start {
+            let Some(checkpoint) = self.checkpoints.last() else {
+                break;
+            };
+            let action = match &checkpoint.previous {
+                Some(contents) => {
+                    if let Some(parent) = checkpoint.path.parent() {
+                        os.fs.create_dir_all(parent).await?;
+                    }
+                    os.fs.write(&checkpoint.path, contents).await?;
+                    RestoreAction::Restored
+                },
+                None => {
+                    if os.fs.exists(&checkpoint.path) {
+                        os.fs.remove_file(&checkpoint.path).await?;
+                    }
+                    RestoreAction::Deleted
+                },
+            };
+            match affected.iter_mut().find(|(path, _)| *path == checkpoint.path) {
+                Some(entry) => entry.1 = action,
+                None => affected.push((checkpoint.path.clone(), action)),
+            }
+            self.checkpoints.pop();
+        }
+
+        Ok(affected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_undo_and_restore() {
+        let os = Os::new().await.unwrap();
+        os.fs.write("/existing.txt", "original").await.unwrap();
+        let mut manager = CheckpointManager::default();
+        assert!(manager.undo(&os).await.unwrap().is_none());
+
+        // Turn 1: modify an existing file twice
+        manager.begin_turn();
+        manager.record(&os, "/existing.txt").await.unwrap();
+        os.fs.write("/existing.txt", "first").await.unwrap();
+        manager.record(&os, "/existing.txt").await.unwrap();
+        os.fs.write("/existing.txt", "second").await.unwrap();
+
+        // Turn 2: nothing written
+        manager.begin_turn();
+
+        // Turn 3: create a file and modify the existing one
+        manager.begin_turn();
+        manager.record(&os, "/dir/new.txt").await.unwrap();
+        os.fs.create_dir_all("/dir").await.unwrap();
+        os.fs.write("/dir/new.txt", "new").await.unwrap();
+        manager.record(&os, "/existing.txt").await.unwrap();
+        os.fs.write("/existing.txt", "third").await.unwrap();
+
+        let turns = manager.turns();
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].len(), 2);
+        assert_eq!(turns[1][0].turn, 3);
+
+        let affected = manager.undo(&os).await.unwrap().unwrap();
+        assert_eq!(affected, vec![
+            (PathBuf::from("/existing.txt"), RestoreAction::Restored),
+            (PathBuf::from("/dir/new.txt"), RestoreAction::Deleted),
+        ]);
+        assert!(!os.fs.exists("/dir/new.txt"));
+        assert_eq!(os.fs.read_to_string("/existing.txt").await.unwrap(), "second");
+
+        assert!(manager.restore(&os, 2).await.is_err());
+        let affected = manager.restore(&os, 1).await.unwrap();
+        assert_eq!(affected, vec![(
+            PathBuf::from("/existing.txt"),
+            RestoreAction::Restored
+        )]);
+        assert_eq!(os.fs.read_to_string("/existing.txt").await.unwrap(), "original");
+        assert!(manager.turns().is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+
+use clap::{
+    Args,
+    Subcommand,
+};
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+use crossterm::{
+    execute,
+    queue,
+};
+
+use crate::cli::chat::checkpoint::RestoreAction;
+use crate::cli::chat::tools::format_path;
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::os::Os;
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+pub struct UndoArgs;
+
+impl UndoArgs {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        match session.checkpoints.undo(os).await {
+            Ok(Some(affected)) => queue_restored(os, session, &affected)?,
+            Ok(None) => queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::Yellow),
+                style::Print("\nThere are no file changes to undo.\n\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?,
+            Err(e) => queue_restore_error(session, &e)?,
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Subcommand)]
+#[command(
+    before_long_help = "Every file change made with fs_write is checkpointed, grouped by the turn of the conversation in
+which it was made. Restoring a checkpoint reverts the changes of that turn and of every turn after it, including
+deleting the files that were created. Checkpoints only last for the current session."
+)]
+pub enum CheckpointsSubcommand {
+    /// List the checkpoints of this session
+    List,
+    /// Roll the workspace back to how it was before the given checkpoint
+    Restore {
+        /// The number of the checkpoint, as shown by /checkpoints list
+        checkpoint: usize,
+    },
+}
+
+impl CheckpointsSubcommand {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        match self {
+            Self::List => {
+                let cwd = os.env.current_dir()?;
+                let turns = session.checkpoints.turns();
+                if turns.is_empty() {
+                    queue!(
+                        session.stderr,
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print("\nNo files have been changed in this session.\n\n"),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                }
+
+                let mut to_display = String::new();
+                for (i, checkpoints) in turns.iter().enumerate() {
+                    let time = checkpoints[0]
+                        .timestamp
+                        .format(time::macros::format_description!("[hour]:[minute]:[second]"))
+                        .unwrap_or_default();
+                    to_display.push_str(&format!("\n{}. {time}\n", i + 1));
+
+                    let mut seen = Vec::<&PathBuf>::new();
+                    for checkpoint in checkpoints.iter() {
+                        if seen.contains(&&checkpoint.path) {
+                            continue;
+                        }
+                        seen.push(&checkpoint.path);
+                        let change = if checkpoint.previous.is_some() {
+                            "modified"
+                        } else {
+                            "created"
+                        };
+                        to_display.push_str(&format!("   {} ({change})\n", format_path(&cwd, &checkpoint.path)));
+                    }
+                }
+
+                if !to_display.is_empty() {
+                    queue!(
+                        session.stderr,
+                        style::SetAttribute(Attribute::Bold),
+                        style::Print("\nCheckpoints:\n"),
+                        style::SetAttribute(Attribute::Reset),
+                        style::Print(to_display),
+                        style::SetForegroundColor(Color::DarkGrey),
+                        style::Print("\nUse /checkpoints restore <n> to revert the changes since checkpoint <n>.\n\n"),
+                        style::SetForegroundColor(Color::Reset),
+                    )?;
+                }
+            },
+            Self::Restore { checkpoint } => match session.checkpoints.restore(os, checkpoint).await {
+                Ok(affected) => queue_restored(os, session, &affected)?,
+                Err(e) => queue_restore_error(session, &e)?,
+            },
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+fn queue_restored(os: &Os, session: &mut ChatSession, affected: &[(PathBuf, RestoreAction)]) -> Result<(), ChatError> {
+    let cwd = os.env.current_dir()?;
+    queue!(session.stderr, style::Print("\n"))?;
+    for (path, action) in affected {
+        queue!(
+            session.stderr,
+            style::Print(match action {
+                RestoreAction::Restored => "Restored: ",
+                RestoreAction::Deleted => "Deleted: ",
+            }),
+            style::SetForegroundColor(Color::Green),
+            style::Print(format_path(&cwd, path)),
+            style::SetForegroundColor(Color::Reset),
+            style::Print("\n"),
+        )?;
+    }
+    execute!(session.stderr, style::Print("\n"))?;
+    Ok(())
+}
+
+fn queue_restore_error(session: &mut ChatSession, error: &eyre::Report) -> Result<(), ChatError> {
+    queue!(
+        session.stderr,
+        style::SetForegroundColor(Color::Red),
+        style::Print(format!("\nFailed to restore checkpoint: {error}\n\n")),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+    Ok(())
+}
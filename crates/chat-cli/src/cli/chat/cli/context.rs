@@ -138,19 +138,10 @@ impl ContextSubcommand {
                         style::SetForegroundColor(Color::DarkYellow),
                         style::Print("\n    🔧 Hooks:\n")
                     )?;
-                    print_hook_section(
-                        &mut session.stderr,
-                        &context_manager.global_config.hooks,
-                        HookTrigger::ConversationStart,
-                    )
-                    .map_err(map_chat_error)?;
-
-                    print_hook_section(
-                        &mut session.stderr,
-                        &context_manager.global_config.hooks,
-                        HookTrigger::PerPrompt,
-                    )
-                    .map_err(map_chat_error)?;
+                    for trigger in HookTrigger::ALL {
+                        print_hook_section(&mut session.stderr, &context_manager.global_config.hooks, trigger)
+                            .map_err(map_chat_error)?;
+                    }
                 }
 
                 // Display profile context
@@ -197,18 +188,10 @@ impl ContextSubcommand {
                         style::SetForegroundColor(Color::DarkYellow),
                         style::Print("    🔧 Hooks:\n")
                     )?;
-                    print_hook_section(
-                        &mut session.stderr,
-                        &context_manager.profile_config.hooks,
-                        HookTrigger::ConversationStart,
-                    )
-                    .map_err(map_chat_error)?;
-                    print_hook_section(
-                        &mut session.stderr,
-                        &context_manager.profile_config.hooks,
-                        HookTrigger::PerPrompt,
-                    )
-                    .map_err(map_chat_error)?;
+                    for trigger in HookTrigger::ALL {
+                        print_hook_section(&mut session.stderr, &context_manager.profile_config.hooks, trigger)
+                            .map_err(map_chat_error)?;
+                    }
                     execute!(session.stderr, style::Print("\n"))?;
                 }
 
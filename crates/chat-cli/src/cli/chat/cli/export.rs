@@ -0,0 +1,83 @@
+use clap::Args;
+use crossterm::execute;
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+
+use crate::cli::chat::export::{
+    ExportFormat,
+    ExportOptions,
+    render,
+};
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::os::Os;
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+pub struct ExportArgs {
+    /// File to write the transcript to
+    path: String,
+    /// Format of the transcript, inferred from the extension of the file if not given
+    #[arg(long, value_enum)]
+    format: Option<ExportFormat>,
+    /// Replace the outputs of tools with a placeholder
+    #[arg(long)]
+    redact: bool,
+    /// Overwrite the file if it already exists
+    #[arg(short, long)]
+    force: bool,
+}
+
+impl ExportArgs {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        if os.fs.exists(&self.path) && !self.force {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!(
+                    "\nFile at {} already exists. To overwrite, use -f or --force\n\n",
+                    &self.path
+                )),
+                style::SetAttribute(Attribute::Reset)
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        let options = ExportOptions {
+            format: self.format.unwrap_or_else(|| ExportFormat::from_path(&self.path)),
+            redact_tool_outputs: self.redact,
+            ..Default::default()
+        };
+        let transcript = render(&session.conversation, &options);
+        if let Err(err) = os.fs.write(&self.path, transcript).await {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("\nFailed to export to {}: {}\n\n", &self.path, &err)),
+                style::SetAttribute(Attribute::Reset)
+            )?;
+            return Ok(ChatState::PromptUser {
+                skip_printing_tools: true,
+            });
+        }
+
+        execute!(
+            session.stderr,
+            style::SetForegroundColor(Color::Green),
+            style::Print(format!("\n✔ Exported conversation transcript to {}\n\n", &self.path)),
+            style::SetAttribute(Attribute::Reset)
+        )?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
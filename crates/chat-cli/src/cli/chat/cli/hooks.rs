@@ -32,6 +32,7 @@ use futures::stream::{
     FuturesUnordered,
     StreamExt,
 };
+use globset::Glob;
 use serde::{
     Deserialize,
     Serialize,
@@ -40,6 +41,7 @@ use spinners::{
     Spinner,
     Spinners,
 };
+use tracing::warn;
 
 use crate::cli::chat::util::truncate_safe;
 use crate::cli::chat::{
@@ -78,6 +80,11 @@ pub struct Hook {
     /// The bash command to execute
     pub command: Option<String>, // For inline hooks
 
+    /// Glob matched against the tool name for [`HookTrigger::PreToolUse`] and
+    /// [`HookTrigger::PostToolUse`] hooks, all tools match if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+
     // Internal data
     #[serde(skip)]
     pub name: String,
@@ -95,6 +102,7 @@ impl Hook {
             max_output_size: Self::default_max_output_size(),
             cache_ttl_seconds: Self::default_cache_ttl_seconds(),
             command: Some(command),
+            matcher: None,
             is_global: false,
             name: "new hook".to_string(),
         }
@@ -115,6 +123,24 @@ impl Hook {
     fn default_cache_ttl_seconds() -> u64 {
         DEFAULT_CACHE_TTL_SECONDS
     }
+
+    /// Whether the hook runs for `event`, see [`Hook::matcher`]
+    fn matches(&self, event: &HookEvent<'_>) -> bool {
+        if self.disabled || self.trigger != event.trigger() {
+            return false;
+        }
+
+        match (&self.matcher, event.tool_name()) {
+            (Some(matcher), Some(tool_name)) => match Glob::new(matcher) {
+                Ok(glob) => glob.compile_matcher().is_match(tool_name),
+                Err(err) => {
+                    warn!(?err, name = self.name, "invalid hook matcher");
+                    false
+                },
+            },
+            _ => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
@@ -124,11 +150,131 @@ pub enum HookType {
     Inline,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum HookTrigger {
     ConversationStart,
     PerPrompt,
+    /// Before a tool is invoked, may deny the tool use
+    PreToolUse,
+    /// After a tool is invoked, may annotate or flag the tool result
+    PostToolUse,
+    /// When the assistant ends its turn, may ask the assistant to continue
+    TurnEnd,
+}
+
+impl HookTrigger {
+    pub const ALL: [HookTrigger; 5] = [
+        HookTrigger::ConversationStart,
+        HookTrigger::PerPrompt,
+        HookTrigger::PreToolUse,
+        HookTrigger::PostToolUse,
+        HookTrigger::TurnEnd,
+    ];
+
+    /// Whether the hook output is added as context to user prompts, rather than run on [`HookEvent`]s
+    pub fn is_context(&self) -> bool {
+        matches!(self, HookTrigger::ConversationStart | HookTrigger::PerPrompt)
+    }
+}
+
+/// Passed as json on stdin to the hooks of the [`HookTrigger`] of the event
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "hook_event", rename_all = "snake_case")]
+pub enum HookEvent<'a> {
+    PreToolUse {
+        tool_name: &'a str,
+        tool_input: &'a serde_json::Value,
+    },
+    PostToolUse {
+        tool_name: &'a str,
+        tool_input: &'a serde_json::Value,
+        /// The output of the tool, or the error if it failed
+        tool_response: &'a str,
+        success: bool,
+    },
+    TurnEnd {
+        assistant_response: &'a str,
+    },
+}
+
+impl HookEvent<'_> {
+    pub fn trigger(&self) -> HookTrigger {
+        match self {
+            HookEvent::PreToolUse { .. } => HookTrigger::PreToolUse,
+            HookEvent::PostToolUse { .. } => HookTrigger::PostToolUse,
+            HookEvent::TurnEnd { .. } => HookTrigger::TurnEnd,
+        }
+    }
+
+    fn tool_name(&self) -> Option<&str> {
+        match self {
+            HookEvent::PreToolUse { tool_name, .. } | HookEvent::PostToolUse { tool_name, .. } => Some(tool_name),
+            HookEvent::TurnEnd { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookDecision {
+    Allow,
+    #[serde(alias = "block")]
+    Deny,
+}
+
+/// What a hook run on a [`HookEvent`] decided.
+///
+/// Hooks may print this as json on stdout. Otherwise, a non-zero exit code denies with stderr as the
+/// reason, and the stdout of a successful hook is additional context.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct HookResponse {
+    #[serde(default)]
+    pub decision: Option<HookDecision>,
+    /// Why the hook denied, fed back to the model
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Added to the tool result, or to the next user message for [`HookTrigger::TurnEnd`]
+    #[serde(default)]
+    pub additional_context: Option<String>,
+}
+
+impl HookResponse {
+    fn from_output(output: &std::process::Output, max_output_size: usize) -> Self {
+        let stdout = output.stdout.to_str_lossy();
+        let stdout = stdout.trim();
+
+        if !output.status.success() {
+            let stderr = output.stderr.to_str_lossy();
+            let reason = [stderr.trim(), stdout]
+                .into_iter()
+                .find(|reason| !reason.is_empty())
+                .map_or_else(
+                    || format!("hook returned non-zero exit code: {}", output.status),
+                    |reason| truncate_output(reason, max_output_size),
+                );
+            return Self {
+                decision: Some(HookDecision::Deny),
+                reason: Some(reason),
+                additional_context: None,
+            };
+        }
+
+        if stdout.starts_with('{') {
+            if let Ok(response) = serde_json::from_str::<HookResponse>(stdout) {
+                return response;
+            }
+        }
+
+        Self {
+            additional_context: (!stdout.is_empty()).then(|| truncate_output(stdout, max_output_size)),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_denied(&self) -> bool {
+        self.decision == Some(HookDecision::Deny)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -285,7 +431,9 @@ impl HookExecutor {
         results.iter().skip(start_cache_index).for_each(|(_, (hook, output))| {
             let expiry = match hook.trigger {
                 HookTrigger::ConversationStart => None,
-                HookTrigger::PerPrompt => Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds)),
+                HookTrigger::PerPrompt | HookTrigger::PreToolUse | HookTrigger::PostToolUse | HookTrigger::TurnEnd => {
+                    Some(Instant::now() + Duration::from_secs(hook.cache_ttl_seconds))
+                },
             };
             self.insert_cache(hook, CachedHook {
                 output: output.clone(),
@@ -298,6 +446,76 @@ impl HookExecutor {
         Ok(results.into_iter().map(|(_, r)| r).collect())
     }
 
+    /// Run the [`Hook`]s that match `event` one after the other, such that hooks editing files do
+    /// not race. Hooks that fail to execute are reported to `output` and not returned, except for
+    /// [`HookEvent::PreToolUse`], for which they deny the tool use.
+    ///
+    /// Event hooks are never cached, as their input differs every time.
+    pub async fn run_event_hooks(
+        &self,
+        hooks: Vec<&Hook>,
+        event: &HookEvent<'_>,
+        output: &mut impl Write,
+    ) -> Result<Vec<(Hook, HookResponse)>, ChatError> {
+        let input = serde_json::to_string(event).map_err(|err| ChatError::Custom(err.to_string().into()))?;
+        let mut results = Vec::new();
+
+        for hook in hooks.into_iter().filter(|hook| hook.matches(event)) {
+            let result = match hook.r#type {
+                HookType::Inline => self.run_inline_command(hook, Some(&input)).await,
+            };
+
+            match result {
+                Ok(command_output) => {
+                    let response = HookResponse::from_output(&command_output, hook.max_output_size);
+                    if response.is_denied() {
+                        queue!(
+                            output,
+                            style::SetForegroundColor(style::Color::Red),
+                            style::Print("✗ "),
+                            style::SetForegroundColor(style::Color::Blue),
+                            style::Print(&hook.name),
+                            style::ResetColor,
+                            style::Print(format!(
+                                " denied: {}\n",
+                                response.reason.as_deref().unwrap_or("no reason given")
+                            )),
+                        )?;
+                    }
+                    results.push((hook.clone(), response));
+                },
+                Err(err) => {
+                    // Hooks guarding tool uses fail closed, such that a broken policy hook can't be
+                    // bypassed
+                    let denies = matches!(event, HookEvent::PreToolUse { .. });
+                    queue!(
+                        output,
+                        style::SetForegroundColor(style::Color::Red),
+                        style::Print("✗ "),
+                        style::SetForegroundColor(style::Color::Blue),
+                        style::Print(&hook.name),
+                        style::ResetColor,
+                        style::Print(format!(
+                            " failed{}: {}\n",
+                            if denies { ", denying the tool use" } else { "" },
+                            err
+                        )),
+                    )?;
+                    if denies {
+                        results.push((hook.clone(), HookResponse {
+                            decision: Some(HookDecision::Deny),
+                            reason: Some(format!("the hook failed to run: {err}")),
+                            additional_context: None,
+                        }));
+                    }
+                },
+            }
+        }
+
+        output.flush()?;
+        Ok(results)
+    }
+
     async fn execute_hook<'a>(&self, hook: &'a Hook) -> (&'a Hook, Result<String>, Duration) {
         let start_time = Instant::now();
         let result = match hook.r#type {
@@ -308,48 +526,52 @@ impl HookExecutor {
     }
 
     async fn execute_inline_hook(&self, hook: &Hook) -> Result<String> {
+        let result = self.run_inline_command(hook, None).await?;
+        if result.status.success() {
+            Ok(truncate_output(&result.stdout.to_str_lossy(), hook.max_output_size))
+        } else {
+            Err(eyre!("command returned non-zero exit code: {}", result.status))
+        }
+    }
+
+    /// Run the command of an inline hook with `input` on stdin
+    async fn run_inline_command(&self, hook: &Hook, input: Option<&str>) -> Result<std::process::Output> {
         let command = hook.command.as_ref().ok_or_else(|| eyre!("no command specified"))?;
 
         #[cfg(unix)]
-        let command_future = tokio::process::Command::new("bash")
+        let mut child = tokio::process::Command::new("bash")
             .arg("-c")
             .arg(command)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output();
+            .kill_on_drop(true)
+            .spawn()?;
 
         #[cfg(windows)]
-        let command_future = tokio::process::Command::new("cmd")
+        let mut child = tokio::process::Command::new("cmd")
             .arg("/C")
             .arg(command)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .output();
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let stdin = child.stdin.take();
+        let command_future = async move {
+            if let (Some(input), Some(mut stdin)) = (input, stdin) {
+                // Hooks don't have to read their input, so a closed stdin is not an error
+                let _ = tokio::io::AsyncWriteExt::write_all(&mut stdin, input.as_bytes()).await;
+            }
+            child.wait_with_output().await
+        };
 
         let timeout = Duration::from_millis(hook.timeout_ms);
 
         // Run with timeout
         match tokio::time::timeout(timeout, command_future).await {
-            Ok(result) => {
-                let result = result?;
-                if result.status.success() {
-                    let stdout = result.stdout.to_str_lossy();
-                    let stdout = format!(
-                        "{}{}",
-                        truncate_safe(&stdout, hook.max_output_size),
-                        if stdout.len() > hook.max_output_size {
-                            " ... truncated"
-                        } else {
-                            ""
-                        }
-                    );
-                    Ok(stdout)
-                } else {
-                    Err(eyre!("command returned non-zero exit code: {}", result.status))
-                }
-            },
+            Ok(result) => Ok(result?),
             Err(_) => Err(eyre!("command timed out after {} ms", timeout.as_millis())),
         }
     }
@@ -396,7 +618,11 @@ in global or local profiles.
 Notes:
 • Hooks are executed in parallel
 • 'conversation_start' hooks run on the first user prompt and are attached once to the conversation history sent to Amazon Q
-• 'per_prompt' hooks run on each user prompt and are attached to the prompt, but are not stored in conversation history"
+• 'per_prompt' hooks run on each user prompt and are attached to the prompt, but are not stored in conversation history
+• 'pre_tool_use' and 'post_tool_use' hooks run around each tool use whose name matches --matcher, with the tool name and input as JSON on stdin
+• 'turn_end' hooks run when Amazon Q ends its turn, with its response as JSON on stdin
+• Tool use and turn end hooks deny by exiting with a non-zero code, with stderr as the reason fed back to Amazon Q.
+  They may instead print {\"decision\": \"allow\" | \"deny\", \"reason\": ..., \"additional_context\": ...}, other output is added as context"
 )]
 pub struct HooksArgs {
     #[command(subcommand)]
@@ -423,18 +649,10 @@ impl HooksArgs {
             style::SetAttribute(Attribute::Reset),
         )?;
 
-        print_hook_section(
-            &mut session.stderr,
-            &context_manager.global_config.hooks,
-            HookTrigger::ConversationStart,
-        )
-        .map_err(map_chat_error)?;
-        print_hook_section(
-            &mut session.stderr,
-            &context_manager.global_config.hooks,
-            HookTrigger::PerPrompt,
-        )
-        .map_err(map_chat_error)?;
+        for trigger in HookTrigger::ALL {
+            print_hook_section(&mut session.stderr, &context_manager.global_config.hooks, trigger)
+                .map_err(map_chat_error)?;
+        }
 
         queue!(
             session.stderr,
@@ -444,18 +662,10 @@ impl HooksArgs {
             style::SetAttribute(Attribute::Reset),
         )?;
 
-        print_hook_section(
-            &mut session.stderr,
-            &context_manager.profile_config.hooks,
-            HookTrigger::ConversationStart,
-        )
-        .map_err(map_chat_error)?;
-        print_hook_section(
-            &mut session.stderr,
-            &context_manager.profile_config.hooks,
-            HookTrigger::PerPrompt,
-        )
-        .map_err(map_chat_error)?;
+        for trigger in HookTrigger::ALL {
+            print_hook_section(&mut session.stderr, &context_manager.profile_config.hooks, trigger)
+                .map_err(map_chat_error)?;
+        }
 
         execute!(
             session.stderr,
@@ -478,12 +688,16 @@ pub enum HooksSubcommand {
     Add {
         /// The name of the hook
         name: String,
-        /// When to trigger the hook, valid options: `per_prompt` or `conversation_start`
-        #[arg(long, value_parser = ["per_prompt", "conversation_start"])]
+        /// When to trigger the hook, valid options: `per_prompt`, `conversation_start`,
+        /// `pre_tool_use`, `post_tool_use` or `turn_end`
+        #[arg(long, value_parser = ["per_prompt", "conversation_start", "pre_tool_use", "post_tool_use", "turn_end"])]
         trigger: String,
         /// Shell command to execute
         #[arg(long, value_parser = clap::value_parser!(String))]
         command: String,
+        /// Glob of the tool names `pre_tool_use` and `post_tool_use` hooks run for, e.g. `fs_write`
+        #[arg(long)]
+        matcher: Option<String>,
         /// Add to global hooks
         #[arg(long)]
         global: bool,
@@ -544,17 +758,20 @@ impl HooksSubcommand {
                 name,
                 trigger,
                 command,
+                matcher,
                 global,
             } => {
-                let trigger = if trigger == "conversation_start" {
-                    HookTrigger::ConversationStart
-                } else {
-                    HookTrigger::PerPrompt
+                let trigger = match trigger.as_str() {
+                    "conversation_start" => HookTrigger::ConversationStart,
+                    "pre_tool_use" => HookTrigger::PreToolUse,
+                    "post_tool_use" => HookTrigger::PostToolUse,
+                    "turn_end" => HookTrigger::TurnEnd,
+                    _ => HookTrigger::PerPrompt,
                 };
 
-                let result = context_manager
-                    .add_hook(os, name.clone(), Hook::new_inline_hook(trigger, command), global)
-                    .await;
+                let mut hook = Hook::new_inline_hook(trigger, command);
+                hook.matcher = matcher;
+                let result = context_manager.add_hook(os, name.clone(), hook, global).await;
                 match result {
                     Ok(_) => {
                         execute!(
@@ -671,18 +888,10 @@ impl HooksSubcommand {
                     style::SetAttribute(Attribute::Reset),
                 )?;
 
-                print_hook_section(
-                    &mut session.stderr,
-                    &context_manager.global_config.hooks,
-                    HookTrigger::ConversationStart,
-                )
-                .map_err(map_chat_error)?;
-                print_hook_section(
-                    &mut session.stderr,
-                    &context_manager.global_config.hooks,
-                    HookTrigger::PerPrompt,
-                )
-                .map_err(map_chat_error)?;
+                for trigger in HookTrigger::ALL {
+                    print_hook_section(&mut session.stderr, &context_manager.global_config.hooks, trigger)
+                        .map_err(map_chat_error)?;
+                }
 
                 // Display profile hooks
                 execute!(
@@ -693,18 +902,10 @@ impl HooksSubcommand {
                     style::SetAttribute(Attribute::Reset),
                 )?;
 
-                print_hook_section(
-                    &mut session.stderr,
-                    &context_manager.profile_config.hooks,
-                    HookTrigger::ConversationStart,
-                )
-                .map_err(map_chat_error)?;
-                print_hook_section(
-                    &mut session.stderr,
-                    &context_manager.profile_config.hooks,
-                    HookTrigger::PerPrompt,
-                )
-                .map_err(map_chat_error)?;
+                for trigger in HookTrigger::ALL {
+                    print_hook_section(&mut session.stderr, &context_manager.profile_config.hooks, trigger)
+                        .map_err(map_chat_error)?;
+                }
                 execute!(session.stderr, style::Print("\n"))?;
             },
         }
@@ -720,6 +921,9 @@ pub fn print_hook_section(output: &mut impl Write, hooks: &HashMap<String, Hook>
     let section = match trigger {
         HookTrigger::ConversationStart => "On Session Start",
         HookTrigger::PerPrompt => "Per User Message",
+        HookTrigger::PreToolUse => "Before Tool Use",
+        HookTrigger::PostToolUse => "After Tool Use",
+        HookTrigger::TurnEnd => "On Turn End",
     };
     let hooks: Vec<(&String, &Hook)> = hooks.iter().filter(|(_, h)| h.trigger == trigger).collect();
 
@@ -754,6 +958,19 @@ pub fn print_hook_section(output: &mut impl Write, hooks: &HashMap<String, Hook>
     Ok(())
 }
 
+/// Truncate hook output to `max_output_size`, marking it as truncated
+fn truncate_output(output: &str, max_output_size: usize) -> String {
+    format!(
+        "{}{}",
+        truncate_safe(output, max_output_size),
+        if output.len() > max_output_size {
+            " ... truncated"
+        } else {
+            ""
+        }
+    )
+}
+
 pub fn map_chat_error(e: ErrReport) -> ChatError {
     ChatError::Custom(e.to_string().into())
 }
@@ -903,6 +1120,160 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_run_hooks_skips_event_hooks() -> Result<()> {
+        let os = Os::new().await.unwrap();
+        let mut manager = create_test_context_manager(None).await?;
+        let hook1 = Hook::new_inline_hook(HookTrigger::PerPrompt, "echo test".to_string());
+        let hook2 = Hook::new_inline_hook(HookTrigger::PreToolUse, "echo test".to_string());
+
+        manager.add_hook(&os, "hook1".to_string(), hook1, false).await?;
+        manager.add_hook(&os, "hook2".to_string(), hook2, false).await?;
+
+        let results = manager.run_hooks(&mut vec![]).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.name, "hook1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hook_event_serialization() {
+        let input = serde_json::json!({ "path": "src/main.rs" });
+        let event = HookEvent::PreToolUse {
+            tool_name: "fs_write",
+            tool_input: &input,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "hook_event": "pre_tool_use",
+                "tool_name": "fs_write",
+                "tool_input": { "path": "src/main.rs" },
+            })
+        );
+
+        let event = HookEvent::TurnEnd {
+            assistant_response: "done",
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({ "hook_event": "turn_end", "assistant_response": "done" })
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_event_hooks() {
+        let executor = HookExecutor::new();
+        let input = serde_json::json!({ "path": "migrations/001.sql" });
+        let event = HookEvent::PreToolUse {
+            tool_name: "fs_write",
+            tool_input: &input,
+        };
+
+        let hook = |name: &str, command: &str| {
+            let mut hook = Hook::new_inline_hook(HookTrigger::PreToolUse, command.to_string());
+            hook.name = name.to_string();
+            hook
+        };
+
+        let json = hook(
+            "json",
+            r#"echo '{"decision": "deny", "reason": "no migrations", "additional_context": "see docs"}'"#,
+        );
+        let exit_code = hook(
+            "exit_code",
+            "grep -q migrations/ && echo 'no edits to migrations' >&2 && exit 2",
+        );
+        let context = hook("context", "echo formatted");
+        let silent = hook("silent", "cat > /dev/null");
+        let mut other_tool = hook("other_tool", "exit 1");
+        other_tool.matcher = Some("execute_*".to_string());
+        let mut matching_tool = hook("matching_tool", "echo matched");
+        matching_tool.matcher = Some("fs_*".to_string());
+        let other_trigger = Hook::new_inline_hook(HookTrigger::PostToolUse, "exit 1".to_string());
+
+        let hooks = vec![
+            &json,
+            &exit_code,
+            &context,
+            &silent,
+            &other_tool,
+            &matching_tool,
+            &other_trigger,
+        ];
+        let mut output = vec![];
+        let results = executor.run_event_hooks(hooks, &event, &mut output).await.unwrap();
+        let results = results
+            .into_iter()
+            .map(|(hook, response)| (hook.name, response))
+            .collect::<Vec<_>>();
+
+        assert_eq!(results, vec![
+            ("json".to_string(), HookResponse {
+                decision: Some(HookDecision::Deny),
+                reason: Some("no migrations".to_string()),
+                additional_context: Some("see docs".to_string()),
+            }),
+            ("exit_code".to_string(), HookResponse {
+                decision: Some(HookDecision::Deny),
+                reason: Some("no edits to migrations".to_string()),
+                additional_context: None,
+            }),
+            ("context".to_string(), HookResponse {
+                additional_context: Some("formatted".to_string()),
+                ..Default::default()
+            }),
+            ("silent".to_string(), HookResponse::default()),
+            ("matching_tool".to_string(), HookResponse {
+                additional_context: Some("matched".to_string()),
+                ..Default::default()
+            }),
+        ]);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("json"));
+        assert!(output.contains("no edits to migrations"));
+    }
+
+    #[tokio::test]
+    async fn test_run_event_hooks_timeout() {
+        let executor = HookExecutor::new();
+        let mut hook = Hook::new_inline_hook(HookTrigger::TurnEnd, "sleep 2".to_string());
+        hook.timeout_ms = 100;
+
+        let event = HookEvent::TurnEnd {
+            assistant_response: "done",
+        };
+        let mut output = vec![];
+        let results = executor
+            .run_event_hooks(vec![&hook], &event, &mut output)
+            .await
+            .unwrap();
+
+        // Hooks that fail to run don't deny
+        assert!(results.is_empty());
+        assert!(String::from_utf8(output).unwrap().contains("timed out"));
+
+        // Unless they guard tool uses
+        let mut hook = Hook::new_inline_hook(HookTrigger::PreToolUse, "sleep 2".to_string());
+        hook.timeout_ms = 100;
+        let input = serde_json::json!({ "command": "ls" });
+        let event = HookEvent::PreToolUse {
+            tool_name: "execute_bash",
+            tool_input: &input,
+        };
+        let mut output = vec![];
+        let results = executor
+            .run_event_hooks(vec![&hook], &event, &mut output)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].1.is_denied());
+        assert!(String::from_utf8(output).unwrap().contains("denying the tool use"));
+    }
+
     #[test]
     fn test_hook_creation() {
         let command = "echo 'hello'";
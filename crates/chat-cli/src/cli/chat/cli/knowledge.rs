@@ -8,8 +8,10 @@ use crossterm::style::{
 };
 use eyre::Result;
 use semantic_search_client::{
+    IndexingOptions,
     KnowledgeContext,
     OperationStatus,
+    SearchFilter,
     SystemStatus,
 };
 
@@ -29,12 +31,45 @@ pub enum KnowledgeSubcommand {
     /// Display the knowledge base contents
     Show,
     /// Add a file or directory to knowledge base
-    Add { path: String },
+    Add {
+        path: String,
+        /// Only index the files matching these glob patterns
+        #[arg(long)]
+        include: Vec<String>,
+        /// Skip the files and directories matching these glob patterns
+        #[arg(long)]
+        exclude: Vec<String>,
+        /// Re-index changed files in the background
+        #[arg(long)]
+        watch: bool,
+    },
+    /// Search the knowledge base
+    Search {
+        /// The text to search for
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Only search the context with this ID or name (can be repeated)
+        #[arg(long = "context")]
+        contexts: Vec<String>,
+        /// Only search the files whose path matches this glob pattern
+        #[arg(long)]
+        path: Option<String>,
+        /// Only search the files of this type (e.g. markdown) or language (e.g. rs)
+        #[arg(long)]
+        file_type: Option<String>,
+        /// Only search the files modified after this date (e.g. 2025-01-31) or timestamp
+        #[arg(long)]
+        modified_after: Option<String>,
+    },
     /// Remove specified knowledge context by path
     #[command(alias = "rm")]
     Remove { path: String },
     /// Update a file or directory in knowledge base
     Update { path: String },
+    /// Re-index the changed files of a directory in knowledge base in the background
+    Watch { path: String },
+    /// Stop re-indexing the changed files of a directory in knowledge base
+    Unwatch { path: String },
     /// Remove all knowledge contexts
     Clear,
     /// Show background operation status
@@ -46,6 +81,9 @@ pub enum KnowledgeSubcommand {
     },
 }
 
+/// Number of lines of text shown for every search result
+const SEARCH_PREVIEW_LINES: usize = 4;
+
 #[derive(Debug)]
 enum OperationResult {
     Success(String),
@@ -98,9 +136,42 @@ impl KnowledgeSubcommand {
                     Err(e) => OperationResult::Error(format!("Failed to show contexts: {}", e)),
                 }
             },
-            KnowledgeSubcommand::Add { path } => Self::handle_add(os, path).await,
+            KnowledgeSubcommand::Add {
+                path,
+                include,
+                exclude,
+                watch,
+            } => {
+                let options = IndexingOptions {
+                    include: include.clone(),
+                    exclude: exclude.clone(),
+                    watch: *watch,
+                };
+                Self::handle_add(os, path, options).await
+            },
+            KnowledgeSubcommand::Search {
+                query,
+                contexts,
+                path,
+                file_type,
+                modified_after,
+            } => {
+                let modified_after = match modified_after.as_deref().map(SearchFilter::parse_time).transpose() {
+                    Ok(modified_after) => modified_after,
+                    Err(e) => return OperationResult::Error(e.to_string()),
+                };
+                let filter = SearchFilter {
+                    contexts: contexts.clone(),
+                    path: path.clone(),
+                    file_type: file_type.clone(),
+                    modified_after,
+                };
+                Self::handle_search(&query.join(" "), &filter).await
+            },
             KnowledgeSubcommand::Remove { path } => Self::handle_remove(os, path).await,
             KnowledgeSubcommand::Update { path } => Self::handle_update(os, path).await,
+            KnowledgeSubcommand::Watch { path } => Self::handle_watch(os, path, true).await,
+            KnowledgeSubcommand::Unwatch { path } => Self::handle_watch(os, path, false).await,
             KnowledgeSubcommand::Clear => Self::handle_clear(session).await,
             KnowledgeSubcommand::Status => Self::handle_status().await,
             KnowledgeSubcommand::Cancel { operation_id } => Self::handle_cancel(operation_id.as_deref()).await,
@@ -180,6 +251,26 @@ impl KnowledgeSubcommand {
             queue!(session.stderr, style::Print(format!("   Source: {}\n", path)))?;
         }
 
+        let indexing = &context.indexing;
+        if !indexing.include.is_empty() {
+            queue!(
+                session.stderr,
+                style::Print(format!("   Include: {}\n", indexing.include.join(", ")))
+            )?;
+        }
+        if !indexing.exclude.is_empty() {
+            queue!(
+                session.stderr,
+                style::Print(format!("   Exclude: {}\n", indexing.exclude.join(", ")))
+            )?;
+        }
+        if indexing.watch {
+            queue!(
+                session.stderr,
+                style::Print("   Watched: changed files are re-indexed in the background\n")
+            )?;
+        }
+
         queue!(
             session.stderr,
             style::Print("   Items: "),
@@ -210,14 +301,14 @@ impl KnowledgeSubcommand {
     }
 
     /// Handle add operation
-    async fn handle_add(os: &Os, path: &str) -> OperationResult {
+    async fn handle_add(os: &Os, path: &str, options: IndexingOptions) -> OperationResult {
         match Self::validate_and_sanitize_path(os, path) {
             Ok(sanitized_path) => {
                 let async_knowledge_store = KnowledgeStore::get_async_instance().await;
                 let mut store = async_knowledge_store.lock().await;
 
                 // Use the async add method which is fire-and-forget
-                match store.add(path, &sanitized_path).await {
+                match store.add_with_options(path, &sanitized_path, options).await {
                     Ok(message) => OperationResult::Info(message),
                     Err(e) => OperationResult::Error(format!("Failed to add to knowledge base: {}", e)),
                 }
@@ -226,6 +317,41 @@ impl KnowledgeSubcommand {
         }
     }
 
+    /// Handle search operation
+    async fn handle_search(query: &str, filter: &SearchFilter) -> OperationResult {
+        let async_knowledge_store = KnowledgeStore::get_async_instance().await;
+        let store = async_knowledge_store.lock().await;
+
+        let results = match store.search(query, filter).await {
+            Ok(results) => results,
+            Err(e) => return OperationResult::Error(format!("Search failed: {}", e)),
+        };
+        if results.is_empty() {
+            return OperationResult::Warning("No matching entries found in knowledge base".to_string());
+        }
+
+        let mut lines = vec![format!("🔍 {} results for '{}':", results.len(), query)];
+        for result in &results {
+            let title = match (result.location(), result.symbol()) {
+                (Some(location), Some(symbol)) => format!("{} ({})", location, symbol),
+                (Some(location), None) => location,
+                (None, _) => "Text".to_string(),
+            };
+            lines.push(format!("\n  📄 {} | score {:.3}", title, result.score));
+
+            let text = result.text().unwrap_or_default();
+            let preview = text.lines().take(SEARCH_PREVIEW_LINES).collect::<Vec<_>>();
+            for line in &preview {
+                lines.push(format!("     {}", line));
+            }
+            if text.lines().count() > preview.len() {
+                lines.push("     ...".to_string());
+            }
+        }
+
+        OperationResult::Info(lines.join("\n"))
+    }
+
     /// Handle remove operation
     async fn handle_remove(os: &Os, path: &str) -> OperationResult {
         let sanitized_path = sanitize_path_tool_arg(os, path);
@@ -258,6 +384,22 @@ impl KnowledgeSubcommand {
         }
     }
 
+    /// Handle watch and unwatch operations
+    async fn handle_watch(os: &Os, path: &str, watch: bool) -> OperationResult {
+        match Self::validate_and_sanitize_path(os, path) {
+            Ok(sanitized_path) => {
+                let async_knowledge_store = KnowledgeStore::get_async_instance().await;
+                let mut store = async_knowledge_store.lock().await;
+
+                match store.set_watch_by_path(&sanitized_path, watch).await {
+                    Ok(message) => OperationResult::Info(message),
+                    Err(e) => OperationResult::Error(format!("Failed to update watch: {}", e)),
+                }
+            },
+            Err(e) => OperationResult::Error(e),
+        }
+    }
+
     /// Handle clear operation
     async fn handle_clear(session: &mut ChatSession) -> OperationResult {
         // Require confirmation
@@ -1,7 +1,9 @@
+pub mod checkpoints;
 pub mod clear;
 pub mod compact;
 pub mod context;
 pub mod editor;
+pub mod export;
 pub mod hooks;
 pub mod knowledge;
 pub mod mcp;
@@ -9,15 +11,22 @@ pub mod model;
 pub mod persist;
 pub mod profile;
 pub mod prompts;
+pub mod resources;
+pub mod sessions;
 pub mod subscribe;
 pub mod tools;
 pub mod usage;
 
+use checkpoints::{
+    CheckpointsSubcommand,
+    UndoArgs,
+};
 use clap::Parser;
 use clear::ClearArgs;
 use compact::CompactArgs;
 use context::ContextSubcommand;
 use editor::EditorArgs;
+use export::ExportArgs;
 use hooks::HooksArgs;
 use knowledge::KnowledgeSubcommand;
 use mcp::McpArgs;
@@ -25,6 +34,8 @@ use model::ModelArgs;
 use persist::PersistSubcommand;
 use profile::ProfileSubcommand;
 use prompts::PromptsArgs;
+use resources::ResourcesArgs;
+use sessions::SessionsArgs;
 use tools::ToolsArgs;
 
 use crate::cli::chat::cli::subscribe::SubscribeArgs;
@@ -62,12 +73,19 @@ pub enum SlashCommand {
     PromptEditor(EditorArgs),
     /// Summarize the conversation to free up context space
     Compact(CompactArgs),
+    /// Revert the file changes made during the most recent turn that changed files
+    Undo(UndoArgs),
+    /// View and restore checkpoints of the file changes made in this session
+    #[command(subcommand)]
+    Checkpoints(CheckpointsSubcommand),
     /// View and manage tools and permissions
     Tools(ToolsArgs),
     /// Create a new Github issue or make a feature request
     Issue(issue::IssueArgs),
     /// View and retrieve prompts
     Prompts(PromptsArgs),
+    /// View, read and attach resources
+    Resources(ResourcesArgs),
     /// View and manage context hooks
     Hooks(HooksArgs),
     /// Show current session's context window usage
@@ -78,6 +96,10 @@ pub enum SlashCommand {
     Model(ModelArgs),
     /// Upgrade to a Q Developer Pro subscription for increased query limits
     Subscribe(SubscribeArgs),
+    /// List, switch between and name the saved sessions of this directory
+    Sessions(SessionsArgs),
+    /// Export the conversation as a Markdown or HTML transcript to share
+    Export(ExportArgs),
     #[command(flatten)]
     Persist(PersistSubcommand),
     // #[command(flatten)]
@@ -94,7 +116,9 @@ impl SlashCommand {
             Self::Knowledge(subcommand) => subcommand.execute(os, session).await,
             Self::PromptEditor(args) => args.execute(session).await,
             Self::Compact(args) => args.execute(os, session).await,
-            Self::Tools(args) => args.execute(session).await,
+            Self::Undo(args) => args.execute(os, session).await,
+            Self::Checkpoints(subcommand) => subcommand.execute(os, session).await,
+            Self::Tools(args) => args.execute(os, session).await,
             Self::Issue(args) => {
                 if let Err(err) = args.execute(os).await {
                     return Err(ChatError::Custom(err.to_string().into()));
@@ -105,11 +129,14 @@ impl SlashCommand {
                 })
             },
             Self::Prompts(args) => args.execute(session).await,
+            Self::Resources(args) => args.execute(session).await,
             Self::Hooks(args) => args.execute(os, session).await,
             Self::Usage(args) => args.execute(os, session).await,
             Self::Mcp(args) => args.execute(session).await,
-            Self::Model(args) => args.execute(session).await,
+            Self::Model(args) => args.execute(os, session).await,
             Self::Subscribe(args) => args.execute(os, session).await,
+            Self::Sessions(args) => args.execute(os, session).await,
+            Self::Export(args) => args.execute(os, session).await,
             Self::Persist(subcommand) => subcommand.execute(os, session).await,
             // Self::Root(subcommand) => {
             //     if let Err(err) = subcommand.execute(os, database, telemetry).await {
@@ -1,4 +1,7 @@
-use clap::Args;
+use clap::{
+    Args,
+    Subcommand,
+};
 use crossterm::style::{
     self,
     Color,
@@ -7,8 +10,16 @@ use crossterm::{
     execute,
     queue,
 };
-use dialoguer::Select;
+use dialoguer::{
+    Password,
+    Select,
+};
 
+use crate::api_client::backend::{
+    ModelBackend,
+    ModelBackendConfig,
+    api_key_secret_key,
+};
 use crate::auth::builder_id::{
     BuilderIdToken,
     TokenType,
@@ -42,26 +53,71 @@ pub const MODEL_OPTIONS: [ModelOption; 3] = [
 
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
-pub struct ModelArgs;
+#[command(
+    before_long_help = "Profiles can send chat requests to an OpenAI compatible server instead of Amazon Q by setting
+\"model_backend\" in their context.json, in which case the models listed there are offered:
+
+  \"model_backend\": {
+    \"type\": \"openai_compatible\",
+    \"base_url\": \"http://localhost:8000/v1\",
+    \"models\": [\"qwen2.5-coder-32b-instruct\"]
+  }"
+)]
+pub struct ModelArgs {
+    #[command(subcommand)]
+    subcommand: Option<ModelSubcommand>,
+}
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum ModelSubcommand {
+    /// Store the API key sent to the model backend of the current profile
+    SetApiKey,
+    /// Remove the stored API key of the model backend of the current profile
+    ClearApiKey,
+}
 
 impl ModelArgs {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
-        Ok(select_model(session)?.unwrap_or(ChatState::PromptUser {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        match self.subcommand {
+            Some(ModelSubcommand::SetApiKey) => set_api_key(os, session).await?,
+            Some(ModelSubcommand::ClearApiKey) => clear_api_key(os, session).await?,
+            None => {
+                if let Some(state) = select_model(os, session)? {
+                    return Ok(state);
+                }
+            },
+        }
+
+        Ok(ChatState::PromptUser {
             skip_printing_tools: false,
-        }))
+        })
     }
 }
 
-pub fn select_model(session: &mut ChatSession) -> Result<Option<ChatState>, ChatError> {
+pub fn select_model(os: &Os, session: &mut ChatSession) -> Result<Option<ChatState>, ChatError> {
     queue!(session.stderr, style::Print("\n"))?;
+    // Pairs of (name, model id)
+    let options: Vec<(String, String)> = match os.client.model_backend() {
+        Some(model_backend) => model_backend
+            .models()
+            .iter()
+            .map(|model| (model.clone(), model.clone()))
+            .collect(),
+        None => MODEL_OPTIONS
+            .iter()
+            .map(|opt| (opt.name.to_owned(), opt.model_id.to_owned()))
+            .collect(),
+    };
+
     let active_model_id = session.conversation.model.as_deref();
-    let labels: Vec<String> = MODEL_OPTIONS
+    let labels: Vec<String> = options
         .iter()
-        .map(|opt| {
-            if (opt.model_id.is_empty() && active_model_id.is_none()) || Some(opt.model_id) == active_model_id {
-                format!("{} (active)", opt.name)
+        .map(|(name, model_id)| {
+            if (model_id.is_empty() && active_model_id.is_none()) || Some(model_id.as_str()) == active_model_id {
+                format!("{} (active)", name)
             } else {
-                opt.name.to_owned()
+                name.clone()
             }
         })
         .collect();
@@ -87,14 +143,13 @@ pub fn select_model(session: &mut ChatSession) -> Result<Option<ChatState>, Chat
     queue!(session.stderr, style::ResetColor)?;
 
     if let Some(index) = selection {
-        let selected = &MODEL_OPTIONS[index];
-        let model_id_str = selected.model_id.to_string();
-        session.conversation.model = Some(model_id_str);
+        let (name, model_id) = &options[index];
+        session.conversation.model = Some(model_id.clone());
 
         queue!(
             session.stderr,
             style::Print("\n"),
-            style::Print(format!(" Using {}\n\n", selected.name)),
+            style::Print(format!(" Using {}\n\n", name)),
             style::ResetColor,
             style::SetForegroundColor(Color::Reset),
             style::SetBackgroundColor(Color::Reset),
@@ -108,6 +163,113 @@ pub fn select_model(session: &mut ChatSession) -> Result<Option<ChatState>, Chat
     }))
 }
 
+/// Points the API client at the model backend configured by the current profile, or back at
+/// Amazon Q if there is none, and makes sure the selected model is one the backend serves.
+///
+/// A misconfigured backend is reported and Amazon Q is used instead.
+pub async fn sync_model_backend(os: &Os, session: &mut ChatSession) -> Result<(), ChatError> {
+    let profile = current_profile(session);
+    let config = session
+        .conversation
+        .context_manager
+        .as_ref()
+        .and_then(|context_manager| context_manager.profile_config.model_backend.clone());
+    let model_backend = match config {
+        Some(config) => match load_model_backend(os, &profile, &config).await {
+            Ok(model_backend) => Some(model_backend),
+            Err(err) => {
+                execute!(
+                    session.stderr,
+                    style::SetForegroundColor(Color::Yellow),
+                    style::Print(format!(
+                        "\nThe model backend of profile {profile} is misconfigured, using Amazon Q instead: {err}\n\n"
+                    )),
+                    style::SetForegroundColor(Color::Reset)
+                )?;
+                None
+            },
+        },
+        None => None,
+    };
+
+    let active_model_id = session.conversation.model.as_deref();
+    match &model_backend {
+        Some(model_backend) => {
+            if !active_model_id.is_some_and(|id| model_backend.models().iter().any(|model| model == id)) {
+                session.conversation.model = model_backend.models().first().cloned();
+            }
+        },
+        None => {
+            if active_model_id.is_some_and(|id| !MODEL_OPTIONS.iter().any(|opt| opt.model_id == id)) {
+                session.conversation.model = Some(default_model_id(os).await.to_owned());
+            }
+        },
+    }
+
+    os.telemetry.set_paused(model_backend.is_some());
+    os.client.set_model_backend(model_backend);
+    Ok(())
+}
+
+async fn load_model_backend(os: &Os, profile: &str, config: &ModelBackendConfig) -> Result<ModelBackend, String> {
+    let api_key = os
+        .database
+        .get_secret(&api_key_secret_key(profile))
+        .await
+        .map_err(|err| format!("failed to read the API key: {err}"))?;
+    ModelBackend::new(config, api_key).map_err(|err| err.to_string())
+}
+
+fn current_profile(session: &ChatSession) -> String {
+    session.conversation.context_manager.as_ref().map_or_else(
+        || "default".to_owned(),
+        |context_manager| context_manager.current_profile.clone(),
+    )
+}
+
+async fn set_api_key(os: &Os, session: &mut ChatSession) -> Result<(), ChatError> {
+    let profile = current_profile(session);
+    let api_key = match Password::with_theme(&crate::util::dialoguer_theme())
+        .with_prompt(format!("API key for the model backend of profile {profile}"))
+        .interact()
+    {
+        Ok(api_key) => api_key,
+        Err(dialoguer::Error::IO(ref e)) if e.kind() == std::io::ErrorKind::Interrupted => return Ok(()),
+        Err(e) => return Err(ChatError::Custom(format!("Failed to read the API key: {e}").into())),
+    };
+
+    os.database
+        .set_secret(&api_key_secret_key(&profile), &api_key)
+        .await
+        .map_err(|err| ChatError::Custom(format!("Failed to store the API key: {err}").into()))?;
+    sync_model_backend(os, session).await?;
+
+    execute!(
+        session.stderr,
+        style::SetForegroundColor(Color::Green),
+        style::Print(format!("\nStored the API key for profile {profile}\n\n")),
+        style::SetForegroundColor(Color::Reset)
+    )?;
+    Ok(())
+}
+
+async fn clear_api_key(os: &Os, session: &mut ChatSession) -> Result<(), ChatError> {
+    let profile = current_profile(session);
+    os.database
+        .delete_secret(&api_key_secret_key(&profile))
+        .await
+        .map_err(|err| ChatError::Custom(format!("Failed to remove the API key: {err}").into()))?;
+    sync_model_backend(os, session).await?;
+
+    execute!(
+        session.stderr,
+        style::SetForegroundColor(Color::Green),
+        style::Print(format!("\nRemoved the API key for profile {profile}\n\n")),
+        style::SetForegroundColor(Color::Reset)
+    )?;
+    Ok(())
+}
+
 /// Returns Claude 3.7 for: Amazon IDC users, FRA region users
 /// Returns Claude 4.0 for: Builder ID users, other regions
 pub async fn default_model_id(os: &Os) -> &'static str {
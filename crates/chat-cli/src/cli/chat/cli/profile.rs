@@ -6,6 +6,8 @@ use crossterm::style::{
 };
 use tracing::warn;
 
+use crate::api_client::backend::api_key_secret_key;
+use crate::cli::chat::cli::model::sync_model_backend;
 use crate::cli::chat::{
     ChatError,
     ChatSession,
@@ -56,6 +58,8 @@ impl ProfileSubcommand {
             };
         }
 
+        let changes_profile = matches!(self, Self::Create { .. } | Self::Set { .. } | Self::Rename { .. });
+
         match self {
             Self::List => {
                 let profiles = match context_manager.list_profiles(os).await {
@@ -134,6 +138,19 @@ impl ProfileSubcommand {
             Self::Rename { old_name, new_name } => {
                 match context_manager.rename_profile(os, &old_name, &new_name).await {
                     Ok(_) => {
+                        if let Ok(Some(api_key)) = os.database.get_secret(&api_key_secret_key(&old_name)).await {
+                            os.database
+                                .set_secret(&api_key_secret_key(&new_name), &api_key.0)
+                                .await
+                                .map_err(|e| warn!(?e, "failed to move the model backend API key"))
+                                .ok();
+                            os.database
+                                .delete_secret(&api_key_secret_key(&old_name))
+                                .await
+                                .map_err(|e| warn!(?e, "failed to delete the model backend API key"))
+                                .ok();
+                        }
+
                         execute!(
                             session.stderr,
                             style::SetForegroundColor(Color::Green),
@@ -146,6 +163,10 @@ impl ProfileSubcommand {
             },
         }
 
+        if changes_profile {
+            sync_model_backend(os, session).await?;
+        }
+
         Ok(ChatState::PromptUser {
             skip_printing_tools: true,
         })
@@ -0,0 +1,470 @@
+use clap::{
+    Args,
+    Subcommand,
+};
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+use crossterm::{
+    execute,
+    queue,
+};
+use thiserror::Error;
+
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::mcp_client::{
+    ResourceInfo,
+    ResourceReadContents,
+    ResourceReadResult,
+};
+
+const RESOURCE_ENTRY_START_HEADER: &str = "--- RESOURCE ENTRY BEGIN ---\n";
+const RESOURCE_ENTRY_END_HEADER: &str = "--- RESOURCE ENTRY END ---\n\n";
+
+#[derive(Debug, Error)]
+pub enum ResourceError {
+    #[error("Resource {0} does not exist")]
+    ResourceNotFound(String),
+    #[error("Resource {0} is offered by more than one server. Use one of the following {1}")]
+    AmbiguousResource(String, String),
+    #[error("Missing client for server {0}")]
+    MissingClient(String),
+    #[error("Result field missing from resources/read response")]
+    MissingResult,
+    #[error("Server returned an error: {0}")]
+    Server(String),
+    #[error(transparent)]
+    Serialization(#[from] serde_json::Error),
+    #[error(transparent)]
+    General(#[from] eyre::Report),
+}
+
+/// The contents of a resource that is to be included in the next user message
+#[derive(Debug, Clone)]
+pub struct AttachedResource {
+    pub server_name: String,
+    pub uri: String,
+    pub result: ResourceReadResult,
+}
+
+impl AttachedResource {
+    /// Formats the resource in the same fashion context entries are formatted, such that it can
+    /// be appended to a user message.
+    pub fn to_context_entry(&self) -> String {
+        let mut entry = format!("{}[@{}:{}]\n", RESOURCE_ENTRY_START_HEADER, self.server_name, self.uri);
+        for contents in &self.result.contents {
+            match contents {
+                ResourceReadContents::Text { text, .. } => {
+                    entry.push_str(text);
+                    entry.push('\n');
+                },
+                ResourceReadContents::Blob { uri, mime_type, .. } => {
+                    entry.push_str(&format!(
+                        "[binary contents of {} ({}) omitted]\n",
+                        uri,
+                        mime_type.as_deref().unwrap_or("unknown type")
+                    ));
+                },
+            }
+        }
+        entry.push_str(RESOURCE_ENTRY_END_HEADER);
+        entry
+    }
+}
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+#[command(color = clap::ColorChoice::Always,
+    before_long_help = color_print::cstr!{"Resources are pieces of data (files, database records, API responses, etc.) exposed by the mcp servers you have installed and configured.
+
+To include a resource in your next message, mention it directly in your prompt:
+  <em>@<<server name>>:<<resource uri>></em>                  <black!>Attach the resource specified</black!>
+Or if you prefer the long way:
+  <em>/resources attach <<server name>>:<<resource uri>></em>  <black!>Attach the resource specified</black!>
+
+Attached resources are watched for updates if their server supports subscriptions. The updated contents are included in your next message."}
+)]
+pub struct ResourcesArgs {
+    #[command(subcommand)]
+    subcommand: Option<ResourcesSubcommand>,
+}
+
+impl ResourcesArgs {
+    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        match self.subcommand {
+            Some(subcommand @ (ResourcesSubcommand::Read { .. } | ResourcesSubcommand::Attach { .. })) => {
+                subcommand.execute(session).await
+            },
+            Some(ResourcesSubcommand::List { search_word }) => list_resources(session, search_word).await,
+            None => list_resources(session, None).await,
+        }
+    }
+}
+
+#[deny(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Subcommand)]
+pub enum ResourcesSubcommand {
+    /// List available resources from all servers or those matching the search word
+    List { search_word: Option<String> },
+    /// Print the contents of a resource
+    Read {
+        /// The resource, as <server name>:<resource uri>
+        resource: String,
+    },
+    /// Include the contents of a resource in the next message
+    Attach {
+        /// The resource, as <server name>:<resource uri>
+        resource: String,
+    },
+}
+
+impl ResourcesSubcommand {
+    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        let (resource, attach) = match self {
+            ResourcesSubcommand::Read { resource } => (resource, false),
+            ResourcesSubcommand::Attach { resource } => (resource, true),
+            ResourcesSubcommand::List { .. } => unreachable!("List has already been parsed out at this point"),
+        };
+
+        let tool_manager = &session.conversation.tool_manager;
+        let result = match tool_manager.resolve_resource(&resource).await {
+            Ok((server_name, uri)) => {
+                tool_manager
+                    .read_resource(&server_name, &uri)
+                    .await
+                    .map(|result| AttachedResource {
+                        server_name,
+                        uri,
+                        result,
+                    })
+            },
+            Err(e) => Err(e),
+        };
+        let attached = match result {
+            Ok(attached) => attached,
+            Err(e) => {
+                queue_resource_error(session, &e)?;
+                execute!(session.stderr, style::Print("\n"))?;
+                return Ok(ChatState::PromptUser {
+                    skip_printing_tools: true,
+                });
+            },
+        };
+
+        if attach {
+            let subscribed = session
+                .conversation
+                .tool_manager
+                .subscribe_resource(&attached.server_name, &attached.uri)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to subscribe to {}: {:?}", attached.uri, e);
+                    false
+                });
+            if subscribed {
+                session
+                    .watched_resources
+                    .insert((attached.server_name.clone(), attached.uri.clone()));
+            }
+            queue!(
+                session.stderr,
+                style::Print("\n"),
+                style::SetForegroundColor(Color::Green),
+                style::Print(format!("Attached @{}:{}", attached.server_name, attached.uri)),
+                style::SetForegroundColor(Color::Reset),
+                style::Print(" - it will be included in your next message"),
+                style::Print(if subscribed {
+                    ", and again whenever it is updated.\n"
+                } else {
+                    ".\n"
+                }),
+            )?;
+            session.pending_resources.push(attached);
+        } else {
+            queue!(session.stderr, style::Print("\n"))?;
+            for contents in &attached.result.contents {
+                match contents {
+                    ResourceReadContents::Text { text, .. } => {
+                        queue!(session.stderr, style::Print(text), style::Print("\n"))?;
+                    },
+                    ResourceReadContents::Blob { uri, mime_type, .. } => {
+                        queue!(
+                            session.stderr,
+                            style::SetForegroundColor(Color::DarkGrey),
+                            style::Print(format!(
+                                "[binary contents of {} ({})]\n",
+                                uri,
+                                mime_type.as_deref().unwrap_or("unknown type")
+                            )),
+                            style::SetForegroundColor(Color::Reset),
+                        )?;
+                    },
+                }
+            }
+        }
+
+        execute!(session.stderr, style::Print("\n"))?;
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+async fn list_resources(session: &mut ChatSession, search_word: Option<String>) -> Result<ChatState, ChatError> {
+    let resources = session.conversation.tool_manager.resources.read().await.clone();
+    let mut resources_by_server = resources
+        .iter()
+        .map(|(server_name, list)| {
+            let mut list = list
+                .iter()
+                .filter(|r| {
+                    search_word
+                        .as_deref()
+                        .is_none_or(|w| r.uri.contains(w) || r.name.contains(w))
+                })
+                .collect::<Vec<&ResourceInfo>>();
+            list.sort_by_key(|r| &r.uri);
+            (server_name, list)
+        })
+        .filter(|(_, list)| !list.is_empty())
+        .collect::<Vec<_>>();
+    resources_by_server.sort_by_key(|(server_name, _)| server_name.as_str());
+
+    queue!(
+        session.stderr,
+        style::Print("\n"),
+        style::SetAttribute(Attribute::Bold),
+        style::Print("Usage: "),
+        style::SetAttribute(Attribute::Reset),
+        style::Print("You can attach a resource to your message by typing "),
+        style::SetAttribute(Attribute::Bold),
+        style::SetForegroundColor(Color::Green),
+        style::Print("'@<server name>:<resource uri>'"),
+        style::SetForegroundColor(Color::Reset),
+        style::SetAttribute(Attribute::Reset),
+        style::Print("\n\n"),
+    )?;
+
+    if resources_by_server.is_empty() {
+        queue!(
+            session.stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print("No resources found\n"),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+    }
+
+    for (i, (server_name, list)) in resources_by_server.iter().enumerate() {
+        if i > 0 {
+            queue!(session.stderr, style::Print("\n"))?;
+        }
+        queue!(
+            session.stderr,
+            style::SetAttribute(Attribute::Bold),
+            style::Print(server_name),
+            style::Print(" (MCP):"),
+            style::SetAttribute(Attribute::Reset),
+            style::Print("\n"),
+        )?;
+        for resource in list {
+            queue!(
+                session.stderr,
+                style::Print("- "),
+                style::Print(&resource.uri),
+                style::SetForegroundColor(Color::DarkGrey),
+                style::Print(format!(" {}", resource.name)),
+            )?;
+            if let Some(description) = &resource.description {
+                queue!(session.stderr, style::Print(format!(": {}", description)))?;
+            }
+            queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::Reset),
+                style::Print("\n")
+            )?;
+        }
+    }
+
+    execute!(session.stderr, style::Print("\n"))?;
+
+    Ok(ChatState::PromptUser {
+        skip_printing_tools: true,
+    })
+}
+
+fn queue_resource_error(session: &mut ChatSession, err: &ResourceError) -> Result<(), ChatError> {
+    match err {
+        ResourceError::AmbiguousResource(uri, alt_msg) => {
+            queue!(
+                session.stderr,
+                style::Print("\n"),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print("Resource "),
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(uri),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(" is ambiguous. Use one of the following "),
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(alt_msg),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        },
+        ResourceError::ResourceNotFound(uri) => {
+            queue!(
+                session.stderr,
+                style::Print("\n"),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print("Resource "),
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(uri),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(" not found. Use "),
+                style::SetForegroundColor(Color::Cyan),
+                style::Print("/resources list"),
+                style::SetForegroundColor(Color::Yellow),
+                style::Print(" to see available resources.\n"),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        },
+        e => {
+            queue!(
+                session.stderr,
+                style::Print("\n"),
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("Failed to read resource: {}\n", e)),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        },
+    }
+    Ok(())
+}
+
+/// Finds all mentions of the form `@server:uri` in `input` whose server is one of
+/// `server_names`, returning them as pairs of server name and uri.
+pub fn parse_resource_mentions(input: &str, server_names: &[&str]) -> Vec<(String, String)> {
+    input
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('@'))
+        .filter_map(|mention| {
+            let mention = mention.trim_end_matches([',', '.', ';', '!', '?', ')']);
+            let (server_name, uri) = mention.split_once(':')?;
+            (server_names.contains(&server_name) && !uri.is_empty()).then(|| (server_name.to_string(), uri.to_string()))
+        })
+        .fold(Vec::new(), |mut acc, mention| {
+            if !acc.contains(&mention) {
+                acc.push(mention);
+            }
+            acc
+        })
+}
+
+/// Collects the resources that are to be included in the next user message, i.e. those
+/// explicitly attached, those mentioned in `input`, and those being watched that have been
+/// updated since they were last read. Returns the formatted resources, if there are any.
+pub async fn take_resources_for_input(session: &mut ChatSession, input: &str) -> Result<Option<String>, ChatError> {
+    let server_names = session
+        .conversation
+        .tool_manager
+        .clients
+        .keys()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let mut to_read = parse_resource_mentions(input, &server_names);
+    let updated = session.conversation.tool_manager.take_updated_resources().await;
+    for key in updated {
+        if session.watched_resources.contains(&key) && !to_read.contains(&key) {
+            to_read.push(key);
+        }
+    }
+    to_read.retain(|(server_name, uri)| {
+        !session
+            .pending_resources
+            .iter()
+            .any(|r| r.server_name == *server_name && r.uri == *uri)
+    });
+
+    let mut resources = std::mem::take(&mut session.pending_resources);
+    for (server_name, uri) in to_read {
+        match session
+            .conversation
+            .tool_manager
+            .read_resource(&server_name, &uri)
+            .await
+        {
+            Ok(result) => resources.push(AttachedResource {
+                server_name,
+                uri,
+                result,
+            }),
+            Err(e) => {
+                queue!(
+                    session.stderr,
+                    style::SetForegroundColor(Color::Yellow),
+                    style::Print(format!("Failed to read resource @{}:{}: {}\n", server_name, uri, e)),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+            },
+        }
+    }
+
+    if resources.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(resources.iter().fold(String::new(), |mut acc, r| {
+        acc.push_str(&r.to_context_entry());
+        acc
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_mentions() {
+        let servers = ["github", "files"];
+        assert_eq!(
+            parse_resource_mentions("summarize @github:repo://aws/q/README.md please", &servers),
+            vec![("github".to_string(), "repo://aws/q/README.md".to_string())]
+        );
+        assert_eq!(
+            parse_resource_mentions("compare @files:file:///a.txt, @files:file:///b.txt.", &servers),
+            vec![
+                ("files".to_string(), "file:///a.txt".to_string()),
+                ("files".to_string(), "file:///b.txt".to_string())
+            ]
+        );
+        // Unknown servers, prompts, and duplicate mentions
+        assert!(parse_resource_mentions("@unknown:foo @my_prompt arg", &servers).is_empty());
+        assert!(parse_resource_mentions("email me at someone@github:123", &servers).is_empty());
+        assert_eq!(parse_resource_mentions("@files:x @files:x", &servers).len(), 1);
+    }
+
+    #[test]
+    fn test_to_context_entry() {
+        let result = serde_json::from_value::<ResourceReadResult>(serde_json::json!({
+            "contents": [
+                { "uri": "file:///a.txt", "mimeType": "text/plain", "text": "hello" },
+                { "uri": "file:///b.png", "mimeType": "image/png", "blob": "aGVsbG8=" }
+            ]
+        }))
+        .unwrap();
+        let attached = AttachedResource {
+            server_name: "files".to_string(),
+            uri: "file:///".to_string(),
+            result,
+        };
+        let entry = attached.to_context_entry();
+        assert!(entry.starts_with(RESOURCE_ENTRY_START_HEADER));
+        assert!(entry.contains("[@files:file:///]\nhello\n"));
+        assert!(entry.contains("[binary contents of file:///b.png (image/png) omitted]"));
+        assert!(entry.ends_with(RESOURCE_ENTRY_END_HEADER));
+    }
+}
@@ -0,0 +1,238 @@
+use clap::{
+    Args,
+    Subcommand,
+};
+use crossterm::style::{
+    self,
+    Attribute,
+    Color,
+};
+use crossterm::{
+    execute,
+    queue,
+};
+use dialoguer::Select;
+use eyre::{
+    Result,
+    bail,
+};
+
+use crate::cli::chat::cli::model::sync_model_backend;
+use crate::cli::chat::sessions::{
+    find_session,
+    format_session,
+    session_display_name,
+    validate_session_name,
+};
+use crate::cli::chat::{
+    ChatError,
+    ChatSession,
+    ChatState,
+};
+use crate::database::Session;
+use crate::os::Os;
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Args)]
+#[command(
+    before_long_help = "Conversations are saved as sessions of the directory they were started in after every response.
+Any number of sessions can be kept for a directory, and they can be resumed with q chat --resume-id <id|name>, or by
+switching to them with /sessions. Run q chat sessions --help to manage the sessions outside of a chat."
+)]
+pub struct SessionsArgs {
+    #[command(subcommand)]
+    subcommand: Option<SessionsSubcommand>,
+}
+
+#[deny(missing_docs)]
+#[derive(Debug, PartialEq, Subcommand)]
+pub enum SessionsSubcommand {
+    /// List the sessions of this directory
+    List,
+    /// Switch to another session, picked from the sessions of this directory if none is given
+    Switch {
+        /// The id or name of the session
+        session: Option<String>,
+    },
+    /// Name the current session, such that it can be resumed with q chat --resume-id <name>
+    Name {
+        /// The name, which must be unique within this directory
+        name: String,
+    },
+    /// Delete a session
+    Delete {
+        /// The id or name of the session
+        session: String,
+    },
+}
+
+impl SessionsArgs {
+    pub async fn execute(self, os: &mut Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        let result = match self.subcommand {
+            Some(SessionsSubcommand::List) => list_sessions(os, session),
+            Some(SessionsSubcommand::Switch {
+                session: Some(id_or_name),
+            }) => match find_session(os, &id_or_name) {
+                Ok(target) => switch_session(os, session, target).await,
+                Err(err) => Err(err),
+            },
+            Some(SessionsSubcommand::Switch { session: None }) | None => pick_session(os, session).await,
+            Some(SessionsSubcommand::Name { name }) => name_session(os, session, &name),
+            Some(SessionsSubcommand::Delete { session: id_or_name }) => delete_session(os, session, &id_or_name),
+        };
+
+        if let Err(err) = result {
+            execute!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("\n{err}\n\n")),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+
+        Ok(ChatState::PromptUser {
+            skip_printing_tools: true,
+        })
+    }
+}
+
+fn list_sessions(os: &Os, session: &mut ChatSession) -> Result<()> {
+    let cwd = os.env.current_dir()?;
+    let sessions = os.database.list_sessions(Some(&cwd))?;
+    if sessions.is_empty() {
+        queue!(
+            session.stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print("\nNo sessions have been saved in this directory.\n\n"),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+        return Ok(());
+    }
+
+    queue!(
+        session.stderr,
+        style::SetAttribute(Attribute::Bold),
+        style::Print("\nSessions:\n"),
+        style::SetAttribute(Attribute::Reset),
+    )?;
+    for saved in &sessions {
+        queue!(session.stderr, style::Print(format!("  {}", format_session(saved))))?;
+        if saved.id == session.conversation.conversation_id() {
+            queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::Cyan),
+                style::Print(" (current)"),
+                style::SetForegroundColor(Color::Reset),
+            )?;
+        }
+        queue!(session.stderr, style::Print("\n"))?;
+    }
+    execute!(
+        session.stderr,
+        style::SetForegroundColor(Color::DarkGrey),
+        style::Print("\nUse /sessions switch <id|name> to continue one of them.\n\n"),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+    Ok(())
+}
+
+async fn pick_session(os: &mut Os, session: &mut ChatSession) -> Result<()> {
+    let cwd = os.env.current_dir()?;
+    let sessions = os
+        .database
+        .list_sessions(Some(&cwd))?
+        .into_iter()
+        .filter(|saved| saved.id != session.conversation.conversation_id())
+        .collect::<Vec<_>>();
+    if sessions.is_empty() {
+        execute!(
+            session.stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print("\nThere are no other sessions in this directory.\n\n"),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+        return Ok(());
+    }
+
+    queue!(session.stderr, style::Print("\n"))?;
+    let labels = sessions.iter().map(format_session).collect::<Vec<_>>();
+    let selection = match Select::with_theme(&crate::util::dialoguer_theme())
+        .with_prompt("Select a session to switch to")
+        .items(&labels)
+        .default(0)
+        .interact_on_opt(&dialoguer::console::Term::stdout())
+    {
+        Ok(selection) => selection,
+        // Ctrl+C was pressed
+        Err(dialoguer::Error::IO(ref err)) if err.kind() == std::io::ErrorKind::Interrupted => None,
+        Err(err) => bail!("Failed to choose a session: {err}"),
+    };
+
+    match selection.and_then(|i| sessions.into_iter().nth(i)) {
+        Some(target) => switch_session(os, session, target).await,
+        None => {
+            execute!(session.stderr, style::Print("\n"))?;
+            Ok(())
+        },
+    }
+}
+
+async fn switch_session(os: &mut Os, session: &mut ChatSession, target: Session) -> Result<()> {
+    if target.id == session.conversation.conversation_id() {
+        bail!("Session {} is the current session", session_display_name(&target));
+    }
+    let Some(mut conversation) = os.database.get_session_conversation(&target.id)? else {
+        bail!("Session {} has no conversation", session_display_name(&target));
+    };
+
+    conversation.reload_serialized_state(os).await;
+    std::mem::swap(&mut conversation.tool_manager, &mut session.conversation.tool_manager);
+    conversation.update_state(true).await;
+    conversation.enforce_tool_use_history_invariants();
+    session.conversation = conversation;
+    session.tool_uses.clear();
+    session.pending_tool_index = None;
+    sync_model_backend(os, session).await?;
+
+    execute!(
+        session.stderr,
+        style::SetForegroundColor(Color::Green),
+        style::Print(format!("\n✔ Switched to session {}\n\n", session_display_name(&target))),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+    Ok(())
+}
+
+fn name_session(os: &mut Os, session: &mut ChatSession, name: &str) -> Result<()> {
+    validate_session_name(name)?;
+    // The session is only saved once a response has been received, so make sure it exists.
+    os.database.save_session(os.env.current_dir()?, &session.conversation)?;
+    os.database
+        .set_session_name(session.conversation.conversation_id(), Some(name))?;
+
+    execute!(
+        session.stderr,
+        style::SetForegroundColor(Color::Green),
+        style::Print(format!("\n✔ Named this session {name}\n")),
+        style::SetForegroundColor(Color::DarkGrey),
+        style::Print(format!("Resume it with q chat --resume-id {name}\n\n")),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+    Ok(())
+}
+
+fn delete_session(os: &mut Os, session: &mut ChatSession, id_or_name: &str) -> Result<()> {
+    let target = find_session(os, id_or_name)?;
+    if target.id == session.conversation.conversation_id() {
+        bail!("The current session cannot be deleted");
+    }
+    os.database.delete_session(&target.id)?;
+
+    execute!(
+        session.stderr,
+        style::SetForegroundColor(Color::Green),
+        style::Print(format!("\n✔ Deleted session {}\n\n", session_display_name(&target))),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+    Ok(())
+}
@@ -17,12 +17,17 @@ use crossterm::{
 use crate::api_client::model::Tool as FigTool;
 use crate::cli::chat::consts::DUMMY_TOOL_NAME;
 use crate::cli::chat::tools::ToolOrigin;
+use crate::cli::chat::tools::trust_rule::{
+    RuleEffect,
+    TrustRule,
+};
 use crate::cli::chat::{
     ChatError,
     ChatSession,
     ChatState,
     TRUST_ALL_TEXT,
 };
+use crate::os::Os;
 
 #[deny(missing_docs)]
 #[derive(Debug, PartialEq, Args)]
@@ -32,9 +37,9 @@ pub struct ToolsArgs {
 }
 
 impl ToolsArgs {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
         if let Some(subcommand) = self.subcommand {
-            return subcommand.execute(session).await;
+            return subcommand.execute(os, session).await;
         }
 
         // No subcommand - print the current tools and their permissions.
@@ -109,6 +114,43 @@ impl ToolsArgs {
             );
         }
 
+        let session_rules = session.tool_permissions.rules.iter().map(|r| (r.clone(), "session"));
+        let profile_rules = session
+            .conversation
+            .context_manager
+            .as_ref()
+            .map(|cm| cm.tool_rules())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|r| (r, "profile"));
+        let rules = session_rules.chain(profile_rules).collect::<Vec<_>>();
+        if !rules.is_empty() {
+            queue!(
+                session.stderr,
+                style::SetAttribute(Attribute::Bold),
+                style::Print("Rules:\n"),
+                style::SetAttribute(Attribute::Reset),
+            )?;
+            for (rule, source) in rules {
+                queue!(
+                    session.stderr,
+                    style::Print(format!("- {rule}  ")),
+                    match rule.effect {
+                        RuleEffect::Allow => style::SetForegroundColor(Color::Green),
+                        RuleEffect::Deny => style::SetForegroundColor(Color::Red),
+                    },
+                    style::Print(match rule.effect {
+                        RuleEffect::Allow => "allow",
+                        RuleEffect::Deny => "deny",
+                    }),
+                    style::SetForegroundColor(Color::DarkGrey),
+                    style::Print(format!(" ({source})\n")),
+                    style::SetForegroundColor(Color::Reset),
+                )?;
+            }
+            queue!(session.stderr, style::Print("\n"))?;
+        }
+
         let loading = session.conversation.tool_manager.pending_clients().await;
         if !loading.is_empty() {
             queue!(
@@ -146,7 +188,14 @@ impl ToolsArgs {
 #[derive(Debug, PartialEq, Subcommand)]
 #[command(
     before_long_help = "By default, Amazon Q will ask for your permission to use certain tools. You can control which tools you
-trust so that no confirmation is required. These settings will last only for this session."
+trust so that no confirmation is required. These settings will last only for this session, unless they are saved to the
+current profile with --save.
+
+Besides tool names, trust accepts glob patterns (or regexes prefixed with re:) and rules scoped to the arguments of a tool:
+  /tools trust @gitlab/*
+  /tools trust \"execute_bash(command=cargo test*)\" \"fs_write(path=src/**)\"
+Deny rules always ask for confirmation and take precedence over every other setting:
+  /tools deny \"execute_bash(command=git push*)\""
 )]
 pub enum ToolsSubcommand {
     /// Show the input schema for all available tools
@@ -155,11 +204,25 @@ pub enum ToolsSubcommand {
     Trust {
         #[arg(required = true)]
         tool_names: Vec<String>,
+        /// Also save the rules to the current profile
+        #[arg(long)]
+        save: bool,
     },
     /// Revert a tool or tools to per-request confirmation
     Untrust {
         #[arg(required = true)]
         tool_names: Vec<String>,
+        /// Also remove the rules from the current profile
+        #[arg(long)]
+        save: bool,
+    },
+    /// Always ask for confirmation for tool uses matching the given rules
+    Deny {
+        #[arg(required = true)]
+        rules: Vec<String>,
+        /// Also save the rules to the current profile
+        #[arg(long)]
+        save: bool,
     },
     /// Trust all tools (equivalent to deprecated /acceptall)
     TrustAll,
@@ -170,13 +233,13 @@ pub enum ToolsSubcommand {
 }
 
 impl ToolsSubcommand {
-    pub async fn execute(self, session: &mut ChatSession) -> Result<ChatState, ChatError> {
-        let existing_tools: HashSet<&String> = session
+    pub async fn execute(self, os: &Os, session: &mut ChatSession) -> Result<ChatState, ChatError> {
+        let existing_tools: HashSet<String> = session
             .conversation
             .tools
             .values()
             .flatten()
-            .map(|FigTool::ToolSpecification(spec)| &spec.name)
+            .map(|FigTool::ToolSpecification(spec)| spec.name.clone())
             .collect();
 
         match self {
@@ -185,7 +248,10 @@ impl ToolsSubcommand {
                     .map_err(|e| ChatError::Custom(format!("Error converting tool schema to string: {e}").into()))?;
                 queue!(session.stderr, style::Print(schema_json), style::Print("\n"))?;
             },
-            Self::Trust { tool_names } => {
+            Self::Trust { tool_names, save } => {
+                let (rules, tool_names): (Vec<String>, Vec<String>) = TrustRule::merge_split_values(tool_names, " ")
+                    .into_iter()
+                    .partition(|tool_name| TrustRule::is_rule(tool_name));
                 let (valid_tools, invalid_tools): (Vec<String>, Vec<String>) = tool_names
                     .into_iter()
                     .partition(|tool_name| existing_tools.contains(tool_name));
@@ -203,6 +269,11 @@ impl ToolsSubcommand {
                         style::SetForegroundColor(Color::Reset),
                     )?;
                 }
+                let mut rules = parse_rules(session, rules)?;
+                if save {
+                    rules.extend(valid_tools.iter().filter_map(|t| t.parse::<TrustRule>().ok()));
+                }
+                add_rules(os, session, rules, save).await?;
                 if !valid_tools.is_empty() {
                     valid_tools.iter().for_each(|t| session.tool_permissions.trust_tool(t));
                     queue!(
@@ -231,7 +302,15 @@ impl ToolsSubcommand {
                     )?;
                 }
             },
-            Self::Untrust { tool_names } => {
+            Self::Untrust { tool_names, save } => {
+                let (rules, tool_names): (Vec<String>, Vec<String>) = TrustRule::merge_split_values(tool_names, " ")
+                    .into_iter()
+                    .partition(|tool_name| TrustRule::is_rule(tool_name));
+                let mut to_remove = rules.clone();
+                if save {
+                    to_remove.extend(tool_names.iter().cloned());
+                }
+                remove_rules(os, session, &to_remove, save).await?;
                 let (valid_tools, invalid_tools): (Vec<String>, Vec<String>) = tool_names
                     .into_iter()
                     .partition(|tool_name| existing_tools.contains(tool_name));
@@ -266,6 +345,17 @@ impl ToolsSubcommand {
                     )?;
                 }
             },
+            Self::Deny { rules, save } => {
+                let rules = TrustRule::merge_split_values(rules, " ")
+                    .into_iter()
+                    .map(|rule| match rule.trim_start().starts_with('!') {
+                        true => rule,
+                        false => format!("!{}", rule.trim_start()),
+                    })
+                    .collect();
+                let rules = parse_rules(session, rules)?;
+                add_rules(os, session, rules, save).await?;
+            },
             Self::TrustAll => {
                 session
                     .conversation
@@ -316,3 +406,93 @@ impl ToolsSubcommand {
         })
     }
 }
+
+/// Parses `rules`, reporting the ones that are invalid.
+fn parse_rules(session: &mut ChatSession, rules: Vec<String>) -> Result<Vec<TrustRule>, ChatError> {
+    let mut parsed = Vec::new();
+    for rule in rules {
+        match rule.parse::<TrustRule>() {
+            Ok(rule) => parsed.push(rule),
+            Err(e) => queue!(
+                session.stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("\nInvalid rule '{rule}': {e}\n")),
+                style::SetForegroundColor(Color::Reset),
+            )?,
+        }
+    }
+    Ok(parsed)
+}
+
+/// Adds `rules` to the session and, if `save` is set, to the current profile.
+async fn add_rules(os: &Os, session: &mut ChatSession, rules: Vec<TrustRule>, save: bool) -> Result<(), ChatError> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    if save {
+        let Some(context_manager) = session.conversation.context_manager.as_mut() else {
+            return Err(ChatError::Custom(
+                "Profiles are not available, the rules were not saved".into(),
+            ));
+        };
+        context_manager
+            .add_tool_rules(os, &rules)
+            .await
+            .map_err(|e| ChatError::Custom(format!("Failed to save rules: {e}").into()))?;
+    }
+
+    let names = rules.iter().map(|r| r.to_string()).collect::<Vec<_>>();
+    rules.into_iter().for_each(|r| session.tool_permissions.add_rule(r));
+    queue!(
+        session.stderr,
+        style::SetForegroundColor(Color::Green),
+        style::Print(format!(
+            "Added {} '{}'{}.\n",
+            if names.len() > 1 { "rules" } else { "rule" },
+            names.join("', '"),
+            if save { " to the current profile" } else { "" }
+        )),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+
+    Ok(())
+}
+
+/// Removes `rules` from the session and, if `save` is set, from the current profile.
+async fn remove_rules(os: &Os, session: &mut ChatSession, rules: &[String], save: bool) -> Result<(), ChatError> {
+    let mut removed = rules
+        .iter()
+        .filter(|r| session.tool_permissions.remove_rule(r))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if save {
+        if let Some(context_manager) = session.conversation.context_manager.as_mut() {
+            let from_profile = context_manager
+                .remove_tool_rules(os, rules)
+                .await
+                .map_err(|e| ChatError::Custom(format!("Failed to save rules: {e}").into()))?;
+            for rule in from_profile {
+                if !removed.contains(&rule) {
+                    removed.push(rule);
+                }
+            }
+        }
+    }
+
+    if !removed.is_empty() {
+        queue!(
+            session.stderr,
+            style::SetForegroundColor(Color::Green),
+            style::Print(format!(
+                "Removed {} '{}'.\n",
+                if removed.len() > 1 { "rules" } else { "rule" },
+                removed.join("', '"),
+            )),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+    }
+
+    Ok(())
+}
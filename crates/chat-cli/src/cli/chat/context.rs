@@ -15,15 +15,22 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use tracing::debug;
+use tracing::{
+    debug,
+    warn,
+};
 
 use super::consts::CONTEXT_FILES_MAX_SIZE;
 use super::util::drop_matched_context_files;
+use crate::api_client::backend::ModelBackendConfig;
 use crate::cli::chat::ChatError;
 use crate::cli::chat::cli::hooks::{
     Hook,
+    HookEvent,
     HookExecutor,
+    HookResponse,
 };
+use crate::cli::chat::tools::trust_rule::TrustRule;
 use crate::os::Os;
 use crate::util::directories;
 
@@ -38,6 +45,15 @@ pub struct ContextConfig {
 
     /// Map of Hook Name to [`Hook`]. The hook name serves as the hook's ID.
     pub hooks: HashMap<String, Hook>,
+
+    /// Tool trust rules, e.g. `execute_bash(command=cargo test*)`. See [`TrustRule`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_rules: Vec<String>,
+
+    /// Backend to send chat requests to instead of Amazon Q. Only read from profile
+    /// configurations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_backend: Option<ModelBackendConfig>,
 }
 
 /// Manager for context files and profiles.
@@ -567,20 +583,83 @@ impl ContextManager {
         self.save_config(os, global).await
     }
 
-    /// Run all the currently enabled hooks from both the global and profile contexts.
+    /// Add tool trust rules to the current profile, skipping the ones it already has.
+    pub async fn add_tool_rules(&mut self, os: &Os, rules: &[TrustRule]) -> Result<()> {
+        for rule in rules {
+            let rule = rule.to_string();
+            if !self.profile_config.tool_rules.contains(&rule) {
+                self.profile_config.tool_rules.push(rule);
+            }
+        }
+
+        self.save_config(os, false).await
+    }
+
+    /// Remove tool trust rules from the current profile, returning the ones that were removed.
+    pub async fn remove_tool_rules(&mut self, os: &Os, rules: &[String]) -> Result<Vec<String>> {
+        let (removed, kept) = self
+            .profile_config
+            .tool_rules
+            .drain(..)
+            .partition(|r| rules.iter().any(|rule| rule.trim() == r));
+        self.profile_config.tool_rules = kept;
+
+        if !removed.is_empty() {
+            self.save_config(os, false).await?;
+        }
+        Ok(removed)
+    }
+
+    /// The tool trust rules of the current profile. Rules that fail to parse are skipped.
+    pub fn tool_rules(&self) -> Vec<TrustRule> {
+        self.profile_config
+            .tool_rules
+            .iter()
+            .filter_map(|rule| match rule.parse::<TrustRule>() {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    warn!(?rule, "skipping invalid tool rule: {}", e);
+                    None
+                },
+            })
+            .collect()
+    }
+
+    /// Run all the currently enabled context hooks from both the global and profile contexts.
     /// Skipped hooks (disabled) will not appear in the output.
     /// # Arguments
     /// * `updates` - output stream to write hook run status to if Some, else do nothing if None
     /// # Returns
     /// A vector containing pairs of a [`Hook`] definition and its execution output
     pub async fn run_hooks(&mut self, output: &mut impl Write) -> Result<Vec<(Hook, String)>, ChatError> {
+        let hooks = Self::hooks(&mut self.global_config, &mut self.profile_config)
+            .into_iter()
+            .filter(|hook| hook.trigger.is_context())
+            .collect();
+
+        self.hook_executor.run_hooks(hooks, output).await
+    }
+
+    /// Run the enabled hooks for `event` from both the global and profile contexts, ordered by
+    /// name with global hooks first.
+    /// # Returns
+    /// A vector containing pairs of a [`Hook`] definition and its response
+    pub async fn run_event_hooks(
+        &mut self,
+        event: &HookEvent<'_>,
+        output: &mut impl Write,
+    ) -> Result<Vec<(Hook, HookResponse)>, ChatError> {
+        let mut hooks = Self::hooks(&mut self.global_config, &mut self.profile_config);
+        hooks.sort_by(|a, b| b.is_global.cmp(&a.is_global).then_with(|| a.name.cmp(&b.name)));
+
+        self.hook_executor.run_event_hooks(hooks, event, output).await
+    }
+
+    /// All hooks of the global and profile contexts, with their internal states set
+    fn hooks<'a>(global_config: &'a mut ContextConfig, profile_config: &'a mut ContextConfig) -> Vec<&'a Hook> {
         let mut hooks: Vec<&Hook> = Vec::new();
 
-        // Set internal hook states
-        let configs = [
-            (&mut self.global_config.hooks, true),
-            (&mut self.profile_config.hooks, false),
-        ];
+        let configs = [(&mut global_config.hooks, true), (&mut profile_config.hooks, false)];
 
         for (hook_list, is_global) in configs {
             hooks.extend(hook_list.iter_mut().map(|(name, h)| {
@@ -590,7 +669,7 @@ impl ContextManager {
             }));
         }
 
-        self.hook_executor.run_hooks(hooks, output).await
+        hooks
     }
 }
 
@@ -625,6 +704,8 @@ async fn load_global_config(os: &Os) -> Result<ContextConfig> {
                 AMAZONQ_FILENAME.to_string(),
             ],
             hooks: HashMap::new(),
+            tool_rules: Vec::new(),
+            model_backend: None,
         })
     }
 }
@@ -632,7 +713,7 @@ async fn load_global_config(os: &Os) -> Result<ContextConfig> {
 /// Load a profile's context configuration.
 ///
 /// If the profile configuration file doesn't exist, creates a default configuration.
-async fn load_profile_config(os: &Os, profile_name: &str) -> Result<ContextConfig> {
+pub async fn load_profile_config(os: &Os, profile_name: &str) -> Result<ContextConfig> {
     let profile_path = profile_context_path(os, profile_name)?;
     debug!(?profile_path, "loading profile config");
     if os.fs.exists(&profile_path) {
@@ -904,4 +985,32 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_tool_rule_ops() -> Result<()> {
+        let os = Os::new().await.unwrap();
+        let mut manager = create_test_context_manager(None).await?;
+
+        let rules = ["@gitlab/*", "!execute_bash(command=rm *)"]
+            .iter()
+            .map(|r| r.parse::<TrustRule>().unwrap())
+            .collect::<Vec<_>>();
+        manager.add_tool_rules(&os, &rules).await?;
+        manager.add_tool_rules(&os, &rules[..1]).await?;
+        manager.reload_config(&os).await?;
+        assert_eq!(manager.profile_config.tool_rules, vec![
+            "@gitlab/*",
+            "!execute_bash(command=rm *)"
+        ]);
+        assert_eq!(manager.tool_rules().len(), 2);
+
+        let removed = manager
+            .remove_tool_rules(&os, &["@gitlab/*".to_string(), "fs_*".to_string()])
+            .await?;
+        assert_eq!(removed, vec!["@gitlab/*"]);
+        manager.reload_config(&os).await?;
+        assert_eq!(manager.profile_config.tool_rules, vec!["!execute_bash(command=rm *)"]);
+
+        Ok(())
+    }
 }
@@ -32,6 +32,11 @@ use super::message::{
     AssistantMessage,
     ToolUseResult,
     UserMessage,
+    UserMessageContent,
+};
+use super::terminal_context::{
+    self,
+    CommandOutput,
 };
 use super::token_counter::{
     CharCount,
@@ -59,12 +64,16 @@ use crate::cli::chat::cli::hooks::{
     Hook,
     HookTrigger,
 };
+use crate::database::settings::Setting;
 use crate::mcp_client::Prompt;
 use crate::os::Os;
 
 const CONTEXT_ENTRY_START_HEADER: &str = "--- CONTEXT ENTRY BEGIN ---\n";
 const CONTEXT_ENTRY_END_HEADER: &str = "--- CONTEXT ENTRY END ---\n\n";
 
+/// How long after it ended a failed terminal command is still added as context, in milliseconds
+const FAILED_COMMAND_MAX_AGE_MS: u64 = 5 * 60 * 1000;
+
 /// Tracks state related to an ongoing conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationState {
@@ -94,6 +103,15 @@ pub struct ConversationState {
     /// Model explicitly selected by the user in this conversation state via `/model`.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// Failed terminal commands that ended at or before this time (in milliseconds since the unix
+    /// epoch) are not added as context. Starts at the beginning of the session and moves to the end
+    /// time of every command that was added, such that a command is only sent once.
+    #[serde(default)]
+    failed_command_cutoff: u64,
+    /// Hook names and the context they added at the end of the last turn, sent with the next user
+    /// prompt.
+    #[serde(skip)]
+    turn_end_context: Vec<(String, String)>,
 }
 
 impl ConversationState {
@@ -146,12 +164,17 @@ impl ConversationState {
             context_message_length: None,
             latest_summary: None,
             model: current_model_id,
+            failed_command_cutoff: now_millis(),
+            turn_end_context: Vec::new(),
         }
     }
 
     /// Reloads necessary fields after being deserialized. This should be called after
     /// deserialization.
     pub async fn reload_serialized_state(&mut self, os: &Os) {
+        // Commands that failed before the session was resumed are unrelated to it
+        self.failed_command_cutoff = self.failed_command_cutoff.max(now_millis());
+
         // Try to reload ContextManager, but do not return an error if we fail.
         // TODO: Currently the failure modes around ContextManager is unclear, and we don't return
         // errors in most cases. Thus, we try to preserve the same behavior here and simply have
@@ -259,7 +282,7 @@ impl ConversationState {
         self.history.push_back((next_user_message, message));
 
         if let Ok(cwd) = std::env::current_dir() {
-            os.database.set_conversation_by_path(cwd, self).ok();
+            os.database.save_session(cwd, self).ok();
         }
     }
 
@@ -268,6 +291,15 @@ impl ConversationState {
         self.conversation_id.as_ref()
     }
 
+    /// Returns a copy of the conversation with a new id, such that it is saved as a separate
+    /// session.
+    pub fn fork(&self, conversation_id: &str) -> Self {
+        Self {
+            conversation_id: conversation_id.to_string(),
+            ..self.clone()
+        }
+    }
+
     /// Returns the message id associated with the last assistant message, if present.
     ///
     /// This is equivalent to `utterance_id` in the Q API.
@@ -357,6 +389,16 @@ impl ConversationState {
     }
 
     pub async fn update_state(&mut self, force_update: bool) {
+        if let Some(cm) = self.context_manager.as_ref() {
+            let paths = cm
+                .global_config
+                .paths
+                .iter()
+                .chain(cm.profile_config.paths.iter())
+                .cloned()
+                .collect();
+            self.tool_manager.set_context_paths(paths);
+        }
         let needs_update = self.tool_manager.has_new_stuff.load(Ordering::Acquire) || force_update;
         if !needs_update {
             return;
@@ -407,6 +449,16 @@ impl ConversationState {
                 next_message.additional_context = format_hook_context(hook_results.iter(), HookTrigger::PerPrompt);
             }
         }
+        if run_hooks {
+            self.add_failed_command_context(os).await;
+            if let Some(next_message) = self.next_message.as_mut() {
+                if !self.turn_end_context.is_empty() {
+                    next_message
+                        .additional_context
+                        .push_str(&format_turn_end_context(&std::mem::take(&mut self.turn_end_context)));
+                }
+            }
+        }
 
         let (context_messages, dropped_context_files) = self.context_messages(os, conversation_start_context).await;
 
@@ -423,6 +475,45 @@ impl ConversationState {
         })
     }
 
+    /// Adds context from [`HookTrigger::TurnEnd`] hooks to the next user prompt.
+    pub fn add_turn_end_context(&mut self, hook_name: &str, context: &str) {
+        self.turn_end_context.push((hook_name.to_owned(), context.to_owned()));
+    }
+
+    /// Adds the last command that failed in the terminal to the next user message, if it wasn't
+    /// added to a previous one.
+    async fn add_failed_command_context(&mut self, os: &Os) {
+        let is_prompt = self
+            .next_message
+            .as_ref()
+            .is_some_and(|message| matches!(message.content, UserMessageContent::Prompt { .. }));
+        if !is_prompt
+            || !os
+                .database
+                .settings
+                .get_bool(Setting::ChatEnableTerminalContext)
+                .unwrap_or(false)
+        {
+            return;
+        }
+
+        let Some(command) = terminal_context::last_failed_command(os).await else {
+            return;
+        };
+        let Some(end_time) = command
+            .end_time
+            .filter(|end_time| is_recent_failed_command(*end_time, self.failed_command_cutoff, now_millis()))
+        else {
+            return;
+        };
+        self.failed_command_cutoff = end_time;
+        if let Some(next_message) = self.next_message.as_mut() {
+            next_message
+                .additional_context
+                .push_str(&format_failed_command_context(&command));
+        }
+    }
+
     /// Returns a [FigConversationState] capable of replacing the history of the current
     /// conversation with a summary generated by the model.
     ///
@@ -773,6 +864,51 @@ fn format_hook_context<'a>(hook_results: impl IntoIterator<Item = &'a (Hook, Str
     context_content
 }
 
+fn format_turn_end_context(hook_contexts: &[(String, String)]) -> String {
+    let mut context_content = String::new();
+
+    context_content.push_str(CONTEXT_ENTRY_START_HEADER);
+    context_content.push_str("This section contains context gathered by script hooks when you ended your previous response. Consider it in this response.\n\n");
+    for (hook_name, context) in hook_contexts {
+        context_content.push_str(&format!("'{hook_name}': {context}\n\n"));
+    }
+    context_content.push_str(CONTEXT_ENTRY_END_HEADER);
+    context_content
+}
+
+/// Whether a failed command that ended at `end_time` is recent enough to be added as context.
+fn is_recent_failed_command(end_time: u64, cutoff: u64, now: u64) -> bool {
+    end_time > cutoff && now.saturating_sub(end_time) <= FAILED_COMMAND_MAX_AGE_MS
+}
+
+fn now_millis() -> u64 {
+    u64::try_from(time::OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000).unwrap_or_default()
+}
+
+fn format_failed_command_context(command: &CommandOutput) -> String {
+    let mut context_content = String::new();
+
+    context_content.push_str(CONTEXT_ENTRY_START_HEADER);
+    context_content.push_str("This section contains the last command that failed in my terminal. Use it when I ask about an error or a failure, otherwise ignore it.\n\n");
+    if let Some(cmd) = &command.command {
+        context_content.push_str(&format!("Command: {}\n", cmd.trim()));
+    }
+    if let Some(exit_code) = command.exit_code {
+        context_content.push_str(&format!("Exit code: {exit_code}\n"));
+    }
+    if let Some(cwd) = &command.cwd {
+        context_content.push_str(&format!("Working directory: {cwd}\n"));
+    }
+    if command.truncated {
+        context_content.push_str("Output (only the end is included):\n");
+    } else {
+        context_content.push_str("Output:\n");
+    }
+    context_content.push_str(&format!("{}\n", command.output));
+    context_content.push_str(CONTEXT_ENTRY_END_HEADER);
+    context_content
+}
+
 fn enforce_conversation_invariants(
     history: &mut VecDeque<(UserMessage, AssistantMessage)>,
     next_message: &mut Option<UserMessage>,
@@ -1152,6 +1288,43 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_conversation_state_turn_end_context() {
+        let mut os = Os::new().await.unwrap();
+        let mut tool_manager = ToolManager::default();
+        let tools = tool_manager.load_tools(&mut os, &mut vec![]).await.unwrap();
+        let mut conversation = ConversationState::new(&mut os, "fake_conv_id", tools, None, tool_manager, None).await;
+
+        conversation.add_turn_end_context("lint", "2 warnings");
+        conversation.set_next_user_message("hello".to_string()).await;
+
+        // Not sent with messages that aren't user prompts
+        let s = conversation
+            .as_sendable_conversation_state(&os, &mut vec![], false)
+            .await
+            .unwrap();
+        assert!(!s.user_input_message.content.contains("2 warnings"));
+
+        let s = conversation
+            .as_sendable_conversation_state(&os, &mut vec![], true)
+            .await
+            .unwrap();
+        assert!(
+            s.user_input_message.content.contains("'lint': 2 warnings"),
+            "expected to contain the turn end context, instead found: {}",
+            s.user_input_message.content
+        );
+
+        // Only sent once
+        conversation.push_assistant_message(&mut os, AssistantMessage::new_response(None, "hi".to_string()));
+        conversation.set_next_user_message("again".to_string()).await;
+        let s = conversation
+            .as_sendable_conversation_state(&os, &mut vec![], true)
+            .await
+            .unwrap();
+        assert!(!s.user_input_message.content.contains("2 warnings"));
+    }
+
     #[tokio::test]
     async fn test_conversation_state_additional_context() {
         let mut os = Os::new().await.unwrap();
@@ -1210,4 +1383,47 @@ mod tests {
             conversation.set_next_user_message(i.to_string()).await;
         }
     }
+
+    #[test]
+    fn test_format_failed_command_context() {
+        let mut command = CommandOutput {
+            command: Some("cargo build\n".to_string()),
+            exit_code: Some(101),
+            cwd: Some("/repo".to_string()),
+            start_time: None,
+            end_time: Some(1_700_000_000_000),
+            output: "error[E0308]: mismatched types".to_string(),
+            truncated: false,
+        };
+        let context = format_failed_command_context(&command);
+        assert!(context.starts_with(CONTEXT_ENTRY_START_HEADER));
+        assert!(context.ends_with(CONTEXT_ENTRY_END_HEADER));
+        assert!(context.contains(
+            "Command: cargo build\nExit code: 101\nWorking directory: /repo\nOutput:\nerror[E0308]: mismatched types\n"
+        ));
+
+        command.truncated = true;
+        command.cwd = None;
+        let context = format_failed_command_context(&command);
+        assert!(context.contains("Exit code: 101\nOutput (only the end is included):\n"));
+    }
+
+    #[test]
+    fn test_is_recent_failed_command() {
+        let now = 1_700_000_600_000;
+        let cutoff = 1_700_000_000_000;
+        assert!(is_recent_failed_command(now - 1000, cutoff, now));
+        assert!(
+            !is_recent_failed_command(cutoff, cutoff, now),
+            "commands that ended before the session started or were already sent are skipped"
+        );
+        assert!(
+            !is_recent_failed_command(
+                now - FAILED_COMMAND_MAX_AGE_MS - 1,
+                cutoff - FAILED_COMMAND_MAX_AGE_MS,
+                now
+            ),
+            "old commands are skipped"
+        );
+    }
 }
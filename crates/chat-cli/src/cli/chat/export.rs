@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::process::ExitCode;
+
+use anstream::{
+    eprintln,
+    print,
+};
+use clap::{
+    Args,
+    ValueEnum,
+};
+use crossterm::style::Stylize;
+use eyre::{
+    Result,
+    bail,
+};
+use pulldown_cmark::{
+    Event,
+    Options,
+    Parser,
+};
+
+use super::ConversationState;
+use super::message::{
+    AssistantMessage,
+    ToolUseResult,
+    ToolUseResultBlock,
+    UserMessage,
+};
+use super::sessions::load_conversation;
+use super::util::truncate_safe;
+use crate::api_client::model::ToolResultStatus;
+use crate::os::Os;
+
+/// Maximum number of bytes of a tool output included in a transcript.
+pub const MAX_TOOL_OUTPUT_LEN: usize = 2000;
+
+/// Stylesheet embedded in HTML transcripts, such that they can be shared as a single file.
+const HTML_STYLE: &str = "\
+body { max-width: 50rem; margin: 2rem auto; padding: 0 1rem; line-height: 1.5; color: #1f2328; }
+body { font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif; }
+h2 { border-bottom: 1px solid #d1d9e0; padding-bottom: 0.3rem; }
+pre { background: #f6f8fa; padding: 0.75rem; border-radius: 6px; overflow-x: auto; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 0.875rem; }
+blockquote { margin: 0; padding: 0 1rem; color: #59636e; border-left: 0.25rem solid #d1d9e0; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #d1d9e0; padding: 0.25rem 0.75rem; }";
+
+/// The format of an exported transcript.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    #[value(alias = "md")]
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    /// Infers the format from the extension of `path`, defaulting to [ExportFormat::Markdown].
+    pub fn from_path(path: &str) -> Self {
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("html" | "htm") => Self::Html,
+            _ => Self::Markdown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Replace the outputs of tools with a placeholder, e.g. because they may contain secrets
+    pub redact_tool_outputs: bool,
+    /// Maximum number of bytes of a tool output to include
+    pub max_tool_output_len: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::default(),
+            redact_tool_outputs: false,
+            max_tool_output_len: MAX_TOOL_OUTPUT_LEN,
+        }
+    }
+}
+
+#[deny(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, Args)]
+pub struct ExportArgs {
+    /// The id or name of the session to export, defaults to the most recent session of this
+    /// directory
+    pub session: Option<String>,
+    /// File to write the transcript to, defaults to stdout
+    #[arg(long, short)]
+    pub output: Option<String>,
+    /// Format of the transcript, inferred from the extension of the output file if not given
+    #[arg(long, value_enum)]
+    pub format: Option<ExportFormat>,
+    /// Replace the outputs of tools with a placeholder
+    #[arg(long)]
+    pub redact: bool,
+    /// Overwrite the output file if it already exists
+    #[arg(long, short)]
+    pub force: bool,
+}
+
+impl ExportArgs {
+    pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
+        let Some(conversation) = load_conversation(os, self.session.as_deref())? else {
+            bail!("No conversation has been saved in this directory");
+        };
+
+        let options = ExportOptions {
+            format: self
+                .format
+                .or_else(|| self.output.as_deref().map(ExportFormat::from_path))
+                .unwrap_or_default(),
+            redact_tool_outputs: self.redact,
+            ..Default::default()
+        };
+        let transcript = render(&conversation, &options);
+
+        match self.output {
+            Some(path) => {
+                if os.fs.exists(&path) && !self.force {
+                    bail!("File at {path} already exists. To overwrite, use -f or --force");
+                }
+                os.fs.write(&path, transcript).await?;
+                eprintln!("Exported conversation to {}", path.green());
+            },
+            None => print!("{transcript}"),
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Renders the history of `conversation` as a transcript to share with others.
+pub fn render(conversation: &ConversationState, options: &ExportOptions) -> String {
+    let markdown = render_markdown(
+        conversation.conversation_id(),
+        conversation.latest_summary(),
+        conversation.history().iter(),
+        options,
+    );
+    match options.format {
+        ExportFormat::Markdown => markdown,
+        ExportFormat::Html => markdown_to_html(&markdown, conversation.conversation_id()),
+    }
+}
+
+fn render_markdown<'a>(
+    conversation_id: &str,
+    summary: Option<&str>,
+    history: impl Iterator<Item = &'a (UserMessage, AssistantMessage)> + Clone,
+    options: &ExportOptions,
+) -> String {
+    // Tool results are sent with the user message following the tool uses, but are rendered right
+    // after the tool use they belong to.
+    let tool_results = history
+        .clone()
+        .filter_map(|(user, _)| user.tool_use_results())
+        .flatten()
+        .map(|result| (result.tool_use_id.as_str(), result))
+        .collect::<HashMap<_, _>>();
+
+    let mut out = format!("# Conversation transcript\n\nConversation `{conversation_id}`\n");
+    if let Some(summary) = summary {
+        let _ = write!(out, "\n## Summary of the earlier conversation\n\n{}\n", summary.trim());
+    }
+
+    for (user, assistant) in history {
+        if let Some(prompt) = user.prompt().filter(|prompt| !prompt.trim().is_empty()) {
+            let _ = write!(out, "\n## User\n\n{}\n", prompt.trim());
+        }
+
+        let content = assistant.content().trim();
+        let tool_uses = assistant.tool_uses().unwrap_or_default();
+        if content.is_empty() && tool_uses.is_empty() {
+            continue;
+        }
+        out.push_str("\n## Assistant\n");
+        if !content.is_empty() {
+            let _ = write!(out, "\n{content}\n");
+        }
+        for tool_use in tool_uses {
+            let input = serde_json::to_string_pretty(&tool_use.args).unwrap_or_default();
+            let _ = write!(
+                out,
+                "\n**Tool use:** `{}`\n\n{}\n",
+                tool_use.name,
+                code_block(&input, "json")
+            );
+            if let Some(result) = tool_results.get(tool_use.id.as_str()) {
+                out.push('\n');
+                out.push_str(&render_tool_result(result, options));
+            }
+        }
+    }
+
+    out
+}
+
+fn render_tool_result(result: &ToolUseResult, options: &ExportOptions) -> String {
+    let status = match result.status {
+        ToolResultStatus::Success => "success",
+        ToolResultStatus::Error => "error",
+    };
+    if options.redact_tool_outputs {
+        return format!("**Result** ({status}): _output redacted_\n");
+    }
+
+    let output = result
+        .content
+        .iter()
+        .map(|block| match block {
+            ToolUseResultBlock::Text(text) => text.clone(),
+            ToolUseResultBlock::Json(value) => serde_json::to_string_pretty(value).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let output = output.trim_end();
+    if output.is_empty() {
+        return format!("**Result** ({status}): _no output_\n");
+    }
+
+    let truncated = truncate_safe(output, options.max_tool_output_len);
+    let mut rendered = format!("**Result** ({status}):\n\n{}\n", code_block(truncated, ""));
+    if truncated.len() < output.len() {
+        let _ = write!(
+            rendered,
+            "\n_{} more bytes truncated_\n",
+            output.len() - truncated.len()
+        );
+    }
+    rendered
+}
+
+/// Wraps `content` in a fenced code block, using a fence longer than any run of backticks in it.
+fn code_block(content: &str, language: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{content}\n{fence}")
+}
+
+fn markdown_to_html(markdown: &str, title: &str) -> String {
+    // Raw HTML written by the user or the model is shown as text rather than interpreted.
+    let parser =
+        Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+            Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+            event => event,
+        });
+    let mut body = String::new();
+    pulldown_cmark::html::push_html(&mut body, parser);
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<meta name=\"viewport\" \
+         content=\"width=device-width, initial-scale=1\">\n<title>{}</title>\n<style>\n{HTML_STYLE}\n</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        escape_html(title)
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::cli::chat::message::AssistantToolUse;
+
+    fn history() -> Vec<(UserMessage, AssistantMessage)> {
+        vec![
+            (
+                UserMessage::new_prompt("What is in README.md?".to_string()),
+                AssistantMessage::new_tool_use(None, "Let me read it.".to_string(), vec![AssistantToolUse {
+                    id: "tooluse_1".to_string(),
+                    name: "fs_read".to_string(),
+                    orig_name: "fs_read".to_string(),
+                    args: json!({ "path": "README.md" }),
+                    orig_args: json!({ "path": "README.md" }),
+                }]),
+            ),
+            (
+                UserMessage::new_tool_use_results(vec![ToolUseResult {
+                    tool_use_id: "tooluse_1".to_string(),
+                    content: vec![ToolUseResultBlock::Text("# Title\n```\ncode\n```".to_string())],
+                    status: ToolResultStatus::Success,
+                }]),
+                AssistantMessage::new_response(None, "It has a <b>title</b> and code.".to_string()),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(ExportFormat::from_path("out.html"), ExportFormat::Html);
+        assert_eq!(ExportFormat::from_path("out.HTM"), ExportFormat::Html);
+        assert_eq!(ExportFormat::from_path("out.md"), ExportFormat::Markdown);
+        assert_eq!(ExportFormat::from_path("out"), ExportFormat::Markdown);
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let history = history();
+        let markdown = render_markdown("abc", Some("We talked."), history.iter(), &ExportOptions::default());
+        assert!(markdown.starts_with("# Conversation transcript\n\nConversation `abc`\n"));
+        assert!(markdown.contains("## Summary of the earlier conversation\n\nWe talked.\n"));
+        assert!(markdown.contains("## User\n\nWhat is in README.md?\n"));
+        assert!(markdown.contains("**Tool use:** `fs_read`\n\n```json\n{\n  \"path\": \"README.md\"\n}\n```"));
+        // The fence is longer than the backticks in the output
+        assert!(markdown.contains("**Result** (success):\n\n````\n# Title\n```\ncode\n```\n````\n"));
+        assert!(markdown.ends_with("## Assistant\n\nIt has a <b>title</b> and code.\n"));
+        // The message only carrying tool results has no user section
+        assert_eq!(markdown.matches("## User").count(), 1);
+
+        let options = ExportOptions {
+            max_tool_output_len: 7,
+            ..Default::default()
+        };
+        let markdown = render_markdown("abc", None, history.iter(), &options);
+        assert!(markdown.contains("```\n# Title\n```\n\n_13 more bytes truncated_\n"));
+        assert!(!markdown.contains("## Summary"));
+
+        let options = ExportOptions {
+            redact_tool_outputs: true,
+            ..Default::default()
+        };
+        let markdown = render_markdown("abc", None, history.iter(), &options);
+        assert!(markdown.contains("**Result** (success): _output redacted_\n"));
+        assert!(!markdown.contains("# Title"));
+    }
+
+    #[test]
+    fn test_markdown_to_html() {
+        let history = history();
+        let markdown = render_markdown("abc", None, history.iter(), &ExportOptions::default());
+        let html = markdown_to_html(&markdown, "<abc>");
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>&lt;abc&gt;</title>"));
+        assert!(html.contains("<h2>User</h2>"));
+        assert!(html.contains("<pre><code class=\"language-json\">"));
+        assert!(html.contains("&lt;b&gt;title&lt;/b&gt;"));
+        assert!(!html.contains("<b>"));
+    }
+}
@@ -1,18 +1,24 @@
+mod checkpoint;
 mod cli;
 mod consts;
 mod context;
 mod conversation;
 mod error_formatter;
+mod export;
 mod input_source;
 mod message;
+mod output;
 mod parse;
 use std::path::MAIN_SEPARATOR;
 mod parser;
 mod prompt;
 mod prompt_parser;
 mod server_messenger;
+mod server_requests;
+mod sessions;
 #[cfg(unix)]
 mod skim_integration;
+mod terminal_context;
 mod token_counter;
 pub mod tool_manager;
 pub mod tools;
@@ -33,13 +39,16 @@ use std::process::ExitCode;
 use std::time::Duration;
 
 use amzn_codewhisperer_client::types::SubscriptionStatus;
+use checkpoint::CheckpointManager;
 use clap::{
     Args,
     CommandFactory,
     Parser,
+    Subcommand,
 };
 use cli::compact::CompactStrategy;
 use cli::model::select_model;
+use consts::CONTEXT_WINDOW_SIZE;
 use context::ContextManager;
 pub use conversation::ConversationState;
 use conversation::TokenWarningLevel;
@@ -55,6 +64,7 @@ use crossterm::{
     style,
     terminal,
 };
+pub use export::ExportArgs;
 use eyre::{
     Report,
     Result,
@@ -68,6 +78,12 @@ use message::{
     ToolUseResult,
     ToolUseResultBlock,
 };
+pub use output::ChatOutputFormat;
+use output::{
+    OutputEmitter,
+    OutputEvent,
+    Usage,
+};
 use parse::{
     ParseState,
     interpret_markdown,
@@ -77,13 +93,20 @@ use parser::{
     ResponseParser,
 };
 use regex::Regex;
+use server_requests::SamplingApproval;
+pub use sessions::SessionsSubcommand;
 use spinners::{
     Spinner,
     Spinners,
 };
 use thiserror::Error;
 use time::OffsetDateTime;
-use token_counter::TokenCounter;
+use token_counter::{
+    CharCount,
+    CharCounter,
+    TokenCount,
+    TokenCounter,
+};
 use tokio::signal::ctrl_c;
 use tool_manager::{
     McpServerConfig,
@@ -91,12 +114,17 @@ use tool_manager::{
     ToolManagerBuilder,
 };
 use tools::gh_issue::GhIssueContext;
+use tools::trust_rule::{
+    RuleEffect,
+    TrustRule,
+};
 use tools::{
     OutputKind,
     QueuedTool,
     Tool,
     ToolPermissions,
     ToolSpec,
+    sanitize_path_tool_arg,
 };
 use tracing::{
     debug,
@@ -115,6 +143,7 @@ use winnow::Partial;
 use winnow::stream::Offset;
 
 use crate::api_client::ApiClientError;
+use crate::api_client::backend::ModelBackendConfig;
 use crate::api_client::model::{
     Tool as FigTool,
     ToolResultStatus,
@@ -123,14 +152,21 @@ use crate::api_client::send_message_output::SendMessageOutput;
 use crate::auth::AuthError;
 use crate::auth::builder_id::is_idc_user;
 use crate::cli::chat::cli::SlashCommand;
+use crate::cli::chat::cli::hooks::HookEvent;
 use crate::cli::chat::cli::model::{
     MODEL_OPTIONS,
     default_model_id,
+    sync_model_backend,
 };
 use crate::cli::chat::cli::prompts::{
     GetPromptError,
     PromptsSubcommand,
 };
+use crate::cli::chat::cli::resources::{
+    AttachedResource,
+    parse_resource_mentions,
+    take_resources_for_input,
+};
 use crate::database::settings::Setting;
 use crate::mcp_client::Prompt;
 use crate::os::Os;
@@ -151,6 +187,7 @@ pub const EXTRA_HELP: &str = color_print::cstr! {"
 
 <cyan,em>Tips:</cyan,em>
 <em>!{command}</em>          <black!>Quickly execute a command in your current session</black!>
+<em>@{server}:{uri}</em>     <black!>Include an MCP resource in your message</black!>
 <em>Ctrl(^) + j</em>         <black!>Insert new-line to provide multi-line prompt</black!>
                     <black!>Alternatively, [Alt(⌥) + Enter(⏎)]</black!>
 <em>Ctrl(^) + s</em>         <black!>Fuzzy search commands and context files</black!>
@@ -165,6 +202,9 @@ pub struct ChatArgs {
     /// Resumes the previous conversation from this directory.
     #[arg(short, long)]
     pub resume: bool,
+    /// Resumes the session with the given id or name
+    #[arg(long, value_name = "ID|NAME", conflicts_with = "resume")]
+    pub resume_id: Option<String>,
     /// Context profile to use
     #[arg(long = "profile")]
     pub profile: Option<String>,
@@ -175,21 +215,54 @@ pub struct ChatArgs {
     #[arg(short = 'a', long)]
     pub trust_all_tools: bool,
     /// Trust only this set of tools. Example: trust some tools:
-    /// '--trust-tools=fs_read,fs_write', trust no tools: '--trust-tools='
+    /// '--trust-tools=fs_read,fs_write', trust no tools: '--trust-tools='. Patterns and rules
+    /// scoped to arguments are supported as well, with '!' marking rules that always ask for
+    /// confirmation: '--trust-tools=@gitlab/*,fs_write(path=src/**),!fs_write(path=src/main.rs)'
     #[arg(long, value_delimiter = ',', value_name = "TOOL_NAMES")]
     pub trust_tools: Option<Vec<String>>,
     /// Whether the command should run without expecting user input
     #[arg(long, alias = "non-interactive")]
     pub no_interactive: bool,
+    /// The format of the output. The JSON formats imply --no-interactive and print the text meant
+    /// for humans to stderr instead
+    #[arg(long, value_enum, default_value_t)]
+    pub output_format: ChatOutputFormat,
     /// The first question to ask
     pub input: Option<String>,
+    #[command(subcommand)]
+    pub subcommand: Option<ChatSubcommand>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum ChatSubcommand {
+    /// Manage the saved chat sessions
+    #[command(subcommand)]
+    Sessions(SessionsSubcommand),
+    /// Export a saved conversation as a Markdown or HTML transcript
+    Export(ExportArgs),
 }
 
 impl ChatArgs {
+    /// The model backend configured by the selected profile, if any.
+    pub async fn model_backend(&self, os: &Os) -> Option<ModelBackendConfig> {
+        context::load_profile_config(os, self.profile.as_deref().unwrap_or("default"))
+            .await
+            .ok()?
+            .model_backend
+    }
+
     pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
+        let model_backend = self.model_backend(os).await;
+        match self.subcommand {
+            Some(ChatSubcommand::Sessions(subcommand)) => return subcommand.execute(os).await,
+            Some(ChatSubcommand::Export(args)) => return args.execute(os).await,
+            None => (),
+        }
+
         let mut input = self.input;
+        let interactive = !self.no_interactive && !self.output_format.is_json();
 
-        if self.no_interactive && input.is_none() {
+        if !interactive && input.is_none() {
             if !std::io::stdin().is_terminal() {
                 let mut buffer = String::new();
                 match std::io::stdin().read_to_string(&mut buffer) {
@@ -255,19 +328,38 @@ impl ChatArgs {
         // If modelId is specified, verify it exists before starting the chat
         let model_id: Option<String> = if let Some(model_name) = self.model {
             let model_name_lower = model_name.to_lowercase();
-            match MODEL_OPTIONS.iter().find(|opt| opt.name == model_name_lower) {
-                Some(opt) => Some((opt.model_id).to_string()),
-                None => {
-                    let available_names: Vec<&str> = MODEL_OPTIONS.iter().map(|opt| opt.name).collect();
+            if let Some(model_backend) = &model_backend {
+                // Models served by the profile's backend are passed through as is
+                if !model_backend.models().contains(&model_name) {
                     bail!(
-                        "Model '{}' does not exist. Available models: {}",
+                        "Model '{}' is not served by the model backend of this profile. Available models: {}",
                         model_name,
-                        available_names.join(", ")
+                        model_backend.models().join(", ")
                     );
-                },
+                }
+                Some(model_name)
+            } else {
+                match MODEL_OPTIONS.iter().find(|opt| opt.name == model_name_lower) {
+                    Some(opt) => Some((opt.model_id).to_string()),
+                    None => {
+                        let available_names: Vec<&str> = MODEL_OPTIONS.iter().map(|opt| opt.name).collect();
+                        bail!(
+                            "Model '{}' does not exist. Available models: {}",
+                            model_name,
+                            available_names.join(", ")
+                        );
+                    },
+                }
             }
         } else {
-            None
+            // Skips looking up the default Amazon Q model, which requires AWS
+            model_backend.and_then(|model_backend| model_backend.models().first().cloned())
+        };
+
+        let previous_conversation = match (self.resume, &self.resume_id) {
+            (_, Some(id_or_name)) => sessions::load_conversation(os, Some(id_or_name.as_str()))?,
+            (true, None) => sessions::load_conversation(os, None)?,
+            (false, None) => None,
         };
 
         let conversation_id = uuid::Uuid::new_v4().to_string();
@@ -279,7 +371,7 @@ impl ChatArgs {
             .prompt_list_sender(prompt_response_sender)
             .prompt_list_receiver(prompt_request_receiver)
             .conversation_id(&conversation_id)
-            .build(os, Box::new(std::io::stderr()), !self.no_interactive)
+            .build(os, Box::new(std::io::stderr()), interactive)
             .await?;
         let tool_config = tool_manager.load_tools(os, &mut stderr).await?;
         let mut tool_permissions = ToolPermissions::new(tool_config.len());
@@ -289,8 +381,18 @@ impl ChatArgs {
             for tool in tool_config.values() {
                 tool_permissions.trust_tool(&tool.name);
             }
-        } else if let Some(trusted) = self.trust_tools.map(|vec| vec.into_iter().collect::<HashSet<_>>()) {
+        } else if let Some(trust_tools) = self.trust_tools {
             // --trust-all-tools takes precedence over --trust-tools=...
+            let (rules, trusted): (Vec<String>, Vec<String>) = TrustRule::merge_split_values(trust_tools, ",")
+                .into_iter()
+                .partition(|value| TrustRule::is_rule(value));
+            let trusted = trusted.into_iter().collect::<HashSet<_>>();
+            for rule in rules {
+                match rule.parse::<TrustRule>() {
+                    Ok(rule) => tool_permissions.add_rule(rule),
+                    Err(e) => bail!("Invalid tool rule '{rule}': {e}"),
+                }
+            }
             for tool_name in &trusted {
                 if !tool_name.is_empty() {
                     // Store the original trust settings for later use with MCP tools
@@ -300,7 +402,7 @@ impl ChatArgs {
 
             // Apply to currently known tools
             for tool in tool_config.values() {
-                if trusted.contains(&tool.name) {
+                if trusted.contains(&tool.name) || tool_permissions.is_trusted(&tool.name) {
                     tool_permissions.trust_tool(&tool.name);
                 } else {
                     tool_permissions.untrust_tool(&tool.name);
@@ -308,26 +410,41 @@ impl ChatArgs {
             }
         }
 
-        ChatSession::new(
+        let result = ChatSession::new(
             os,
             stdout,
             stderr,
             &conversation_id,
             input,
             InputSource::new(os, prompt_request_sender, prompt_response_receiver)?,
-            self.resume,
+            previous_conversation,
             || terminal::window_size().map(|s| s.columns.into()).ok(),
             tool_manager,
             self.profile,
             model_id,
             tool_config,
             tool_permissions,
-            !self.no_interactive,
+            interactive,
+            self.output_format,
         )
         .await?
         .spawn(os)
-        .await
-        .map(|_| ExitCode::SUCCESS)
+        .await;
+
+        // A replayed session fails if it did not send exactly the requests of the cassette
+        let problems = os.client.cassette_problems();
+        if !problems.is_empty() {
+            let mut stderr = std::io::stderr();
+            execute!(
+                stderr,
+                style::SetForegroundColor(Color::Red),
+                style::Print(format!("\nChat cassette replay failed:\n{}\n", problems.join("\n"))),
+                style::SetForegroundColor(Color::Reset)
+            )?;
+            return result.map(|_| ExitCode::FAILURE);
+        }
+
+        result.map(|_| ExitCode::SUCCESS)
     }
 }
 
@@ -391,6 +508,9 @@ const TRUST_ALL_TEXT: &str = color_print::cstr! {"<green!>All tools are now trus
 \nAgents can sometimes do unexpected things so understand the risks.</green!>
 \nLearn more at https://docs.aws.amazon.com/amazonq/latest/qdeveloper-ug/command-line-chat-security.html#command-line-chat-trustall-safety"};
 
+/// How many times [`HookTrigger::TurnEnd`](cli::hooks::HookTrigger::TurnEnd) hooks may ask the
+/// assistant to continue before the user is prompted
+const MAX_TURN_END_CONTINUATIONS: usize = 3;
 const TOOL_BULLET: &str = " ● ";
 const CONTINUATION_LINE: &str = " ⋮ ";
 const PURPOSE_ARROW: &str = " ↳ ";
@@ -479,8 +599,9 @@ impl From<parser::RecvError> for ChatError {
 }
 
 pub struct ChatSession {
-    /// For output read by humans and machine
-    pub stdout: std::io::Stdout,
+    /// For output read by humans and machine. This is stderr with the JSON output formats, as
+    /// stdout is then reserved for the events written by [OutputEmitter].
+    pub stdout: Box<dyn Write + Send>,
     /// For display output, only read by humans
     pub stderr: std::io::Stderr,
     initial_input: Option<String>,
@@ -504,6 +625,19 @@ pub struct ChatSession {
     failed_request_ids: Vec<String>,
     /// Pending prompts to be sent
     pending_prompts: VecDeque<Prompt>,
+    /// Resources attached via `/resources attach`, to be included in the next user message
+    pending_resources: Vec<AttachedResource>,
+    /// Attached resources (as server name and uri) that have been subscribed to. These are
+    /// included again in the next user message whenever their server reports them as updated.
+    watched_resources: HashSet<(String, String)>,
+    /// Checkpoints of the files written by the assistant, used to undo its changes
+    checkpoints: CheckpointManager,
+    /// Machine readable output of the session, see [ChatOutputFormat]
+    output: OutputEmitter,
+    /// How many times turn end hooks asked the assistant to continue since the last user prompt
+    turn_end_continuations: usize,
+    /// Sampling requests of mcp servers awaiting the approval of the user
+    sampling_approvals: Option<tokio::sync::mpsc::Receiver<SamplingApproval>>,
     interactive: bool,
     inner: Option<ChatState>,
 }
@@ -517,14 +651,15 @@ impl ChatSession {
         conversation_id: &str,
         mut input: Option<String>,
         input_source: InputSource,
-        resume_conversation: bool,
+        previous_conversation: Option<ConversationState>,
         terminal_width_provider: fn() -> Option<usize>,
-        tool_manager: ToolManager,
+        mut tool_manager: ToolManager,
         profile: Option<String>,
         model_id: Option<String>,
         tool_config: HashMap<String, ToolSpec>,
         tool_permissions: ToolPermissions,
         interactive: bool,
+        output_format: ChatOutputFormat,
     ) -> Result<Self> {
         let valid_model_id = match model_id {
             Some(id) => id,
@@ -547,22 +682,18 @@ impl ChatSession {
             },
         };
 
-        // Reload prior conversation
-        let mut existing_conversation = false;
-        let previous_conversation = std::env::current_dir()
-            .ok()
-            .and_then(|cwd| os.database.get_conversation_by_path(cwd).ok())
-            .flatten();
+        let sampling_approvals = tool_manager.take_sampling_approvals();
 
         // Only restore conversations where there were actual messages.
-        // Prevents edge case where user clears conversation then exits without chatting.
-        let conversation = match resume_conversation
-            && previous_conversation
-                .as_ref()
-                .is_some_and(|cs| !cs.history().is_empty())
-        {
-            true => {
-                let mut cs = previous_conversation.unwrap();
+        // Prevents edge case where user clears conversation then exits without chatting. The id is
+        // kept regardless, such that a session named before any message was sent is continued.
+        let mut existing_conversation = false;
+        let conversation_id = previous_conversation
+            .as_ref()
+            .map_or(conversation_id, |cs| cs.conversation_id())
+            .to_string();
+        let conversation = match previous_conversation.filter(|cs| !cs.history().is_empty()) {
+            Some(mut cs) => {
                 existing_conversation = true;
                 cs.reload_serialized_state(os).await;
                 input = Some(input.unwrap_or("In a few words, summarize our conversation so far.".to_owned()));
@@ -571,10 +702,10 @@ impl ChatSession {
                 cs.enforce_tool_use_history_invariants();
                 cs
             },
-            false => {
+            None => {
                 ConversationState::new(
                     os,
-                    conversation_id,
+                    &conversation_id,
                     tool_config,
                     profile,
                     tool_manager,
@@ -584,6 +715,12 @@ impl ChatSession {
             },
         };
 
+        let stdout: Box<dyn Write + Send> = match output_format {
+            ChatOutputFormat::Text => Box::new(stdout),
+            ChatOutputFormat::Json | ChatOutputFormat::StreamJson => Box::new(std::io::stderr()),
+        };
+        let output = OutputEmitter::new(output_format, conversation.conversation_id());
+
         Ok(Self {
             stdout,
             stderr,
@@ -600,6 +737,12 @@ impl ChatSession {
             tool_use_status: ToolUseStatus::Idle,
             failed_request_ids: Vec::new(),
             pending_prompts: VecDeque::new(),
+            pending_resources: Vec::new(),
+            watched_resources: HashSet::new(),
+            checkpoints: CheckpointManager::default(),
+            output,
+            turn_end_continuations: 0,
+            sampling_approvals,
             interactive,
             inner: Some(ChatState::default()),
         })
@@ -681,6 +824,10 @@ impl ChatSession {
         // We encountered an error. Handle it.
         error!(?err, "An error occurred processing the current state");
         let (reason, reason_desc) = get_error_reason(&err);
+        let error_event = OutputEvent::Error {
+            message: err.to_string(),
+            reason: reason.clone(),
+        };
         self.send_error_telemetry(os, reason, Some(reason_desc), err.status_code())
             .await;
 
@@ -876,6 +1023,7 @@ impl ChatSession {
                         )?;
                     }
 
+                    self.output.emit(error_event);
                     self.inner = Some(ChatState::PromptUser {
                         skip_printing_tools: false,
                     });
@@ -917,6 +1065,7 @@ impl ChatSession {
             )?;
         }
 
+        self.output.emit(error_event);
         self.conversation.enforce_conversation_invariants();
         self.conversation.reset_next_user_message();
         self.pending_tool_index = None;
@@ -1056,12 +1205,20 @@ impl ChatSession {
         }
         self.stderr.flush()?;
 
+        sync_model_backend(os, self).await?;
         if let Some(ref id) = self.conversation.model {
-            if let Some(model_option) = MODEL_OPTIONS.iter().find(|option| option.model_id == *id) {
+            let model_name = match os.client.model_backend() {
+                Some(_) => Some(id.as_str()),
+                None => MODEL_OPTIONS
+                    .iter()
+                    .find(|option| option.model_id == *id)
+                    .map(|option| option.name),
+            };
+            if let Some(model_name) = model_name {
                 execute!(
                     self.stderr,
                     style::SetForegroundColor(Color::Cyan),
-                    style::Print(format!("🤖 You are chatting with {}\n", model_option.name)),
+                    style::Print(format!("🤖 You are chatting with {}\n", model_name)),
                     style::SetForegroundColor(Color::Reset),
                     style::Print("\n")
                 )?;
@@ -1072,10 +1229,33 @@ impl ChatSession {
             self.inner = Some(ChatState::HandleInput { input: user_input });
         }
 
+        self.output.emit(OutputEvent::Init {
+            conversation_id: self.conversation.conversation_id().to_string(),
+            model: self.conversation.model.clone(),
+            tools: self
+                .conversation
+                .tools
+                .values()
+                .flatten()
+                .map(|tool| match tool {
+                    FigTool::ToolSpecification(spec) => spec.name.clone(),
+                })
+                .collect(),
+        });
+
         while !matches!(self.inner, Some(ChatState::Exit)) {
-            self.next(os).await?;
+            if let Err(err) = self.next(os).await {
+                let (reason, _) = get_error_reason(&err);
+                self.output.emit(OutputEvent::Error {
+                    message: err.to_string(),
+                    reason,
+                });
+                self.output.finish();
+                return Err(err.into());
+            }
         }
 
+        self.output.finish();
         Ok(())
     }
 
@@ -1394,6 +1574,11 @@ impl ChatSession {
             style::SetForegroundColor(Color::Reset),
             style::SetAttribute(Attribute::Reset)
         )?;
+        // Sampling requests that arrived since the last prompt are answered before reading the input
+        while let Some(approval) = self.sampling_approvals.as_mut().and_then(|rx| rx.try_recv().ok()) {
+            approval.ask(&mut self.input_source, &mut self.stderr);
+        }
+
         let prompt = self.generate_tool_trust_prompt();
         let user_input = match self.read_user_input(&prompt, false) {
             Some(input) => input,
@@ -1482,7 +1667,10 @@ impl ChatSession {
             Ok(ChatState::PromptUser {
                 skip_printing_tools: false,
             })
-        } else if let Some(command) = input.strip_prefix("@") {
+        } else if let Some(command) = input
+            .strip_prefix("@")
+            .filter(|_| !self.starts_with_resource_mention(input))
+        {
             let input_parts =
                 shlex::split(command).ok_or(ChatError::Custom("Error splitting prompt command".into()))?;
 
@@ -1563,15 +1751,24 @@ impl ChatSession {
                 // TODO: Update this flow to something that does *not* require two requests just to
                 // get a meaningful response from the user - this is a short term solution before
                 // we decide on a better flow.
-                let user_input = if ["n", "N"].contains(&user_input.trim()) {
-                    "I deny this tool request. Ask a follow up question clarifying the expected action".to_string()
-                } else {
-                    user_input
-                };
+                if ["n", "N"].contains(&user_input.trim()) {
+                    user_input =
+                        "I deny this tool request. Ask a follow up question clarifying the expected action".to_string();
+                }
+            }
+
+            // Include any attached, mentioned, or updated resources
+            if let Some(resources) = take_resources_for_input(self, &user_input).await? {
+                user_input = format!("{}\n\n{}", user_input.trim_end(), resources.trim_end());
+            }
+
+            if self.pending_tool_index.is_some() {
                 self.conversation.abandon_tool_use(&self.tool_uses, user_input);
             } else {
                 self.conversation.set_next_user_message(user_input).await;
             }
+            self.checkpoints.begin_turn();
+            self.turn_end_continuations = 0;
 
             let conv_state = self
                 .conversation
@@ -1593,20 +1790,81 @@ impl ChatSession {
         }
     }
 
+    /// Whether the input starts with an `@server:uri` resource mention, as opposed to an
+    /// `@prompt` invocation.
+    fn starts_with_resource_mention(&self, input: &str) -> bool {
+        let first_word = input.split_whitespace().next().unwrap_or_default();
+        let server_names = self
+            .conversation
+            .tool_manager
+            .clients
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        !parse_resource_mentions(first_word, &server_names).is_empty()
+    }
+
+    /// Runs the pre tool use hooks of a queued tool, returning the context they added, or why they
+    /// denied the tool use
+    async fn run_pre_tool_use_hooks(
+        &mut self,
+        index: usize,
+    ) -> Result<Result<Vec<(String, String)>, String>, ChatError> {
+        let Some(context_manager) = self.conversation.context_manager.as_mut() else {
+            return Ok(Ok(Vec::new()));
+        };
+        let tool = &self.tool_uses[index];
+        let event = HookEvent::PreToolUse {
+            tool_name: &tool.name,
+            tool_input: &tool.args,
+        };
+        let responses = context_manager.run_event_hooks(&event, &mut self.stderr).await?;
+        if let Some((hook, response)) = responses.iter().find(|(_, response)| response.is_denied()) {
+            return Ok(Err(format!(
+                "The tool use was denied by the hook '{}': {}",
+                hook.name,
+                response.reason.as_deref().unwrap_or("no reason given")
+            )));
+        }
+        Ok(Ok(responses
+            .into_iter()
+            .filter_map(|(hook, response)| response.additional_context.map(|context| (hook.name, context)))
+            .collect()))
+    }
+
     async fn tool_use_execute(&mut self, os: &mut Os) -> Result<ChatState, ChatError> {
+        let profile_rules = self
+            .conversation
+            .context_manager
+            .as_ref()
+            .map(|cm| cm.tool_rules())
+            .unwrap_or_default();
+        let cwd = os.env.current_dir()?;
+
         // Verify tools have permissions.
         for i in 0..self.tool_uses.len() {
+            // Hooks run before the user is asked, such that they can deny the tool use first
+            if self.tool_uses[i].pre_hook_result.is_none() {
+                let result = self.run_pre_tool_use_hooks(i).await?;
+                self.tool_uses[i].pre_hook_result = Some(result);
+            }
             let tool = &mut self.tool_uses[i];
 
-            // Manually accepted by the user or otherwise verified already.
-            if tool.accepted {
+            // Manually accepted by the user, otherwise verified already, or denied by a hook.
+            if tool.accepted || matches!(tool.pre_hook_result, Some(Err(_))) {
                 continue;
             }
 
-            // If there is an override, we will use it. Otherwise fall back to Tool's default.
-            let allowed = self.tool_permissions.trust_all
-                || (self.tool_permissions.has(&tool.name) && self.tool_permissions.is_trusted(&tool.name))
-                || !tool.tool.requires_acceptance(os);
+            // Rules take precedence. Then, if there is an override, we will use it. Otherwise fall
+            // back to Tool's default.
+            let allowed = match self.tool_permissions.evaluate_rules(tool, &profile_rules, &cwd) {
+                Some(effect) => effect == RuleEffect::Allow,
+                None => {
+                    self.tool_permissions.trust_all
+                        || (self.tool_permissions.has(&tool.name) && self.tool_permissions.is_trusted(&tool.name))
+                        || !tool.tool.requires_acceptance(os)
+                },
+            };
 
             if os
                 .database
@@ -1654,8 +1912,61 @@ impl ChatSession {
                 }
             }
 
+            // Hooks may have denied the tool use, or added context to its result
+            let mut hook_context = Vec::new();
+            match &tool.pre_hook_result {
+                Some(Err(reason)) => {
+                    let reason = reason.clone();
+                    self.output.emit(OutputEvent::ToolResult {
+                        tool_use_id: tool.id.clone(),
+                        name: tool.name.clone(),
+                        status: output::ToolResultStatus::Error,
+                        content: serde_json::Value::String(reason.clone()),
+                        duration_ms: 0,
+                    });
+                    tool_telemetry.and_modify(|ev| ev.is_success = Some(false));
+                    tool_results.push(ToolUseResult {
+                        tool_use_id: tool.id.clone(),
+                        content: vec![ToolUseResultBlock::Text(reason)],
+                        status: ToolResultStatus::Error,
+                    });
+                    continue;
+                },
+                Some(Ok(context)) => hook_context.extend(context.iter().cloned()),
+                None => {},
+            }
+
+            if let Tool::FsWrite(fs_write) = &tool.tool {
+                for path in fs_write.paths() {
+                    let path = sanitize_path_tool_arg(os, path);
+                    if let Err(e) = self.checkpoints.record(os, &path).await {
+                        error!(?path, "Failed to record checkpoint: {:?}", e);
+                    }
+                }
+            }
+
             let tool_start = std::time::Instant::now();
-            let invoke_result = tool.tool.invoke(os, &mut self.stdout).await;
+            // Servers may request sampling while handling a tool call, which the user is asked to
+            // approve while the tool is still running
+            let invoke_result = {
+                let invoke = tool.tool.invoke(os, &mut self.stdout);
+                tokio::pin!(invoke);
+                loop {
+                    let approval = async {
+                        match self.sampling_approvals.as_mut() {
+                            Some(rx) => rx.recv().await,
+                            None => std::future::pending().await,
+                        }
+                    };
+                    tokio::select! {
+                        result = &mut invoke => break result,
+                        Some(approval) = approval => {
+                            drop(self.spinner.take());
+                            approval.ask(&mut self.input_source, &mut self.stderr);
+                        },
+                    }
+                }
+            };
 
             if self.spinner.is_some() {
                 queue!(
@@ -1668,6 +1979,7 @@ impl ChatSession {
             execute!(self.stdout, style::Print("\n"))?;
 
             let tool_time = std::time::Instant::now().duration_since(tool_start);
+            let duration_ms = u64::try_from(tool_time.as_millis()).unwrap_or(u64::MAX);
             if let Tool::Custom(ct) = &tool.tool {
                 tool_telemetry = tool_telemetry.and_modify(|ev| {
                     ev.custom_tool_call_latency = Some(tool_time.as_secs() as usize);
@@ -1676,6 +1988,13 @@ impl ChatSession {
                 });
             }
             let tool_time = format!("{}.{}", tool_time.as_secs(), tool_time.subsec_millis());
+            let (tool_response, success) = match &invoke_result {
+                Ok(result) => match &result.output {
+                    OutputKind::Json(json) => (json.to_string(), true),
+                    _ => (result.as_str().to_owned(), true),
+                },
+                Err(err) => (err.to_string(), false),
+            };
             match invoke_result {
                 Ok(result) => {
                     match result.output {
@@ -1702,6 +2021,13 @@ impl ChatSession {
                         style::Print("\n\n"),
                     )?;
 
+                    self.output.emit(OutputEvent::ToolResult {
+                        tool_use_id: tool.id.clone(),
+                        name: tool.name.clone(),
+                        status: output::ToolResultStatus::Success,
+                        content: OutputEvent::tool_result_content(&result.output),
+                        duration_ms,
+                    });
                     tool_telemetry = tool_telemetry.and_modify(|ev| ev.is_success = Some(true));
                     if let Tool::Custom(_) = &tool.tool {
                         tool_telemetry
@@ -1729,6 +2055,13 @@ impl ChatSession {
                         style::Print("\n\n"),
                     )?;
 
+                    self.output.emit(OutputEvent::ToolResult {
+                        tool_use_id: tool.id.clone(),
+                        name: tool.name.clone(),
+                        status: output::ToolResultStatus::Error,
+                        content: serde_json::Value::String(err.to_string()),
+                        duration_ms,
+                    });
                     tool_telemetry.and_modify(|ev| ev.is_success = Some(false));
                     tool_results.push(ToolUseResult {
                         tool_use_id: tool.id.clone(),
@@ -1747,6 +2080,38 @@ impl ChatSession {
                     }
                 },
             }
+
+            // Hooks run after the tool may flag its result, e.g. if formatting or linting failed
+            if let Some(context_manager) = self.conversation.context_manager.as_mut() {
+                let event = HookEvent::PostToolUse {
+                    tool_name: &tool.name,
+                    tool_input: &tool.args,
+                    tool_response: &tool_response,
+                    success,
+                };
+                let responses = context_manager.run_event_hooks(&event, &mut self.stderr).await?;
+                if let Some(tool_result) = tool_results.last_mut() {
+                    for (hook, response) in responses {
+                        if response.is_denied() {
+                            tool_result.status = ToolResultStatus::Error;
+                            tool_result.content.push(ToolUseResultBlock::Text(format!(
+                                "The hook '{}' reported a problem with this tool use: {}",
+                                hook.name,
+                                response.reason.as_deref().unwrap_or("no reason given")
+                            )));
+                        }
+                        if let Some(context) = response.additional_context {
+                            hook_context.push((hook.name, context));
+                        }
+                    }
+
+                    for (hook_name, context) in hook_context {
+                        tool_result.content.push(ToolUseResultBlock::Text(format!(
+                            "Context from the hook '{hook_name}': {context}"
+                        )));
+                    }
+                }
+            }
         }
 
         if !image_blocks.is_empty() {
@@ -1791,6 +2156,8 @@ impl ChatSession {
 
         let mut tool_uses = Vec::new();
         let mut tool_name_being_recvd: Option<String> = None;
+        let mut output_chars = CharCount::from(0);
+        let mut assistant_response = String::new();
 
         if self.spinner.is_some() {
             drop(self.spinner.take());
@@ -1823,6 +2190,7 @@ impl ChatSession {
                                 response_prefix_printed = true;
                             }
                             buf.push_str(&text);
+                            self.output.emit(OutputEvent::AssistantText { text });
                         },
                         parser::ResponseEvent::ToolUse(tool_use) => {
                             if self.spinner.is_some() {
@@ -1834,6 +2202,11 @@ impl ChatSession {
                                     cursor::Show
                                 )?;
                             }
+                            self.output.emit(OutputEvent::ToolUse {
+                                id: tool_use.id.clone(),
+                                name: tool_use.name.clone(),
+                                input: tool_use.args.clone(),
+                            });
                             tool_uses.push(tool_use);
                             tool_name_being_recvd = None;
                         },
@@ -1843,6 +2216,8 @@ impl ChatSession {
                             if message.content() == RESPONSE_TIMEOUT_CONTENT {
                                 error!(?request_id, ?message, "Encountered an unexpected model response");
                             }
+                            output_chars = message.char_count();
+                            assistant_response = message.content().to_owned();
                             self.conversation.push_assistant_message(os, message);
                             ended = true;
                         },
@@ -1981,6 +2356,18 @@ impl ChatSession {
             }
 
             if ended {
+                if self.output.format().is_json() {
+                    let context_chars = self.conversation.calculate_char_count(os).await?;
+                    self.output.emit(OutputEvent::Usage {
+                        request_id: request_id.clone(),
+                        usage: Usage {
+                            output_tokens: TokenCount::from(output_chars).value(),
+                            context_tokens: TokenCount::from(context_chars).value(),
+                            context_window_tokens: CONTEXT_WINDOW_SIZE,
+                        },
+                    });
+                }
+
                 self.send_chat_telemetry(os, request_id, TelemetryResult::Succeeded, None, None, None)
                     .await;
 
@@ -2019,12 +2406,63 @@ impl ChatSession {
             self.tool_uses.clear();
             self.pending_tool_index = None;
 
+            if let Some(reason) = self.run_turn_end_hooks(&assistant_response).await? {
+                self.conversation.set_next_user_message(reason).await;
+                if self.interactive {
+                    self.spinner = Some(Spinner::new(Spinners::Dots, "Thinking...".to_owned()));
+                }
+                return Ok(ChatState::HandleResponseStream(
+                    os.client
+                        .send_message(
+                            self.conversation
+                                .as_sendable_conversation_state(os, &mut self.stderr, false)
+                                .await?,
+                        )
+                        .await?,
+                ));
+            }
+
             Ok(ChatState::PromptUser {
                 skip_printing_tools: false,
             })
         }
     }
 
+    /// Run the turn end hooks, returning the message asking the assistant to continue if a hook
+    /// denied the end of the turn. Context added by the hooks is sent with the next user prompt.
+    async fn run_turn_end_hooks(&mut self, assistant_response: &str) -> Result<Option<String>, ChatError> {
+        let Some(context_manager) = self.conversation.context_manager.as_mut() else {
+            return Ok(None);
+        };
+
+        let event = HookEvent::TurnEnd { assistant_response };
+        let responses = context_manager.run_event_hooks(&event, &mut self.stderr).await?;
+
+        let mut reasons = Vec::new();
+        for (hook, response) in responses {
+            if response.is_denied() {
+                reasons.push(format!(
+                    "'{}': {}",
+                    hook.name,
+                    response.reason.as_deref().unwrap_or("no reason given")
+                ));
+            }
+            if let Some(context) = response.additional_context {
+                self.conversation.add_turn_end_context(&hook.name, &context);
+            }
+        }
+
+        if reasons.is_empty() || self.turn_end_continuations >= MAX_TURN_END_CONTINUATIONS {
+            return Ok(None);
+        }
+
+        self.turn_end_continuations += 1;
+        Ok(Some(format!(
+            "[SYSTEM NOTE: This is an automated message from script hooks, not from the user]\n\nHooks did not allow you to end your response yet. Address the following and continue:\n{}",
+            reasons.join("\n")
+        )))
+    }
+
     async fn validate_tools(&mut self, os: &Os, tool_uses: Vec<AssistantToolUse>) -> Result<ChatState, ChatError> {
         let conv_id = self.conversation.conversation_id().to_owned();
         debug!(?tool_uses, "Validating tool uses");
@@ -2034,6 +2472,7 @@ impl ChatSession {
         for tool_use in tool_uses {
             let tool_use_id = tool_use.id.clone();
             let tool_use_name = tool_use.name.clone();
+            let tool_use_args = tool_use.args.clone();
             let mut tool_telemetry =
                 ToolUseEventBuilder::new(conv_id.clone(), tool_use.id.clone(), self.conversation.model.clone())
                     .set_tool_use_id(tool_use_id.clone())
@@ -2052,6 +2491,8 @@ impl ChatSession {
                                 name: tool_use_name,
                                 tool,
                                 accepted: false,
+                                args: tool_use_args,
+                                pre_hook_result: None,
                             });
                         },
                         Err(err) => {
@@ -2130,7 +2571,7 @@ impl ChatSession {
     }
 
     async fn retry_model_overload(&mut self, os: &mut Os) -> Result<ChatState, ChatError> {
-        match select_model(self) {
+        match select_model(os, self) {
             Ok(Some(_)) => (),
             Ok(None) => {
                 // User did not select a model, so reset the current request state.
@@ -2491,7 +2932,7 @@ mod tests {
                 "y".to_string(),
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -2499,6 +2940,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            ChatOutputFormat::Text,
         )
         .await
         .unwrap()
@@ -2509,6 +2951,190 @@ mod tests {
         assert_eq!(os.fs.read_to_string("/file.txt").await.unwrap(), "Hello, world!\n");
     }
 
+    #[tokio::test]
+    async fn test_flow_misconfigured_model_backend() {
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(serde_json::json!([["Hello from Amazon Q"]]));
+
+        let config = serde_json::json!({
+            "model_backend": {
+                "type": "openai_compatible",
+                "base_url": "http://localhost:8000/v1",
+                "models": []
+            }
+        });
+        let config_path = context::profile_context_path(&os, "default").unwrap();
+        os.fs.create_dir_all(config_path.parent().unwrap()).await.unwrap();
+        os.fs
+            .write(&config_path, serde_json::to_string(&config).unwrap())
+            .await
+            .unwrap();
+
+        let tool_manager = ToolManager::default();
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            None,
+            InputSource::new_mock(vec!["hello".to_string(), "exit".to_string()]),
+            None,
+            || Some(80),
+            tool_manager,
+            None,
+            None,
+            tool_config,
+            ToolPermissions::new(0),
+            true,
+            ChatOutputFormat::Text,
+        )
+        .await
+        .unwrap()
+        .spawn(&mut os)
+        .await
+        .expect("a misconfigured model backend must not end the session");
+
+        assert!(os.client.model_backend().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_flow_record_and_replay() {
+        async fn run(os: &mut Os) {
+            let tool_manager = ToolManager::default();
+            let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+                .expect("Tools failed to load");
+            ChatSession::new(
+                os,
+                std::io::stdout(),
+                std::io::stderr(),
+                "fake_conv_id",
+                None,
+                InputSource::new_mock(vec![
+                    "create a new file".to_string(),
+                    "y".to_string(),
+                    "exit".to_string(),
+                ]),
+                None,
+                || Some(80),
+                tool_manager,
+                None,
+                None,
+                tool_config,
+                ToolPermissions::new(0),
+                true,
+                ChatOutputFormat::Text,
+            )
+            .await
+            .unwrap()
+            .spawn(os)
+            .await
+            .unwrap();
+        }
+
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Sure, I'll create a file for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file.txt",
+                    }
+                }
+            ],
+            [
+                "Hope that looks good to you!",
+            ],
+        ]));
+        let fs = os.fs.clone();
+        os.client.set_cassette_record(&fs, "/cassette.json");
+        run(&mut os).await;
+
+        let cassette = crate::api_client::cassette::Cassette::load(&os.fs, "/cassette.json")
+            .await
+            .unwrap();
+
+        let mut os = Os::new().await.unwrap();
+        os.client.set_cassette_replay(cassette);
+        run(&mut os).await;
+
+        os.client.assert_cassette_finished();
+        assert_eq!(os.fs.read_to_string("/file.txt").await.unwrap(), "Hello, world!\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_flow_pre_tool_use_hook_denies() {
+        let mut os = Os::new().await.unwrap();
+        os.client.set_mock_output(serde_json::json!([
+            [
+                "Sure, I'll create a file for you",
+                {
+                    "tool_use_id": "1",
+                    "name": "fs_write",
+                    "args": {
+                        "command": "create",
+                        "file_text": "Hello, world!",
+                        "path": "/file.txt",
+                    }
+                }
+            ],
+            [
+                "The hook denied it",
+            ],
+        ]));
+
+        let config = serde_json::json!({
+            "hooks": {
+                "no_writes": {
+                    "trigger": "pre_tool_use",
+                    "type": "inline",
+                    "matcher": "fs_*",
+                    "command": "echo 'no writes allowed' >&2; exit 1"
+                }
+            }
+        });
+        let config_path = context::profile_context_path(&os, "default").unwrap();
+        os.fs.create_dir_all(config_path.parent().unwrap()).await.unwrap();
+        os.fs
+            .write(&config_path, serde_json::to_string(&config).unwrap())
+            .await
+            .unwrap();
+
+        let tool_manager = ToolManager::default();
+        let tool_config = serde_json::from_str::<HashMap<String, ToolSpec>>(include_str!("tools/tool_index.json"))
+            .expect("Tools failed to load");
+        let mut session = ChatSession::new(
+            &mut os,
+            std::io::stdout(),
+            std::io::stderr(),
+            "fake_conv_id",
+            None,
+            // The hook denies before the user is asked to accept the tool
+            InputSource::new_mock(vec!["create a new file".to_string(), "exit".to_string()]),
+            None,
+            || Some(80),
+            tool_manager,
+            None,
+            None,
+            tool_config,
+            ToolPermissions::new(0),
+            true,
+            ChatOutputFormat::Text,
+        )
+        .await
+        .unwrap();
+        session.spawn(&mut os).await.unwrap();
+
+        assert!(!os.fs.exists("/file.txt"));
+        assert!(format!("{:?}", session.conversation.history()).contains("denied by the hook 'no_writes'"));
+    }
+
     #[tokio::test]
     async fn test_flow_tool_permissions() {
         let mut os = Os::new().await.unwrap();
@@ -2632,7 +3258,7 @@ mod tests {
                 "n".to_string(),             // cancel
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -2640,6 +3266,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            ChatOutputFormat::Text,
         )
         .await
         .unwrap()
@@ -2727,7 +3354,7 @@ mod tests {
                 "y".to_string(),
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -2735,6 +3362,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            ChatOutputFormat::Text,
         )
         .await
         .unwrap()
@@ -2801,7 +3429,7 @@ mod tests {
                 "create a new file".to_string(),
                 "exit".to_string(),
             ]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -2809,6 +3437,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            ChatOutputFormat::Text,
         )
         .await
         .unwrap()
@@ -2849,7 +3478,7 @@ mod tests {
             "fake_conv_id",
             None,
             InputSource::new_mock(vec!["/subscribe".to_string(), "y".to_string(), "/quit".to_string()]),
-            false,
+            None,
             || Some(80),
             tool_manager,
             None,
@@ -2857,6 +3486,7 @@ mod tests {
             tool_config,
             ToolPermissions::new(0),
             true,
+            ChatOutputFormat::Text,
         )
         .await
         .unwrap()
@@ -0,0 +1,322 @@
+use std::io::Write;
+use std::time::Instant;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use tracing::error;
+
+use super::tools::OutputKind;
+
+/// The format in which `q chat` writes its output to stdout.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ChatOutputFormat {
+    /// Styled text meant to be read in a terminal
+    #[default]
+    Text,
+    /// A single JSON object describing the result, written once the chat ends
+    Json,
+    /// Newline delimited JSON events, written as the chat progresses
+    StreamJson,
+}
+
+impl ChatOutputFormat {
+    /// Whether the output is meant to be read by machines
+    pub fn is_json(&self) -> bool {
+        !matches!(self, Self::Text)
+    }
+}
+
+/// Token usage of the conversation.
+///
+/// The backend does not report token usage, so the counts are estimated from the number of
+/// characters (see [super::token_counter::TokenCounter]).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    /// Tokens generated by the model
+    pub output_tokens: usize,
+    /// Tokens of the whole conversation, including context files and tool results
+    pub context_tokens: usize,
+    /// Size of the context window of the model
+    pub context_window_tokens: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolResultStatus {
+    Success,
+    Error,
+}
+
+/// An event written by the JSON output formats. Every event is serialized as a single JSON object
+/// with a `type` field naming the variant in snake_case, e.g. `{"type":"assistant_text",...}`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputEvent {
+    /// Written once, before the first message is sent
+    Init {
+        conversation_id: String,
+        model: Option<String>,
+        /// Names of the tools available to the model
+        tools: Vec<String>,
+    },
+    /// A chunk of the response of the assistant, as it was streamed
+    AssistantText { text: String },
+    /// A tool the assistant requested to use
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    /// The outcome of running a tool
+    ToolResult {
+        tool_use_id: String,
+        name: String,
+        status: ToolResultStatus,
+        /// The output of the tool: a string, or JSON for tools that return structured output
+        content: serde_json::Value,
+        duration_ms: u64,
+    },
+    /// Written at the end of every response of the assistant
+    Usage {
+        request_id: Option<String>,
+        #[serde(flatten)]
+        usage: Usage,
+    },
+    /// An error that ended the current turn of the conversation
+    Error { message: String, reason: String },
+    /// Written last, summarizing the session. This is the only event written by
+    /// [ChatOutputFormat::Json].
+    Result {
+        conversation_id: String,
+        /// Whether the session ended because of an error
+        is_error: bool,
+        /// The final response of the assistant, or the error message if `is_error` is true
+        result: String,
+        /// Number of responses received from the assistant
+        num_turns: usize,
+        duration_ms: u64,
+        /// Usage accumulated over the session
+        usage: Usage,
+    },
+}
+
+impl OutputEvent {
+    /// The content of a [OutputEvent::ToolResult] for the output of a tool
+    pub fn tool_result_content(output: &OutputKind) -> serde_json::Value {
+        match output {
+            OutputKind::Text(text) => serde_json::Value::String(text.clone()),
+            OutputKind::Json(json) => json.clone(),
+            OutputKind::Images(images) => serde_json::Value::String(format!("{} image(s)", images.len())),
+        }
+    }
+}
+
+/// Writes the [OutputEvent]s of a chat session to stdout according to the [ChatOutputFormat],
+/// keeping track of what is needed for the final [OutputEvent::Result].
+#[derive(Debug)]
+pub struct OutputEmitter {
+    format: ChatOutputFormat,
+    conversation_id: String,
+    start: Instant,
+    num_turns: usize,
+    /// Text of the response currently being received
+    response_text: String,
+    /// Text of the last complete response
+    last_response: String,
+    usage: Usage,
+    error: Option<String>,
+}
+
+impl OutputEmitter {
+    pub fn new(format: ChatOutputFormat, conversation_id: impl Into<String>) -> Self {
+        Self {
+            format,
+            conversation_id: conversation_id.into(),
+            start: Instant::now(),
+            num_turns: 0,
+            response_text: String::new(),
+            last_response: String::new(),
+            usage: Usage::default(),
+            error: None,
+        }
+    }
+
+    pub fn format(&self) -> ChatOutputFormat {
+        self.format
+    }
+
+    pub fn emit(&mut self, event: OutputEvent) {
+        if !self.format.is_json() {
+            return;
+        }
+
+        match &event {
+            OutputEvent::AssistantText { text } => self.response_text.push_str(text),
+            OutputEvent::Usage { usage, .. } => {
+                self.num_turns += 1;
+                self.last_response = std::mem::take(&mut self.response_text);
+                self.usage.output_tokens += usage.output_tokens;
+                self.usage.context_tokens = usage.context_tokens;
+                self.usage.context_window_tokens = usage.context_window_tokens;
+                // A new response after an error means the conversation recovered from it.
+                self.error = None;
+            },
+            OutputEvent::Error { message, .. } => self.error = Some(message.clone()),
+            _ => (),
+        }
+
+        if self.format == ChatOutputFormat::StreamJson {
+            write_event(&event);
+        }
+    }
+
+    /// Writes the [OutputEvent::Result] of the session. Must be called once the session has ended.
+    pub fn finish(&mut self) {
+        if self.format.is_json() {
+            write_event(&self.result());
+        }
+    }
+
+    fn result(&self) -> OutputEvent {
+        OutputEvent::Result {
+            conversation_id: self.conversation_id.clone(),
+            is_error: self.error.is_some(),
+            result: self.error.clone().unwrap_or_else(|| self.last_response.clone()),
+            num_turns: self.num_turns,
+            duration_ms: u64::try_from(self.start.elapsed().as_millis()).unwrap_or(u64::MAX),
+            usage: self.usage,
+        }
+    }
+}
+
+fn write_event(event: &OutputEvent) {
+    let mut stdout = std::io::stdout().lock();
+    let result = serde_json::to_writer(&mut stdout, event)
+        .map_err(std::io::Error::from)
+        .and_then(|_| writeln!(stdout))
+        .and_then(|_| stdout.flush());
+    if let Err(err) = result {
+        error!(?err, "Failed to write output event");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_event_schema() {
+        let event = OutputEvent::ToolUse {
+            id: "tooluse_1".to_string(),
+            name: "fs_read".to_string(),
+            input: json!({ "path": "README.md" }),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({ "type": "tool_use", "id": "tooluse_1", "name": "fs_read", "input": { "path": "README.md" } })
+        );
+
+        let event = OutputEvent::ToolResult {
+            tool_use_id: "tooluse_1".to_string(),
+            name: "fs_read".to_string(),
+            status: ToolResultStatus::Error,
+            content: json!("not found"),
+            duration_ms: 5,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "type": "tool_result",
+                "tool_use_id": "tooluse_1",
+                "name": "fs_read",
+                "status": "error",
+                "content": "not found",
+                "duration_ms": 5
+            })
+        );
+
+        let event = OutputEvent::Usage {
+            request_id: None,
+            usage: Usage {
+                output_tokens: 10,
+                context_tokens: 100,
+                context_window_tokens: 1000,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({
+                "type": "usage",
+                "request_id": null,
+                "output_tokens": 10,
+                "context_tokens": 100,
+                "context_window_tokens": 1000
+            })
+        );
+    }
+
+    #[test]
+    fn test_emitter_result() {
+        let usage = Usage {
+            output_tokens: 10,
+            context_tokens: 100,
+            context_window_tokens: 1000,
+        };
+        let mut emitter = OutputEmitter::new(ChatOutputFormat::Json, "abc");
+        emitter.emit(OutputEvent::AssistantText {
+            text: "Let me ".to_string(),
+        });
+        emitter.emit(OutputEvent::AssistantText {
+            text: "check.".to_string(),
+        });
+        emitter.emit(OutputEvent::Usage {
+            request_id: None,
+            usage,
+        });
+        emitter.emit(OutputEvent::AssistantText {
+            text: "Done".to_string(),
+        });
+        emitter.emit(OutputEvent::Usage {
+            request_id: None,
+            usage,
+        });
+
+        let OutputEvent::Result {
+            conversation_id,
+            is_error,
+            result,
+            num_turns,
+            usage,
+            ..
+        } = emitter.result()
+        else {
+            panic!("expected a result event");
+        };
+        assert_eq!(conversation_id, "abc");
+        assert!(!is_error);
+        assert_eq!(result, "Done");
+        assert_eq!(num_turns, 2);
+        assert_eq!(usage.output_tokens, 20);
+        assert_eq!(usage.context_tokens, 100);
+
+        emitter.emit(OutputEvent::Error {
+            message: "Tool approval required".to_string(),
+            reason: "NonInteractiveToolApproval".to_string(),
+        });
+        let OutputEvent::Result { is_error, result, .. } = emitter.result() else {
+            panic!("expected a result event");
+        };
+        assert!(is_error);
+        assert_eq!(result, "Tool approval required");
+
+        // Text output ignores events
+        let mut emitter = OutputEmitter::new(ChatOutputFormat::Text, "abc");
+        emitter.emit(OutputEvent::Usage {
+            request_id: None,
+            usage,
+        });
+        assert_eq!(emitter.num_turns, 0);
+    }
+}
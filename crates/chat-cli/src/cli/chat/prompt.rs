@@ -49,6 +49,7 @@ pub const COMMANDS: &[&str] = &[
     "/tools",
     "/tools trust",
     "/tools untrust",
+    "/tools deny",
     "/tools trust-all",
     "/tools reset",
     "/mcp",
@@ -61,6 +62,10 @@ pub const COMMANDS: &[&str] = &[
     "/profile rename",
     "/profile set",
     "/prompts",
+    "/resources",
+    "/resources list",
+    "/resources read",
+    "/resources attach",
     "/context",
     "/context help",
     "/context show",
@@ -81,7 +86,17 @@ pub const COMMANDS: &[&str] = &[
     "/hooks disable-all",
     "/compact",
     "/compact help",
+    "/undo",
+    "/checkpoints",
+    "/checkpoints list",
+    "/checkpoints restore",
+    "/sessions",
+    "/sessions list",
+    "/sessions switch",
+    "/sessions name",
+    "/sessions delete",
     "/usage",
+    "/export",
     "/save",
     "/load",
     "/subscribe",
@@ -3,8 +3,11 @@ use tokio::sync::mpsc::{
     Sender,
     channel,
 };
+use tokio::sync::oneshot;
 
+use crate::mcp_client::error::ErrorCode;
 use crate::mcp_client::{
+    JsonRpcError,
     Messenger,
     MessengerError,
     PromptsListResult,
@@ -32,6 +35,16 @@ pub enum UpdateEventMessage {
         server_name: String,
         result: eyre::Result<ResourceTemplatesListResult>,
     },
+    ResourceUpdated {
+        server_name: String,
+        uri: String,
+    },
+    ServerRequest {
+        server_name: String,
+        method: String,
+        params: Option<serde_json::Value>,
+        responder: oneshot::Sender<Result<serde_json::Value, JsonRpcError>>,
+    },
     InitStart {
         server_name: String,
     },
@@ -117,6 +130,40 @@ impl Messenger for ServerMessenger {
             .map_err(|e| MessengerError::Custom(e.to_string()))?)
     }
 
+    async fn send_resource_updated(&self, uri: String) -> Result<(), MessengerError> {
+        Ok(self
+            .update_event_sender
+            .send(UpdateEventMessage::ResourceUpdated {
+                server_name: self.server_name.clone(),
+                uri,
+            })
+            .await
+            .map_err(|e| MessengerError::Custom(e.to_string()))?)
+    }
+
+    async fn send_server_request(
+        &self,
+        method: String,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, JsonRpcError> {
+        let internal_error = |message: String| JsonRpcError {
+            code: ErrorCode::InternalError.into(),
+            message,
+            data: None,
+        };
+        let (responder, response) = oneshot::channel();
+        self.update_event_sender
+            .send(UpdateEventMessage::ServerRequest {
+                server_name: self.server_name.clone(),
+                method,
+                params,
+                responder,
+            })
+            .await
+            .map_err(|e| internal_error(e.to_string()))?;
+        response.await.map_err(|e| internal_error(e.to_string()))?
+    }
+
     async fn send_init_msg(&self) -> Result<(), MessengerError> {
         Ok(self
             .update_event_sender
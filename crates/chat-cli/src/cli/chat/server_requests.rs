@@ -0,0 +1,511 @@
+//! Handling of requests that are initiated by mcp servers rather than by us, namely
+//! `sampling/createMessage` and `roots/list`.
+
+use std::io::Write;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::{
+    Arc,
+    RwLock as SyncRwLock,
+};
+
+use crossterm::style::{
+    self,
+    Color,
+};
+use crossterm::{
+    execute,
+    queue,
+};
+use serde_json::Value;
+use tokio::sync::{
+    mpsc,
+    oneshot,
+};
+use tracing::error;
+
+use crate::api_client::ApiClient;
+use crate::api_client::model::{
+    AssistantResponseMessage,
+    ChatMessage,
+    ChatResponseStream,
+    ConversationState,
+    UserInputMessage,
+};
+use crate::cli::chat::cli::model::{
+    MODEL_OPTIONS,
+    ModelOption,
+};
+use crate::cli::chat::input_source::InputSource;
+use crate::mcp_client::error::ErrorCode;
+use crate::mcp_client::{
+    CreateMessageParams,
+    CreateMessageResult,
+    JsonRpcError,
+    MessageContent,
+    ModelPreferences,
+    Role,
+    Root,
+    RootsListResult,
+    SamplingMessage,
+};
+use crate::os::Env;
+
+/// Error code with which a sampling request declined by the user is answered
+const USER_REJECTED_CODE: i32 = -1;
+
+/// Number of characters of each sampling message shown to the user when asking for approval
+const MESSAGE_PREVIEW_LEN: usize = 200;
+
+/// Model name reported back to the server when it has not expressed any preference
+const DEFAULT_MODEL_NAME: &str = "amazon-q";
+
+/// The state needed to answer requests initiated by mcp servers. This is shared between the
+/// [crate::cli::chat::tool_manager::ToolManager] and the task that receives the requests.
+#[derive(Clone, Debug)]
+pub struct ServerRequestHandler {
+    client: ApiClient,
+    env: Env,
+    is_interactive: bool,
+    /// Paths (or glob patterns) of the context files of the active profile. This is kept in sync
+    /// by the conversation and is used to answer `roots/list`.
+    context_paths: Arc<SyncRwLock<Vec<String>>>,
+    /// Hands sampling requests to the chat session, which asks the user to approve them one at a
+    /// time, even if multiple servers issue sampling requests concurrently.
+    approval_sender: mpsc::Sender<SamplingApproval>,
+}
+
+impl ServerRequestHandler {
+    pub fn new(
+        client: ApiClient,
+        env: Env,
+        is_interactive: bool,
+        context_paths: Arc<SyncRwLock<Vec<String>>>,
+        approval_sender: mpsc::Sender<SamplingApproval>,
+    ) -> Self {
+        Self {
+            client,
+            env,
+            is_interactive,
+            context_paths,
+            approval_sender,
+        }
+    }
+
+    pub async fn handle(&self, server_name: &str, method: &str, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        match method {
+            "roots/list" => self.list_roots(),
+            "sampling/createMessage" => self.create_message(server_name, params).await,
+            _ => Err(JsonRpcError {
+                code: ErrorCode::MethodNotFound.into(),
+                message: format!("Method {method} is not supported"),
+                data: None,
+            }),
+        }
+    }
+
+    fn list_roots(&self) -> Result<Value, JsonRpcError> {
+        let cwd = self.env.current_dir().map_err(|e| internal_error(e.to_string()))?;
+        let home = self.env.home();
+        let paths = self.context_paths.read().map(|p| p.clone()).unwrap_or_default();
+        let roots = roots_from_paths(&cwd, home.as_deref(), &paths);
+        serde_json::to_value(RootsListResult { roots }).map_err(|e| internal_error(e.to_string()))
+    }
+
+    async fn create_message(&self, server_name: &str, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        let params = params
+            .map(serde_json::from_value::<CreateMessageParams>)
+            .transpose()
+            .map_err(|e| JsonRpcError {
+                code: ErrorCode::InvalidParams.into(),
+                message: e.to_string(),
+                data: None,
+            })?
+            .ok_or(JsonRpcError {
+                code: ErrorCode::InvalidParams.into(),
+                message: "Missing params for sampling/createMessage".to_string(),
+                data: None,
+            })?;
+
+        if !self.is_interactive || !self.ask_for_approval(server_name, &params).await {
+            return Err(JsonRpcError {
+                code: USER_REJECTED_CODE,
+                message: "User rejected sampling request".to_string(),
+                data: None,
+            });
+        }
+
+        let model = params.model_preferences.as_ref().and_then(select_model);
+        let conversation = build_conversation(&params, model.map(|m| m.model_id.to_string()))?;
+        let mut output = self
+            .client
+            .send_message(conversation)
+            .await
+            .map_err(|e| internal_error(e.to_string()))?;
+        let mut text = String::new();
+        loop {
+            match output.recv().await {
+                Ok(Some(ChatResponseStream::AssistantResponseEvent { content })) => text.push_str(&content),
+                Ok(Some(_)) => {},
+                Ok(None) => break,
+                Err(e) => return Err(internal_error(e.to_string())),
+            }
+        }
+
+        serde_json::to_value(CreateMessageResult {
+            role: Role::Assistant,
+            content: MessageContent::Text { text },
+            model: model.map_or(DEFAULT_MODEL_NAME, |m| m.name).to_string(),
+            stop_reason: Some("endTurn".to_string()),
+        })
+        .map_err(|e| internal_error(e.to_string()))
+    }
+
+    /// Waits for the chat session to ask the user whether the request may be sampled.
+    async fn ask_for_approval(&self, server_name: &str, params: &CreateMessageParams) -> bool {
+        let (responder, response) = oneshot::channel();
+        let approval = SamplingApproval {
+            server_name: server_name.to_string(),
+            params: params.clone(),
+            responder,
+        };
+        if self.approval_sender.send(approval).await.is_err() {
+            error!("Error asking for approval of sampling request from {server_name}: session has ended");
+            return false;
+        }
+        response.await.unwrap_or(false)
+    }
+}
+
+/// A sampling request awaiting the approval of the user. The chat session asks for it through its
+/// input source, the same way it asks for the approval of tool uses, such that nothing else reads
+/// from the terminal in the meantime.
+#[derive(Debug)]
+pub struct SamplingApproval {
+    server_name: String,
+    params: CreateMessageParams,
+    responder: oneshot::Sender<bool>,
+}
+
+impl SamplingApproval {
+    /// Shows the user what the server is asking to be sampled and reads a y/n answer.
+    pub fn ask(self, input_source: &mut InputSource, output: &mut impl Write) {
+        let approved = match print_sampling_request(output, &self.server_name, &self.params) {
+            Ok(()) => match input_source.read_line(Some("> ")) {
+                Ok(Some(answer)) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+                Ok(None) => false,
+                Err(e) => {
+                    error!("Error reading approval for sampling request: {:?}", e);
+                    false
+                },
+            },
+            Err(e) => {
+                error!("Error displaying sampling request from {}: {:?}", self.server_name, e);
+                false
+            },
+        };
+        if self.responder.send(approved).is_err() {
+            error!(
+                "Error answering sampling request from {}: receiver dropped",
+                self.server_name
+            );
+        }
+    }
+}
+
+fn internal_error(message: String) -> JsonRpcError {
+    JsonRpcError {
+        code: ErrorCode::InternalError.into(),
+        message,
+        data: None,
+    }
+}
+
+fn print_sampling_request(
+    stderr: &mut impl Write,
+    server_name: &str,
+    params: &CreateMessageParams,
+) -> std::io::Result<()> {
+    queue!(
+        stderr,
+        style::Print("\n"),
+        style::SetForegroundColor(Color::Yellow),
+        style::Print(format!("MCP server {server_name} is requesting a model completion\n")),
+        style::SetForegroundColor(Color::Reset),
+    )?;
+    if let Some(system_prompt) = &params.system_prompt {
+        queue!(
+            stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print("system: "),
+            style::SetForegroundColor(Color::Reset),
+            style::Print(format!("{}\n", truncate(system_prompt))),
+        )?;
+    }
+    for message in &params.messages {
+        queue!(
+            stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(format!("{}: ", message.role)),
+            style::SetForegroundColor(Color::Reset),
+            style::Print(format!("{}\n", truncate(&message.content.to_string()))),
+        )?;
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        queue!(
+            stderr,
+            style::SetForegroundColor(Color::DarkGrey),
+            style::Print(format!("max tokens: {max_tokens}\n")),
+            style::SetForegroundColor(Color::Reset),
+        )?;
+    }
+    execute!(
+        stderr,
+        style::Print("\nAllow this request? ["),
+        style::SetForegroundColor(Color::Green),
+        style::Print("y"),
+        style::SetForegroundColor(Color::Reset),
+        style::Print("/"),
+        style::SetForegroundColor(Color::Green),
+        style::Print("n"),
+        style::SetForegroundColor(Color::Reset),
+        style::Print("]:\n\n"),
+    )?;
+    stderr.flush()
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(MESSAGE_PREVIEW_LEN) {
+        Some((idx, _)) => format!("{}...", &text[..idx]),
+        None => text.to_string(),
+    }
+}
+
+/// Picks the first model whose name contains one of the hints, in the order the server prefers
+/// them. Returns [None] if no hint matches, in which case the default model is used.
+fn select_model(preferences: &ModelPreferences) -> Option<&'static ModelOption> {
+    preferences
+        .hints
+        .iter()
+        .filter_map(|hint| hint.name.as_deref())
+        .find_map(|hint| {
+            let hint = hint.to_lowercase();
+            MODEL_OPTIONS.iter().find(|option| option.name.contains(hint.as_str()))
+        })
+}
+
+/// Converts the messages of a sampling request into a conversation understood by the backend, i.e.
+/// one that alternates between user and assistant messages and ends with a user message.
+fn build_conversation(
+    params: &CreateMessageParams,
+    model_id: Option<String>,
+) -> Result<ConversationState, JsonRpcError> {
+    let mut turns = Vec::<(Role, String)>::new();
+    for SamplingMessage { role, content } in &params.messages {
+        let text = match content {
+            MessageContent::Text { text } => text.clone(),
+            MessageContent::Image { .. } => "[image omitted]".to_string(),
+            MessageContent::Resource { .. } => content.to_string(),
+        };
+        match turns.last_mut() {
+            Some((last_role, last_text)) if last_role == role => {
+                last_text.push_str("\n\n");
+                last_text.push_str(&text);
+            },
+            _ => turns.push((role.clone(), text)),
+        }
+    }
+
+    if !matches!(turns.first(), Some((Role::User, _))) {
+        turns.insert(0, (Role::User, String::new()));
+    }
+    if let Some(system_prompt) = &params.system_prompt {
+        if let Some((_, first)) = turns.first_mut() {
+            *first = format!("--- SYSTEM PROMPT BEGIN ---\n{system_prompt}\n--- SYSTEM PROMPT END ---\n\n{first}");
+        }
+    }
+
+    let current = match turns.pop() {
+        Some((Role::User, text)) => text,
+        _ => {
+            return Err(JsonRpcError {
+                code: ErrorCode::InvalidParams.into(),
+                message: "The last message of a sampling request must be from the user".to_string(),
+                data: None,
+            });
+        },
+    };
+    let user_message = |content: String, model_id: Option<String>| UserInputMessage {
+        content,
+        user_input_message_context: None,
+        user_intent: None,
+        images: None,
+        model_id,
+    };
+    let history = turns
+        .into_iter()
+        .map(|(role, content)| match role {
+            Role::User => ChatMessage::UserInputMessage(user_message(content, model_id.clone())),
+            Role::Assistant => ChatMessage::AssistantResponseMessage(AssistantResponseMessage {
+                message_id: None,
+                content,
+                tool_uses: None,
+            }),
+        })
+        .collect::<Vec<_>>();
+
+    Ok(ConversationState {
+        conversation_id: None,
+        user_input_message: user_message(current, model_id),
+        history: (!history.is_empty()).then_some(history),
+    })
+}
+
+/// Derives the roots exposed to servers: the current working directory, followed by the
+/// directories referenced by the context paths that are not already covered by it.
+fn roots_from_paths(cwd: &Path, home: Option<&Path>, paths: &[String]) -> Vec<Root> {
+    let mut dirs = vec![cwd.to_path_buf()];
+    for path in paths {
+        let path = match (path.strip_prefix("~"), home) {
+            (Some(rest), Some(home)) => home.join(rest.trim_start_matches(['/', '\\'])),
+            _ => PathBuf::from(path),
+        };
+        // Only the components preceding the first glob are meaningful as a root
+        let base = path
+            .components()
+            .take_while(|c| !c.as_os_str().to_string_lossy().contains(['*', '?', '[', '{']))
+            .collect::<PathBuf>();
+        let base = if base.is_absolute() { base } else { cwd.join(base) };
+        let dir = if base.is_file() {
+            match base.parent() {
+                Some(parent) => parent.to_path_buf(),
+                None => continue,
+            }
+        } else {
+            base
+        };
+        if dir.is_dir() && !dirs.iter().any(|d| dir.starts_with(d)) {
+            dirs.push(dir);
+        }
+    }
+
+    dirs.into_iter()
+        .filter_map(|dir| {
+            let uri = url::Url::from_directory_path(&dir).ok()?.to_string();
+            let name = dir.file_name().map(|n| n.to_string_lossy().to_string());
+            Some(Root { uri, name })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp_client::ModelHint;
+
+    fn text_message(role: Role, text: &str) -> SamplingMessage {
+        SamplingMessage {
+            role,
+            content: MessageContent::Text { text: text.to_string() },
+        }
+    }
+
+    #[test]
+    fn test_build_conversation() {
+        let params = CreateMessageParams {
+            messages: vec![
+                text_message(Role::User, "hello"),
+                text_message(Role::User, "there"),
+                text_message(Role::Assistant, "hi"),
+                text_message(Role::User, "summarize this"),
+            ],
+            model_preferences: None,
+            system_prompt: Some("be brief".to_string()),
+            max_tokens: Some(100),
+        };
+        let conversation = build_conversation(&params, None).unwrap();
+        assert_eq!(conversation.user_input_message.content, "summarize this");
+        let history = conversation.history.unwrap();
+        assert_eq!(history.len(), 2);
+        match &history[0] {
+            ChatMessage::UserInputMessage(msg) => {
+                assert!(msg.content.contains("be brief"));
+                assert!(msg.content.ends_with("hello\n\nthere"));
+            },
+            ChatMessage::AssistantResponseMessage(_) => panic!("expected a user message"),
+        }
+        assert!(matches!(&history[1], ChatMessage::AssistantResponseMessage(msg) if msg.content == "hi"));
+
+        let params = CreateMessageParams {
+            messages: vec![text_message(Role::User, "hi"), text_message(Role::Assistant, "hello")],
+            model_preferences: None,
+            system_prompt: None,
+            max_tokens: None,
+        };
+        assert!(build_conversation(&params, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sampling_approval() {
+        let params = CreateMessageParams {
+            messages: vec![text_message(Role::User, "summarize this")],
+            model_preferences: None,
+            system_prompt: None,
+            max_tokens: None,
+        };
+        for (answer, expected) in [("y", true), ("Yes", true), ("n", false), ("summarize", false)] {
+            let (responder, response) = oneshot::channel();
+            let approval = SamplingApproval {
+                server_name: "docs".to_string(),
+                params: params.clone(),
+                responder,
+            };
+            let mut output = vec![];
+            approval.ask(&mut InputSource::new_mock(vec![answer.to_string()]), &mut output);
+            assert_eq!(response.await.unwrap(), expected, "answer {answer}");
+            assert!(String::from_utf8(output).unwrap().contains("MCP server docs"));
+        }
+    }
+
+    #[test]
+    fn test_select_model() {
+        let hint = |name: &str| ModelHint {
+            name: Some(name.to_string()),
+        };
+        let preferences = ModelPreferences {
+            hints: vec![hint("gpt-4"), hint("3.7-Sonnet"), hint("sonnet")],
+        };
+        assert_eq!(select_model(&preferences).unwrap().name, "claude-3.7-sonnet");
+        let preferences = ModelPreferences {
+            hints: vec![hint("gpt-4")],
+        };
+        assert!(select_model(&preferences).is_none());
+    }
+
+    #[test]
+    fn test_roots_from_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().join("project");
+        let home = dir.path().join("home");
+        std::fs::create_dir_all(cwd.join("docs")).unwrap();
+        std::fs::create_dir_all(home.join("rules")).unwrap();
+        std::fs::write(cwd.join("README.md"), "").unwrap();
+
+        let paths = [
+            "README.md".to_string(),
+            "docs/**/*.md".to_string(),
+            "~/rules/*.md".to_string(),
+            "~/missing/*.md".to_string(),
+        ];
+        let roots = roots_from_paths(&cwd, Some(&home), &paths);
+        assert_eq!(roots.len(), 2);
+        assert_eq!(roots[0].uri, url::Url::from_directory_path(&cwd).unwrap().to_string());
+        assert_eq!(roots[0].name.as_deref(), Some("project"));
+        assert_eq!(
+            roots[1].uri,
+            url::Url::from_directory_path(home.join("rules")).unwrap().to_string()
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::process::ExitCode;
+
+use anstream::println;
+use clap::Subcommand;
+use crossterm::style::Stylize;
+use eyre::{
+    Result,
+    bail,
+    eyre,
+};
+use serde_json::json;
+use time::OffsetDateTime;
+
+use super::ConversationState;
+use crate::cli::OutputFormat;
+use crate::database::Session;
+use crate::os::Os;
+use crate::util::CLI_BINARY_NAME;
+
+/// Number of characters of a session id shown in listings, which is enough to refer to it.
+const SHORT_ID_LEN: usize = 8;
+
+#[deny(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq, Subcommand)]
+pub enum SessionsSubcommand {
+    /// List the saved chat sessions
+    List {
+        /// List the sessions of every directory instead of only the current one
+        #[arg(short, long)]
+        all: bool,
+        /// Output format to use
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Show the transcript of a session
+    Show {
+        /// The id or name of the session
+        session: String,
+        /// Output format to use
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Delete a session
+    Delete {
+        /// The id or name of the session
+        session: String,
+    },
+    /// Copy a session, such that the copy can be resumed without affecting the original
+    Fork {
+        /// The id or name of the session
+        session: String,
+        /// Name of the new session
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+impl SessionsSubcommand {
+    pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
+        let cwd = os.env.current_dir()?;
+        match self {
+            Self::List { all, format } => {
+                let sessions = os.database.list_sessions((!all).then_some(cwd.as_path()))?;
+                format.print(
+                    || {
+                        if sessions.is_empty() {
+                            return "No sessions found".to_string();
+                        }
+                        sessions
+                            .iter()
+                            .map(|session| match all {
+                                true => {
+                                    format!("{}\n    {}", format_session(session), session.path.clone().dark_grey())
+                                },
+                                false => format_session(session),
+                            })
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    },
+                    || &sessions,
+                );
+            },
+            Self::Show { session, format } => {
+                let session = find_session(os, &session)?;
+                let conversation = os
+                    .database
+                    .get_session_conversation(&session.id)?
+                    .ok_or_else(|| eyre!("Session {} has no conversation", session.id))?;
+                format.print(
+                    || {
+                        let mut text = format!("{}\n{}\n", format_session(&session), session.path.clone().dark_grey());
+                        for entry in &conversation.transcript {
+                            text.push('\n');
+                            text.push_str(entry.trim_end());
+                            text.push('\n');
+                        }
+                        text
+                    },
+                    || json!({ "session": &session, "transcript": &conversation.transcript }),
+                );
+            },
+            Self::Delete { session } => {
+                let session = find_session(os, &session)?;
+                os.database.delete_session(&session.id)?;
+                println!("Deleted session {}", session_display_name(&session));
+            },
+            Self::Fork { session, name } => {
+                let session = find_session(os, &session)?;
+                let conversation = os
+                    .database
+                    .get_session_conversation(&session.id)?
+                    .ok_or_else(|| eyre!("Session {} has no conversation", session.id))?;
+                if let Some(name) = &name {
+                    validate_session_name(name)?;
+                }
+                let fork = conversation.fork(&uuid::Uuid::new_v4().to_string());
+                os.database.save_session(&session.path, &fork)?;
+                if let Some(name) = &name {
+                    if let Err(err) = os.database.set_session_name(fork.conversation_id(), Some(name)) {
+                        os.database.delete_session(fork.conversation_id())?;
+                        return Err(err.into());
+                    }
+                }
+
+                let resume_with = name.as_deref().unwrap_or(fork.conversation_id());
+                println!(
+                    "Forked session {} into {}\nResume it with {}",
+                    session_display_name(&session),
+                    fork.conversation_id(),
+                    format!("{CLI_BINARY_NAME} chat --resume-id {resume_with}").magenta()
+                );
+            },
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Loads the conversation to resume with `q chat --resume[-id]`: the session matching `id_or_name`,
+/// or if it is [None], the most recently updated session of the current directory.
+pub fn load_conversation(os: &mut Os, id_or_name: Option<&str>) -> Result<Option<ConversationState>> {
+    let cwd = os.env.current_dir()?;
+    let session = match id_or_name {
+        Some(id_or_name) => Some(find_session(os, id_or_name)?),
+        None => os.database.list_sessions(Some(&cwd))?.into_iter().next(),
+    };
+
+    match session {
+        Some(session) => Ok(os.database.get_session_conversation(&session.id)?),
+        // Conversations used to be saved by directory only.
+        None => Ok(os.database.get_conversation_by_path(&cwd)?),
+    }
+}
+
+/// Finds the session with the given id, id prefix, or name in the current directory.
+pub fn find_session(os: &Os, id_or_name: &str) -> Result<Session> {
+    let cwd = os.env.current_dir()?;
+    match os.database.find_session(&cwd, id_or_name)? {
+        Some(session) => Ok(session),
+        None => bail!(
+            "No session matching '{id_or_name}' was found. Run {} to see the saved sessions",
+            format!("{CLI_BINARY_NAME} chat sessions list").magenta()
+        ),
+    }
+}
+
+pub fn validate_session_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() || name.chars().any(char::is_whitespace) {
+        bail!("Session names must not be empty or contain whitespace");
+    }
+    Ok(())
+}
+
+/// The name of a session if it has one, otherwise its (shortened) id
+pub fn session_display_name(session: &Session) -> &str {
+    session.name.as_deref().unwrap_or_else(|| short_id(&session.id))
+}
+
+/// A single line describing a session, for listings
+pub fn format_session(session: &Session) -> String {
+    let updated = OffsetDateTime::from_unix_timestamp(session.updated_at)
+        .map(|time| match time::UtcOffset::current_local_offset() {
+            Ok(offset) => time.to_offset(offset),
+            Err(_) => time,
+        })
+        .ok()
+        .and_then(|time| {
+            time.format(time::macros::format_description!(
+                "[year]-[month]-[day] [hour]:[minute]"
+            ))
+            .ok()
+        })
+        .unwrap_or_default();
+
+    let mut line = format!("{}  {}", short_id(&session.id).yellow(), updated.dark_grey());
+    if let Some(name) = &session.name {
+        line.push_str(&format!("  {}", name.as_str().green().bold()));
+    }
+    if let Some(title) = &session.title {
+        line.push_str(&format!("  {title}"));
+    }
+    line
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..SHORT_ID_LEN).unwrap_or(id)
+}
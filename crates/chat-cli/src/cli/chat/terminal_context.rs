@@ -0,0 +1,241 @@
+//! Output of the commands run in the terminal `q chat` is running in, as recorded by qterm.
+//!
+//! Messages are a subset of the figterm protocol, see `proto/figterm.proto`. They are sent
+//! over the qterm socket of the session as `\x1b@fig-pbuf`, the big endian length of the
+//! message as a u64, and the protobuf encoded message.
+
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use prost::Message;
+use tracing::debug;
+
+use crate::os::Os;
+use crate::util::env_var::QTERM_SESSION_ID;
+
+const MESSAGE_HEADER: &[u8] = b"\x1b@fig-pbuf";
+
+/// Maximum length of a response, qterm bounds the output it keeps well below this
+const MAX_RESPONSE_LEN: u64 = 4 * 1024 * 1024;
+
+/// How long to wait for qterm, such that a stuck terminal doesn't hold up the chat
+const QUERY_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Clone, PartialEq, Message)]
+struct FigtermRequestMessage {
+    #[prost(oneof = "FigtermRequest", tags = "12")]
+    request: Option<FigtermRequest>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum FigtermRequest {
+    #[prost(message, tag = "12")]
+    CommandOutput(CommandOutputRequest),
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct FigtermResponseMessage {
+    #[prost(oneof = "FigtermResponse", tags = "3")]
+    response: Option<FigtermResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum FigtermResponse {
+    #[prost(message, tag = "3")]
+    CommandOutput(CommandOutputResponse),
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandOutputRequest {
+    /// Maximum number of commands to return, all of the recorded ones if unset
+    #[prost(uint32, optional, tag = "1")]
+    pub limit: Option<u32>,
+    /// Only return commands that exited with a non-zero exit code
+    #[prost(bool, tag = "2")]
+    pub failed_only: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandOutputResponse {
+    /// The commands, most recent first
+    #[prost(message, repeated, tag = "1")]
+    pub commands: Vec<CommandOutput>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CommandOutput {
+    #[prost(string, optional, tag = "1")]
+    pub command: Option<String>,
+    #[prost(int32, optional, tag = "2")]
+    pub exit_code: Option<i32>,
+    #[prost(string, optional, tag = "3")]
+    pub cwd: Option<String>,
+    /// Milliseconds since the unix epoch
+    #[prost(uint64, optional, tag = "4")]
+    pub start_time: Option<u64>,
+    /// Milliseconds since the unix epoch
+    #[prost(uint64, optional, tag = "5")]
+    pub end_time: Option<u64>,
+    /// Text written by the command, without escape sequences
+    #[prost(string, tag = "6")]
+    pub output: String,
+    /// Whether the start of the output was dropped
+    #[prost(bool, tag = "7")]
+    pub truncated: bool,
+}
+
+/// Get the last command that failed in the terminal of the current session, if `q chat` runs
+/// in qterm and a command failed.
+pub async fn last_failed_command(os: &Os) -> Option<CommandOutput> {
+    let session_id = os.env.get(QTERM_SESSION_ID).ok()?;
+    let socket_path = socket_path(&session_id)?;
+
+    let request = CommandOutputRequest {
+        limit: Some(1),
+        failed_only: true,
+    };
+    match tokio::time::timeout(QUERY_TIMEOUT, query_command_output(&socket_path, request)).await {
+        Ok(Ok(response)) => response.commands.into_iter().next(),
+        Ok(Err(err)) => {
+            debug!(?err, "Failed to query the command output from qterm");
+            None
+        },
+        Err(_) => {
+            debug!("Timed out querying the command output from qterm");
+            None
+        },
+    }
+}
+
+#[cfg(unix)]
+fn socket_path(session_id: &str) -> Option<std::path::PathBuf> {
+    crate::util::directories::figterm_socket_path(session_id).ok()
+}
+
+#[cfg(not(unix))]
+fn socket_path(_session_id: &str) -> Option<std::path::PathBuf> {
+    None
+}
+
+#[cfg(unix)]
+async fn query_command_output(socket_path: &Path, request: CommandOutputRequest) -> io::Result<CommandOutputResponse> {
+    use tokio::io::{
+        AsyncReadExt,
+        AsyncWriteExt,
+    };
+
+    let mut stream = tokio::net::UnixStream::connect(socket_path).await?;
+
+    let body = FigtermRequestMessage {
+        request: Some(FigtermRequest::CommandOutput(request)),
+    }
+    .encode_to_vec();
+    let mut message = Vec::with_capacity(MESSAGE_HEADER.len() + 8 + body.len());
+    message.extend_from_slice(MESSAGE_HEADER);
+    message.extend_from_slice(&(body.len() as u64).to_be_bytes());
+    message.extend_from_slice(&body);
+    stream.write_all(&message).await?;
+
+    let invalid = |reason: &str| io::Error::new(io::ErrorKind::InvalidData, reason.to_string());
+    let mut header = [0; MESSAGE_HEADER.len()];
+    stream.read_exact(&mut header).await?;
+    if header != MESSAGE_HEADER {
+        return Err(invalid("unexpected message type"));
+    }
+    let len = stream.read_u64().await?;
+    if len > MAX_RESPONSE_LEN {
+        return Err(invalid("response too long"));
+    }
+    let mut body = vec![0; len as usize];
+    stream.read_exact(&mut body).await?;
+
+    match FigtermResponseMessage::decode(body.as_slice()) {
+        Ok(FigtermResponseMessage {
+            response: Some(FigtermResponse::CommandOutput(response)),
+        }) => Ok(response),
+        Ok(_) => Err(invalid("unexpected response")),
+        Err(err) => Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+    }
+}
+
+#[cfg(not(unix))]
+async fn query_command_output(
+    _socket_path: &Path,
+    _request: CommandOutputRequest,
+) -> io::Result<CommandOutputResponse> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use tokio::io::{
+        AsyncReadExt,
+        AsyncWriteExt,
+    };
+    use tokio::net::UnixListener;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_query_command_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("session.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let command = CommandOutput {
+            command: Some("cargo build".into()),
+            exit_code: Some(101),
+            cwd: Some("/repo".into()),
+            start_time: Some(1_700_000_000_000),
+            end_time: Some(1_700_000_004_000),
+            output: "error[E0308]: mismatched types".into(),
+            truncated: false,
+        };
+
+        let server = tokio::spawn({
+            let command = command.clone();
+            async move {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut header = [0; MESSAGE_HEADER.len()];
+                stream.read_exact(&mut header).await.unwrap();
+                assert_eq!(header, MESSAGE_HEADER);
+                let mut body = vec![0; stream.read_u64().await.unwrap() as usize];
+                stream.read_exact(&mut body).await.unwrap();
+                let request = FigtermRequestMessage::decode(body.as_slice()).unwrap();
+                assert_eq!(
+                    request.request,
+                    Some(FigtermRequest::CommandOutput(CommandOutputRequest {
+                        limit: Some(1),
+                        failed_only: true,
+                    }))
+                );
+
+                let body = FigtermResponseMessage {
+                    response: Some(FigtermResponse::CommandOutput(CommandOutputResponse {
+                        commands: vec![command],
+                    })),
+                }
+                .encode_to_vec();
+                stream.write_all(MESSAGE_HEADER).await.unwrap();
+                stream.write_u64(body.len() as u64).await.unwrap();
+                stream.write_all(&body).await.unwrap();
+            }
+        });
+
+        let response = query_command_output(&socket_path, CommandOutputRequest {
+            limit: Some(1),
+            failed_only: true,
+        })
+        .await
+        .unwrap();
+        server.await.unwrap();
+        assert_eq!(response.commands, vec![command]);
+
+        assert!(
+            query_command_output(&dir.path().join("missing.sock"), CommandOutputRequest::default())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -64,11 +64,16 @@ use crate::api_client::model::{
     ToolResultStatus,
 };
 use crate::cli::chat::cli::prompts::GetPromptError;
+use crate::cli::chat::cli::resources::ResourceError;
 use crate::cli::chat::message::AssistantToolUse;
 use crate::cli::chat::server_messenger::{
     ServerMessengerBuilder,
     UpdateEventMessage,
 };
+use crate::cli::chat::server_requests::{
+    SamplingApproval,
+    ServerRequestHandler,
+};
 use crate::cli::chat::tools::custom_tool::{
     CustomTool,
     CustomToolClient,
@@ -92,12 +97,14 @@ use crate::mcp_client::{
     JsonRpcResponse,
     Messenger,
     PromptGet,
+    ResourceInfo,
+    ResourceReadResult,
 };
 use crate::os::Os;
 use crate::telemetry::TelemetryThread;
 use crate::util::directories::home_dir;
 
-const NAMESPACE_DELIMITER: &str = "___";
+pub const NAMESPACE_DELIMITER: &str = "___";
 // This applies for both mcp server and tool name since in the end the tool name as seen by the
 // model is just {server_name}{NAMESPACE_DELIMITER}{tool_name}
 const VALID_TOOL_NAME: &str = "^[a-zA-Z][a-zA-Z0-9_]*$";
@@ -414,7 +421,20 @@ impl ToolManagerBuilder {
         let has_new_stuff_clone = has_new_stuff.clone();
         let pending = Arc::new(RwLock::new(HashSet::<String>::new()));
         let pending_clone = pending.clone();
+        let resources = Arc::new(RwLock::new(HashMap::<String, Vec<ResourceInfo>>::new()));
+        let resources_clone = resources.clone();
+        let updated_resources = Arc::new(Mutex::new(HashSet::<(String, String)>::new()));
+        let updated_resources_clone = updated_resources.clone();
         let (mut msg_rx, messenger_builder) = ServerMessengerBuilder::new(20);
+        let context_paths = Arc::new(SyncRwLock::new(Vec::<String>::new()));
+        let (approval_sender, sampling_approvals) = tokio::sync::mpsc::channel(8);
+        let server_request_handler = ServerRequestHandler::new(
+            os.client.clone(),
+            os.env.clone(),
+            interactive,
+            context_paths.clone(),
+            approval_sender,
+        );
         let telemetry_clone = os.telemetry.clone();
         let database_clone = os.database.clone();
         let notify = Arc::new(Notify::new());
@@ -564,14 +584,46 @@ impl ToolManagerBuilder {
                         server_name: _,
                         result: _,
                     } => {},
-                    UpdateEventMessage::ResourcesListResult {
-                        server_name: _,
-                        result: _,
-                    } => {},
+                    UpdateEventMessage::ResourcesListResult { server_name, result } => match result {
+                        Ok(result) => {
+                            let list = result
+                                .resources
+                                .into_iter()
+                                .filter_map(|resource| {
+                                    serde_json::from_value::<ResourceInfo>(resource)
+                                        .map_err(|e| warn!("Malformed resource from {server_name}: {:?}", e))
+                                        .ok()
+                                })
+                                .collect::<Vec<_>>();
+                            resources_clone.write().await.insert(server_name, list);
+                        },
+                        Err(e) => {
+                            error!("Error retrieving resource list for server {}: {:?}", server_name, e);
+                        },
+                    },
                     UpdateEventMessage::ResourceTemplatesListResult {
                         server_name: _,
                         result: _,
                     } => {},
+                    UpdateEventMessage::ResourceUpdated { server_name, uri } => {
+                        updated_resources_clone.lock().await.insert((server_name, uri));
+                    },
+                    UpdateEventMessage::ServerRequest {
+                        server_name,
+                        method,
+                        params,
+                        responder,
+                    } => {
+                        // These can involve user interaction (and a round trip to the model), which
+                        // must not hold up the processing of the other messages
+                        let handler = server_request_handler.clone();
+                        tokio::spawn(async move {
+                            let result = handler.handle(&server_name, &method, params).await;
+                            if responder.send(result).is_err() {
+                                error!("Error responding to {method} from {server_name}: receiver dropped");
+                            }
+                        });
+                    },
                     UpdateEventMessage::InitStart { server_name } => {
                         pending_clone.write().await.insert(server_name.clone());
                         loading_servers.insert(server_name, std::time::Instant::now());
@@ -696,6 +748,10 @@ impl ToolManagerBuilder {
             conversation_id,
             clients,
             prompts,
+            resources,
+            updated_resources,
+            context_paths,
+            sampling_approvals: Some(sampling_approvals),
             pending_clients: pending,
             notify: Some(notify),
             loading_status_sender,
@@ -767,6 +823,23 @@ pub struct ToolManager {
     /// cases where multiple servers offer prompts with the same name.
     pub prompts: Arc<SyncRwLock<HashMap<String, Vec<PromptBundle>>>>,
 
+    /// Cache for resources advertised by the different servers.
+    /// Key: server name
+    /// Value: the resources as last listed by said server
+    pub resources: Arc<RwLock<HashMap<String, Vec<ResourceInfo>>>>,
+
+    /// Resources that servers have reported as updated (via `notifications/resources/updated`)
+    /// since they were last read, stored as pairs of server name and uri.
+    updated_resources: Arc<Mutex<HashSet<(String, String)>>>,
+
+    /// Paths of the context files of the active profile, which are exposed to servers as roots
+    /// (alongside the current working directory) via `roots/list`.
+    context_paths: Arc<SyncRwLock<Vec<String>>>,
+
+    /// Sampling requests of servers awaiting the approval of the user. This is taken by the chat
+    /// session, which asks for the approvals.
+    sampling_approvals: Option<tokio::sync::mpsc::Receiver<SamplingApproval>>,
+
     /// A notifier to understand if the initial loading has completed.
     /// This is only used for initial loading and is discarded after.
     notify: Option<Arc<Notify>>,
@@ -806,6 +879,9 @@ impl Clone for ToolManager {
             has_new_stuff: self.has_new_stuff.clone(),
             new_tool_specs: self.new_tool_specs.clone(),
             prompts: self.prompts.clone(),
+            resources: self.resources.clone(),
+            updated_resources: self.updated_resources.clone(),
+            context_paths: self.context_paths.clone(),
             tn_map: self.tn_map.clone(),
             schema: self.schema.clone(),
             is_interactive: self.is_interactive,
@@ -1265,6 +1341,96 @@ impl ToolManager {
         Ok(())
     }
 
+    /// Resolves a resource reference of the form `server:uri` into its server name and uri.
+    ///
+    /// A reference without a known server prefix is looked up among the cached resources, and
+    /// is only resolved if exactly one server offers a resource with said uri.
+    pub async fn resolve_resource(&self, reference: &str) -> Result<(String, String), ResourceError> {
+        if let Some((server_name, uri)) = reference.split_once(':') {
+            if self.clients.contains_key(server_name) && !uri.is_empty() {
+                return Ok((server_name.to_string(), uri.to_string()));
+            }
+        }
+        let resources = self.resources.read().await;
+        let mut candidates = resources
+            .iter()
+            .filter(|(_, list)| list.iter().any(|r| r.uri == reference))
+            .map(|(server_name, _)| server_name.clone())
+            .collect::<Vec<_>>();
+        match candidates.len() {
+            0 => Err(ResourceError::ResourceNotFound(reference.to_string())),
+            1 => Ok((candidates.remove(0), reference.to_string())),
+            _ => {
+                candidates.sort();
+                Err(ResourceError::AmbiguousResource(
+                    reference.to_string(),
+                    candidates.iter().fold("\n".to_string(), |mut acc, server_name| {
+                        acc.push_str(&format!("- @{}:{}\n", server_name, reference));
+                        acc
+                    }),
+                ))
+            },
+        }
+    }
+
+    /// Reads the resource identified by `uri` from the server `server_name`.
+    pub async fn read_resource(&self, server_name: &str, uri: &str) -> Result<ResourceReadResult, ResourceError> {
+        let client = self
+            .clients
+            .get(server_name)
+            .ok_or(ResourceError::MissingClient(server_name.to_string()))?;
+        let resp = client
+            .request("resources/read", Some(serde_json::json!({ "uri": uri })))
+            .await?;
+        if let Some(error) = resp.error {
+            return Err(ResourceError::Server(error.message));
+        }
+        let result = resp.result.ok_or(ResourceError::MissingResult)?;
+        // A fresh read supersedes any update we have been notified of
+        self.updated_resources
+            .lock()
+            .await
+            .remove(&(server_name.to_string(), uri.to_string()));
+        Ok(serde_json::from_value::<ResourceReadResult>(result)?)
+    }
+
+    /// Subscribes to updates of the resource identified by `uri`, if the server supports it.
+    /// Returns whether or not a subscription was made.
+    pub async fn subscribe_resource(&self, server_name: &str, uri: &str) -> Result<bool, ResourceError> {
+        let client = self
+            .clients
+            .get(server_name)
+            .ok_or(ResourceError::MissingClient(server_name.to_string()))?;
+        if !client.supports_resource_subscriptions().await {
+            return Ok(false);
+        }
+        let resp = client
+            .request("resources/subscribe", Some(serde_json::json!({ "uri": uri })))
+            .await?;
+        if let Some(error) = resp.error {
+            return Err(ResourceError::Server(error.message));
+        }
+        Ok(true)
+    }
+
+    /// Drains the set of resources servers have reported as updated.
+    pub async fn take_updated_resources(&self) -> HashSet<(String, String)> {
+        std::mem::take(&mut *self.updated_resources.lock().await)
+    }
+
+    /// Updates the context paths that are exposed to servers as roots
+    pub fn set_context_paths(&self, paths: Vec<String>) {
+        match self.context_paths.write() {
+            Ok(mut context_paths) => *context_paths = paths,
+            Err(e) => error!("Failed to update context paths: {:?}", e),
+        }
+    }
+
+    /// Takes the receiver of the sampling requests awaiting the approval of the user
+    pub fn take_sampling_approvals(&mut self) -> Option<tokio::sync::mpsc::Receiver<SamplingApproval>> {
+        self.sampling_approvals.take()
+    }
+
     pub async fn pending_clients(&self) -> Vec<String> {
         self.pending_clients.read().await.iter().cloned().collect::<Vec<_>>()
     }
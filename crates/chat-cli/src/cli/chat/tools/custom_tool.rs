@@ -21,6 +21,8 @@ use crate::cli::chat::token_counter::TokenCounter;
 use crate::mcp_client::{
     Client as McpClient,
     ClientConfig as McpClientConfig,
+    HttpClientConfig as McpHttpClientConfig,
+    HttpTransport,
     JsonRpcResponse,
     JsonRpcStdioTransport,
     MessageContent,
@@ -32,20 +34,37 @@ use crate::mcp_client::{
 };
 use crate::os::Os;
 
-// TODO: support http transport type
+/// Configuration of an mcp server as it appears in mcp.json. Servers that are launched locally
+/// are configured with a `command`, while servers that are reached over http are configured with
+/// a `url`.
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct CustomToolConfig {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub command: String,
     #[serde(default)]
     pub args: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
+    /// Url of a server speaking the Streamable HTTP (or the deprecated HTTP+SSE) transport
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Headers sent along with every request made to `url`. Values may reference environment
+    /// variables, e.g. `"Authorization": "Bearer ${GITLAB_TOKEN}"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
     #[serde(default = "default_timeout")]
     pub timeout: u64,
     #[serde(default)]
     pub disabled: bool,
 }
 
+impl CustomToolConfig {
+    /// A short human readable description of how the server is reached
+    pub fn display_target(&self) -> &str {
+        self.url.as_deref().unwrap_or(&self.command)
+    }
+}
+
 pub fn default_timeout() -> u64 {
     120 * 1000
 }
@@ -57,27 +76,62 @@ pub enum CustomToolClient {
         client: McpClient<StdioTransport>,
         server_capabilities: RwLock<Option<ServerCapabilities>>,
     },
+    Http {
+        server_name: String,
+        client: McpClient<HttpTransport>,
+        server_capabilities: RwLock<Option<ServerCapabilities>>,
+    },
 }
 
 impl CustomToolClient {
-    // TODO: add support for http transport
     pub fn from_config(server_name: String, config: CustomToolConfig) -> Result<Self> {
         let CustomToolConfig {
             command,
             args,
             env,
+            url,
+            headers,
             timeout,
             disabled: _,
         } = config;
+        let client_info = serde_json::json!({
+           "name": "Q CLI Chat",
+           "version": "1.0.0"
+        });
+        if let Some(url) = url {
+            let headers = headers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = shellexpand::env(&value)
+                        .map_err(|e| eyre::eyre!("Failed to expand value of header {name}: {e}"))?
+                        .to_string();
+                    Ok((name, value))
+                })
+                .collect::<Result<HashMap<_, _>>>()?;
+            let mcp_client_config = McpHttpClientConfig {
+                server_name: server_name.clone(),
+                url,
+                headers,
+                timeout,
+                client_info,
+            };
+            let client = McpClient::<HttpTransport>::from_config(mcp_client_config)?;
+            return Ok(CustomToolClient::Http {
+                server_name,
+                client,
+                server_capabilities: RwLock::new(None),
+            });
+        }
+        if command.is_empty() {
+            eyre::bail!("Server {server_name} must be configured with either a command or a url");
+        }
         let mcp_client_config = McpClientConfig {
             server_name: server_name.clone(),
             bin_path: command.clone(),
             args,
             timeout,
-            client_info: serde_json::json!({
-               "name": "Q CLI Chat",
-               "version": "1.0.0"
-            }),
+            client_info,
             env,
         };
         let client = McpClient::<JsonRpcStdioTransport>::from_config(mcp_client_config)?;
@@ -89,6 +143,10 @@ impl CustomToolClient {
     }
 
     pub async fn init(&self) -> Result<()> {
+        // We'll need to first initialize. This is the handshake every client and server
+        // needs to do before proceeding to anything else
+        // We'll be scrapping this for background server load: https://github.com/aws/amazon-q-developer-cli/issues/1466
+        // So don't worry about the tidiness for now
         match self {
             CustomToolClient::Stdio {
                 client,
@@ -98,11 +156,19 @@ impl CustomToolClient {
                 if let Some(messenger) = &client.messenger {
                     let _ = messenger.send_init_msg().await;
                 }
-                // We'll need to first initialize. This is the handshake every client and server
-                // needs to do before proceeding to anything else
                 let cap = client.init().await?;
-                // We'll be scrapping this for background server load: https://github.com/aws/amazon-q-developer-cli/issues/1466
-                // So don't worry about the tidiness for now
+                server_capabilities.write().await.replace(cap);
+                Ok(())
+            },
+            CustomToolClient::Http {
+                client,
+                server_capabilities,
+                ..
+            } => {
+                if let Some(messenger) = &client.messenger {
+                    let _ = messenger.send_init_msg().await;
+                }
+                let cap = client.init().await?;
                 server_capabilities.write().await.replace(cap);
                 Ok(())
             },
@@ -114,24 +180,31 @@ impl CustomToolClient {
             CustomToolClient::Stdio { client, .. } => {
                 client.messenger = Some(messenger);
             },
+            CustomToolClient::Http { client, .. } => {
+                client.messenger = Some(messenger);
+            },
         }
     }
 
     pub fn get_server_name(&self) -> &str {
         match self {
-            CustomToolClient::Stdio { server_name, .. } => server_name.as_str(),
+            CustomToolClient::Stdio { server_name, .. } | CustomToolClient::Http { server_name, .. } => {
+                server_name.as_str()
+            },
         }
     }
 
     pub async fn request(&self, method: &str, params: Option<serde_json::Value>) -> Result<JsonRpcResponse> {
         match self {
             CustomToolClient::Stdio { client, .. } => Ok(client.request(method, params).await?),
+            CustomToolClient::Http { client, .. } => Ok(client.request(method, params).await?),
         }
     }
 
     pub fn list_prompt_gets(&self) -> Arc<std::sync::RwLock<HashMap<String, PromptGet>>> {
         match self {
             CustomToolClient::Stdio { client, .. } => client.prompt_gets.clone(),
+            CustomToolClient::Http { client, .. } => client.prompt_gets.clone(),
         }
     }
 
@@ -139,18 +212,38 @@ impl CustomToolClient {
     pub async fn notify(&self, method: &str, params: Option<serde_json::Value>) -> Result<()> {
         match self {
             CustomToolClient::Stdio { client, .. } => Ok(client.notify(method, params).await?),
+            CustomToolClient::Http { client, .. } => Ok(client.notify(method, params).await?),
         }
     }
 
+    /// Whether the server has advertised support for `resources/subscribe`
+    pub async fn supports_resource_subscriptions(&self) -> bool {
+        let (CustomToolClient::Stdio {
+            server_capabilities, ..
+        }
+        | CustomToolClient::Http {
+            server_capabilities, ..
+        }) = self;
+        server_capabilities.read().await.as_ref().is_some_and(|cap| {
+            cap.resources
+                .as_ref()
+                .and_then(|r| r.get("subscribe"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false)
+        })
+    }
+
     pub fn is_prompts_out_of_date(&self) -> bool {
         match self {
             CustomToolClient::Stdio { client, .. } => client.is_prompts_out_of_date.load(Ordering::Relaxed),
+            CustomToolClient::Http { client, .. } => client.is_prompts_out_of_date.load(Ordering::Relaxed),
         }
     }
 
     pub fn prompts_updated(&self) {
         match self {
             CustomToolClient::Stdio { client, .. } => client.is_prompts_out_of_date.store(false, Ordering::Relaxed),
+            CustomToolClient::Http { client, .. } => client.is_prompts_out_of_date.store(false, Ordering::Relaxed),
         }
     }
 }
@@ -17,6 +17,9 @@ use crate::cli::chat::util::truncate_safe;
 use crate::os::Os;
 
 // Platform-specific modules
+mod sandbox;
+pub use sandbox::*;
+
 #[cfg(windows)]
 mod windows;
 #[cfg(windows)]
@@ -32,6 +35,9 @@ pub const READONLY_COMMANDS: &[&str] = &[
     "ls", "cat", "echo", "pwd", "which", "head", "tail", "find", "grep", "dir", "type",
 ];
 
+// Shell syntax that lets a single command line run further commands or write to files
+const DANGEROUS_PATTERNS: &[&str] = &["<(", "$(", "`", ">", "&&", "||", "&", ";"];
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct ExecuteCommand {
     pub command: String,
@@ -44,7 +50,6 @@ impl ExecuteCommand {
             return true;
         };
 
-        const DANGEROUS_PATTERNS: &[&str] = &["<(", "$(", "`", ">", "&&", "||", "&", ";"];
         if args
             .iter()
             .any(|arg| DANGEROUS_PATTERNS.iter().any(|p| arg.contains(p)))
@@ -104,8 +109,31 @@ impl ExecuteCommand {
         false
     }
 
-    pub async fn invoke(&self, output: &mut impl Write) -> Result<InvokeOutput> {
-        let output = run_command(&self.command, MAX_TOOL_RESPONSE_SIZE / 3, Some(output)).await?;
+    /// Whether the command consists of more than a single simple command, i.e. whether it chains,
+    /// pipes, substitutes or redirects.
+    pub fn is_compound(&self) -> bool {
+        // Line breaks separate commands just like `;` does, but are dropped by shlex
+        if self.command.contains(['\n', '\r']) {
+            return true;
+        }
+
+        let Some(args) = shlex::split(&self.command) else {
+            return true;
+        };
+
+        args.iter()
+            .any(|arg| arg.contains('|') || DANGEROUS_PATTERNS.iter().any(|p| arg.contains(p)))
+    }
+
+    pub async fn invoke(&self, os: &Os, output: &mut impl Write) -> Result<InvokeOutput> {
+        let sandbox = SandboxConfig::from_settings(os)?;
+        let output = run_command(
+            &self.command,
+            MAX_TOOL_RESPONSE_SIZE / 3,
+            Some(output),
+            sandbox.as_ref(),
+        )
+        .await?;
         let result = serde_json::json!({
             "exit_status": output.exit_status.unwrap_or(0).to_string(),
             "stdout": output.stdout,
@@ -267,4 +295,34 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_is_compound() {
+        let cmds = &[
+            ("cargo test", false),
+            ("cargo test -p foo -- --nocapture", false),
+            ("cargo test && rm -rf ~", true),
+            ("cargo test; rm -rf ~", true),
+            ("cargo test | sh", true),
+            ("cargo test\nrm -rf ~", true),
+            ("cargo test\r\nrm -rf ~", true),
+            ("cargo test $(rm -rf ~)", true),
+            ("cargo test \"$(rm -rf ~)\"", true),
+            ("cargo test `rm -rf ~`", true),
+            ("cargo test > out.txt", true),
+        ];
+        for (cmd, expected) in cmds {
+            let tool = serde_json::from_value::<ExecuteCommand>(serde_json::json!({
+                "command": cmd,
+            }))
+            .unwrap();
+            assert_eq!(
+                tool.is_compound(),
+                *expected,
+                "expected command: `{}` to have is_compound: `{}`",
+                cmd,
+                expected
+            );
+        }
+    }
 }
@@ -0,0 +1,449 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use eyre::Result;
+
+use crate::database::settings::Setting;
+use crate::os::Os;
+
+/// Maximum CPU time, in seconds, of a sandboxed command unless configured otherwise
+const DEFAULT_CPU_TIME_LIMIT: u64 = 600;
+/// Maximum size, in MiB, of the data segment of a sandboxed command unless configured otherwise
+const DEFAULT_MEMORY_LIMIT: u64 = 4096;
+/// Maximum wall clock time, in seconds, of a sandboxed command unless configured otherwise
+const DEFAULT_TIMEOUT: u64 = 900;
+
+/// Restrictions applied to commands run by `execute_bash` when `chat.sandbox.enabled` is set.
+///
+/// On Linux, writes outside of [Self::writable_paths] are blocked with Landlock, and network
+/// access is blocked with a seccomp filter that only permits Unix domain sockets. Sandboxing is
+/// not supported on other platforms, where commands fail to run rather than run unrestricted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandboxConfig {
+    /// Whether the command may open network sockets
+    pub allow_network: bool,
+    /// Files and directories that the command may write to. Everything else is read-only.
+    pub writable_paths: Vec<PathBuf>,
+    /// Maximum CPU time of the command
+    pub cpu_time_limit: Option<Duration>,
+    /// Maximum size of the data segment of the command in bytes
+    pub memory_limit: Option<u64>,
+    /// Maximum wall clock time of the command, after which it is killed
+    pub timeout: Option<Duration>,
+}
+
+impl SandboxConfig {
+    /// Reads the sandbox settings, returning [None] if the sandbox is not enabled.
+    ///
+    /// The current working directory, the temporary directory, and `/dev/null` are always
+    /// writable. Limits that are set to `0` are disabled.
+    pub fn from_settings(os: &Os) -> Result<Option<Self>> {
+        let settings = &os.database.settings;
+        if !settings.get_bool(Setting::ChatSandboxEnabled).unwrap_or(false) {
+            return Ok(None);
+        }
+
+        let mut writable_paths = vec![os.env.current_dir()?, std::env::temp_dir(), PathBuf::from("/dev/null")];
+        let configured_paths = settings
+            .get(Setting::ChatSandboxWritablePaths)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|v| v.as_str());
+        for path in configured_paths {
+            match (path.strip_prefix("~/"), os.env.home()) {
+                (Some(relative), Some(home)) => writable_paths.push(home.join(relative)),
+                _ => writable_paths.push(PathBuf::from(path)),
+            }
+        }
+
+        let limit = |setting: Setting, default: u64| match settings.get_int(setting) {
+            Some(limit) if limit <= 0 => None,
+            Some(limit) => Some(limit as u64),
+            None => Some(default),
+        };
+
+        Ok(Some(Self {
+            allow_network: settings.get_bool(Setting::ChatSandboxAllowNetwork).unwrap_or(false),
+            writable_paths,
+            cpu_time_limit: limit(Setting::ChatSandboxCpuTimeLimit, DEFAULT_CPU_TIME_LIMIT).map(Duration::from_secs),
+            memory_limit: limit(Setting::ChatSandboxMemoryLimit, DEFAULT_MEMORY_LIMIT).map(|mib| mib * 1024 * 1024),
+            timeout: limit(Setting::ChatSandboxTimeout, DEFAULT_TIMEOUT).map(Duration::from_secs),
+        }))
+    }
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub use linux::sandbox_command;
+
+/// Configures `command` to run under the restrictions of `config`.
+#[cfg(all(
+    unix,
+    not(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))
+))]
+pub fn sandbox_command(_command: &mut tokio::process::Command, _config: &SandboxConfig) -> Result<()> {
+    eyre::bail!("The execute_bash sandbox is only supported on Linux, disable it with `chat.sandbox.enabled`")
+}
+
+#[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod linux {
+    use std::fs::OpenOptions;
+    use std::io;
+    use std::os::fd::{
+        AsRawFd,
+        FromRawFd,
+        OwnedFd,
+        RawFd,
+    };
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::process::Stdio;
+
+    use eyre::{
+        Result,
+        bail,
+    };
+    use tracing::warn;
+
+    use super::SandboxConfig;
+
+    const LANDLOCK_CREATE_RULESET_VERSION: libc::c_uint = 1;
+    const LANDLOCK_RULE_PATH_BENEATH: libc::c_int = 1;
+
+    const LANDLOCK_ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const LANDLOCK_ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const LANDLOCK_ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const LANDLOCK_ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const LANDLOCK_ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const LANDLOCK_ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const LANDLOCK_ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const LANDLOCK_ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const LANDLOCK_ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const LANDLOCK_ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    /// Available since Landlock ABI 2
+    const LANDLOCK_ACCESS_FS_REFER: u64 = 1 << 13;
+    /// Available since Landlock ABI 3
+    const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+    /// Every access right that modifies the file system, as of Landlock ABI 1
+    const LANDLOCK_ACCESS_FS_WRITE: u64 = LANDLOCK_ACCESS_FS_WRITE_FILE
+        | LANDLOCK_ACCESS_FS_REMOVE_DIR
+        | LANDLOCK_ACCESS_FS_REMOVE_FILE
+        | LANDLOCK_ACCESS_FS_MAKE_CHAR
+        | LANDLOCK_ACCESS_FS_MAKE_DIR
+        | LANDLOCK_ACCESS_FS_MAKE_REG
+        | LANDLOCK_ACCESS_FS_MAKE_SOCK
+        | LANDLOCK_ACCESS_FS_MAKE_FIFO
+        | LANDLOCK_ACCESS_FS_MAKE_BLOCK
+        | LANDLOCK_ACCESS_FS_MAKE_SYM;
+    /// The access rights that can be granted on a file rather than a directory
+    const LANDLOCK_ACCESS_FS_FILE: u64 = LANDLOCK_ACCESS_FS_WRITE_FILE | LANDLOCK_ACCESS_FS_TRUNCATE;
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xc000_003e;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xc000_00b7;
+    /// Syscall numbers at or above this use the x32 ABI on x86_64
+    const X32_SYSCALL_BIT: u32 = 0x4000_0000;
+
+    #[repr(C)]
+    struct LandlockRulesetAttr {
+        handled_access_fs: u64,
+    }
+
+    #[repr(C, packed)]
+    struct LandlockPathBeneathAttr {
+        allowed_access: u64,
+        parent_fd: RawFd,
+    }
+
+    /// The restrictions of a [SandboxConfig], prepared ahead of spawning the command such that
+    /// applying them in the child process only requires async-signal-safe syscalls.
+    struct Sandbox {
+        ruleset: OwnedFd,
+        seccomp_filter: Option<Vec<libc::sock_filter>>,
+        cpu_time_limit: Option<u64>,
+        memory_limit: Option<u64>,
+    }
+
+    impl Sandbox {
+        fn new(config: &SandboxConfig) -> Result<Self> {
+            Ok(Self {
+                ruleset: landlock_ruleset(config)?,
+                seccomp_filter: (!config.allow_network).then(network_filter),
+                cpu_time_limit: config.cpu_time_limit.map(|d| d.as_secs().max(1)),
+                memory_limit: config.memory_limit,
+            })
+        }
+
+        /// Restricts the current process. Called in the child process after forking.
+        fn apply(&self) -> io::Result<()> {
+            let set_limit = |resource, limit: u64| {
+                let rlimit = libc::rlimit {
+                    rlim_cur: limit,
+                    rlim_max: limit,
+                };
+                // SAFETY: rlimit is a valid pointer for the duration of the call.
+                match unsafe { libc::setrlimit(resource, &rlimit) } {
+                    0 => Ok(()),
+                    _ => Err(io::Error::last_os_error()),
+                }
+            };
+            if let Some(limit) = self.cpu_time_limit {
+                set_limit(libc::RLIMIT_CPU, limit)?;
+            }
+            if let Some(limit) = self.memory_limit {
+                set_limit(libc::RLIMIT_DATA, limit)?;
+            }
+
+            // SAFETY: these syscalls only read from the arguments passed, which are valid for the
+            // duration of the calls.
+            unsafe {
+                if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if libc::syscall(libc::SYS_landlock_restrict_self, self.ruleset.as_raw_fd(), 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if let Some(filter) = &self.seccomp_filter {
+                    let program = libc::sock_fprog {
+                        len: filter.len() as libc::c_ushort,
+                        filter: filter.as_ptr().cast_mut(),
+                    };
+                    if libc::prctl(libc::PR_SET_SECCOMP, libc::SECCOMP_MODE_FILTER, &program) != 0 {
+                        return Err(io::Error::last_os_error());
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Configures `command` to run under the restrictions of `config`.
+    ///
+    /// The command is run in its own process group, such that everything it spawns can be killed
+    /// when it times out, and without stdin since it can no longer read from the terminal.
+    pub fn sandbox_command(command: &mut tokio::process::Command, config: &SandboxConfig) -> Result<()> {
+        let sandbox = Sandbox::new(config)?;
+        command.stdin(Stdio::null()).process_group(0);
+        // SAFETY: Sandbox::apply only makes async-signal-safe syscalls and does not allocate.
+        unsafe {
+            command.pre_exec(move || sandbox.apply());
+        }
+        Ok(())
+    }
+
+    /// Creates a Landlock ruleset that only allows modifying the file system beneath the writable
+    /// paths of `config`.
+    fn landlock_ruleset(config: &SandboxConfig) -> Result<OwnedFd> {
+        // SAFETY: querying the ABI version takes no attributes.
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<LandlockRulesetAttr>(),
+                0,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            bail!(
+                "The execute_bash sandbox requires Landlock, which is not available: {}",
+                io::Error::last_os_error()
+            );
+        }
+
+        let mut handled_access_fs = LANDLOCK_ACCESS_FS_WRITE;
+        if abi >= 2 {
+            handled_access_fs |= LANDLOCK_ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled_access_fs |= LANDLOCK_ACCESS_FS_TRUNCATE;
+        }
+        let attr = LandlockRulesetAttr { handled_access_fs };
+        // SAFETY: attr is valid for the duration of the call, and the returned fd is owned by us.
+        let ruleset = unsafe {
+            match libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr,
+                size_of::<LandlockRulesetAttr>(),
+                0,
+            ) {
+                fd if fd < 0 => bail!("Failed to create the sandbox: {}", io::Error::last_os_error()),
+                fd => OwnedFd::from_raw_fd(fd as RawFd),
+            }
+        };
+
+        for path in &config.writable_paths {
+            let file = match OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_PATH | libc::O_CLOEXEC)
+                .open(path)
+            {
+                Ok(file) => file,
+                Err(err) => {
+                    warn!(?path, %err, "Unable to make path writable in the sandbox");
+                    continue;
+                },
+            };
+            let allowed_access = match file.metadata()?.is_dir() {
+                true => handled_access_fs,
+                false => handled_access_fs & LANDLOCK_ACCESS_FS_FILE,
+            };
+            let rule = LandlockPathBeneathAttr {
+                allowed_access,
+                parent_fd: file.as_raw_fd(),
+            };
+            // SAFETY: rule is valid for the duration of the call.
+            let res = unsafe {
+                libc::syscall(
+                    libc::SYS_landlock_add_rule,
+                    ruleset.as_raw_fd(),
+                    LANDLOCK_RULE_PATH_BENEATH,
+                    &rule,
+                    0,
+                )
+            };
+            if res != 0 {
+                bail!(
+                    "Failed to make {} writable in the sandbox: {}",
+                    path.display(),
+                    io::Error::last_os_error()
+                );
+            }
+        }
+
+        Ok(ruleset)
+    }
+
+    /// A seccomp filter that fails the creation of any socket other than a Unix domain socket
+    /// with `EACCES`, along with `io_uring` which is able to create sockets itself. Syscalls made
+    /// through another ABI (e.g. 32-bit syscalls on x86_64) are rejected entirely since they
+    /// would otherwise bypass the filter.
+    fn network_filter() -> Vec<libc::sock_filter> {
+        const ARCH_OFFSET: u32 = 4;
+        const NR_OFFSET: u32 = 0;
+        const ARG0_OFFSET: u32 = 16;
+
+        let load = |offset: u32| libc::sock_filter {
+            code: (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as u16,
+            jt: 0,
+            jf: 0,
+            k: offset,
+        };
+        let jump = |op: u32, k: u32, jt: u8, jf: u8| libc::sock_filter {
+            code: (libc::BPF_JMP | op | libc::BPF_K) as u16,
+            jt,
+            jf,
+            k,
+        };
+        let ret = |k: u32| libc::sock_filter {
+            code: (libc::BPF_RET | libc::BPF_K) as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        };
+
+        // Jump offsets are relative to the following instruction.
+        vec![
+            // 0
+            load(ARCH_OFFSET),
+            // 1
+            jump(libc::BPF_JEQ, AUDIT_ARCH, 0, 7),
+            // 2
+            load(NR_OFFSET),
+            // 3
+            jump(libc::BPF_JGE, X32_SYSCALL_BIT, 5, 0),
+            // 4
+            jump(libc::BPF_JEQ, libc::SYS_socket as u32, 0, 2),
+            // 5
+            load(ARG0_OFFSET),
+            // 6
+            jump(libc::BPF_JEQ, libc::AF_UNIX as u32, 1, 2),
+            // 7
+            jump(libc::BPF_JEQ, libc::SYS_io_uring_setup as u32, 1, 0),
+            // 8
+            ret(libc::SECCOMP_RET_ALLOW),
+            // 9
+            ret(libc::SECCOMP_RET_ERRNO | libc::EACCES as u32),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_from_settings() {
+        let mut os = Os::new().await.unwrap();
+        assert_eq!(SandboxConfig::from_settings(&os).unwrap(), None);
+
+        let settings = &mut os.database.settings;
+        settings.set(Setting::ChatSandboxEnabled, true).await.unwrap();
+        let config = SandboxConfig::from_settings(&os).unwrap().unwrap();
+        assert!(!config.allow_network);
+        assert_eq!(config.writable_paths[0], os.env.current_dir().unwrap());
+        assert_eq!(config.timeout, Some(Duration::from_secs(DEFAULT_TIMEOUT)));
+
+        let settings = &mut os.database.settings;
+        settings.set(Setting::ChatSandboxAllowNetwork, true).await.unwrap();
+        settings
+            .set(
+                Setting::ChatSandboxWritablePaths,
+                serde_json::json!(["~/.cargo", "/opt/cache"]),
+            )
+            .await
+            .unwrap();
+        settings.set(Setting::ChatSandboxMemoryLimit, 1).await.unwrap();
+        settings.set(Setting::ChatSandboxTimeout, 0).await.unwrap();
+        let config = SandboxConfig::from_settings(&os).unwrap().unwrap();
+        assert!(config.allow_network);
+        assert!(config.writable_paths.contains(&os.env.home().unwrap().join(".cargo")));
+        assert!(config.writable_paths.contains(&PathBuf::from("/opt/cache")));
+        assert_eq!(config.memory_limit, Some(1024 * 1024));
+        assert_eq!(config.timeout, None);
+    }
+
+    #[cfg(all(target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+    #[tokio::test]
+    async fn test_sandboxed_command() {
+        use super::super::run_command;
+
+        let workspace = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let config = SandboxConfig {
+            allow_network: false,
+            writable_paths: vec![workspace.path().to_path_buf(), PathBuf::from("/dev/null")],
+            cpu_time_limit: Some(Duration::from_secs(10)),
+            memory_limit: None,
+            timeout: Some(Duration::from_secs(1)),
+        };
+        let run = |command: String| {
+            let config = config.clone();
+            async move { run_command(&command, 1024, None::<std::io::Stdout>, Some(&config)).await }
+        };
+
+        // Landlock may not be available, e.g. in containers on older kernels.
+        let inside = workspace.path().join("file");
+        let result = match run(format!("echo hello > {} 2>/dev/null", inside.display())).await {
+            Ok(result) => result,
+            Err(err) => {
+                println!("skipping sandbox test: {err:?}");
+                return;
+            },
+        };
+        assert_eq!(result.exit_status, Some(0));
+        assert!(inside.exists());
+
+        let result = run(format!("echo hello > {}", outside.path().join("file").display()))
+            .await
+            .unwrap();
+        assert_ne!(result.exit_status, Some(0));
+        assert!(!outside.path().join("file").exists());
+
+        let result = run("exec 3<>/dev/tcp/127.0.0.1/80".to_string()).await.unwrap();
+        assert_ne!(result.exit_status, Some(0));
+
+        let result = run("sleep 10".to_string()).await.unwrap();
+        assert!(result.stderr.contains("timed out"));
+    }
+}
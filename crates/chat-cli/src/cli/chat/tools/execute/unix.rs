@@ -1,18 +1,26 @@
 use std::collections::VecDeque;
 use std::io::Write;
 use std::process::Stdio;
+use std::time::Duration;
 
 use eyre::{
     Context as EyreContext,
     Result,
 };
+use nix::sys::signal::{
+    Signal,
+    killpg,
+};
+use nix::unistd::Pid;
 use tokio::io::AsyncBufReadExt;
 use tokio::select;
 use tracing::error;
 
 use super::{
     CommandResult,
+    SandboxConfig,
     format_output,
+    sandbox_command,
 };
 
 /// Run a bash command on Unix systems.
@@ -20,25 +28,38 @@ use super::{
 /// * `command` - The command to run
 /// * `max_result_size` - max size of output streams, truncating if required
 /// * `updates` - output stream to push informational messages about the progress
+/// * `sandbox` - restrictions to run the command under, if any
 /// # Returns
 /// A [`CommandResult`]
 pub async fn run_command<W: Write>(
     command: &str,
     max_result_size: usize,
     mut updates: Option<W>,
+    sandbox: Option<&SandboxConfig>,
 ) -> Result<CommandResult> {
     let shell = std::env::var("AMAZON_Q_CHAT_SHELL").unwrap_or("bash".to_string());
 
     // We need to maintain a handle on stderr and stdout, but pipe it to the terminal as well
-    let mut child = tokio::process::Command::new(shell)
-        .arg("-c")
+    let mut cmd = tokio::process::Command::new(shell);
+    cmd.arg("-c")
         .arg(command)
         .stdin(Stdio::inherit())
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stderr(Stdio::piped());
+    if let Some(sandbox) = sandbox {
+        sandbox_command(&mut cmd, sandbox)?;
+    }
+    let mut child = cmd
         .spawn()
         .wrap_err_with(|| format!("Unable to spawn command '{}'", command))?;
 
+    // Sandboxed commands run in their own process group, which is killed once the timeout elapses.
+    let timeout = sandbox.and_then(|s| s.timeout);
+    let deadline = tokio::time::sleep(timeout.unwrap_or(Duration::MAX));
+    tokio::pin!(deadline);
+    let mut timed_out = false;
+    let process_group = child.id().map(|pid| Pid::from_raw(pid as i32));
+
     let stdout_final: String;
     let stderr_final: String;
     let exit_status;
@@ -87,6 +108,10 @@ pub async fn run_command<W: Write>(
                 exit_status = child.wait() => {
                     break exit_status;
                 },
+                _ = &mut deadline, if !timed_out => {
+                    timed_out = true;
+                    kill_process_group(process_group);
+                },
             };
         }
         .wrap_err_with(|| format!("No exit status for '{}'", command))?;
@@ -101,31 +126,60 @@ pub async fn run_command<W: Write>(
         // NOTE: If we don't split this logic, then any writes to stdout while calling
         // this function concurrently may cause the piped child output to be ignored
 
-        let output = child
-            .wait_with_output()
-            .await
-            .wrap_err_with(|| format!("No exit status for '{}'", command))?;
+        let output = child.wait_with_output();
+        tokio::pin!(output);
+        let output = loop {
+            select! {
+                output = &mut output => break output,
+                _ = &mut deadline, if !timed_out => {
+                    timed_out = true;
+                    kill_process_group(process_group);
+                },
+            }
+        }
+        .wrap_err_with(|| format!("No exit status for '{}'", command))?;
 
         exit_status = output.status;
         stdout_final = String::from_utf8_lossy(&output.stdout).to_string();
         stderr_final = String::from_utf8_lossy(&output.stderr).to_string();
     }
 
+    let mut stderr = format_output(&stderr_final, max_result_size);
+    if timed_out {
+        if !stderr.is_empty() {
+            stderr.push('\n');
+        }
+        stderr.push_str(&format!(
+            "The command timed out after {} seconds and was killed",
+            timeout.unwrap_or_default().as_secs()
+        ));
+    }
+
     Ok(CommandResult {
         exit_status: exit_status.code(),
         stdout: format_output(&stdout_final, max_result_size),
-        stderr: format_output(&stderr_final, max_result_size),
+        stderr,
     })
 }
 
+fn kill_process_group(process_group: Option<Pid>) {
+    if let Some(pgid) = process_group {
+        if let Err(err) = killpg(pgid, Signal::SIGKILL) {
+            error!(%err, "Failed to kill timed out command");
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::cli::chat::tools::OutputKind;
     use crate::cli::chat::tools::execute::ExecuteCommand;
+    use crate::os::Os;
 
     #[ignore = "todo: fix failing on musl for some reason"]
     #[tokio::test]
     async fn test_execute_bash_tool() {
+        let os = Os::new().await.unwrap();
         let mut stdout = std::io::stdout();
 
         // Verifying stdout
@@ -134,7 +188,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&mut stdout)
+            .invoke(&os, &mut stdout)
             .await
             .unwrap();
 
@@ -152,7 +206,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&mut stdout)
+            .invoke(&os, &mut stdout)
             .await
             .unwrap();
 
@@ -170,7 +224,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&mut stdout)
+            .invoke(&os, &mut stdout)
             .await
             .unwrap();
         if let OutputKind::Json(json) = out.output {
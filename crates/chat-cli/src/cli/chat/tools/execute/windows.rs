@@ -12,6 +12,7 @@ use tracing::error;
 
 use super::{
     CommandResult,
+    SandboxConfig,
     format_output,
 };
 
@@ -20,13 +21,19 @@ use super::{
 /// * `command` - The command to run
 /// * `max_result_size` - max size of output streams, truncating if required
 /// * `updates` - output stream to push informational messages about the progress
+/// * `sandbox` - must be [None] since sandboxing is not supported on Windows
 /// # Returns
 /// A [`CommandResult`]
 pub async fn run_command<W: Write>(
     command: &str,
     max_result_size: usize,
     mut updates: Option<W>,
+    sandbox: Option<&SandboxConfig>,
 ) -> Result<CommandResult> {
+    if sandbox.is_some() {
+        eyre::bail!("The execute_bash sandbox is only supported on Linux, disable it with `chat.sandbox.enabled`");
+    }
+
     // We need to maintain a handle on stderr and stdout, but pipe it to the terminal as well
     let mut child = tokio::process::Command::new("cmd")
         .arg("/C")
@@ -116,9 +123,11 @@ pub async fn run_command<W: Write>(
 mod tests {
     use crate::cli::chat::tools::OutputKind;
     use crate::cli::chat::tools::execute::ExecuteCommand;
+    use crate::os::Os;
 
     #[tokio::test]
     async fn test_execute_cmd_tool() {
+        let os = Os::new().await.unwrap();
         let mut stdout = std::io::stdout();
 
         // Verifying stdout
@@ -127,7 +136,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&mut stdout)
+            .invoke(&os, &mut stdout)
             .await
             .unwrap();
 
@@ -145,7 +154,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&mut stdout)
+            .invoke(&os, &mut stdout)
             .await
             .unwrap();
 
@@ -163,7 +172,7 @@ mod tests {
         });
         let out = serde_json::from_value::<ExecuteCommand>(v)
             .unwrap()
-            .invoke(&mut stdout)
+            .invoke(&os, &mut stdout)
             .await
             .unwrap();
         if let OutputKind::Json(json) = out.output {
@@ -1,5 +1,8 @@
 use std::io::Write;
-use std::path::Path;
+use std::path::{
+    Path,
+    PathBuf,
+};
 use std::sync::LazyLock;
 
 use crossterm::queue;
@@ -32,6 +35,7 @@ use super::{
     format_path,
     sanitize_path_tool_arg,
     supports_truecolor,
+    unified_diff,
 };
 use crate::os::Os;
 
@@ -70,6 +74,34 @@ pub enum FsWrite {
         new_str: String,
         summary: Option<String>,
     },
+    /// Applies each of `edits` in order, writing the file only if all of them succeed.
+    #[serde(rename = "multi_edit")]
+    MultiEdit {
+        path: String,
+        edits: Vec<Edit>,
+        summary: Option<String>,
+    },
+    /// Applies a unified diff that may touch multiple files. The diff is applied to every file
+    /// before any of them are written.
+    #[serde(rename = "patch")]
+    Patch { patch: String, summary: Option<String> },
+}
+
+/// A single replacement of a [FsWrite::MultiEdit] command.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Edit {
+    pub old_str: String,
+    pub new_str: String,
+}
+
+/// The result of applying a [FsWrite::Patch] to a single file.
+#[derive(Debug, Clone)]
+struct PatchedFile {
+    path: PathBuf,
+    /// Contents of the file before the patch, [None] if the patch creates the file
+    old: Option<String>,
+    /// Contents of the file after the patch, [None] if the patch deletes the file
+    new: Option<String>,
 }
 
 impl FsWrite {
@@ -173,6 +205,53 @@ impl FsWrite {
                 write_to_file(os, path, file).await?;
                 Ok(Default::default())
             },
+            FsWrite::MultiEdit { path, edits, .. } => {
+                let path = sanitize_path_tool_arg(os, path);
+                let file = os.fs.read_to_string(&path).await?;
+                queue!(
+                    output,
+                    style::Print("Updating: "),
+                    style::SetForegroundColor(Color::Green),
+                    style::Print(format_path(cwd, &path)),
+                    style::ResetColor,
+                    style::Print("\n"),
+                )?;
+
+                let file = apply_edits(&file, edits)?;
+                os.fs.write(path, file).await?;
+                Ok(Default::default())
+            },
+            FsWrite::Patch { patch, .. } => {
+                // Every file is patched in memory first so that nothing is written if any hunk
+                // fails to apply.
+                let patched_files = apply_patch(os, patch)?;
+                for file in patched_files {
+                    let invoke_description = match (&file.old, &file.new) {
+                        (None, _) => "Creating: ",
+                        (_, None) => "Deleting: ",
+                        _ => "Updating: ",
+                    };
+                    queue!(
+                        output,
+                        style::Print(invoke_description),
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(format_path(&cwd, &file.path)),
+                        style::ResetColor,
+                        style::Print("\n"),
+                    )?;
+
+                    match file.new {
+                        Some(content) => {
+                            if let Some(parent) = file.path.parent() {
+                                os.fs.create_dir_all(parent).await?;
+                            }
+                            os.fs.write(&file.path, content).await?;
+                        },
+                        None => os.fs.remove_file(&file.path).await?,
+                    }
+                }
+                Ok(Default::default())
+            },
         }
     }
 
@@ -256,6 +335,48 @@ impl FsWrite {
                 // Display summary as purpose if available after the diff
                 super::display_purpose(self.get_summary(), output)?;
 
+                Ok(())
+            },
+            FsWrite::MultiEdit { path, edits, .. } => {
+                let path = sanitize_path_tool_arg(os, path);
+                let relative_path = format_path(cwd, &path);
+                let old = os.fs.read_to_string_sync(&path)?;
+                let new = apply_edits(&old, edits)?;
+                print_changed_lines(os, output, &relative_path, &old, &new)?;
+
+                // Display summary as purpose if available after the diff
+                super::display_purpose(self.get_summary(), output)?;
+
+                Ok(())
+            },
+            FsWrite::Patch { patch, .. } => {
+                for file in apply_patch(os, patch)? {
+                    let relative_path = format_path(&cwd, &file.path);
+                    queue!(
+                        output,
+                        style::Print("Path: "),
+                        style::SetForegroundColor(Color::Green),
+                        style::Print(&relative_path),
+                        style::ResetColor,
+                        style::Print(match (&file.old, &file.new) {
+                            (None, _) => " (new file)",
+                            (_, None) => " (deleted)",
+                            _ => "",
+                        }),
+                        style::Print("\n\n"),
+                    )?;
+                    print_changed_lines(
+                        os,
+                        output,
+                        &relative_path,
+                        file.old.as_deref().unwrap_or_default(),
+                        file.new.as_deref().unwrap_or_default(),
+                    )?;
+                }
+
+                // Display summary as purpose if available after the diff
+                super::display_purpose(self.get_summary(), output)?;
+
                 Ok(())
             },
         }
@@ -282,19 +403,45 @@ impl FsWrite {
                     bail!("Content to append must not be empty")
                 };
             },
+            FsWrite::MultiEdit { path, edits, .. } => {
+                if edits.is_empty() {
+                    bail!("At least one edit must be provided")
+                }
+                let path = sanitize_path_tool_arg(os, path);
+                if !path.exists() {
+                    bail!("The provided path must exist in order to edit it")
+                }
+                apply_edits(&os.fs.read_to_string(&path).await?, edits)?;
+            },
+            FsWrite::Patch { patch, .. } => {
+                apply_patch(os, patch)?;
+            },
         }
 
         Ok(())
     }
 
+    /// The paths of the files to be written, as supplied by the model. Empty if the paths of a
+    /// [FsWrite::Patch] cannot be parsed.
+    pub fn paths(&self) -> Vec<String> {
+        match self {
+            FsWrite::Create { path, .. }
+            | FsWrite::StrReplace { path, .. }
+            | FsWrite::Insert { path, .. }
+            | FsWrite::Append { path, .. }
+            | FsWrite::MultiEdit { path, .. } => vec![path.clone()],
+            FsWrite::Patch { patch, .. } => unified_diff::parse(patch)
+                .map(|patches| patches.iter().map(|p| p.path().to_string()).collect())
+                .unwrap_or_default(),
+        }
+    }
+
     fn print_relative_path(&self, os: &Os, output: &mut impl Write) -> Result<()> {
-        let cwd = os.env.current_dir()?;
-        let path = match self {
-            FsWrite::Create { path, .. } => path,
-            FsWrite::StrReplace { path, .. } => path,
-            FsWrite::Insert { path, .. } => path,
-            FsWrite::Append { path, .. } => path,
+        // Each file of a patch is printed alongside its diff.
+        let [path] = &self.paths()[..] else {
+            return Ok(());
         };
+        let cwd = os.env.current_dir()?;
         // Sanitize the path to handle tilde expansion
         let path = sanitize_path_tool_arg(os, path);
         let relative_path = format_path(cwd, &path);
@@ -335,10 +482,67 @@ impl FsWrite {
             FsWrite::StrReplace { summary, .. } => summary.as_ref(),
             FsWrite::Insert { summary, .. } => summary.as_ref(),
             FsWrite::Append { summary, .. } => summary.as_ref(),
+            FsWrite::MultiEdit { summary, .. } => summary.as_ref(),
+            FsWrite::Patch { summary, .. } => summary.as_ref(),
         }
     }
 }
 
+/// Applies `edits` to `file` in order, failing if the `old_str` of any edit does not occur exactly
+/// once in the file as updated by the edits before it.
+fn apply_edits(file: &str, edits: &[Edit]) -> Result<String> {
+    let mut file = file.to_string();
+    for (i, Edit { old_str, new_str }) in edits.iter().enumerate() {
+        if old_str.is_empty() {
+            bail!("edit {}: old_str must not be empty", i + 1);
+        }
+        match file.matches(old_str.as_str()).count() {
+            0 => bail!("edit {}: no occurrences of \"{old_str}\" were found", i + 1),
+            1 => file = file.replacen(old_str.as_str(), new_str, 1),
+            x => bail!(
+                "edit {}: {x} occurrences of old_str were found when only 1 is expected",
+                i + 1
+            ),
+        }
+    }
+    Ok(file)
+}
+
+/// Parses `patch` as a unified diff and applies it to each of the files it touches, without
+/// writing anything.
+fn apply_patch(os: &Os, patch: &str) -> Result<Vec<PatchedFile>> {
+    let mut patched_files = Vec::<PatchedFile>::new();
+    for file_patch in unified_diff::parse(patch)? {
+        if let (Some(old_path), Some(new_path)) = (&file_patch.old_path, &file_patch.new_path) {
+            if old_path != new_path {
+                bail!("renaming {old_path} to {new_path} is not supported, create and delete the files instead");
+            }
+        }
+        let path = sanitize_path_tool_arg(os, file_patch.path());
+        if patched_files.iter().any(|f| f.path == path) {
+            bail!("{} is changed more than once by the patch", file_patch.path());
+        }
+
+        let old = match (&file_patch.old_path, os.fs.exists(&path)) {
+            (Some(_), true) => Some(os.fs.read_to_string_sync(&path)?),
+            (Some(_), false) => bail!("{} does not exist", file_patch.path()),
+            (None, true) => bail!("{} cannot be created since it already exists", file_patch.path()),
+            (None, false) => None,
+        };
+        let new = file_patch.apply(old.as_deref().unwrap_or_default())?;
+        let new = match file_patch.new_path {
+            Some(_) => Some(new),
+            None if new.is_empty() => None,
+            None => bail!(
+                "{} cannot be deleted since the patch does not remove all of its contents",
+                file_patch.path()
+            ),
+        };
+        patched_files.push(PatchedFile { path, old, new });
+    }
+    Ok(patched_files)
+}
+
 /// Writes `content` to `path`, adding a newline if necessary.
 async fn write_to_file(os: &Os, path: impl AsRef<Path>, mut content: String) -> Result<()> {
     let path_ref = path.as_ref();
@@ -518,6 +722,25 @@ fn print_diff(
     Ok(())
 }
 
+/// Prints a [print_diff] of each of the regions that differ between `old` and `new`, the full
+/// contents of a file before and after a change, along with a few lines of surrounding context.
+fn print_changed_lines(os: &Os, output: &mut impl Write, path: &str, old: &str, new: &str) -> Result<()> {
+    let diff = similar::TextDiff::from_lines(old, new);
+    let old_lines = LinesWithEndings::from(old).collect::<Vec<_>>();
+    let new_lines = LinesWithEndings::from(new).collect::<Vec<_>>();
+    for group in diff.grouped_ops(3) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+        let old = stylize_output_if_able(os, path, &old_lines[old_range.clone()].concat());
+        let new = stylize_output_if_able(os, path, &new_lines[new_range].concat());
+        print_diff(output, &old, &new, old_range.start + 1)?;
+    }
+    Ok(())
+}
+
 /// Returns a 1-indexed line number range of the start and end of `needle` inside `file`.
 fn line_number_at(file: impl AsRef<str>, needle: impl AsRef<str>) -> Option<(usize, usize)> {
     let file = file.as_ref();
@@ -686,6 +909,27 @@ mod tests {
         });
         let fw = serde_json::from_value::<FsWrite>(v).unwrap();
         assert!(matches!(fw, FsWrite::Append { .. }));
+
+        // multi_edit
+        let v = serde_json::json!({
+            "path": path,
+            "command": "multi_edit",
+            "edits": [
+                { "old_str": "prev string", "new_str": "new string" },
+                { "old_str": "other string", "new_str": "" },
+            ],
+        });
+        let fw = serde_json::from_value::<FsWrite>(v).unwrap();
+        assert!(matches!(fw, FsWrite::MultiEdit { ref edits, .. } if edits.len() == 2));
+
+        // patch
+        let v = serde_json::json!({
+            "command": "patch",
+            "patch": "--- a/my-file\n+++ b/my-file\n@@ -1 +1 @@\n-hello\n+world\n",
+        });
+        let fw = serde_json::from_value::<FsWrite>(v).unwrap();
+        assert!(matches!(fw, FsWrite::Patch { .. }));
+        assert_eq!(fw.paths(), vec!["my-file"]);
     }
 
     #[test]
@@ -840,6 +1084,101 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_fs_write_tool_multi_edit() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+
+        // The second edit fails since its old_str occurs twice, so nothing is written.
+        let v = serde_json::json!({
+            "path": TEST_FILE_PATH,
+            "command": "multi_edit",
+            "edits": [
+                { "old_str": "3: asdf", "new_str": "3: qwerty" },
+                { "old_str": "Hello world!", "new_str": "Goodbye world!" },
+            ],
+        });
+        let mut fw = serde_json::from_value::<FsWrite>(v).unwrap();
+        assert!(fw.validate(&os).await.is_err());
+        assert!(fw.invoke(&os, &mut stdout).await.is_err());
+        assert_eq!(os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(), TEST_FILE_CONTENTS);
+
+        // Later edits apply to the result of earlier ones.
+        let v = serde_json::json!({
+            "path": TEST_FILE_PATH,
+            "command": "multi_edit",
+            "edits": [
+                { "old_str": "1: Hello world!", "new_str": "1: Goodbye world!" },
+                { "old_str": "Hello world!", "new_str": "Goodbye world!" },
+                { "old_str": "3: asdf", "new_str": "3: qwerty" },
+            ],
+        });
+        let mut fw = serde_json::from_value::<FsWrite>(v).unwrap();
+        fw.validate(&os).await.unwrap();
+        fw.invoke(&os, &mut stdout).await.unwrap();
+        assert_eq!(
+            os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(),
+            "1: Goodbye world!\n2: This is line 2\n3: qwerty\n4: Goodbye world!\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fs_write_tool_patch() {
+        let os = setup_test_directory().await;
+        let mut stdout = std::io::stdout();
+        os.fs.write("/old.txt", "goodbye\n").await.unwrap();
+
+        // The second file doesn't match the patch, so nothing is written.
+        let v = serde_json::json!({
+            "command": "patch",
+            "patch": "--- /test_file.txt
++++ /test_file.txt
+@@ -2,2 +2,2 @@
+ 2: This is line 2
+-3: asdf
++3: qwerty
+--- /old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-hello
+",
+        });
+        let mut fw = serde_json::from_value::<FsWrite>(v).unwrap();
+        assert!(fw.validate(&os).await.is_err());
+        assert!(fw.invoke(&os, &mut stdout).await.is_err());
+        assert_eq!(os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(), TEST_FILE_CONTENTS);
+        assert!(os.fs.exists("/old.txt"));
+
+        let v = serde_json::json!({
+            "command": "patch",
+            "patch": "--- /test_file.txt
++++ /test_file.txt
+@@ -2,2 +2,2 @@
+ 2: This is line 2
+-3: asdf
++3: qwerty
+--- /old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-goodbye
+--- /dev/null
++++ /dir/new.txt
+@@ -0,0 +1 @@
++hello
+",
+        });
+        let mut fw = serde_json::from_value::<FsWrite>(v).unwrap();
+        fw.validate(&os).await.unwrap();
+        assert_eq!(fw.paths(), vec!["/test_file.txt", "/old.txt", "/dir/new.txt"]);
+        fw.invoke(&os, &mut stdout).await.unwrap();
+        assert_eq!(
+            os.fs.read_to_string(TEST_FILE_PATH).await.unwrap(),
+            "1: Hello world!\n2: This is line 2\n3: qwerty\n4: Hello world!\n"
+        );
+        assert!(!os.fs.exists("/old.txt"));
+        assert_eq!(os.fs.read_to_string("/dir/new.txt").await.unwrap(), "hello\n");
+    }
+
     #[tokio::test]
     async fn test_fs_write_tool_insert_at_beginning() {
         let os = setup_test_directory().await;
@@ -6,6 +6,7 @@ use crossterm::style::{
     Color,
 };
 use eyre::Result;
+use semantic_search_client::SearchFilter;
 use serde::Deserialize;
 use tracing::warn;
 
@@ -62,6 +63,33 @@ pub struct KnowledgeClear {
 pub struct KnowledgeSearch {
     pub query: String,
     pub context_id: Option<String>,
+    /// IDs or names of the contexts to search
+    #[serde(default)]
+    pub contexts: Vec<String>,
+    /// Glob pattern the paths of the files must match
+    pub path_pattern: Option<String>,
+    /// Type or language of the files
+    pub file_type: Option<String>,
+    /// Only search the files modified after this date or timestamp
+    pub modified_after: Option<String>,
+}
+
+impl KnowledgeSearch {
+    /// The contexts and files to search, or an error if the modification time is invalid
+    pub fn filter(&self) -> Result<SearchFilter> {
+        let mut contexts = self.contexts.clone();
+        contexts.extend(self.context_id.clone());
+        Ok(SearchFilter {
+            contexts,
+            path: self.path_pattern.clone(),
+            file_type: self.file_type.clone(),
+            modified_after: self
+                .modified_after
+                .as_deref()
+                .map(SearchFilter::parse_time)
+                .transpose()?,
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -139,7 +167,10 @@ impl Knowledge {
                 }
                 Ok(())
             },
-            Knowledge::Search(_) => Ok(()),
+            Knowledge::Search(search) => {
+                search.filter()?;
+                Ok(())
+            },
             Knowledge::Show => Ok(()),
             Knowledge::Status => Ok(()),
             Knowledge::Cancel(_) => Ok(()),
@@ -277,16 +308,39 @@ impl Knowledge {
                     style::ResetColor,
                 )?;
 
-                if let Some(context_id) = &search.context_id {
+                let filter = search.filter().unwrap_or_default();
+                if filter.contexts.is_empty() {
+                    queue!(updates, style::Print(" across all contexts"),)?;
+                } else {
+                    let label = if filter.contexts.len() == 1 {
+                        "context"
+                    } else {
+                        "contexts"
+                    };
                     queue!(
                         updates,
-                        style::Print(" in context: "),
+                        style::Print(format!(" in {}: ", label)),
                         style::SetForegroundColor(Color::Green),
-                        style::Print(context_id),
+                        style::Print(filter.contexts.join(", ")),
                         style::ResetColor,
                     )?;
-                } else {
-                    queue!(updates, style::Print(" across all contexts"),)?;
+                }
+
+                let mut criteria = Vec::new();
+                if let Some(path) = &filter.path {
+                    criteria.push(format!("path: {}", path));
+                }
+                if let Some(file_type) = &filter.file_type {
+                    criteria.push(format!("type: {}", file_type));
+                }
+                if let Some(modified_after) = &filter.modified_after {
+                    criteria.push(format!(
+                        "modified after: {}",
+                        modified_after.format("%Y-%m-%d %H:%M:%S")
+                    ));
+                }
+                if !criteria.is_empty() {
+                    queue!(updates, style::Print(format!(" ({})", criteria.join(", "))))?;
                 }
             },
             Knowledge::Show => {
@@ -409,7 +463,14 @@ impl Knowledge {
                 .unwrap_or_else(|e| format!("Failed to clear knowledge base: {}", e)),
             Knowledge::Search(search) => {
                 // Only use a spinner for search, not a full progress bar
-                let results = store.search(&search.query, search.context_id.as_deref()).await;
+                let results = match search.filter() {
+                    Ok(filter) => store.search(&search.query, &filter).await,
+                    Err(e) => {
+                        return Ok(InvokeOutput {
+                            output: OutputKind::Text(format!("Search failed: {}", e)),
+                        });
+                    },
+                };
                 match results {
                     Ok(results) => {
                         if results.is_empty() {
@@ -417,8 +478,15 @@ impl Knowledge {
                         } else {
                             let mut output = String::from("Search results:\n");
                             for result in results {
-                                if let Some(text) = result.text() {
-                                    output.push_str(&format!("- {}\n", text));
+                                let Some(text) = result.text() else {
+                                    continue;
+                                };
+                                match (result.location(), result.symbol()) {
+                                    (Some(location), Some(symbol)) => {
+                                        output.push_str(&format!("- {} ({})\n{}\n", location, symbol, text));
+                                    },
+                                    (Some(location), None) => output.push_str(&format!("- {}\n{}\n", location, text)),
+                                    (None, _) => output.push_str(&format!("- {}\n", text)),
                                 }
                             }
                             output
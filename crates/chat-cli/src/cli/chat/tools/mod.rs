@@ -5,6 +5,8 @@ pub mod fs_write;
 pub mod gh_issue;
 pub mod knowledge;
 pub mod thinking;
+pub mod trust_rule;
+pub mod unified_diff;
 pub mod use_aws;
 
 use std::collections::{
@@ -35,6 +37,10 @@ use serde::{
     Serialize,
 };
 use thinking::Thinking;
+use trust_rule::{
+    RuleEffect,
+    TrustRule,
+};
 use use_aws::UseAws;
 
 use super::consts::MAX_TOOL_RESPONSE_SIZE;
@@ -93,7 +99,7 @@ impl Tool {
         match self {
             Tool::FsRead(fs_read) => fs_read.invoke(os, stdout).await,
             Tool::FsWrite(fs_write) => fs_write.invoke(os, stdout).await,
-            Tool::ExecuteCommand(execute_command) => execute_command.invoke(stdout).await,
+            Tool::ExecuteCommand(execute_command) => execute_command.invoke(os, stdout).await,
             Tool::UseAws(use_aws) => use_aws.invoke(os, stdout).await,
             Tool::Custom(custom_tool) => custom_tool.invoke(os, stdout).await,
             Tool::GhIssue(gh_issue) => gh_issue.invoke(os, stdout).await,
@@ -155,6 +161,9 @@ pub struct ToolPermissions {
     pub permissions: HashMap<String, ToolPermission>,
     // Store pending trust-tool patterns for MCP tools that may be loaded later
     pub pending_trusted_tools: HashSet<String>,
+    /// Pattern based rules for the session. These take precedence over [Self::permissions] and
+    /// [Self::trust_all].
+    pub rules: Vec<TrustRule>,
 }
 
 impl ToolPermissions {
@@ -163,6 +172,7 @@ impl ToolPermissions {
             trust_all: false,
             permissions: HashMap::with_capacity(capacity),
             pending_trusted_tools: HashSet::new(),
+            rules: Vec::new(),
         }
     }
 
@@ -173,13 +183,16 @@ impl ToolPermissions {
             self.pending_trusted_tools.remove(tool_name);
         }
 
-        self.trust_all || self.permissions.get(tool_name).is_some_and(|perm| perm.trusted)
+        match self.rule_effect_for_tool(tool_name) {
+            Some(effect) => effect == RuleEffect::Allow,
+            None => self.trust_all || self.permissions.get(tool_name).is_some_and(|perm| perm.trusted),
+        }
     }
 
     /// Returns a label to describe the permission status for a given tool.
     pub fn display_label(&mut self, tool_name: &str) -> String {
         let is_trusted = self.is_trusted(tool_name);
-        let has_setting = self.has(tool_name) || self.trust_all;
+        let has_setting = self.has(tool_name) || self.trust_all || self.rule_effect_for_tool(tool_name).is_some();
 
         match (has_setting, is_trusted) {
             (true, true) => format!("  {}", "trusted".dark_green().bold()),
@@ -204,6 +217,7 @@ impl ToolPermissions {
         self.trust_all = false;
         self.permissions.clear();
         self.pending_trusted_tools.clear();
+        self.rules.clear();
     }
 
     pub fn reset_tool(&mut self, tool_name: &str) {
@@ -223,6 +237,84 @@ impl ToolPermissions {
         self.pending_trusted_tools.contains(tool_name)
     }
 
+    /// Adds a rule to the session, replacing an identical one if present
+    pub fn add_rule(&mut self, rule: TrustRule) {
+        self.remove_rule(&rule.to_string());
+        self.rules.push(rule);
+    }
+
+    /// Removes the rule written as `rule` from the session, returning whether it was present
+    pub fn remove_rule(&mut self, rule: &str) -> bool {
+        let len = self.rules.len();
+        self.rules.retain(|r| r.to_string() != rule.trim());
+        self.rules.len() != len
+    }
+
+    /// Evaluates the session rules along with `extra_rules` (e.g. those of the active profile)
+    /// against a tool use. Deny rules win over allow rules. Returns [None] if no rule applies, in
+    /// which case the other permission settings are to be consulted.
+    ///
+    /// An `fs_write` patch is evaluated once per file it touches, as though each file was given as
+    /// `path`. It is denied if any file is denied, and allowed only if every file is allowed.
+    pub fn evaluate_rules(&self, tool: &QueuedTool, extra_rules: &[TrustRule], cwd: &Path) -> Option<RuleEffect> {
+        let Tool::FsWrite(fs_write @ FsWrite::Patch { .. }) = &tool.tool else {
+            return self.evaluate_rules_with_args(tool, &tool.args, extra_rules, cwd);
+        };
+
+        let mut effects = Vec::new();
+        for path in fs_write.paths() {
+            let mut args = tool.args.clone();
+            if let Some(args) = args.as_object_mut() {
+                args.insert("path".to_string(), serde_json::Value::String(path));
+            }
+            effects.push(self.evaluate_rules_with_args(tool, &args, extra_rules, cwd));
+        }
+        if effects.contains(&Some(RuleEffect::Deny)) {
+            Some(RuleEffect::Deny)
+        } else if !effects.is_empty() && effects.iter().all(|e| *e == Some(RuleEffect::Allow)) {
+            Some(RuleEffect::Allow)
+        } else {
+            None
+        }
+    }
+
+    fn evaluate_rules_with_args(
+        &self,
+        tool: &QueuedTool,
+        args: &serde_json::Value,
+        extra_rules: &[TrustRule],
+        cwd: &Path,
+    ) -> Option<RuleEffect> {
+        let mut effect = None;
+        for rule in self.rules.iter().chain(extra_rules) {
+            if !rule.matches(&tool.name, args, cwd) {
+                continue;
+            }
+            match rule.effect {
+                RuleEffect::Deny => return Some(RuleEffect::Deny),
+                // A rule scoped to a command must not be satisfiable by appending arbitrary
+                // commands to an allowed one.
+                RuleEffect::Allow
+                    if rule.constrains_arg("command")
+                        && matches!(&tool.tool, Tool::ExecuteCommand(cmd) if cmd.is_compound()) => {},
+                RuleEffect::Allow => effect = Some(RuleEffect::Allow),
+            }
+        }
+        effect
+    }
+
+    /// The effect of the session rules that apply to a tool regardless of its arguments
+    fn rule_effect_for_tool(&self, tool_name: &str) -> Option<RuleEffect> {
+        let mut effect = None;
+        for rule in self.rules.iter().filter(|r| r.matches_tool(tool_name)) {
+            if rule.effect == RuleEffect::Deny {
+                return Some(RuleEffect::Deny);
+            }
+            effect = Some(RuleEffect::Allow);
+        }
+        effect
+    }
+
     pub fn has(&mut self, tool_name: &str) -> bool {
         // Check if we should trust from pending tools first
         if self.should_trust_from_pending(tool_name) {
@@ -318,6 +410,12 @@ pub struct QueuedTool {
     pub name: String,
     pub accepted: bool,
     pub tool: Tool,
+    /// The input of the tool use as supplied by the model
+    pub args: serde_json::Value,
+    /// Outcome of the pre tool use hooks, which run before the user is asked to accept the tool:
+    /// the context they added as pairs of hook name and context, or why they denied the tool use.
+    /// [None] until they ran.
+    pub pre_hook_result: Option<Result<Vec<(String, String)>, String>>,
 }
 
 /// The schema specification describing a tool's fields.
@@ -408,7 +506,7 @@ fn absolute_to_relative(cwd: impl AsRef<Path>, path: impl AsRef<Path>) -> Result
 }
 
 /// Small helper for formatting the path as a relative path, if able.
-fn format_path(cwd: impl AsRef<Path>, path: impl AsRef<Path>) -> String {
+pub fn format_path(cwd: impl AsRef<Path>, path: impl AsRef<Path>) -> String {
     absolute_to_relative(cwd, path.as_ref())
         .map(|p| p.to_string_lossy().to_string())
         // If we have three consecutive ".." then it should probably just stay as an absolute path.
@@ -520,4 +618,88 @@ mod tests {
         )
         .await;
     }
+
+    #[test]
+    fn test_evaluate_rules() {
+        fn bash(command: &str) -> QueuedTool {
+            QueuedTool {
+                id: "id".to_string(),
+                name: "execute_bash".to_string(),
+                accepted: false,
+                tool: Tool::ExecuteCommand(ExecuteCommand {
+                    command: command.to_string(),
+                    summary: None,
+                }),
+                args: serde_json::json!({ "command": command }),
+                pre_hook_result: None,
+            }
+        }
+        let rule = |s: &str| s.parse::<TrustRule>().unwrap();
+        let cwd = Path::new("/");
+
+        let mut permissions = ToolPermissions::new(1);
+        assert_eq!(permissions.evaluate_rules(&bash("cargo test"), &[], cwd), None);
+
+        permissions.add_rule(rule("execute_bash(command=cargo *)"));
+        assert_eq!(
+            permissions.evaluate_rules(&bash("cargo test"), &[], cwd),
+            Some(RuleEffect::Allow)
+        );
+        assert_eq!(
+            permissions.evaluate_rules(&bash("cargo test && rm -rf ~"), &[], cwd),
+            None,
+            "allow rules scoped to a command must not match chained commands"
+        );
+        assert_eq!(
+            permissions.evaluate_rules(&bash("cargo test\nrm -rf ~"), &[], cwd),
+            None,
+            "allow rules scoped to a command must not match multi-line commands"
+        );
+        assert_eq!(
+            permissions.evaluate_rules(&bash("cargo test $(rm -rf ~)"), &[], cwd),
+            None,
+            "allow rules scoped to a command must not match command substitutions"
+        );
+
+        let profile_rules = [rule("!execute_bash(command=cargo publish*)")];
+        assert_eq!(
+            permissions.evaluate_rules(&bash("cargo publish"), &profile_rules, cwd),
+            Some(RuleEffect::Deny),
+            "deny rules take precedence over allow rules"
+        );
+
+        let args = serde_json::json!({
+            "command": "patch",
+            "patch": "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n--- a/build.rs\n+++ b/build.rs\n@@ -1 +1 @@\n-a\n+b\n",
+        });
+        let patch = QueuedTool {
+            id: "id".to_string(),
+            name: "fs_write".to_string(),
+            accepted: false,
+            tool: Tool::FsWrite(serde_json::from_value(args.clone()).unwrap()),
+            args,
+            pre_hook_result: None,
+        };
+        permissions.add_rule(rule("fs_write(path=src/**)"));
+        assert_eq!(
+            permissions.evaluate_rules(&patch, &[], cwd),
+            None,
+            "a patch is only allowed if every file it touches is allowed"
+        );
+        permissions.add_rule(rule("fs_write(path=build.rs)"));
+        assert_eq!(permissions.evaluate_rules(&patch, &[], cwd), Some(RuleEffect::Allow));
+        assert_eq!(
+            permissions.evaluate_rules(&patch, &[rule("!fs_write(path=src/lib.rs)")], cwd),
+            Some(RuleEffect::Deny)
+        );
+
+        permissions.add_rule(rule("!execute_bash"));
+        assert!(!permissions.is_trusted("execute_bash"));
+        assert!(permissions.remove_rule("!execute_bash"));
+        assert!(!permissions.remove_rule("!execute_bash"));
+        permissions.add_rule(rule("@gitlab/*"));
+        assert!(permissions.is_trusted("gitlab___create_issue"));
+        permissions.reset();
+        assert!(permissions.rules.is_empty());
+    }
 }
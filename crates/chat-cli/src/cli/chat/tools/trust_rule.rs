@@ -0,0 +1,376 @@
+use std::fmt;
+use std::path::{
+    Component,
+    Path,
+    PathBuf,
+};
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use globset::{
+    GlobBuilder,
+    GlobMatcher,
+};
+use regex::Regex;
+use thiserror::Error;
+
+use crate::cli::chat::tool_manager::NAMESPACE_DELIMITER;
+
+/// Prefix marking a pattern as a regular expression rather than a glob
+const REGEX_PREFIX: &str = "re:";
+
+/// Prefix marking a rule as a deny rule
+const DENY_PREFIX: char = '!';
+
+/// Arguments whose values are treated as paths, i.e. they are normalized before being matched
+const PATH_ARGS: &[&str] = &["path"];
+
+/// The start of an argument list, i.e. a parenthesis followed by `<name>=`
+static ARG_LIST_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(\s*[A-Za-z_][A-Za-z0-9_]*\s*=").expect("invalid regex"));
+
+/// A comma separating two argument constraints, i.e. one followed by `<name>=`
+static ARG_SEPARATOR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r",\s*[A-Za-z_][A-Za-z0-9_]*\s*=").expect("invalid regex"));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleEffect {
+    /// Tool uses matching the rule run without asking for confirmation
+    Allow,
+    /// Tool uses matching the rule always ask for confirmation, regardless of any other setting
+    Deny,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TrustRuleError {
+    #[error("Rule is empty")]
+    Empty,
+    #[error("Invalid pattern '{0}': {1}")]
+    InvalidPattern(String, String),
+    #[error("Invalid argument constraint '{0}', expected <argument>=<pattern>")]
+    InvalidArgument(String),
+    #[error("Missing closing parenthesis in '{0}'")]
+    Unclosed(String),
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Glob {
+        matcher: GlobMatcher,
+        /// Whether the glob only matches values on a single line
+        single_line: bool,
+    },
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// Globs of allow rules are strict: `*` neither crosses path separators nor line breaks, since
+    /// `cargo test*` must not match a second command on the next line. Deny rules are not narrowed
+    /// that way.
+    fn parse(pattern: &str, effect: RuleEffect) -> Result<Self, TrustRuleError> {
+        let to_err = |e: String| TrustRuleError::InvalidPattern(pattern.to_string(), e);
+        let strict = effect == RuleEffect::Allow;
+        match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(re) => Regex::new(&format!("^(?:{re})$"))
+                .map(Self::Regex)
+                .map_err(|e| to_err(e.to_string())),
+            None => GlobBuilder::new(pattern)
+                .literal_separator(strict)
+                .build()
+                .map(|glob| Self::Glob {
+                    matcher: glob.compile_matcher(),
+                    single_line: strict,
+                })
+                .map_err(|e| to_err(e.to_string())),
+        }
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Self::Glob { matcher, single_line } => {
+                !(*single_line && value.contains(['\n', '\r'])) && matcher.is_match(value)
+            },
+            Self::Regex(re) => re.is_match(value),
+        }
+    }
+}
+
+/// A rule deciding whether a tool use may run without confirmation.
+///
+/// Rules are written as `[!]<tool>[(<argument>=<pattern>, ...)]` where:
+/// - a leading `!` makes it a deny rule, which wins over any allow rule
+/// - `<tool>` is a glob (or a regex when prefixed with `re:`) matched against the tool name. Tools
+///   of an MCP server can be addressed with `@<server>/<tool>`, e.g. `@gitlab/*`
+/// - every argument constraint must match for the rule to apply. Values that are not strings are
+///   matched against their JSON representation
+/// - in globs of allow rules `*` does not match `/`, use `**` for that. Values spanning multiple
+///   lines never match them
+///
+/// For example `execute_bash(command=cargo test*)` or `fs_write(path=src/**)`.
+#[derive(Debug, Clone)]
+pub struct TrustRule {
+    /// The rule as written by the user
+    raw: String,
+    pub effect: RuleEffect,
+    tool: Pattern,
+    args: Vec<(String, Pattern)>,
+}
+
+impl TrustRule {
+    /// Whether `value` should be parsed as a rule rather than be treated as a plain tool name
+    pub fn is_rule(value: &str) -> bool {
+        value.starts_with(DENY_PREFIX)
+            || value.starts_with('@')
+            || value.starts_with(REGEX_PREFIX)
+            || value.contains(['*', '?', '[', '{', '('])
+    }
+
+    /// Whether the rule constrains the arguments of the tool use
+    pub fn has_arg_constraints(&self) -> bool {
+        !self.args.is_empty()
+    }
+
+    /// Whether the rule constrains the given argument
+    pub fn constrains_arg(&self, name: &str) -> bool {
+        self.args.iter().any(|(arg, _)| arg == name)
+    }
+
+    /// Whether the rule applies to the tool regardless of its arguments
+    pub fn matches_tool(&self, tool_name: &str) -> bool {
+        !self.has_arg_constraints() && self.tool.is_match(tool_name)
+    }
+
+    /// Whether the rule applies to a use of `tool_name` with the given `args`. Relative paths are
+    /// resolved against `cwd`.
+    pub fn matches(&self, tool_name: &str, args: &serde_json::Value, cwd: &Path) -> bool {
+        self.tool.is_match(tool_name)
+            && self.args.iter().all(|(name, pattern)| {
+                let Some(value) = args.get(name) else {
+                    return false;
+                };
+                let value = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if PATH_ARGS.contains(&name.as_str()) {
+                    path_candidates(&value, cwd).iter().any(|v| pattern.is_match(v))
+                } else {
+                    pattern.is_match(&value)
+                }
+            })
+    }
+
+    /// Merges values that were split apart by a delimiter appearing within a rule's parentheses,
+    /// e.g. `--trust-tools=execute_bash(command=a,b)` being split at the comma.
+    pub fn merge_split_values(values: Vec<String>, delimiter: &str) -> Vec<String> {
+        let mut merged = Vec::<String>::new();
+        let mut open = false;
+        for value in values {
+            match merged.last_mut() {
+                Some(last) if open => {
+                    last.push_str(delimiter);
+                    last.push_str(&value);
+                },
+                _ => merged.push(value),
+            }
+            if let Some(last) = merged.last() {
+                open = last.matches('(').count() > last.matches(')').count();
+            }
+        }
+        merged
+    }
+}
+
+impl FromStr for TrustRule {
+    type Err = TrustRuleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = s.trim();
+        let (effect, rule) = match raw.strip_prefix(DENY_PREFIX) {
+            Some(rule) => (RuleEffect::Deny, rule.trim_start()),
+            None => (RuleEffect::Allow, raw),
+        };
+        if rule.is_empty() {
+            return Err(TrustRuleError::Empty);
+        }
+
+        // Parentheses that do not start an argument list are left alone since they may be part of a
+        // regex tool pattern.
+        let (tool, args) = match ARG_LIST_START.find(rule) {
+            Some(m) => {
+                let inner = rule[m.start() + 1..]
+                    .strip_suffix(')')
+                    .ok_or_else(|| TrustRuleError::Unclosed(raw.to_string()))?;
+                (&rule[..m.start()], parse_args(inner, effect)?)
+            },
+            None => (rule, Vec::new()),
+        };
+
+        let tool = match tool.strip_prefix('@').and_then(|t| t.split_once('/')) {
+            Some((server, tool)) => format!("{server}{NAMESPACE_DELIMITER}{tool}"),
+            None => tool.to_string(),
+        };
+
+        Ok(Self {
+            raw: raw.to_string(),
+            effect,
+            tool: Pattern::parse(tool.trim(), effect)?,
+            args,
+        })
+    }
+}
+
+impl fmt::Display for TrustRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+/// Parses `<name>=<pattern>` pairs separated by commas. A comma only separates two constraints
+/// if it is followed by `<name>=`, so patterns themselves may contain commas.
+fn parse_args(inner: &str, effect: RuleEffect) -> Result<Vec<(String, Pattern)>, TrustRuleError> {
+    let mut starts = vec![0];
+    starts.extend(ARG_SEPARATOR.find_iter(inner).map(|m| m.start() + 1));
+    starts.push(inner.len() + 1);
+
+    starts
+        .windows(2)
+        .map(|w| {
+            let constraint = &inner[w[0]..w[1] - 1];
+            let (name, pattern) = constraint
+                .split_once('=')
+                .ok_or_else(|| TrustRuleError::InvalidArgument(constraint.trim().to_string()))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(TrustRuleError::InvalidArgument(constraint.trim().to_string()));
+            }
+            let pattern = pattern.trim_start();
+            let pattern = if PATH_ARGS.contains(&name) {
+                shellexpand::tilde(pattern).to_string()
+            } else {
+                pattern.to_string()
+            };
+            Ok((name.to_string(), Pattern::parse(&pattern, effect)?))
+        })
+        .collect()
+}
+
+/// The forms a path argument is matched in: tilde expanded and lexically normalized, both as an
+/// absolute path and relative to `cwd` if it lies within it.
+fn path_candidates(value: &str, cwd: &Path) -> Vec<String> {
+    let path = PathBuf::from(shellexpand::tilde(value).as_ref());
+    let path = if path.is_absolute() { path } else { cwd.join(path) };
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {},
+            Component::ParentDir => {
+                normalized.pop();
+            },
+            c => normalized.push(c),
+        }
+    }
+
+    let mut candidates = vec![normalized.to_string_lossy().to_string()];
+    if let Ok(relative) = normalized.strip_prefix(cwd) {
+        candidates.push(relative.to_string_lossy().to_string());
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rule(s: &str) -> TrustRule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        assert_eq!(rule("fs_read").effect, RuleEffect::Allow);
+        assert_eq!(rule("!execute_bash").effect, RuleEffect::Deny);
+        assert!(!rule("@gitlab/*").has_arg_constraints());
+        assert!(rule("execute_bash(command=cargo test*)").constrains_arg("command"));
+        let multiple = rule("use_aws(service_name=s3, operation_name=list-*)");
+        assert!(multiple.constrains_arg("service_name"));
+        assert!(multiple.constrains_arg("operation_name"));
+        assert_eq!(rule(" !fs_write(path=src/**) ").to_string(), "!fs_write(path=src/**)");
+
+        assert_eq!("".parse::<TrustRule>().unwrap_err(), TrustRuleError::Empty);
+        assert_eq!("!".parse::<TrustRule>().unwrap_err(), TrustRuleError::Empty);
+        assert!(matches!(
+            "execute_bash(command=ls".parse::<TrustRule>(),
+            Err(TrustRuleError::Unclosed(_))
+        ));
+        assert!(matches!(
+            "re:fs_(read".parse::<TrustRule>(),
+            Err(TrustRuleError::InvalidPattern(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_match_tool_names() {
+        let cwd = Path::new("/");
+        let args = json!({});
+        assert!(rule("@gitlab/*").matches("gitlab___create_issue", &args, cwd));
+        assert!(!rule("@gitlab/*").matches("github___create_issue", &args, cwd));
+        assert!(rule("fs_*").matches("fs_write", &args, cwd));
+        assert!(rule("re:fs_(read|write)").matches("fs_read", &args, cwd));
+        assert!(!rule("re:fs_(read|write)").matches("fs_read_more", &args, cwd));
+        assert!(rule("fs_*").matches_tool("fs_read"));
+        assert!(!rule("fs_read(path=*)").matches_tool("fs_read"));
+    }
+
+    #[test]
+    fn test_match_args() {
+        let cwd = Path::new("/project");
+        let cargo = rule("execute_bash(command=cargo test*)");
+        assert!(cargo.matches("execute_bash", &json!({ "command": "cargo test" }), cwd));
+        assert!(cargo.matches("execute_bash", &json!({ "command": "cargo test -p foo" }), cwd));
+        assert!(!cargo.matches("execute_bash", &json!({ "command": "cargo publish" }), cwd));
+        assert!(!cargo.matches("execute_bash", &json!({}), cwd));
+        assert!(!cargo.matches("execute_bash", &json!({ "command": "cargo test\nrm -rf ~" }), cwd));
+        assert!(!cargo.matches("execute_bash", &json!({ "command": "cargo test ../../etc" }), cwd));
+
+        let regex = rule("execute_bash(command=re:git (status|diff)( .*)?)");
+        assert!(regex.matches("execute_bash", &json!({ "command": "git status" }), cwd));
+        assert!(regex.matches("execute_bash", &json!({ "command": "git diff HEAD" }), cwd));
+        assert!(!regex.matches("execute_bash", &json!({ "command": "git push" }), cwd));
+
+        let commas = rule("execute_bash(command=echo a,b)");
+        assert!(commas.matches("execute_bash", &json!({ "command": "echo a,b" }), cwd));
+
+        let src = rule("fs_write(path=src/**)");
+        assert!(src.matches("fs_write", &json!({ "path": "src/main.rs" }), cwd));
+        assert!(src.matches("fs_write", &json!({ "path": "/project/src/lib/mod.rs" }), cwd));
+        assert!(src.matches("fs_write", &json!({ "path": "./src/main.rs" }), cwd));
+        assert!(!src.matches("fs_write", &json!({ "path": "src/../Cargo.toml" }), cwd));
+        assert!(!src.matches("fs_write", &json!({ "path": "/other/src/main.rs" }), cwd));
+
+        let rm = rule("!execute_bash(command=rm *)");
+        assert!(rm.matches("execute_bash", &json!({ "command": "rm /tmp/file" }), cwd));
+        assert!(rm.matches("execute_bash", &json!({ "command": "rm a\nls" }), cwd));
+
+        let etc = rule("!fs_write(path=/etc/**)");
+        assert!(etc.matches("fs_write", &json!({ "path": "/tmp/../etc/passwd" }), cwd));
+
+        let non_string = rule("fs_read(depth=1)");
+        assert!(non_string.matches("fs_read", &json!({ "depth": 1 }), cwd));
+    }
+
+    #[test]
+    fn test_merge_split_values() {
+        let values = ["fs_read", "execute_bash(command=echo a", "b)", "@git/*"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(TrustRule::merge_split_values(values, ","), vec![
+            "fs_read",
+            "execute_bash(command=echo a,b)",
+            "@git/*"
+        ]);
+    }
+}
@@ -0,0 +1,296 @@
+use std::sync::LazyLock;
+
+use eyre::{
+    Result,
+    bail,
+    eyre,
+};
+use regex::Regex;
+
+/// Path used by unified diffs in place of a file that doesn't exist on one side of the change
+const DEV_NULL: &str = "/dev/null";
+
+static HUNK_HEADER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").expect("invalid regex"));
+
+/// The changes a unified diff makes to a single file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    /// Path of the file before the change, [None] if the patch creates the file
+    pub old_path: Option<String>,
+    /// Path of the file after the change, [None] if the patch deletes the file
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The path of the file the patch applies to
+    pub fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Applies the hunks to `content`, the current contents of the file. Each hunk is expected to
+    /// match at the line given by its header, but is allowed to have moved (e.g. due to the model
+    /// miscounting lines), in which case the closest match is used.
+    pub fn apply(&self, content: &str) -> Result<String> {
+        let lines = content.lines().collect::<Vec<_>>();
+        let mut result = Vec::<&str>::new();
+        // Index of the first line of `lines` that hasn't been copied over to `result` yet
+        let mut cursor = 0;
+        for (i, hunk) in self.hunks.iter().enumerate() {
+            let old = hunk.old_lines();
+            let expected = if old.is_empty() {
+                hunk.old_start
+            } else {
+                hunk.old_start.saturating_sub(1)
+            };
+            let Some(start) = find_lines(&lines, &old, cursor, expected) else {
+                bail!("hunk {} ({}) does not apply to {}", i + 1, hunk.header(), self.path());
+            };
+            result.extend(&lines[cursor..start]);
+            result.extend(hunk.new_lines());
+            cursor = start + old.len();
+        }
+        result.extend(&lines[cursor..]);
+
+        let mut patched = result.join("\n");
+        if !patched.is_empty() && (content.is_empty() || content.ends_with('\n')) {
+            patched.push('\n');
+        }
+        Ok(patched)
+    }
+}
+
+/// A contiguous region of changes within a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-indexed line in the original file that the hunk starts at
+    pub old_start: usize,
+    pub old_len: usize,
+    /// 1-indexed line in the updated file that the hunk starts at
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// The lines of the original file that the hunk covers
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(s) | HunkLine::Remove(s) => Some(s.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// The lines that replace [Self::old_lines]
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(s) | HunkLine::Add(s) => Some(s.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+
+    fn header(&self) -> String {
+        format!(
+            "@@ -{},{} +{},{} @@",
+            self.old_start, self.old_len, self.new_start, self.new_len
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// Parses a unified diff, as produced by `diff -u` or `git diff`, into the changes made to each
+/// file. Any lines outside of the file headers and hunks (e.g. `diff --git` or `index` lines) are
+/// ignored.
+pub fn parse(diff: &str) -> Result<Vec<FilePatch>> {
+    let mut patches = Vec::new();
+    let mut lines = diff.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some(old_path) = line.strip_prefix("--- ") else {
+            continue;
+        };
+        let Some(new_path) = lines.next().and_then(|l| l.strip_prefix("+++ ")) else {
+            bail!("expected a `+++` line to follow `{line}`");
+        };
+        let mut patch = FilePatch {
+            old_path: parse_path(old_path, "a/"),
+            new_path: parse_path(new_path, "b/"),
+            hunks: Vec::new(),
+        };
+        if patch.old_path.is_none() && patch.new_path.is_none() {
+            bail!("`{line}` must name a file on at least one side of the diff");
+        }
+
+        while let Some(header) = lines.next_if(|l| l.starts_with("@@")) {
+            let captures = HUNK_HEADER
+                .captures(header)
+                .ok_or_else(|| eyre!("invalid hunk header `{header}`"))?;
+            let number = |i: usize, default: usize| {
+                captures
+                    .get(i)
+                    .map_or(Ok(default), |m| m.as_str().parse::<usize>())
+                    .map_err(|e| eyre!("invalid hunk header `{header}`: {e}"))
+            };
+            let mut hunk = Hunk {
+                old_start: number(1, 0)?,
+                old_len: number(2, 1)?,
+                new_start: number(3, 0)?,
+                new_len: number(4, 1)?,
+                lines: Vec::new(),
+            };
+
+            let (mut old_remaining, mut new_remaining) = (hunk.old_len, hunk.new_len);
+            while old_remaining > 0 || new_remaining > 0 {
+                let Some(line) = lines.next() else {
+                    bail!("hunk `{header}` of {} ends early", patch.path());
+                };
+                let rest = line.get(1..).unwrap_or_default().to_string();
+                let hunk_line = match line.chars().next() {
+                    Some(' ') => HunkLine::Context(rest),
+                    Some('-') => HunkLine::Remove(rest),
+                    Some('+') => HunkLine::Add(rest),
+                    Some('\\') => continue,
+                    // Some tools strip the trailing whitespace of empty context lines.
+                    None => HunkLine::Context(rest),
+                    Some(_) => bail!("unexpected line `{line}` in hunk `{header}` of {}", patch.path()),
+                };
+                match &hunk_line {
+                    HunkLine::Context(_) => {
+                        old_remaining = old_remaining.saturating_sub(1);
+                        new_remaining = new_remaining.saturating_sub(1);
+                    },
+                    HunkLine::Remove(_) => old_remaining = old_remaining.saturating_sub(1),
+                    HunkLine::Add(_) => new_remaining = new_remaining.saturating_sub(1),
+                }
+                hunk.lines.push(hunk_line);
+            }
+            // Skip any "\ No newline at end of file" marker following the last line of the hunk.
+            lines.next_if(|l| l.starts_with('\\'));
+            patch.hunks.push(hunk);
+        }
+
+        if patch.hunks.is_empty() {
+            bail!("no hunks were found for {}", patch.path());
+        }
+        patches.push(patch);
+    }
+
+    if patches.is_empty() {
+        bail!("no file changes were found, expected a unified diff with `---` and `+++` file headers");
+    }
+    Ok(patches)
+}
+
+/// Parses the path of a `---` or `+++` line, removing any trailing timestamp and the `a/` or `b/`
+/// prefix used by git.
+fn parse_path(path: &str, git_prefix: &str) -> Option<String> {
+    let path = path.split('\t').next().unwrap_or_default().trim();
+    if path == DEV_NULL {
+        return None;
+    }
+    Some(path.strip_prefix(git_prefix).unwrap_or(path).to_string())
+}
+
+/// Returns the index of the occurrence of `needle` in `lines`, at or after `min`, that is closest
+/// to `expected`.
+fn find_lines(lines: &[&str], needle: &[&str], min: usize, expected: usize) -> Option<usize> {
+    let max = lines.len().checked_sub(needle.len())?;
+    if min > max {
+        return None;
+    }
+    let expected = expected.clamp(min, max);
+    let matches_at = |i: usize| lines[i..i + needle.len()] == *needle;
+    (0..=(max - min)).find_map(|distance| {
+        [expected.checked_sub(distance), expected.checked_add(distance)]
+            .into_iter()
+            .flatten()
+            .find(|i| (min..=max).contains(i) && matches_at(*i))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1234567..89abcde 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn one() {}
+-fn two() {}
++fn deux() {}
+ fn three() {}
+@@ -6,2 +6,3 @@ fn five() {}
+ fn six() {}
+ fn seven() {}
++fn eight() {}
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++hello
++world
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-goodbye
+";
+
+    #[test]
+    fn test_parse() {
+        let patches = parse(DIFF).unwrap();
+        assert_eq!(patches.len(), 3);
+        assert_eq!(patches[0].path(), "src/lib.rs");
+        assert_eq!(patches[0].hunks.len(), 2);
+        assert_eq!(patches[0].hunks[1].old_start, 6);
+        assert_eq!(patches[0].hunks[1].new_len, 3);
+        assert_eq!(patches[1].old_path, None);
+        assert_eq!(patches[1].path(), "new.txt");
+        assert_eq!(patches[2].new_path, None);
+        assert_eq!(patches[2].path(), "old.txt");
+        assert_eq!(patches[2].hunks[0].lines, vec![HunkLine::Remove("goodbye".to_string())]);
+
+        assert!(parse("not a diff").is_err());
+        assert!(parse("--- a/file\n+++ b/file\n@@ -1,2 +1,2 @@\n-one\n+two\n").is_err());
+        assert!(parse("--- a/file\n+++ b/file\n@@ bad @@\n").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let patches = parse(DIFF).unwrap();
+        let content =
+            "fn one() {}\nfn two() {}\nfn three() {}\nfn four() {}\nfn five() {}\nfn six() {}\nfn seven() {}\n";
+        assert_eq!(
+            patches[0].apply(content).unwrap(),
+            "fn one() {}\nfn deux() {}\nfn three() {}\nfn four() {}\nfn five() {}\nfn six() {}\nfn seven() {}\nfn eight() {}\n"
+        );
+        assert_eq!(patches[1].apply("").unwrap(), "hello\nworld\n");
+        assert_eq!(patches[2].apply("goodbye\n").unwrap(), "");
+
+        // Hunks that have moved are still applied.
+        let shifted = format!("// header\n\n{content}");
+        assert_eq!(
+            patches[0].apply(&shifted).unwrap(),
+            "// header\n\nfn one() {}\nfn deux() {}\nfn three() {}\nfn four() {}\nfn five() {}\nfn six() {}\nfn seven() {}\nfn eight() {}\n"
+        );
+
+        // Context that doesn't match is an error.
+        assert!(patches[0].apply(&content.replace("three", "tres")).is_err());
+        assert!(patches[2].apply("hello\n").is_err());
+    }
+}
@@ -80,8 +80,14 @@ pub struct AddArgs {
     #[arg(long)]
     pub name: String,
     /// The command used to launch the server
+    #[arg(long, required_unless_present = "url", conflicts_with = "url")]
+    pub command: Option<String>,
+    /// Url of a server reached over Streamable HTTP (or HTTP with SSE)
     #[arg(long)]
-    pub command: String,
+    pub url: Option<String>,
+    /// Headers to send along with every request made to the server url
+    #[arg(long = "header", value_parser = parse_headers, requires = "url")]
+    pub headers: Vec<HashMap<String, String>>,
     /// Arguments to pass to the command
     #[arg(long, action = ArgAction::Append, allow_hyphen_values = true, value_delimiter = ',')]
     pub args: Vec<String>,
@@ -119,13 +125,24 @@ impl AddArgs {
         }
 
         let merged_env = self.env.into_iter().flatten().collect::<HashMap<_, _>>();
-        let tool: CustomToolConfig = serde_json::from_value(serde_json::json!({
-            "command": self.command,
-            "args": self.args,
-            "env": merged_env,
-            "timeout": self.timeout.unwrap_or(default_timeout()),
-            "disabled": self.disabled,
-        }))?;
+        let tool: CustomToolConfig = match self.url {
+            Some(url) => {
+                let merged_headers = self.headers.into_iter().flatten().collect::<HashMap<_, _>>();
+                serde_json::from_value(serde_json::json!({
+                    "url": url,
+                    "headers": merged_headers,
+                    "timeout": self.timeout.unwrap_or(default_timeout()),
+                    "disabled": self.disabled,
+                }))?
+            },
+            None => serde_json::from_value(serde_json::json!({
+                "command": self.command,
+                "args": self.args,
+                "env": merged_env,
+                "timeout": self.timeout.unwrap_or(default_timeout()),
+                "disabled": self.disabled,
+            }))?,
+        };
 
         writeln!(
             output,
@@ -209,7 +226,7 @@ impl ListArgs {
                 Some(cfg) if !cfg.mcp_servers.is_empty() => {
                     for (name, tool_cfg) in &cfg.mcp_servers {
                         let status = if tool_cfg.disabled { " (disabled)" } else { "" };
-                        writeln!(output, "    • {name:<12} {}{}", tool_cfg.command, status)?;
+                        writeln!(output, "    • {name:<12} {}{}", tool_cfg.display_target(), status)?;
                     }
                 },
                 _ => {
@@ -291,7 +308,10 @@ impl StatusArgs {
                     style::Print("\n─────────────\n"),
                     style::Print(format!("Scope   : {}\n", scope_display(&sc))),
                     style::Print(format!("File    : {}\n", path.display())),
-                    style::Print(format!("Command : {}\n", cfg.command)),
+                    style::Print(match &cfg.url {
+                        Some(url) => format!("Url     : {}\n", url),
+                        None => format!("Command : {}\n", cfg.command),
+                    }),
                     style::Print(format!("Timeout : {} ms\n", cfg.timeout)),
                     style::Print(format!("Disabled: {}\n", cfg.disabled)),
                     style::Print(format!(
@@ -397,6 +417,13 @@ fn parse_env_vars(arg: &str) -> Result<HashMap<String, String>> {
     Ok(vars)
 }
 
+fn parse_headers(arg: &str) -> Result<HashMap<String, String>> {
+    match arg.split_once(':').or_else(|| arg.split_once('=')) {
+        Some((name, value)) => Ok(HashMap::from([(name.trim().to_string(), value.trim().to_string())])),
+        None => bail!("Failed to parse header '{}'. Expected 'name: value'", arg),
+    }
+}
+
 async fn load_cfg(os: &Os, p: &PathBuf) -> Result<McpServerConfig> {
     Ok(if os.fs.exists(p) {
         McpServerConfig::load_from_file(os, p).await?
@@ -452,7 +479,9 @@ mod tests {
         // 1. add
         AddArgs {
             name: "local".into(),
-            command: "echo hi".into(),
+            command: Some("echo hi".into()),
+            url: None,
+            headers: vec![],
             args: vec![
                 "awslabs.eks-mcp-server".to_string(),
                 "--allow-write".to_string(),
@@ -503,7 +532,9 @@ mod tests {
             ],
             RootSubcommand::Mcp(McpSubcommand::Add(AddArgs {
                 name: "test_server".to_string(),
-                command: "test_command".to_string(),
+                command: Some("test_command".to_string()),
+                url: None,
+                headers: vec![],
                 args: vec![
                     "awslabs.eks-mcp-server".to_string(),
                     "--allow-write".to_string(),
@@ -525,6 +556,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mcp_subcommand_add_url() {
+        assert_parse!(
+            [
+                "mcp",
+                "add",
+                "--name",
+                "gitlab",
+                "--url",
+                "https://mcp.example.com/mcp",
+                "--header",
+                "Authorization: Bearer ${GITLAB_TOKEN}"
+            ],
+            RootSubcommand::Mcp(McpSubcommand::Add(AddArgs {
+                name: "gitlab".to_string(),
+                command: None,
+                url: Some("https://mcp.example.com/mcp".to_string()),
+                headers: vec![
+                    [("Authorization".to_string(), "Bearer ${GITLAB_TOKEN}".to_string())]
+                        .into_iter()
+                        .collect()
+                ],
+                args: vec![],
+                scope: None,
+                env: vec![],
+                timeout: None,
+                disabled: false,
+                force: false,
+            }))
+        );
+    }
+
     #[test]
     fn test_mcp_subcomman_remove_workspace() {
         assert_parse!(
@@ -123,12 +123,27 @@ impl RootSubcommand {
     }
 
     pub fn requires_auth(&self) -> bool {
-        matches!(self, Self::Chat(_) | Self::Profile)
+        match self {
+            Self::Chat(args) => args.subcommand.is_none(),
+            Self::Profile => true,
+            _ => false,
+        }
     }
 
     pub async fn execute(self, os: &mut Os) -> Result<ExitCode> {
+        // Chats sent to the model backend of a profile don't use Amazon Q, so they neither need a
+        // login nor report telemetry, which allows chatting without access to AWS. Telemetry stays
+        // paused until the chat switches to a profile without a model backend.
+        let uses_model_backend = match &self {
+            Self::Chat(args) if args.subcommand.is_none() => args.model_backend(os).await.is_some(),
+            _ => false,
+        };
+        if uses_model_backend {
+            os.telemetry.set_paused(true);
+        }
+
         // Check for auth on subcommands that require it.
-        if self.requires_auth() && !crate::auth::is_logged_in(&mut os.database).await {
+        if self.requires_auth() && !uses_model_backend && !crate::auth::is_logged_in(&mut os.database).await {
             bail!(
                 "You are not logged in, please log in with {}",
                 format!("{CLI_BINARY_NAME} login").bold()
@@ -321,6 +336,12 @@ impl Cli {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::cli::chat::{
+        ChatOutputFormat,
+        ChatSubcommand,
+        ExportArgs,
+        SessionsSubcommand,
+    };
     use crate::util::CHAT_BINARY_NAME;
     use crate::util::test::assert_parse;
 
@@ -353,12 +374,15 @@ mod test {
         assert_eq!(Cli::parse_from([CHAT_BINARY_NAME, "chat", "-vv"]), Cli {
             subcommand: Some(RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: None,
                 profile: None,
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })),
             verbose: 2,
             help_all: false,
@@ -392,12 +416,15 @@ mod test {
             ["chat", "--profile", "my-profile"],
             RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: None,
                 profile: Some("my-profile".to_string()),
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
     }
@@ -408,12 +435,15 @@ mod test {
             ["chat", "--profile", "my-profile", "Hello"],
             RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: Some("Hello".to_string()),
                 profile: Some("my-profile".to_string()),
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
     }
@@ -424,12 +454,15 @@ mod test {
             ["chat", "--profile", "my-profile", "--trust-all-tools"],
             RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: None,
                 profile: Some("my-profile".to_string()),
                 model: None,
                 trust_all_tools: true,
                 trust_tools: None,
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
     }
@@ -440,24 +473,30 @@ mod test {
             ["chat", "--no-interactive", "--resume"],
             RootSubcommand::Chat(ChatArgs {
                 resume: true,
+                resume_id: None,
                 input: None,
                 profile: None,
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: true
+                no_interactive: true,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
         assert_parse!(
             ["chat", "--non-interactive", "-r"],
             RootSubcommand::Chat(ChatArgs {
                 resume: true,
+                resume_id: None,
                 input: None,
                 profile: None,
                 model: None,
                 trust_all_tools: false,
                 trust_tools: None,
-                no_interactive: true
+                no_interactive: true,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
     }
@@ -468,12 +507,15 @@ mod test {
             ["chat", "--trust-all-tools"],
             RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: None,
                 profile: None,
                 model: None,
                 trust_all_tools: true,
                 trust_tools: None,
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
     }
@@ -484,12 +526,15 @@ mod test {
             ["chat", "--trust-tools="],
             RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: None,
                 profile: None,
                 model: None,
                 trust_all_tools: false,
                 trust_tools: Some(vec!["".to_string()]),
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
             })
         );
     }
@@ -500,12 +545,95 @@ mod test {
             ["chat", "--trust-tools=fs_read,fs_write"],
             RootSubcommand::Chat(ChatArgs {
                 resume: false,
+                resume_id: None,
                 input: None,
                 profile: None,
                 model: None,
                 trust_all_tools: false,
                 trust_tools: Some(vec!["fs_read".to_string(), "fs_write".to_string()]),
-                no_interactive: false
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_with_output_format() {
+        assert_parse!(
+            ["chat", "--output-format", "stream-json", "Hello"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: false,
+                resume_id: None,
+                input: Some("Hello".to_string()),
+                profile: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                no_interactive: false,
+                output_format: ChatOutputFormat::StreamJson,
+                subcommand: None
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_with_sessions() {
+        assert_parse!(
+            ["chat", "--resume", "fix the build"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: true,
+                resume_id: None,
+                input: Some("fix the build".to_string()),
+                profile: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
+            })
+        );
+        assert_parse!(
+            ["chat", "--resume-id", "migration"],
+            RootSubcommand::Chat(ChatArgs {
+                resume: false,
+                resume_id: Some("migration".to_string()),
+                input: None,
+                profile: None,
+                model: None,
+                trust_all_tools: false,
+                trust_tools: None,
+                no_interactive: false,
+                output_format: ChatOutputFormat::Text,
+                subcommand: None
+            })
+        );
+        assert_parse!(
+            ["chat", "sessions", "fork", "migration", "--name", "bugfix"],
+            RootSubcommand::Chat(ChatArgs {
+                subcommand: Some(ChatSubcommand::Sessions(SessionsSubcommand::Fork {
+                    session: "migration".to_string(),
+                    name: Some("bugfix".to_string())
+                })),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_chat_export() {
+        assert_parse!(
+            ["chat", "export", "migration", "-o", "migration.html", "--redact"],
+            RootSubcommand::Chat(ChatArgs {
+                subcommand: Some(ChatSubcommand::Export(ExportArgs {
+                    session: Some("migration".to_string()),
+                    output: Some("migration.html".to_string()),
+                    format: None,
+                    redact: true,
+                    force: false,
+                })),
+                ..Default::default()
             })
         );
     }
@@ -13,6 +13,8 @@ use rusqlite::types::FromSql;
 use rusqlite::{
     Connection,
     Error,
+    ErrorCode,
+    Row,
     ToSql,
     params,
 };
@@ -27,6 +29,7 @@ use serde_json::{
 };
 use settings::Settings;
 use thiserror::Error;
+use time::OffsetDateTime;
 use tracing::{
     error,
     info,
@@ -69,7 +72,8 @@ const MIGRATIONS: &[Migration] = migrations![
     "004_state_table",
     "005_auth_table",
     "006_make_state_blob",
-    "007_conversations_table"
+    "007_conversations_table",
+    "008_sessions_table"
 ];
 
 #[derive(Debug, serde::Deserialize, serde::Serialize)]
@@ -143,6 +147,8 @@ pub enum DatabaseError {
     StrFromUtf8(#[from] std::str::Utf8Error),
     #[error("`{}` is not a valid setting", .0)]
     InvalidSetting(String),
+    #[error("A session named `{}` already exists in this directory", .0)]
+    SessionNameTaken(String),
 }
 
 impl<T> From<PoisonError<T>> for DatabaseError {
@@ -171,6 +177,28 @@ impl std::fmt::Display for Table {
     }
 }
 
+/// A chat conversation saved in the sessions table. Any number of sessions can be saved for a
+/// directory, each of which can be given a name that is unique within that directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Session {
+    /// The id of the conversation
+    pub id: String,
+    pub name: Option<String>,
+    /// The directory the conversation was started in
+    pub path: String,
+    /// The first prompt of the conversation
+    pub title: Option<String>,
+    /// Unix timestamp (in seconds) of when the session was created
+    pub created_at: i64,
+    /// Unix timestamp (in seconds) of when the session was last saved
+    pub updated_at: i64,
+}
+
+const SESSION_COLUMNS: &str = "id, name, path, title, created_at, updated_at";
+
+/// Maximum length in characters of [Session::title]
+const SESSION_TITLE_MAX_LEN: usize = 80;
+
 #[derive(Debug)]
 struct Migration {
     name: &'static str,
@@ -315,7 +343,8 @@ impl Database {
     //     self.delete_entry(Table::State, LAST_USED_MODEL_ID)
     // }
 
-    /// Get a chat conversation given a path to the conversation.
+    /// Get a chat conversation given a path to the conversation. Conversations are now saved as
+    /// sessions instead, see [Self::save_session].
     pub fn get_conversation_by_path(
         &mut self,
         path: impl AsRef<Path>,
@@ -329,19 +358,119 @@ impl Database {
         self.get_json_entry(Table::Conversations, path)
     }
 
-    /// Set a chat conversation given a path to the conversation.
-    pub fn set_conversation_by_path(
-        &mut self,
-        path: impl AsRef<Path>,
-        state: &ConversationState,
-    ) -> Result<usize, DatabaseError> {
+    /// Save a chat conversation as a session of the directory at `path`, creating the session if it
+    /// doesn't exist yet.
+    pub fn save_session(&self, path: impl AsRef<Path>, state: &ConversationState) -> Result<usize, DatabaseError> {
         // We would need to encode this to support non utf8 paths.
         let path = match path.as_ref().to_str() {
             Some(path) => path,
             None => return Ok(0),
         };
 
-        self.set_json_entry(Table::Conversations, path, state)
+        let title = state
+            .history()
+            .front()
+            .and_then(|(message, _)| message.prompt())
+            .map(|prompt| {
+                let prompt = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+                match prompt.char_indices().nth(SESSION_TITLE_MAX_LEN) {
+                    Some((i, _)) => format!("{}...", &prompt[..i]),
+                    None => prompt,
+                }
+            });
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        Ok(self.pool.get()?.execute(
+            "INSERT INTO sessions (id, path, title, created_at, updated_at, conversation)
+            VALUES (?1, ?2, ?3, ?4, ?4, ?5)
+            ON CONFLICT (id) DO UPDATE SET
+                title = COALESCE(title, excluded.title),
+                updated_at = excluded.updated_at,
+                conversation = excluded.conversation",
+            params![state.conversation_id(), path, title, now, serde_json::to_string(state)?],
+        )?)
+    }
+
+    /// List the saved sessions, most recently updated first. Only the sessions of the directory at
+    /// `path` are listed if it is given.
+    pub fn list_sessions(&self, path: Option<&Path>) -> Result<Vec<Session>, DatabaseError> {
+        let conn = self.pool.get()?;
+        let sessions = match path {
+            Some(path) => {
+                let Some(path) = path.to_str() else {
+                    return Ok(vec![]);
+                };
+                conn.prepare(&format!(
+                    "SELECT {SESSION_COLUMNS} FROM sessions WHERE path = ?1 ORDER BY updated_at DESC, rowid DESC"
+                ))?
+                .query_map([path], session_from_row)?
+                .collect::<Result<Vec<_>, _>>()?
+            },
+            None => conn
+                .prepare(&format!(
+                    "SELECT {SESSION_COLUMNS} FROM sessions ORDER BY updated_at DESC, rowid DESC"
+                ))?
+                .query_map([], session_from_row)?
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        Ok(sessions)
+    }
+
+    /// Find a session given its id, a prefix of its id that matches no other session, or its name
+    /// in the directory at `path`.
+    pub fn find_session(&self, path: impl AsRef<Path>, id_or_name: &str) -> Result<Option<Session>, DatabaseError> {
+        let conn = self.pool.get()?;
+        let query = |sql: &str, params: &[&dyn ToSql]| -> Result<Vec<Session>, DatabaseError> {
+            Ok(conn
+                .prepare(&format!("SELECT {SESSION_COLUMNS} FROM sessions WHERE {sql}"))?
+                .query_map(params, session_from_row)?
+                .collect::<Result<Vec<_>, _>>()?)
+        };
+
+        if let Some(session) = query("id = ?1", &[&id_or_name])?.pop() {
+            return Ok(Some(session));
+        }
+        if let Some(path) = path.as_ref().to_str() {
+            if let Some(session) = query("path = ?1 AND name = ?2", &[&path, &id_or_name])?.pop() {
+                return Ok(Some(session));
+            }
+        }
+        let mut sessions = query("substr(id, 1, length(?1)) = ?1", &[&id_or_name])?;
+        Ok(match sessions.len() {
+            1 => sessions.pop(),
+            _ => None,
+        })
+    }
+
+    /// Get the conversation of the session with the given id.
+    pub fn get_session_conversation(&self, id: &str) -> Result<Option<ConversationState>, DatabaseError> {
+        let conn = self.pool.get()?;
+        let mut stmt = conn.prepare("SELECT conversation FROM sessions WHERE id = ?1")?;
+        match stmt.query_row([id], |row| row.get::<_, String>(0)) {
+            Ok(conversation) => Ok(Some(serde_json::from_str(&conversation)?)),
+            Err(Error::QueryReturnedNoRows) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Name the session with the given id, or remove its name if `name` is [None].
+    pub fn set_session_name(&self, id: &str, name: Option<&str>) -> Result<(), DatabaseError> {
+        match self
+            .pool
+            .get()?
+            .execute("UPDATE sessions SET name = ?2 WHERE id = ?1", params![id, name])
+        {
+            Ok(_) => Ok(()),
+            Err(Error::SqliteFailure(err, _)) if err.code == ErrorCode::ConstraintViolation => {
+                Err(DatabaseError::SessionNameTaken(name.unwrap_or_default().to_string()))
+            },
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Delete the session with the given id, returning whether it existed.
+    pub fn delete_session(&self, id: &str) -> Result<bool, DatabaseError> {
+        Ok(self.pool.get()?.execute("DELETE FROM sessions WHERE id = ?1", [id])? > 0)
     }
 
     pub async fn get_secret(&self, key: &str) -> Result<Option<Secret>, DatabaseError> {
@@ -454,6 +583,17 @@ impl Database {
     }
 }
 
+fn session_from_row(row: &Row<'_>) -> rusqlite::Result<Session> {
+    Ok(Session {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        path: row.get(2)?,
+        title: row.get(3)?,
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+    })
+}
+
 fn max_migration_version<C: Deref<Target = Connection>>(conn: &C) -> Option<i64> {
     let mut stmt = conn.prepare("SELECT MAX(version) FROM migrations").ok()?;
     stmt.query_row([], |row| row.get(0)).ok()
@@ -576,6 +716,49 @@ mod tests {
         assert!(db.get_entry::<bool>(Table::State, "bool").unwrap().is_some());
     }
 
+    #[tokio::test]
+    async fn sessions_table_tests() {
+        let mut os = crate::os::Os::new().await.unwrap();
+        let conversation = ConversationState::new(
+            &mut os,
+            "0123456789",
+            std::collections::HashMap::new(),
+            None,
+            Default::default(),
+            None,
+        )
+        .await;
+        let db = &os.database;
+
+        db.save_session("/repo", &conversation).unwrap();
+        db.save_session("/repo", &conversation.fork("abcdef")).unwrap();
+        db.save_session("/other", &conversation.fork("abc123")).unwrap();
+        // Saving again updates the existing session
+        db.save_session("/other", &conversation).unwrap();
+        assert_eq!(db.list_sessions(Some(Path::new("/repo"))).unwrap().len(), 2);
+        assert_eq!(db.list_sessions(None).unwrap().len(), 3);
+
+        // Names are unique within a directory
+        db.set_session_name("0123456789", Some("migration")).unwrap();
+        assert!(matches!(
+            db.set_session_name("abcdef", Some("migration")),
+            Err(DatabaseError::SessionNameTaken(_))
+        ));
+        db.set_session_name("abc123", Some("migration")).unwrap();
+
+        assert_eq!(db.find_session("/repo", "migration").unwrap().unwrap().id, "0123456789");
+        assert_eq!(db.find_session("/other", "migration").unwrap().unwrap().id, "abc123");
+        assert_eq!(db.find_session("/repo", "abcd").unwrap().unwrap().id, "abcdef");
+        assert_eq!(db.find_session("/repo", "abc123").unwrap().unwrap().id, "abc123");
+        assert!(db.find_session("/repo", "abc").unwrap().is_none());
+
+        let forked = db.get_session_conversation("abcdef").unwrap().unwrap();
+        assert_eq!(forked.conversation_id(), "abcdef");
+        assert!(db.delete_session("abcdef").unwrap());
+        assert!(!db.delete_session("abcdef").unwrap());
+        assert!(db.get_session_conversation("abcdef").unwrap().is_none());
+    }
+
     #[tokio::test]
     #[ignore = "not on ci"]
     async fn test_set_password() {
@@ -35,6 +35,13 @@ pub enum Setting {
     ChatDefaultModel,
     ChatDisableAutoCompaction,
     ChatEnableHistoryHints,
+    ChatEnableTerminalContext,
+    ChatSandboxEnabled,
+    ChatSandboxAllowNetwork,
+    ChatSandboxWritablePaths,
+    ChatSandboxCpuTimeLimit,
+    ChatSandboxMemoryLimit,
+    ChatSandboxTimeout,
 }
 
 impl AsRef<str> for Setting {
@@ -58,6 +65,13 @@ impl AsRef<str> for Setting {
             Self::ChatDefaultModel => "chat.defaultModel",
             Self::ChatDisableAutoCompaction => "chat.disableAutoCompaction",
             Self::ChatEnableHistoryHints => "chat.enableHistoryHints",
+            Self::ChatEnableTerminalContext => "chat.enableTerminalContext",
+            Self::ChatSandboxEnabled => "chat.sandbox.enabled",
+            Self::ChatSandboxAllowNetwork => "chat.sandbox.allowNetwork",
+            Self::ChatSandboxWritablePaths => "chat.sandbox.writablePaths",
+            Self::ChatSandboxCpuTimeLimit => "chat.sandbox.cpuTimeLimit",
+            Self::ChatSandboxMemoryLimit => "chat.sandbox.memoryLimit",
+            Self::ChatSandboxTimeout => "chat.sandbox.timeout",
         }
     }
 }
@@ -91,6 +105,13 @@ impl TryFrom<&str> for Setting {
             "chat.defaultModel" => Ok(Self::ChatDefaultModel),
             "chat.disableAutoCompaction" => Ok(Self::ChatDisableAutoCompaction),
             "chat.enableHistoryHints" => Ok(Self::ChatEnableHistoryHints),
+            "chat.enableTerminalContext" => Ok(Self::ChatEnableTerminalContext),
+            "chat.sandbox.enabled" => Ok(Self::ChatSandboxEnabled),
+            "chat.sandbox.allowNetwork" => Ok(Self::ChatSandboxAllowNetwork),
+            "chat.sandbox.writablePaths" => Ok(Self::ChatSandboxWritablePaths),
+            "chat.sandbox.cpuTimeLimit" => Ok(Self::ChatSandboxCpuTimeLimit),
+            "chat.sandbox.memoryLimit" => Ok(Self::ChatSandboxMemoryLimit),
+            "chat.sandbox.timeout" => Ok(Self::ChatSandboxTimeout),
             _ => Err(DatabaseError::InvalidSetting(value.to_string())),
         }
     }
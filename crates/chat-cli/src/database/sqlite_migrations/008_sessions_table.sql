@@ -0,0 +1,12 @@
+CREATE TABLE sessions (
+    id TEXT PRIMARY KEY,
+    name TEXT,
+    path TEXT NOT NULL,
+    title TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    conversation TEXT NOT NULL
+);
+
+CREATE INDEX sessions_path_updated_at ON sessions (path, updated_at);
+CREATE UNIQUE INDEX sessions_path_name ON sessions (path, name);
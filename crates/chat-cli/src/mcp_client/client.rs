@@ -19,12 +19,15 @@ use thiserror::Error;
 use tokio::time;
 use tokio::time::error::Elapsed;
 
+use super::error::ErrorCode;
 use super::transport::base_protocol::{
+    JsonRpcError,
     JsonRpcMessage,
     JsonRpcNotification,
     JsonRpcRequest,
     JsonRpcVersion,
 };
+use super::transport::http::JsonRpcHttpTransport;
 use super::transport::stdio::JsonRpcStdioTransport;
 use super::transport::{
     self,
@@ -51,6 +54,7 @@ use crate::util::process::{
 
 pub type ClientInfo = serde_json::Value;
 pub type StdioTransport = JsonRpcStdioTransport;
+pub type HttpTransport = JsonRpcHttpTransport;
 
 /// Represents the capabilities of a client in the Model Context Protocol.
 /// This structure is sent to the server during initialization to communicate
@@ -67,8 +71,14 @@ struct ClientCapabilities {
 
 impl From<ClientInfo> for ClientCapabilities {
     fn from(client_info: ClientInfo) -> Self {
+        let mut capabilities = HashMap::new();
+        // Requests for these are delivered to the consumer of the client through
+        // [Messenger::send_server_request]
+        capabilities.insert("sampling".to_owned(), serde_json::json!({}));
+        capabilities.insert("roots".to_owned(), serde_json::json!({ "listChanged": false }));
         ClientCapabilities {
             client_info,
+            capabilities,
             ..Default::default()
         }
     }
@@ -84,6 +94,15 @@ pub struct ClientConfig {
     pub env: Option<HashMap<String, String>>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct HttpClientConfig {
+    pub server_name: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub timeout: u64,
+    pub client_info: serde_json::Value,
+}
+
 #[allow(dead_code)]
 #[derive(Debug, Error)]
 pub enum ClientError {
@@ -266,6 +285,30 @@ impl Client<StdioTransport> {
     }
 }
 
+impl Client<HttpTransport> {
+    pub fn from_config(config: HttpClientConfig) -> Result<Self, ClientError> {
+        let HttpClientConfig {
+            server_name,
+            url,
+            headers,
+            timeout,
+            client_info,
+        } = config;
+        let transport = Arc::new(transport::http::JsonRpcHttpTransport::client(&url, headers)?);
+        Ok(Self {
+            server_name,
+            transport,
+            timeout,
+            server_process_id: None,
+            client_info,
+            current_id: Arc::new(AtomicU64::new(0)),
+            messenger: None,
+            prompt_gets: Arc::new(SyncRwLock::new(HashMap::new())),
+            is_prompts_out_of_date: Arc::new(AtomicBool::new(false)),
+        })
+    }
+}
+
 impl<T> Drop for Client<T>
 where
     T: Transport,
@@ -357,6 +400,13 @@ where
                 fetch_tools_and_notify_with_messenger(&client_ref, messenger_ref.as_ref()).await;
             });
         }
+        if cap.resources.is_some() {
+            let client_ref = (*self).clone();
+            let messenger_ref = self.messenger.as_ref().map(|m| m.duplicate());
+            tokio::spawn(async move {
+                fetch_resources_and_notify_with_messenger(&client_ref, messenger_ref.as_ref()).await;
+            });
+        }
 
         let transport_ref = self.transport.clone();
         let server_name = self.server_name.clone();
@@ -365,13 +415,45 @@ where
 
         let prompts_list_changed_supported = cap.prompts.as_ref().is_some_and(|p| p.get("listChanged").is_some());
         let tools_list_changed_supported = cap.tools.as_ref().is_some_and(|t| t.get("listChanged").is_some());
+        let resources_list_changed_supported = cap.resources.as_ref().is_some_and(|r| r.get("listChanged").is_some());
         tokio::spawn(async move {
             let mut listener = transport_ref.get_listener();
             loop {
                 match listener.recv().await {
                     Ok(msg) => {
                         match msg {
-                            JsonRpcMessage::Request(_req) => {},
+                            JsonRpcMessage::Request(req) => {
+                                // Server initiated requests (e.g. sampling) can take a while to
+                                // fulfill, so they are handled off of the listening loop
+                                let transport_ref = transport_ref.clone();
+                                let messenger_ref = messenger_ref.as_ref().map(|m| m.duplicate());
+                                let server_name = server_name.clone();
+                                tokio::spawn(async move {
+                                    let JsonRpcRequest { id, method, params, .. } = req;
+                                    let result = match (method.as_str(), messenger_ref) {
+                                        ("ping", _) => Ok(serde_json::json!({})),
+                                        (_, Some(messenger)) => messenger.send_server_request(method, params).await,
+                                        (_, None) => Err(JsonRpcError {
+                                            code: ErrorCode::MethodNotFound.into(),
+                                            message: format!("Method {method} is not supported"),
+                                            data: None,
+                                        }),
+                                    };
+                                    let (result, error) = match result {
+                                        Ok(result) => (Some(result), None),
+                                        Err(error) => (None, Some(error)),
+                                    };
+                                    let resp = JsonRpcMessage::Response(JsonRpcResponse {
+                                        jsonrpc: JsonRpcVersion::default(),
+                                        id,
+                                        result,
+                                        error,
+                                    });
+                                    if let Err(e) = transport_ref.send(&resp).await {
+                                        tracing::error!("Failed to respond to request from {server_name}: {:?}", e);
+                                    }
+                                });
+                            },
                             JsonRpcMessage::Notification(notif) => {
                                 let JsonRpcNotification { method, params, .. } = notif;
                                 match method.as_str() {
@@ -421,6 +503,23 @@ where
                                         fetch_tools_and_notify_with_messenger(&client_ref, messenger_ref.as_ref())
                                             .await;
                                     },
+                                    "notifications/resources/list_changed" | "resources/list_changed"
+                                        if resources_list_changed_supported =>
+                                    {
+                                        fetch_resources_and_notify_with_messenger(&client_ref, messenger_ref.as_ref())
+                                            .await;
+                                    },
+                                    "notifications/resources/updated" | "resources/updated" => {
+                                        let uri = params.as_ref().and_then(|p| p.get("uri")).and_then(|v| v.as_str());
+                                        if let (Some(uri), Some(messenger)) = (uri, messenger_ref.as_ref()) {
+                                            if let Err(e) = messenger.send_resource_updated(uri.to_owned()).await {
+                                                tracing::error!(
+                                                    "Failed to send resource update through messenger {:?}",
+                                                    e
+                                                );
+                                            }
+                                        }
+                                    },
                                     _ => {},
                                 }
                             },
@@ -466,10 +565,12 @@ where
         };
         tracing::trace!(target: "mcp", "To {}:\n{:#?}", self.server_name, request);
         let msg = JsonRpcMessage::Request(request);
+        // The listener needs to be obtained before the request is sent, as transports such as
+        // http can deliver the response before send returns.
+        let mut listener = self.transport.get_listener();
         time::timeout(Duration::from_millis(self.timeout), self.transport.send(&msg))
             .await
             .map_err(send_map_err)??;
-        let mut listener = self.transport.get_listener();
         let mut resp = time::timeout(Duration::from_millis(self.timeout), async {
             // we want to ignore all other messages sent by the server at this point and let the
             // background loop handle them
@@ -682,6 +783,46 @@ where
     }
 }
 
+#[allow(clippy::borrowed_box)]
+async fn fetch_resources_and_notify_with_messenger<T>(client: &Client<T>, messenger: Option<&Box<dyn Messenger>>)
+where
+    T: Transport,
+{
+    let resources_list_result = 'resources_list_result: {
+        let resp = match client.request("resources/list", None).await {
+            Ok(resp) => resp,
+            Err(e) => break 'resources_list_result Err(e.into()),
+        };
+        if let Some(error) = resp.error {
+            let msg = format!(
+                "Failed to retrieve resource list for {}: {:?}",
+                client.server_name, error
+            );
+            break 'resources_list_result Err(eyre::eyre!(msg));
+        }
+        let Some(result) = resp.result else {
+            let msg = format!("Resource list response from {} is missing result", client.server_name);
+            break 'resources_list_result Err(eyre::eyre!(msg));
+        };
+        match serde_json::from_value::<ResourcesListResult>(result) {
+            Ok(result) => Ok::<ResourcesListResult, eyre::Report>(result),
+            Err(e) => {
+                let msg = format!(
+                    "Failed to deserialize resource list from {}: {:?}",
+                    client.server_name, e
+                );
+                Err(eyre::eyre!(msg))
+            },
+        }
+    };
+    if let Some(messenger) = messenger {
+        let _ = messenger
+            .send_resources_list_result(resources_list_result)
+            .await
+            .map_err(|e| tracing::error!("Failed to send resource list result through messenger {:?}", e));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -73,6 +73,54 @@ pub struct ResourcesListResult {
     pub next_cursor: Option<String>,
 }
 
+/// An entry of [ResourcesListResult::resources], describing a resource a server exposes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceInfo {
+    /// Unique identifier for the resource
+    pub uri: String,
+    /// Human-readable name
+    pub name: String,
+    /// Optional description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Optional mime type of the resource contents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// Result of reading a resource
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceReadResult {
+    /// The contents of the resource. A single read may yield more than one entry, e.g. when the
+    /// uri points to a directory.
+    pub contents: Vec<ResourceReadContents>,
+}
+
+/// Contents of a resource as returned by resources/read
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResourceReadContents {
+    /// Text contents
+    #[serde(rename_all = "camelCase")]
+    Text {
+        uri: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        text: String,
+    },
+    /// Binary contents
+    #[serde(rename_all = "camelCase")]
+    Blob {
+        uri: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mime_type: Option<String>,
+        /// base64-encoded-data
+        blob: String,
+    },
+}
+
 /// Result of listing resource templates operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -229,6 +277,70 @@ pub struct Resource {
     pub contents: ResourceContents,
 }
 
+/// A message of a sampling/createMessage request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    pub role: Role,
+    pub content: MessageContent,
+}
+
+/// A hint as to which model a server would like a sampling request to be fulfilled with
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModelHint {
+    /// A (sub)string of a model name, e.g. `sonnet`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Preferences of a server regarding model selection for a sampling request
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ModelPreferences {
+    /// Hints to use for model selection, in order of preference
+    #[serde(default)]
+    pub hints: Vec<ModelHint>,
+}
+
+/// Parameters of a sampling/createMessage request, through which a server asks the client to
+/// obtain a completion from a model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<ModelPreferences>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+}
+
+/// Result of a sampling/createMessage request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: Role,
+    pub content: MessageContent,
+    /// Name of the model that generated the message
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+/// A directory or file the client exposes to servers as a boundary for them to operate within
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Root {
+    /// Must be a `file://` uri
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Result of a roots/list request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootsListResult {
+    pub roots: Vec<Root>,
+}
+
 /// Represents the capabilities supported by a Model Context Protocol server
 /// This is the "capabilities" field in the result of a response for init
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use super::error::ErrorCode;
+use super::transport::base_protocol::JsonRpcError;
 use super::{
     PromptsListResult,
     ResourceTemplatesListResult,
@@ -34,6 +36,17 @@ pub trait Messenger: std::fmt::Debug + Send + Sync + 'static {
         result: eyre::Result<ResourceTemplatesListResult>,
     ) -> Result<(), MessengerError>;
 
+    /// Signals to the consumer that a resource it has subscribed to has been updated
+    async fn send_resource_updated(&self, uri: String) -> Result<(), MessengerError>;
+
+    /// Delivers a request initiated by the server (e.g. `sampling/createMessage` or
+    /// `roots/list`) to the consumer, and yields the result with which the server is to be answered
+    async fn send_server_request(
+        &self,
+        method: String,
+        params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, JsonRpcError>;
+
     /// Signals to the orchestrator that a server has started initializing
     async fn send_init_msg(&self) -> Result<(), MessengerError>;
 
@@ -75,6 +88,22 @@ impl Messenger for NullMessenger {
         Ok(())
     }
 
+    async fn send_resource_updated(&self, _uri: String) -> Result<(), MessengerError> {
+        Ok(())
+    }
+
+    async fn send_server_request(
+        &self,
+        method: String,
+        _params: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, JsonRpcError> {
+        Err(JsonRpcError {
+            code: ErrorCode::MethodNotFound.into(),
+            message: format!("Method {method} is not supported"),
+            data: None,
+        })
+    }
+
     async fn send_init_msg(&self) -> Result<(), MessengerError> {
         Ok(())
     }
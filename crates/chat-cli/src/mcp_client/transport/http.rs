@@ -0,0 +1,698 @@
+//! Client side of the Streamable HTTP transport, with a fallback to the deprecated HTTP+SSE
+//! transport for servers that have yet to migrate.
+//!
+//! Referencing https://modelcontextprotocol.io/specification/2025-03-26/basic/transports#streamable-http
+//! and https://modelcontextprotocol.io/specification/2024-11-05/basic/transports#http-with-sse
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use reqwest::header::{
+    ACCEPT,
+    CONTENT_TYPE,
+    HeaderMap,
+    HeaderName,
+    HeaderValue,
+};
+use reqwest::{
+    Response,
+    StatusCode,
+};
+use tokio::sync::{
+    Mutex,
+    broadcast,
+    oneshot,
+};
+use tokio::task::JoinHandle;
+use url::Url;
+
+use super::base_protocol::JsonRpcMessage;
+use super::{
+    Listener,
+    LogListener,
+    Transport,
+    TransportError,
+};
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+const EVENT_STREAM_MIME: &str = "text/event-stream";
+const JSON_MIME: &str = "application/json";
+
+/// The flavour of HTTP transport the server on the other end speaks. This is only known after
+/// the first message (which is always `initialize`) has been exchanged.
+#[derive(Debug, Clone)]
+enum HttpMode {
+    /// No message has been sent yet.
+    Undetermined,
+    /// Every message is POSTed to the configured url. Responses come back either as the body of
+    /// the POST or as an SSE stream opened by the POST.
+    Streamable,
+    /// Deprecated HTTP+SSE transport. A long-lived GET stream carries every server message and
+    /// client messages are POSTed to the endpoint advertised on that stream.
+    Sse { endpoint: Url },
+}
+
+#[derive(Debug)]
+pub struct JsonRpcHttpTransport {
+    client: reqwest::Client,
+    url: Url,
+    headers: HeaderMap,
+    mode: Mutex<HttpMode>,
+    session_id: Arc<std::sync::RwLock<Option<String>>>,
+    tx: broadcast::Sender<Result<JsonRpcMessage, TransportError>>,
+    receiver: broadcast::Receiver<Result<JsonRpcMessage, TransportError>>,
+    log_tx: broadcast::Sender<String>,
+    log_receiver: broadcast::Receiver<String>,
+    /// Handles of the tasks reading from SSE streams, aborted on shutdown.
+    stream_tasks: std::sync::Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl JsonRpcHttpTransport {
+    pub fn client(url: &str, headers: HashMap<String, String>) -> Result<Self, TransportError> {
+        let url = Url::parse(url).map_err(|e| TransportError::Custom(format!("Invalid server url {url}: {e}")))?;
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            let header_name = HeaderName::try_from(name.as_str())
+                .map_err(|e| TransportError::Custom(format!("Invalid header name {name}: {e}")))?;
+            let header_value = HeaderValue::try_from(value.as_str())
+                .map_err(|e| TransportError::Custom(format!("Invalid value for header {name}: {e}")))?;
+            header_map.insert(header_name, header_value);
+        }
+        let client = crate::request::new_client().map_err(|e| TransportError::Http(e.to_string()))?;
+        let (tx, receiver) = broadcast::channel::<Result<JsonRpcMessage, TransportError>>(100);
+        let (log_tx, log_receiver) = broadcast::channel::<String>(100);
+        Ok(Self {
+            client,
+            url,
+            headers: header_map,
+            mode: Mutex::new(HttpMode::Undetermined),
+            session_id: Arc::new(std::sync::RwLock::new(None)),
+            tx,
+            receiver,
+            log_tx,
+            log_receiver,
+            stream_tasks: std::sync::Mutex::new(Vec::new()),
+        })
+    }
+
+    fn session_id(&self) -> Option<String> {
+        self.session_id.read().ok().and_then(|id| id.clone())
+    }
+
+    fn base_request(&self, method: reqwest::Method, url: Url) -> reqwest::RequestBuilder {
+        let mut req = self.client.request(method, url).headers(self.headers.clone());
+        if let Some(session_id) = self.session_id() {
+            req = req.header(SESSION_ID_HEADER, session_id);
+        }
+        req
+    }
+
+    async fn post(&self, url: Url, msg: &JsonRpcMessage) -> Result<Response, TransportError> {
+        Ok(self
+            .base_request(reqwest::Method::POST, url)
+            .header(ACCEPT, format!("{JSON_MIME}, {EVENT_STREAM_MIME}"))
+            .header(CONTENT_TYPE, JSON_MIME)
+            .body(serde_json::to_vec(msg)?)
+            .send()
+            .await?)
+    }
+
+    /// Sends a message using the Streamable HTTP transport and dispatches whatever comes back.
+    async fn send_streamable(&self, msg: &JsonRpcMessage) -> Result<(), TransportError> {
+        let resp = self.post(self.url.clone(), msg).await?;
+        self.handle_streamable_response(msg, resp).await
+    }
+
+    async fn handle_streamable_response(&self, msg: &JsonRpcMessage, resp: Response) -> Result<(), TransportError> {
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(TransportError::Http(format!("Server responded with {status}: {body}")));
+        }
+        if let Some(session_id) = resp.headers().get(SESSION_ID_HEADER).and_then(|v| v.to_str().ok()) {
+            if let Ok(mut id) = self.session_id.write() {
+                id.replace(session_id.to_owned());
+            }
+        }
+        if status == StatusCode::ACCEPTED {
+            return Ok(());
+        }
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+        if content_type.starts_with(EVENT_STREAM_MIME) {
+            self.spawn_sse_reader(resp, None);
+        } else if content_type.starts_with(JSON_MIME) {
+            let body = resp.bytes().await?;
+            dispatch_body(&self.tx, &body);
+        } else if !matches!(msg, JsonRpcMessage::Request(_)) {
+            // Responses to notifications and responses carry no body of interest.
+        } else {
+            return Err(TransportError::Http(format!(
+                "Unexpected content type in response: {content_type}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Opens the optional GET stream through which a Streamable HTTP server can send requests and
+    /// notifications unprompted. Servers that do not offer one answer with 405, which is fine.
+    async fn open_server_stream(&self) {
+        let resp = self
+            .base_request(reqwest::Method::GET, self.url.clone())
+            .header(ACCEPT, EVENT_STREAM_MIME)
+            .send()
+            .await;
+        match resp {
+            Ok(resp) if resp.status().is_success() => self.spawn_sse_reader(resp, None),
+            Ok(resp) => {
+                let _ = self.log_tx.send(format!(
+                    "Server did not open a stream for unprompted messages: {}",
+                    resp.status()
+                ));
+            },
+            Err(e) => {
+                let _ = self.log_tx.send(format!("Failed to open server stream: {e}"));
+            },
+        }
+    }
+
+    /// Connects to a server speaking the deprecated HTTP+SSE transport and returns the endpoint
+    /// to which messages are to be posted.
+    async fn connect_sse(&self) -> Result<Url, TransportError> {
+        let resp = self
+            .base_request(reqwest::Method::GET, self.url.clone())
+            .header(ACCEPT, EVENT_STREAM_MIME)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            return Err(TransportError::Http(format!(
+                "Server responded with {} while opening SSE stream",
+                resp.status()
+            )));
+        }
+        let (endpoint_tx, endpoint_rx) = oneshot::channel::<String>();
+        self.spawn_sse_reader(resp, Some(endpoint_tx));
+        let endpoint = endpoint_rx
+            .await
+            .map_err(|_e| TransportError::Http("SSE stream closed before an endpoint was sent".to_owned()))?;
+        self.url
+            .join(endpoint.trim())
+            .map_err(|e| TransportError::Custom(format!("Invalid endpoint {endpoint}: {e}")))
+    }
+
+    fn spawn_sse_reader(&self, mut resp: Response, endpoint_tx: Option<oneshot::Sender<String>>) {
+        let tx = self.tx.clone();
+        let log_tx = self.log_tx.clone();
+        let handle = tokio::spawn(async move {
+            let mut endpoint_tx = endpoint_tx;
+            let mut parser = SseParser::default();
+            loop {
+                match resp.chunk().await {
+                    Ok(Some(chunk)) => {
+                        for event in parser.feed(&chunk) {
+                            match event.event.as_deref() {
+                                Some("endpoint") => {
+                                    if let Some(endpoint_tx) = endpoint_tx.take() {
+                                        let _ = endpoint_tx.send(event.data);
+                                    }
+                                },
+                                None | Some("message") => dispatch_body(&tx, event.data.as_bytes()),
+                                Some(other) => {
+                                    let _ = log_tx.send(format!("Ignoring SSE event of type {other}"));
+                                },
+                            }
+                        }
+                    },
+                    Ok(None) => break,
+                    Err(e) => {
+                        let _ = tx.send(Err(e.into()));
+                        break;
+                    },
+                }
+            }
+        });
+        if let Ok(mut tasks) = self.stream_tasks.lock() {
+            tasks.retain(|task| !task.is_finished());
+            tasks.push(handle);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for JsonRpcHttpTransport {
+    async fn send(&self, msg: &JsonRpcMessage) -> Result<(), TransportError> {
+        let mut mode = self.mode.lock().await;
+        match mode.clone() {
+            HttpMode::Streamable => {
+                drop(mode);
+                let is_initialized = matches!(
+                    msg,
+                    JsonRpcMessage::Notification(notif) if notif.method == "notifications/initialized"
+                );
+                self.send_streamable(msg).await?;
+                if is_initialized {
+                    self.open_server_stream().await;
+                }
+                Ok(())
+            },
+            HttpMode::Sse { endpoint } => {
+                drop(mode);
+                let resp = self.post(endpoint, msg).await?;
+                if !resp.status().is_success() {
+                    return Err(TransportError::Http(format!("Server responded with {}", resp.status())));
+                }
+                Ok(())
+            },
+            // The lock is held for the first exchange so that no other message is sent before
+            // the flavour of the transport is settled.
+            HttpMode::Undetermined => {
+                let resp = self.post(self.url.clone(), msg).await?;
+                // Servers that only speak the deprecated transport reject POSTs to the SSE url.
+                // See https://modelcontextprotocol.io/specification/2025-03-26/basic/transports#backwards-compatibility
+                if msg.is_initialize()
+                    && matches!(
+                        resp.status(),
+                        StatusCode::BAD_REQUEST | StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED
+                    )
+                {
+                    let _ = self
+                        .log_tx
+                        .send(format!("POST rejected with {}, falling back to SSE", resp.status()));
+                    let endpoint = self.connect_sse().await?;
+                    let resp = self.post(endpoint.clone(), msg).await?;
+                    if !resp.status().is_success() {
+                        return Err(TransportError::Http(format!("Server responded with {}", resp.status())));
+                    }
+                    *mode = HttpMode::Sse { endpoint };
+                    return Ok(());
+                }
+                self.handle_streamable_response(msg, resp).await?;
+                *mode = HttpMode::Streamable;
+                Ok(())
+            },
+        }
+    }
+
+    fn get_listener(&self) -> impl Listener {
+        HttpListener {
+            receiver: self.receiver.resubscribe(),
+        }
+    }
+
+    async fn shutdown(&self) -> Result<(), TransportError> {
+        if let Ok(mut tasks) = self.stream_tasks.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+        let is_streamable = matches!(*self.mode.lock().await, HttpMode::Streamable);
+        if is_streamable && self.session_id().is_some() {
+            // Servers are allowed to refuse session termination, so the status is not checked.
+            self.base_request(reqwest::Method::DELETE, self.url.clone())
+                .send()
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn get_log_listener(&self) -> impl LogListener {
+        HttpLogListener {
+            receiver: self.log_receiver.resubscribe(),
+        }
+    }
+}
+
+impl Drop for JsonRpcHttpTransport {
+    fn drop(&mut self) {
+        if let Ok(mut tasks) = self.stream_tasks.lock() {
+            for task in tasks.drain(..) {
+                task.abort();
+            }
+        }
+    }
+}
+
+/// Body of a response may either contain a single message or a batch of them.
+fn dispatch_body(tx: &broadcast::Sender<Result<JsonRpcMessage, TransportError>>, body: &[u8]) {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return;
+    }
+    match serde_json::from_slice::<Vec<JsonRpcMessage>>(body) {
+        Ok(batch) => {
+            for msg in batch {
+                let _ = tx.send(Ok(msg));
+            }
+        },
+        Err(_) => match serde_json::from_slice::<JsonRpcMessage>(body) {
+            Ok(msg) => {
+                let _ = tx.send(Ok(msg));
+            },
+            Err(e) => {
+                let _ = tx.send(Err(e.into()));
+            },
+        },
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    pub(crate) event: Option<String>,
+    pub(crate) data: String,
+}
+
+/// Incremental parser for `text/event-stream` bodies.
+/// See https://html.spec.whatwg.org/multipage/server-sent-events.html#event-stream-interpretation
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line = self.buffer.drain(..=pos).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        event: self.event.take(),
+                        data: self.data.join("\n"),
+                    });
+                }
+                self.event = None;
+                self.data.clear();
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_owned()),
+                "data" => self.data.push(value.to_owned()),
+                _ => {},
+            }
+        }
+        events
+    }
+}
+
+pub struct HttpListener {
+    pub receiver: broadcast::Receiver<Result<JsonRpcMessage, TransportError>>,
+}
+
+#[async_trait::async_trait]
+impl Listener for HttpListener {
+    async fn recv(&mut self) -> Result<JsonRpcMessage, TransportError> {
+        self.receiver.recv().await?
+    }
+}
+
+pub struct HttpLogListener {
+    pub receiver: broadcast::Receiver<String>,
+}
+
+#[async_trait::async_trait]
+impl LogListener for HttpLogListener {
+    async fn recv(&mut self) -> Result<String, TransportError> {
+        Ok(self.receiver.recv().await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+    use http_body_util::combinators::BoxBody;
+    use http_body_util::{
+        BodyExt,
+        Full,
+        StreamBody,
+    };
+    use hyper::body::{
+        Frame,
+        Incoming,
+    };
+    use hyper::server::conn::http1;
+    use hyper::service::service_fn;
+    use hyper::{
+        Method,
+        Request,
+    };
+    use hyper_util::rt::TokioIo;
+    use serde_json::json;
+    use tokio::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::mcp_client::{
+        Client,
+        HttpClientConfig,
+        HttpTransport,
+        JsonRpcNotification,
+        JsonRpcRequest,
+        JsonRpcResponse,
+        JsonRpcVersion,
+    };
+
+    type Body = BoxBody<Bytes, Infallible>;
+
+    #[derive(Clone, Copy)]
+    enum ServerKind {
+        Json,
+        EventStream,
+        LegacySse,
+    }
+
+    /// State shared by the connections of the stand-in server.
+    #[derive(Clone)]
+    struct StandIn {
+        kind: ServerKind,
+        /// Sender for the GET stream of the legacy transport.
+        sse_tx: Arc<Mutex<Option<mpsc::UnboundedSender<String>>>>,
+        received_headers: Arc<Mutex<Vec<hyper::HeaderMap>>>,
+    }
+
+    fn full(body: impl Into<Bytes>) -> Body {
+        Full::new(body.into()).boxed()
+    }
+
+    fn respond_to(msg: &JsonRpcMessage) -> Option<JsonRpcMessage> {
+        let JsonRpcMessage::Request(req) = msg else {
+            return None;
+        };
+        let result = match req.method.as_str() {
+            "initialize" => json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "serverInfo": { "name": "stand-in", "version": "1.0.0" }
+            }),
+            method => json!({ "echo": method, "params": req.params }),
+        };
+        Some(JsonRpcMessage::Response(JsonRpcResponse {
+            jsonrpc: JsonRpcVersion::default(),
+            id: req.id,
+            result: Some(result),
+            error: None,
+        }))
+    }
+
+    async fn handle(state: StandIn, req: Request<Incoming>) -> Result<hyper::Response<Body>, Infallible> {
+        state.received_headers.lock().await.push(req.headers().clone());
+        let builder = hyper::Response::builder();
+        let resp = match (state.kind, req.method().clone(), req.uri().path()) {
+            (ServerKind::LegacySse, Method::GET, "/mcp") => {
+                let (tx, rx) = mpsc::unbounded_channel::<String>();
+                tx.send("event: endpoint\ndata: /messages?session=1\n\n".to_owned())
+                    .unwrap();
+                state.sse_tx.lock().await.replace(tx);
+                let stream = futures::stream::unfold(rx, |mut rx| async move {
+                    rx.recv()
+                        .await
+                        .map(|s| (Ok::<_, Infallible>(Frame::data(Bytes::from(s))), rx))
+                });
+                builder
+                    .header(CONTENT_TYPE, EVENT_STREAM_MIME)
+                    .body(StreamBody::new(stream).boxed())
+                    .unwrap()
+            },
+            (ServerKind::LegacySse, Method::POST, "/messages") => {
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                let msg = serde_json::from_slice::<JsonRpcMessage>(&body).unwrap();
+                if let Some(resp) = respond_to(&msg) {
+                    let data = serde_json::to_string(&resp).unwrap();
+                    if let Some(tx) = state.sse_tx.lock().await.as_ref() {
+                        tx.send(format!("event: message\ndata: {data}\n\n")).unwrap();
+                    }
+                }
+                builder.status(202).body(full("")).unwrap()
+            },
+            (ServerKind::LegacySse, _, _) => builder.status(405).body(full("")).unwrap(),
+            (_, Method::POST, "/mcp") => {
+                let body = req.into_body().collect().await.unwrap().to_bytes();
+                let msg = serde_json::from_slice::<JsonRpcMessage>(&body).unwrap();
+                let builder = builder.header(SESSION_ID_HEADER, "session-1");
+                match (respond_to(&msg), state.kind) {
+                    (None, _) => builder.status(202).body(full("")).unwrap(),
+                    (Some(resp), ServerKind::EventStream) => {
+                        let data = serde_json::to_string(&resp).unwrap();
+                        builder
+                            .header(CONTENT_TYPE, EVENT_STREAM_MIME)
+                            .body(full(format!(": keep-alive\r\nevent: message\r\ndata: {data}\r\n\r\n")))
+                            .unwrap()
+                    },
+                    (Some(resp), _) => builder
+                        .header(CONTENT_TYPE, JSON_MIME)
+                        .body(full(serde_json::to_vec(&resp).unwrap()))
+                        .unwrap(),
+                }
+            },
+            _ => builder.status(405).body(full("")).unwrap(),
+        };
+        Ok(resp)
+    }
+
+    async fn spawn_stand_in(kind: ServerKind) -> (SocketAddr, StandIn) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = StandIn {
+            kind,
+            sse_tx: Arc::new(Mutex::new(None)),
+            received_headers: Arc::new(Mutex::new(Vec::new())),
+        };
+        let state_clone = state.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    break;
+                };
+                let state = state_clone.clone();
+                tokio::spawn(async move {
+                    let _ = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service_fn(move |req| handle(state.clone(), req)))
+                        .await;
+                });
+            }
+        });
+        (addr, state)
+    }
+
+    fn request(id: u64, method: &str) -> JsonRpcMessage {
+        JsonRpcMessage::Request(JsonRpcRequest {
+            jsonrpc: JsonRpcVersion::default(),
+            id,
+            method: method.to_owned(),
+            params: Some(json!({ "key": "value" })),
+        })
+    }
+
+    async fn send_and_recv(transport: &JsonRpcHttpTransport, msg: JsonRpcMessage) -> JsonRpcMessage {
+        let mut listener = transport.get_listener();
+        transport.send(&msg).await.expect("failed to send message");
+        tokio::time::timeout(Duration::from_secs(5), listener.recv())
+            .await
+            .expect("timed out waiting for response")
+            .expect("failed to receive response")
+    }
+
+    #[test]
+    fn test_sse_parser() {
+        let mut parser = SseParser::default();
+        assert!(parser.feed(b"event: endpoint\nda").is_empty());
+        let events = parser.feed(b"ta: /messages\n\n: comment\ndata: one\ndata:two\r\n\r\n");
+        assert_eq!(events, vec![
+            SseEvent {
+                event: Some("endpoint".to_owned()),
+                data: "/messages".to_owned(),
+            },
+            SseEvent {
+                event: None,
+                data: "one\ntwo".to_owned(),
+            },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_streamable_json_response() {
+        let (addr, state) = spawn_stand_in(ServerKind::Json).await;
+        let headers = HashMap::from([("Authorization".to_owned(), "Bearer token".to_owned())]);
+        let transport = JsonRpcHttpTransport::client(&format!("http://{addr}/mcp"), headers).unwrap();
+
+        let resp = send_and_recv(&transport, request(0, "initialize")).await;
+        assert_eq!(resp.id(), Some(0));
+        let resp = send_and_recv(&transport, request(1, "tools/list")).await;
+        let JsonRpcMessage::Response(resp) = resp else {
+            panic!("expected a response");
+        };
+        assert_eq!(resp.result.unwrap()["echo"], "tools/list");
+
+        let received_headers = state.received_headers.lock().await;
+        assert!(received_headers.iter().all(|h| h["authorization"] == "Bearer token"));
+        // The session id is only known after the initialize response
+        assert!(received_headers[0].get(SESSION_ID_HEADER).is_none());
+        assert_eq!(received_headers[1][SESSION_ID_HEADER], "session-1");
+    }
+
+    #[tokio::test]
+    async fn test_streamable_event_stream_response() {
+        let (addr, _) = spawn_stand_in(ServerKind::EventStream).await;
+        let transport = JsonRpcHttpTransport::client(&format!("http://{addr}/mcp"), HashMap::new()).unwrap();
+
+        let _ = send_and_recv(&transport, request(0, "initialize")).await;
+        let notification = JsonRpcMessage::Notification(JsonRpcNotification {
+            jsonrpc: JsonRpcVersion::default(),
+            method: "notifications/initialized".to_owned(),
+            params: None,
+        });
+        transport.send(&notification).await.unwrap();
+        let resp = send_and_recv(&transport, request(1, "prompts/list")).await;
+        assert_eq!(resp.id(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_sse_fallback() {
+        let (addr, _) = spawn_stand_in(ServerKind::LegacySse).await;
+        let transport = JsonRpcHttpTransport::client(&format!("http://{addr}/mcp"), HashMap::new()).unwrap();
+
+        let resp = send_and_recv(&transport, request(0, "initialize")).await;
+        assert_eq!(resp.id(), Some(0));
+        assert!(matches!(&*transport.mode.lock().await, HttpMode::Sse { endpoint } if endpoint.path() == "/messages"));
+        let resp = send_and_recv(&transport, request(1, "tools/list")).await;
+        assert_eq!(resp.id(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_client_over_http() {
+        for kind in [ServerKind::Json, ServerKind::EventStream, ServerKind::LegacySse] {
+            let (addr, _) = spawn_stand_in(kind).await;
+            let client = Client::<HttpTransport>::from_config(HttpClientConfig {
+                server_name: "stand_in".to_owned(),
+                url: format!("http://{addr}/mcp"),
+                headers: HashMap::new(),
+                timeout: 5000,
+                client_info: json!({ "name": "TestClient", "version": "1.0.0" }),
+            })
+            .expect("failed to create client");
+            client.init().await.expect("failed to initialize client");
+            let resp = client
+                .request("resources/read", Some(json!({ "uri": "file:///a" })))
+                .await
+                .expect("request failed");
+            assert_eq!(resp.result.unwrap()["echo"], "resources/read");
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod base_protocol;
+pub mod http;
 pub mod stdio;
 
 use std::fmt::Debug;
@@ -13,6 +14,8 @@ pub enum TransportError {
     Serialization(String),
     #[error("IO error: {0}")]
     Stdio(String),
+    #[error("HTTP error: {0}")]
+    Http(String),
     #[error("{0}")]
     Custom(String),
     #[error(transparent)]
@@ -25,6 +28,12 @@ impl From<serde_json::Error> for TransportError {
     }
 }
 
+impl From<reqwest::Error> for TransportError {
+    fn from(err: reqwest::Error) -> Self {
+        TransportError::Http(err.to_string())
+    }
+}
+
 impl From<std::io::Error> for TransportError {
     fn from(err: std::io::Error) -> Self {
         TransportError::Stdio(err.to_string())
@@ -6,6 +6,11 @@ mod install_method;
 
 use core::ToolUseEventBuilder;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
 
 use amzn_codewhisperer_client::types::{
     ChatAddMessageEvent,
@@ -176,6 +181,7 @@ impl Clone for TelemetrySender {
 pub struct TelemetryThread {
     handle: Option<JoinHandle<()>>,
     tx: TelemetrySender,
+    paused: Arc<AtomicBool>,
 }
 
 impl Clone for TelemetryThread {
@@ -183,6 +189,7 @@ impl Clone for TelemetryThread {
         Self {
             handle: None,
             tx: self.tx.clone(),
+            paused: Arc::clone(&self.paused),
         }
     }
 }
@@ -202,6 +209,7 @@ impl TelemetryThread {
         Ok(Self {
             handle: Some(handle),
             tx,
+            paused: Arc::new(AtomicBool::new(false)),
         })
     }
 
@@ -223,8 +231,23 @@ impl TelemetryThread {
         Ok(())
     }
 
+    /// While paused, events are dropped instead of being sent. This applies to every clone of the
+    /// thread.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    fn send(&self, event: Event) -> Result<(), TelemetryError> {
+        if self.paused.load(Ordering::Relaxed) {
+            trace!("TelemetryThread is paused, dropping telemetry event: {:?}", event);
+            return Ok(());
+        }
+
+        Ok(self.tx.send(event)?)
+    }
+
     pub fn send_user_logged_in(&self) -> Result<(), TelemetryError> {
-        Ok(self.tx.send(Event::new(EventType::UserLoggedIn {}))?)
+        self.send(Event::new(EventType::UserLoggedIn {}))
     }
 
     pub async fn send_cli_subcommand_executed(
@@ -237,7 +260,7 @@ impl TelemetryThread {
         });
         set_event_metadata(database, &mut telemetry_event).await;
 
-        Ok(self.tx.send(telemetry_event)?)
+        self.send(telemetry_event)
     }
 
     #[allow(clippy::too_many_arguments)] // TODO: Should make a parameters struct.
@@ -267,7 +290,7 @@ impl TelemetryThread {
         });
         set_event_metadata(database, &mut telemetry_event).await;
 
-        Ok(self.tx.send(telemetry_event)?)
+        self.send(telemetry_event)
     }
 
     pub async fn send_tool_use_suggested(
@@ -294,7 +317,7 @@ impl TelemetryThread {
         });
         set_event_metadata(database, &mut telemetry_event).await;
 
-        Ok(self.tx.send(telemetry_event)?)
+        self.send(telemetry_event)
     }
 
     pub async fn send_mcp_server_init(
@@ -311,7 +334,7 @@ impl TelemetryThread {
         });
         set_event_metadata(database, &mut telemetry_event).await;
 
-        Ok(self.tx.send(telemetry_event)?)
+        self.send(telemetry_event)
     }
 
     pub fn send_did_select_profile(
@@ -322,13 +345,13 @@ impl TelemetryThread {
         sso_region: Option<String>,
         profile_count: Option<i64>,
     ) -> Result<(), TelemetryError> {
-        Ok(self.tx.send(Event::new(EventType::DidSelectProfile {
+        self.send(Event::new(EventType::DidSelectProfile {
             source,
             amazonq_profile_region,
             result,
             sso_region,
             profile_count,
-        }))?)
+        }))
     }
 
     pub fn send_profile_state(
@@ -338,12 +361,12 @@ impl TelemetryThread {
         result: TelemetryResult,
         sso_region: Option<String>,
     ) -> Result<(), TelemetryError> {
-        Ok(self.tx.send(Event::new(EventType::ProfileState {
+        self.send(Event::new(EventType::ProfileState {
             source,
             amazonq_profile_region,
             result,
             sso_region,
-        }))?)
+        }))
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -367,7 +390,7 @@ impl TelemetryThread {
         });
         set_event_metadata(database, &mut telemetry_event).await;
 
-        Ok(self.tx.send(telemetry_event)?)
+        self.send(telemetry_event)
     }
 }
 
@@ -115,6 +115,18 @@ pub fn runtime_dir() -> Result<PathBuf> {
     dir.ok_or(DirectoryError::NoRuntimeDirectory)
 }
 
+/// Get path to a qterm socket
+///
+/// - MacOS: `$TMPDIR/cwrun/t/$SESSION_ID.sock`
+/// - Linux: `$XDG_RUNTIME_DIR/cwrun/t/$SESSION_ID.sock`
+#[cfg(unix)]
+pub fn figterm_socket_path(session_id: impl std::fmt::Display) -> Result<PathBuf> {
+    Ok(runtime_dir()?
+        .join("cwrun")
+        .join("t")
+        .join(format!("{session_id}.sock")))
+}
+
 /// The directory to all the fig logs
 /// - Linux: `/tmp/fig/$USER/logs`
 /// - MacOS: `$TMPDIR/logs`
@@ -4,9 +4,13 @@ use std::sync::{
 };
 
 use eyre::Result;
-use semantic_search_client::KnowledgeContext;
 use semantic_search_client::client::AsyncSemanticSearchClient;
 use semantic_search_client::types::SearchResult;
+use semantic_search_client::{
+    IndexingOptions,
+    KnowledgeContext,
+    SearchFilter,
+};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
@@ -66,6 +70,16 @@ impl KnowledgeStore {
 
     /// Add context - delegates to async client
     pub async fn add(&mut self, name: &str, path_str: &str) -> Result<String, String> {
+        self.add_with_options(name, path_str, IndexingOptions::default()).await
+    }
+
+    /// Add context indexing the files selected by `options` - delegates to async client
+    pub async fn add_with_options(
+        &mut self,
+        name: &str,
+        path_str: &str,
+        options: IndexingOptions,
+    ) -> Result<String, String> {
         let path_buf = std::path::PathBuf::from(path_str);
         let canonical_path = path_buf
             .canonicalize()
@@ -73,7 +87,13 @@ impl KnowledgeStore {
 
         match self
             .client
-            .add_context_from_path(&canonical_path, name, &format!("Knowledge context for {}", name), true)
+            .add_context_from_path_with_options(
+                &canonical_path,
+                name,
+                &format!("Knowledge context for {}", name),
+                true,
+                options,
+            )
             .await
         {
             Ok((operation_id, _)) => Ok(format!(
@@ -91,11 +111,11 @@ impl KnowledgeStore {
         Ok(self.client.get_contexts().await)
     }
 
-    /// Search - delegates to async client
-    pub async fn search(&self, query: &str, _context_id: Option<&str>) -> Result<Vec<SearchResult>, KnowledgeError> {
+    /// Search the contexts and files matching a filter - delegates to async client
+    pub async fn search(&self, query: &str, filter: &SearchFilter) -> Result<Vec<SearchResult>, KnowledgeError> {
         let results = self
             .client
-            .search_all(query, None)
+            .search_filtered(query, None, filter)
             .await
             .map_err(|e| KnowledgeError::ClientError(e.to_string()))?;
 
@@ -104,7 +124,7 @@ impl KnowledgeStore {
             flattened.extend(context_results);
         }
 
-        flattened.sort_by(|a, b| a.distance.partial_cmp(&b.distance).unwrap_or(std::cmp::Ordering::Equal));
+        flattened.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
 
         Ok(flattened)
     }
@@ -198,17 +218,10 @@ impl KnowledgeStore {
             .map_err(|e| e.to_string())
     }
 
-    /// Update context by path
+    /// Update context by path, re-indexing only the files that changed
     pub async fn update_by_path(&mut self, path_str: &str) -> Result<String, String> {
         if let Some(context) = self.client.get_context_by_path(path_str).await {
-            // Remove the existing context first
-            self.client
-                .remove_context_by_id(&context.id)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Then add it back with the same name
-            self.add(&context.name, path_str).await
+            self.update(&context).await
         } else {
             // Debug: List all available contexts
             let available_paths = self.client.list_context_paths().await;
@@ -228,35 +241,77 @@ impl KnowledgeStore {
     pub async fn update_context_by_id(&mut self, context_id: &str, path_str: &str) -> Result<String, String> {
         let contexts = self.get_all().await.map_err(|e| e.to_string())?;
         let context = contexts
-            .iter()
+            .into_iter()
             .find(|c| c.id == context_id)
             .ok_or_else(|| format!("Context '{}' not found", context_id))?;
 
-        let context_name = context.name.clone();
+        self.update_from_path(&context, path_str).await
+    }
+
+    /// Update context by name
+    pub async fn update_context_by_name(&mut self, name: &str, path_str: &str) -> Result<String, String> {
+        if let Some(context) = self.client.get_context_by_name(name).await {
+            self.update_from_path(&context, path_str).await
+        } else {
+            Err(format!("Context with name '{}' not found", name))
+        }
+    }
+
+    /// Start or stop keeping the context of a path fresh in the background
+    pub async fn set_watch_by_path(&mut self, path_str: &str, watch: bool) -> Result<String, String> {
+        let context = self
+            .client
+            .get_context_by_path(path_str)
+            .await
+            .ok_or_else(|| format!("No context found with path '{}'", path_str))?;
 
-        // Remove the existing context first
         self.client
-            .remove_context_by_id(context_id)
+            .set_watch(&context.id, watch)
             .await
             .map_err(|e| e.to_string())?;
 
-        // Then add it back with the same name
-        self.add(&context_name, path_str).await
+        Ok(if watch {
+            format!(
+                "👀 Watching '{}'\n📁 Path: {}\nChanged files are re-indexed in the background.",
+                context.name, path_str
+            )
+        } else {
+            format!("Stopped watching '{}'", context.name)
+        })
     }
 
-    /// Update context by name
-    pub async fn update_context_by_name(&mut self, name: &str, path_str: &str) -> Result<String, String> {
-        if let Some(context) = self.client.get_context_by_name(name).await {
-            // Remove the existing context first
-            self.client
-                .remove_context_by_id(&context.id)
-                .await
-                .map_err(|e| e.to_string())?;
+    /// Update a context from the given path, which is re-indexed from scratch if it is not the
+    /// source path of the context
+    async fn update_from_path(&mut self, context: &KnowledgeContext, path_str: &str) -> Result<String, String> {
+        let same_path = context.source_path.as_ref().is_some_and(|source_path| {
+            let canonical = |path: &str| std::path::PathBuf::from(path).canonicalize().ok();
+            source_path == path_str || canonical(source_path).is_some_and(|source| canonical(path_str) == Some(source))
+        });
+        if same_path {
+            return self.update(context).await;
+        }
 
-            // Then add it back with the same name
-            self.add(name, path_str).await
-        } else {
-            Err(format!("Context with name '{}' not found", name))
+        // Remove the existing context first
+        self.client
+            .remove_context_by_id(&context.id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        // Then add it back with the same name and options
+        self.add_with_options(&context.name, path_str, context.indexing.clone())
+            .await
+    }
+
+    /// Re-index the files of a context that changed since it was last indexed
+    async fn update(&mut self, context: &KnowledgeContext) -> Result<String, String> {
+        match self.client.update_context(&context.id).await {
+            Ok((operation_id, _)) => Ok(format!(
+                "🚀 Started updating '{}'\n📁 Path: {}\n🆔 Operation ID: {}. Only files changed since the last update are re-indexed.",
+                context.name,
+                context.source_path.as_deref().unwrap_or_default(),
+                &operation_id.to_string()[..8]
+            )),
+            Err(e) => Err(format!("Failed to start updating: {}", e)),
         }
     }
 }
@@ -37,7 +37,7 @@ where
     match request.action() {
         OnboardingAction::InstallationScript => {
             let mut errs: Vec<String> = vec![];
-            for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            for shell in Shell::all() {
                 match shell.get_shell_integrations(ctx.env()) {
                     Ok(integrations) => {
                         for integration in integrations {
@@ -0,0 +1,284 @@
+#--------------------------------------------------------------------#
+# Inline Shell Completion for Bash                                   #
+#--------------------------------------------------------------------#
+# Shows the suggestion for the current line as ghost text after the
+# cursor. Printable characters are inserted by `bind -x` handlers,
+# which request a suggestion in the background. Readline redraws the
+# line once a handler returns, so the suggestion is always drawn by a
+# background job, and only if no key was pressed in the meantime.
+#
+# Requires bash 4 for READLINE_LINE and READLINE_POINT.
+
+# Color to use when highlighting suggestion
+# Uses the format of zsh `region_highlight`, e.g. `fg=8,bold`
+: "${Q_AUTOSUGGEST_HIGHLIGHT_STYLE:=fg=8}"
+
+# Path prefix of the files shared with the background jobs: `.id` holds
+# the id of the last key press, `.suggestion` the suggested line. They
+# live in a private directory created with mktemp on start.
+_q_inline_dir=""
+_q_inline_file=""
+_q_inline_id=0
+
+# Keys that aren't handled here, which cancel the suggestion before they
+# run the readline function they are bound to
+_q_inline_cancel_keys=(
+	'\C-m' '\C-j' '\C-i' '\C-r' '\C-s' '\C-w' '\C-u' '\C-k' '\C-y' '\C-_'
+	'\C-a' '\C-b' '\C-p' '\C-n' '\C-t' '\eb' '\ed' '\e\C-?'
+	'\e[A' '\eOA' '\e[B' '\eOB' '\e[D' '\eOD' '\e[H' '\eOH' '\e[1;5D' '\e[3~'
+)
+
+# Convert a highlight style to an SGR escape sequence
+_q_inline_sgr() {
+	local attr color sgr=""
+	local -a colors=(black red green yellow blue magenta cyan white)
+	local IFS=,
+	for attr in $1; do
+		case "$attr" in
+			fg=\#??????|bg=\#??????)
+				color="${attr#*=#}"
+				[[ "$attr" == fg=* ]] && sgr+=";38;2" || sgr+=";48;2"
+				sgr+=";$((16#${color:0:2}));$((16#${color:2:2}));$((16#${color:4:2}))"
+				;;
+			fg=[0-9]*) sgr+=";38;5;${attr#fg=}" ;;
+			bg=[0-9]*) sgr+=";48;5;${attr#bg=}" ;;
+			fg=*|bg=*)
+				for color in "${!colors[@]}"; do
+					if [[ "${attr#*=}" == "${colors[$color]}" ]]; then
+						[[ "$attr" == fg=* ]] && sgr+=";3$color" || sgr+=";4$color"
+					fi
+				done
+				;;
+			bold) sgr+=";1" ;;
+			underline) sgr+=";4" ;;
+			standout) sgr+=";7" ;;
+		esac
+	done
+	printf '\033[0%sm' "$sgr"
+}
+
+# Record a key press, such that pending background jobs don't draw
+_q_inline_next_id() {
+	(( ++_q_inline_id ))
+	printf '%s' "$_q_inline_id" > "$_q_inline_file.id"
+}
+
+# Forget the suggestion
+_q_inline_reset() {
+	: > "$_q_inline_file.suggestion"
+}
+
+# Stop pending background jobs from drawing, and clear the suggestion
+# drawn after the cursor
+_q_inline_cancel() {
+	_q_inline_next_id
+	printf '\033[K' > /dev/tty
+}
+
+# Set _q_inline_suggestion to the suggested line, if it extends the
+# current line and the cursor is at its end
+_q_inline_get_suggestion() {
+	_q_inline_suggestion=""
+	(( ${#READLINE_LINE} && READLINE_POINT == ${#READLINE_LINE} )) || return 1
+	IFS= read -r _q_inline_suggestion < "$_q_inline_file.suggestion" 2>/dev/null
+	(( ${#_q_inline_suggestion} > ${#READLINE_LINE} )) || return 1
+	[[ "${_q_inline_suggestion:0:${#READLINE_LINE}}" == "$READLINE_LINE" ]]
+}
+
+# Draw the suggestion after the cursor once readline redrew the line
+_q_inline_draw() {
+	local id="$_q_inline_id" file="$_q_inline_file" style="$_q_inline_style"
+	local rest="${_q_inline_suggestion:${#READLINE_LINE}}"
+
+	( (
+		command sleep 0.05
+		IFS= read -r current < "$file.id"
+		[[ "$current" == "$id" ]] || exit
+		printf '\0337%s%s\033[0m\0338' "$style" "$rest" > /dev/tty
+	) & ) > /dev/null 2>&1
+}
+
+# Request a suggestion for the current line in the background, and draw
+# it if no key was pressed until it arrives
+_q_inline_fetch() {
+	local id="$_q_inline_id" buffer="$READLINE_LINE" file="$_q_inline_file"
+	local style="$_q_inline_style"
+
+	(( ${#buffer} && READLINE_POINT == ${#buffer} )) || return
+	[[ "$buffer" != *$'\n'* ]] || return
+
+	( (
+		suggestion="$(command q _ inline-shell-completion --buffer "$buffer")" || exit
+		(( ${#suggestion} > ${#buffer} )) || exit
+		[[ "${suggestion:0:${#buffer}}" == "$buffer" ]] || exit
+
+		IFS= read -r current < "$file.id"
+		[[ "$current" == "$id" ]] || exit
+		printf '%s\n' "$suggestion" > "$file.suggestion"
+		printf '\0337%s%s\033[0m\0338' "$style" "${suggestion:${#buffer}}" > /dev/tty
+	) & ) > /dev/null 2>&1
+}
+
+# Update the suggestion after the line was edited
+_q_inline_update() {
+	_q_inline_next_id
+
+	# Keep the suggestion while the line matches it
+	if _q_inline_get_suggestion; then
+		_q_inline_draw
+	else
+		_q_inline_reset
+		_q_inline_fetch
+	fi
+}
+
+# Insert a typed character
+_q_inline_insert() {
+	READLINE_LINE="${READLINE_LINE:0:READLINE_POINT}$1${READLINE_LINE:READLINE_POINT}"
+	(( READLINE_POINT += ${#1} ))
+	_q_inline_update
+}
+
+# Delete the character before the cursor
+_q_inline_backspace() {
+	(( READLINE_POINT )) || return
+	READLINE_LINE="${READLINE_LINE:0:READLINE_POINT-1}${READLINE_LINE:READLINE_POINT}"
+	(( READLINE_POINT-- ))
+	_q_inline_update
+}
+
+# Accept the suggestion, or run the given movement if there is none
+#
+# - forward-char and end-of-line accept the entire suggestion
+# - forward-word accepts the suggestion up to the end of its next word
+_q_inline_accept() {
+	local rest skip word
+	if ! _q_inline_get_suggestion; then
+		case "$1" in
+			forward-char)
+				(( READLINE_POINT < ${#READLINE_LINE} )) && (( READLINE_POINT++ ))
+				;;
+			end-of-line)
+				READLINE_POINT=${#READLINE_LINE}
+				;;
+			forward-word)
+				rest="${READLINE_LINE:READLINE_POINT}"
+				skip="${rest%%[[:alnum:]]*}"
+				rest="${rest:${#skip}}"
+				word="${rest%%[^[:alnum:]]*}"
+				(( READLINE_POINT += ${#skip} + ${#word} ))
+				;;
+		esac
+		_q_inline_next_id
+		return
+	fi
+
+	rest="${_q_inline_suggestion:${#READLINE_LINE}}"
+	if [[ "$1" == forward-word ]]; then
+		skip="${rest%%[^[:space:]]*}"
+		word="${rest:${#skip}}"
+		word="${word%%[[:space:]]*}"
+		rest="$skip$word"
+	else
+		(command q _ inline-shell-completion-accept --buffer "$READLINE_LINE" --suggestion "$rest" > /dev/null 2>&1 &)
+	fi
+
+	READLINE_LINE+="$rest"
+	READLINE_POINT=${#READLINE_LINE}
+	_q_inline_update
+}
+
+# Forget the suggestion of the command line, once it runs and before the
+# next prompt
+_q_inline_forget() {
+	_q_inline_next_id
+	_q_inline_reset
+}
+
+# Bind the keys handled by the inline shell completion
+_q_inline_bind() {
+	local keymap code octal char key arg
+
+	for keymap in emacs vi-insert; do
+		for (( code = 32; code < 127; code++ )); do
+			printf -v octal '%03o' "$code"
+			printf -v char "\\$octal"
+			case "$char" in
+				'"' | '\') key="\\$char" ;;
+				*) key="$char" ;;
+			esac
+			printf -v arg '%q' "$char"
+			bind -m "$keymap" -x "\"$key\": _q_inline_insert $arg"
+		done
+
+		bind -m "$keymap" -x '"\C-?": _q_inline_backspace'
+		bind -m "$keymap" -x '"\C-h": _q_inline_backspace'
+		bind -m "$keymap" -x '"\e[C": _q_inline_accept forward-char'
+		bind -m "$keymap" -x '"\eOC": _q_inline_accept forward-char'
+		bind -m "$keymap" -x '"\e[F": _q_inline_accept end-of-line'
+		bind -m "$keymap" -x '"\eOF": _q_inline_accept end-of-line'
+		bind -m "$keymap" -x '"\e[1;5C": _q_inline_accept forward-word'
+	done
+
+	bind -m emacs -x '"\C-f": _q_inline_accept forward-char'
+	bind -m emacs -x '"\C-e": _q_inline_accept end-of-line'
+	bind -m emacs -x '"\ef": _q_inline_accept forward-word'
+
+	for keymap in emacs vi-insert vi-command; do
+		_q_inline_bind_cancel "$keymap"
+	done
+}
+
+# Make the keys that aren't handled here cancel the suggestion, by binding
+# them to a macro that runs _q_inline_cancel followed by the readline
+# function they were bound to
+_q_inline_bind_cancel() {
+	local keymap="$1" bindings line key meta function seq i=0
+	local seqs=abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ
+
+	bindings="$(bind -m "$keymap" -p 2>/dev/null)"
+	bind -m "$keymap" -x '"\C-x\C-q@": _q_inline_cancel'
+
+	for key in "${_q_inline_cancel_keys[@]}"; do
+		# Keys bound to macros or shell commands aren't listed, which
+		# leaves them as they are, e.g. when the script is sourced again
+		# Newer versions of bash list `\e` as `\M-`
+		function="" meta="$key"
+		[[ "$key" == '\e'* ]] && meta="\\M-${key#'\e'}"
+		while IFS= read -r line; do
+			if [[ "$line" == "\"$key\": "* || "$line" == "\"$meta\": "* ]]; then
+				function="${line#*\": }"
+			fi
+		done <<< "$bindings"
+		[[ -n "$function" ]] || continue
+
+		seq="\\C-x\\C-q${seqs:i:1}"
+		bind -m "$keymap" "\"$seq\": $function"
+		bind -m "$keymap" "\"$key\": \"\\C-x\\C-q@$seq\""
+		(( ++i ))
+	done
+}
+
+# Start the inline shell completion if the shell supports it
+_q_inline_start() {
+	[[ $- == *i* ]] || return
+	(( BASH_VERSINFO[0] >= 4 )) || return
+	[[ -n "${QTERM_SESSION_ID:-}" ]] || return
+
+	local tmpdir="${TMPDIR:-/tmp}"
+	_q_inline_dir="$(command mktemp -d "${tmpdir%/}/q-inline.XXXXXX" 2>/dev/null)" || return
+	_q_inline_file="$_q_inline_dir/line"
+	(: > "$_q_inline_file.id" && : > "$_q_inline_file.suggestion") 2>/dev/null || return
+	_q_inline_style="$(_q_inline_sgr "$Q_AUTOSUGGEST_HIGHLIGHT_STYLE")"
+	_q_inline_bind
+	precmd_functions+=(_q_inline_forget)
+	preexec_functions+=(_q_inline_forget)
+
+	# Remove the files shared with the background jobs on exit, unless
+	# the user has their own exit trap
+	if [[ -z "$(trap -p EXIT)" ]]; then
+		trap 'command rm -rf "$_q_inline_dir"' EXIT
+	fi
+}
+
+_q_inline_start
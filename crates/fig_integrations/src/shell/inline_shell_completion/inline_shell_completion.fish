@@ -0,0 +1,259 @@
+#--------------------------------------------------------------------#
+# Inline Shell Completion for Fish                                   #
+#--------------------------------------------------------------------#
+# Shows the suggestion for the current line as ghost text after the
+# cursor, in place of the fish autosuggestions. Printable characters
+# are bound to functions that request a suggestion in the background.
+# Fish redraws the line once a binding ran, so the suggestion is always
+# drawn by a background job, and only if no key was pressed in the
+# meantime.
+
+# Path prefix of the files shared with the background jobs: `.id` holds
+# the id of the last key press, `.suggestion` the suggested line. They
+# live in a private directory created with mktemp on start.
+set --global _q_inline_tmpdir /tmp
+set --query TMPDIR[1]; and test -n "$TMPDIR"
+and set _q_inline_tmpdir (string trim --right --chars / -- $TMPDIR)
+set --global _q_inline_file
+set --global _q_inline_id 0
+
+# Record a key press, such that pending background jobs don't draw
+function _q_inline_next_id
+    set --global _q_inline_id (math $_q_inline_id + 1)
+    echo -n $_q_inline_id >$_q_inline_file.id
+end
+
+# Forget the suggestion
+function _q_inline_reset
+    true >$_q_inline_file.suggestion
+end
+
+# Stop pending background jobs from drawing, and clear the suggestion
+# drawn after the cursor
+function _q_inline_cancel
+    _q_inline_next_id
+    printf '\e[K' >/dev/tty
+end
+
+# Set _q_inline_rest to the rest of the suggested line, if it extends the
+# current line and the cursor is at its end
+function _q_inline_get_suggestion
+    set --global _q_inline_rest
+    set --local buffer (commandline | string collect)
+    set --local length (string length -- "$buffer")
+    test $length -gt 0; and test (commandline --cursor) -eq $length; or return 1
+
+    read --local suggestion <$_q_inline_file.suggestion; or return 1
+    test (string length -- "$suggestion") -gt $length; or return 1
+    set --local prefix (string sub --length $length -- "$suggestion")
+    test "$prefix" = "$buffer"; or return 1
+
+    set --global _q_inline_rest (string sub --start (math $length + 1) -- "$suggestion")
+end
+
+# Draw the rest of the suggestion after the cursor once fish redrew the line
+function _q_inline_draw
+    command sh -c '
+        sleep 0.05
+        [ "$(cat "$1.id")" = "$2" ] || exit
+        printf "\0337%s%s\033[0m\0338" "$3" "$4" > /dev/tty
+    ' q-inline $_q_inline_file $_q_inline_id "$_q_inline_style" "$_q_inline_rest" &>/dev/null &
+    disown
+end
+
+# Request a suggestion for the current line in the background, and draw
+# it if no key was pressed until it arrives
+function _q_inline_fetch
+    set --local buffer (commandline | string collect)
+    test -n "$buffer"; and test (count (commandline)) -eq 1; or return
+    test (commandline --cursor) -eq (string length -- "$buffer"); or return
+
+    command sh -c '
+        suggestion=$(command q _ inline-shell-completion --buffer "$3") || exit
+        case $suggestion in "$3"?*) ;; *) exit ;; esac
+        [ "$(cat "$1.id")" = "$2" ] || exit
+        printf "%s\n" "$suggestion" > "$1.suggestion"
+        printf "\0337%s%s\033[0m\0338" "$4" "${suggestion#"$3"}" > /dev/tty
+    ' q-inline $_q_inline_file $_q_inline_id "$buffer" "$_q_inline_style" &>/dev/null &
+    disown
+end
+
+# Update the suggestion after the line was edited
+function _q_inline_update
+    _q_inline_next_id
+
+    # Keep the suggestion while the line matches it
+    if _q_inline_get_suggestion
+        _q_inline_draw
+    else
+        _q_inline_reset
+        _q_inline_fetch
+    end
+end
+
+# Insert a typed character
+function _q_inline_insert --argument-names char
+    commandline --insert -- $char
+    _q_inline_update
+end
+
+# Delete the character before the cursor
+function _q_inline_backspace
+    set --local buffer (commandline | string collect)
+    set --local cursor (commandline --cursor)
+    test $cursor -gt 0; or return
+
+    set --local before (string sub --length (math $cursor - 1) -- "$buffer")
+    set --local after (string sub --start (math $cursor + 1) -- "$buffer")
+    commandline --replace -- "$before$after"
+    commandline --cursor (math $cursor - 1)
+    _q_inline_update
+end
+
+# Accept the suggestion, or run the given input function if there is none
+#
+# - forward-char and end-of-line accept the entire suggestion
+# - forward-word accepts the suggestion up to the end of its next word
+function _q_inline_accept --argument-names fallback
+    if not _q_inline_get_suggestion
+        commandline --function $fallback
+        _q_inline_next_id
+        return
+    end
+
+    set --local rest $_q_inline_rest
+    if test $fallback = forward-word
+        set rest (string match --regex -- '^\s*\S*' "$rest")
+    else
+        command q _ inline-shell-completion-accept --buffer (commandline | string collect) --suggestion "$rest" &>/dev/null &
+        disown
+    end
+
+    commandline --insert -- $rest
+    _q_inline_update
+end
+
+# Print the bind modes to bind keys in. With vi key bindings `default`
+# is normal mode, whose keys must keep their vi meaning.
+function _q_inline_modes
+    if test "$fish_key_bindings" = fish_vi_key_bindings
+        echo insert
+    else
+        echo default
+        echo insert
+    end
+end
+
+# Bind the keys handled by the inline shell completion
+function _q_inline_bind
+    # Fish 4 names keys rather than matching escape sequences
+    set --local fish_major (string split --max 1 . -- $version)[1]
+    set --local chars (string split '' -- (printf %b (printf '\\\\%03o' (seq 32 126))))
+
+    for mode in (_q_inline_modes)
+        for char in $chars
+            set --local key $char
+            if test $char = ' '; and test $fish_major -ge 4
+                set key space
+            end
+            bind -M $mode -- $key "_q_inline_insert "(string escape -- $char)
+        end
+
+        if test $fish_major -ge 4
+            bind -M $mode backspace _q_inline_backspace
+            bind -M $mode right '_q_inline_accept forward-char'
+            bind -M $mode end '_q_inline_accept end-of-line'
+            bind -M $mode ctrl-right '_q_inline_accept forward-word'
+            bind -M $mode ctrl-f '_q_inline_accept forward-char'
+            bind -M $mode ctrl-e '_q_inline_accept end-of-line'
+            bind -M $mode alt-f '_q_inline_accept forward-word'
+        else
+            bind -M $mode \x7f _q_inline_backspace
+            bind -M $mode \b _q_inline_backspace
+            bind -M $mode \e\[C '_q_inline_accept forward-char'
+            bind -M $mode \eOC '_q_inline_accept forward-char'
+            bind -M $mode \e\[F '_q_inline_accept end-of-line'
+            bind -M $mode \eOF '_q_inline_accept end-of-line'
+            bind -M $mode \e\[1\;5C '_q_inline_accept forward-word'
+            bind -M $mode \cf '_q_inline_accept forward-char'
+            bind -M $mode \ce '_q_inline_accept end-of-line'
+            bind -M $mode \ef '_q_inline_accept forward-word'
+        end
+    end
+
+    # Keys that aren't handled here cancel the suggestion before they run
+    # what they are bound to
+    set --local cancel_keys enter ctrl-j tab shift-tab up down left home delete \
+        ctrl-a ctrl-b ctrl-c ctrl-k ctrl-n ctrl-p ctrl-r ctrl-u ctrl-w ctrl-y ctrl-z \
+        ctrl-left alt-b alt-d alt-backspace
+    if test $fish_major -lt 4
+        set cancel_keys \r \n \t \e\[Z \e\[A \eOA \e\[B \eOB \e\[D \eOD \e\[H \eOH \e\[3~ \
+            \ca \cb \cc \ck \cn \cp \cr \cu \cw \cy \cz \e\[1\;5D \eb \ed \e\x7f
+    end
+    for mode in default insert
+        for key in $cancel_keys
+            _q_inline_bind_cancel $mode $key
+        end
+    end
+end
+
+# Bind a key that isn't handled here to _q_inline_cancel followed by what
+# it was bound to
+function _q_inline_bind_cancel --argument-names mode key
+    # Preset bindings are listed before user bindings, which take precedence
+    set --local bindings (bind -M $mode -- $key 2>/dev/null)
+    set --query bindings[1]; or return
+    string match --quiet '*_q_inline_*' -- $bindings[-1]; and return
+
+    # Listed bindings are commands that would bind the key again
+    eval (string replace --regex '^bind ' "_q_inline_bind_cancel_to $mode " -- $bindings[-1])
+end
+
+function _q_inline_bind_cancel_to --argument-names mode
+    argparse --ignore-unknown preset 'M=' 'm=' k -- $argv[2..-1]; or return
+
+    set --local flags -M $mode
+    set --query _flag_m; and set --append flags -m $_flag_m
+    set --query _flag_k; and set --append flags -k
+    bind $flags -- $argv[1] _q_inline_cancel $argv[2..-1]
+end
+
+# Forget the suggestion of the previous command line, and bind the keys
+# again if loading key bindings replaced ours
+function _q_inline_precmd --on-event fish_prompt
+    _q_inline_next_id
+    _q_inline_reset
+
+    if set --query fish_color_autosuggestion[1]
+        set --global _q_inline_style (set_color $fish_color_autosuggestion)
+    else
+        set --global _q_inline_style (set_color brblack)
+    end
+
+    if not string match --quiet '*_q_inline_insert*' -- (bind -M insert a)
+        _q_inline_bind
+    end
+end
+
+# Stop pending background jobs from drawing over the output of the command
+function _q_inline_preexec --on-event fish_preexec
+    _q_inline_next_id
+    _q_inline_reset
+end
+
+function _q_inline_exit --on-event fish_exit
+    command rm -rf $_q_inline_dir
+end
+
+# Start the inline shell completion if the shell supports it, otherwise
+# remove its functions
+if status is-interactive; and set --query QTERM_SESSION_ID
+    and set --global _q_inline_dir (command mktemp -d $_q_inline_tmpdir/q-inline.XXXXXX 2>/dev/null)
+    and set _q_inline_file $_q_inline_dir/line
+    and command sh -c ': > "$1.id" && : > "$1.suggestion"' q-inline $_q_inline_file
+
+    # The suggestion is drawn where fish shows its autosuggestions
+    set --global fish_autosuggestion_enabled 0
+else
+    functions --erase (functions --all | string match '_q_inline_*')
+end
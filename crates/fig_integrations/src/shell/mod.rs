@@ -42,6 +42,18 @@ pub mod inline_shell_completion_plugin {
         "\n"
     );
 
+    pub const BASH_SCRIPT: &str = concat!(
+        "\n",
+        include_str!("inline_shell_completion/inline_shell_completion.bash"),
+        "\n"
+    );
+
+    pub const FISH_SCRIPT: &str = concat!(
+        "\n",
+        include_str!("inline_shell_completion/inline_shell_completion.fish"),
+        "\n"
+    );
+
     #[cfg(test)]
     mod tests {
         use fig_util::CLI_BINARY_NAME;
@@ -60,6 +72,21 @@ pub mod inline_shell_completion_plugin {
 
             // Ensure script adds precmd hook
             assert!(ZSH_SCRIPT.contains(&format!("add-zsh-hook precmd _{CLI_BINARY_NAME}_autosuggest_start")));
+
+            // Ensure the bash and fish scripts request suggestions and bind the accept keys
+            for script in [BASH_SCRIPT, FISH_SCRIPT] {
+                assert!(script.contains(&format!("command {CLI_BINARY_NAME} _ inline-shell-completion --buffer")));
+                assert!(script.contains(&format!("command {CLI_BINARY_NAME} _ inline-shell-completion-accept")));
+                assert!(script.contains("_q_inline_accept forward-word"));
+            }
+
+            // Ensure pending suggestions are cancelled before a command runs
+            assert!(BASH_SCRIPT.contains("preexec_functions+=(_q_inline_forget)"));
+            assert!(FISH_SCRIPT.contains("--on-event fish_preexec"));
+
+            // Ensure the bash script only starts in supported shells
+            assert!(BASH_SCRIPT.contains("BASH_VERSINFO[0] >= 4"));
+            assert!(BASH_SCRIPT.trim_end().ends_with("_q_inline_start"));
         }
     }
 }
@@ -86,6 +113,38 @@ impl std::fmt::Display for When {
     }
 }
 
+/// Nushell reads `env.nu` before `config.nu`, so the pre integration goes into the former and the
+/// post integration into the latter
+const NU_ENV_FILE: &str = "env.nu";
+const NU_CONFIG_FILE: &str = "config.nu";
+
+/// The oldest Nushell version the integration works with. It relies on `def --env`, optional cell
+/// paths, `$nu` being a constant and `source null` doing nothing.
+pub const NU_MIN_VERSION: semver::Version = semver::Version::new(0, 101, 0);
+
+/// Parse the output of `nu --version`
+pub fn parse_nu_version(output: &str) -> Option<semver::Version> {
+    semver::Version::parse(output.trim()).ok()
+}
+
+/// Whether the installed Nushell is recent enough for the integration, older versions fail to parse
+/// config.nu once it sources the integration
+fn nu_is_supported() -> bool {
+    match std::process::Command::new("nu").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            parse_nu_version(&String::from_utf8_lossy(&output.stdout)).is_some_and(|version| version >= NU_MIN_VERSION)
+        },
+        _ => false,
+    }
+}
+
+fn nu_dotfile_when(dotfile_name: &str) -> When {
+    match dotfile_name {
+        NU_ENV_FILE => When::Pre,
+        _ => When::Post,
+    }
+}
+
 fn integration_file_name(dotfile_name: &str, when: &When, shell: &Shell) -> String {
     format!(
         "{}.{when}.{shell}",
@@ -98,6 +157,8 @@ pub trait ShellExt {
     /// Script integrations are installed into ~/.fig/shell
     fn get_script_integrations(&self) -> Result<Vec<ShellScriptShellIntegration>>;
     fn get_fig_integration_source(&self, when: &When) -> &'static str;
+    /// The inline shell completion script, if the shell supports it
+    fn get_inline_shell_completion_source(&self) -> Option<&'static str>;
 }
 
 impl ShellExt for Shell {
@@ -107,9 +168,14 @@ impl ShellExt for Shell {
         for file in match self {
             Shell::Bash => [".bashrc", ".bash_profile", ".bash_login", ".profile"].iter(),
             Shell::Zsh => [".zshrc", ".zprofile"].iter(),
-            Shell::Fish | Shell::Nu => [].iter(),
+            Shell::Fish => [].iter(),
+            Shell::Nu => [NU_ENV_FILE, NU_CONFIG_FILE].iter(),
         } {
             for when in &When::all() {
+                if self.is_nu() && *when != nu_dotfile_when(file) {
+                    continue;
+                }
+
                 let path = directories::fig_data_dir()?
                     .join("shell")
                     .join(integration_file_name(file, when, self));
@@ -180,7 +246,27 @@ impl ShellExt for Shell {
                     }),
                 ]
             },
-            Shell::Nu => vec![],
+            Shell::Nu => {
+                // Only integrate once Nushell was set up, creating its config files would skip
+                // the creation of the default ones
+                if config_dir.exists() && nu_is_supported() {
+                    [NU_ENV_FILE, NU_CONFIG_FILE]
+                        .into_iter()
+                        .map(|filename| {
+                            let when = nu_dotfile_when(filename);
+                            Box::new(DotfileShellIntegration {
+                                pre: when == When::Pre,
+                                post: when == When::Post,
+                                shell: *self,
+                                dotfile_directory: config_dir.clone(),
+                                dotfile_name: filename,
+                            }) as Box<dyn ShellIntegration>
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                }
+            },
         };
 
         Ok(integrations)
@@ -216,6 +302,15 @@ impl ShellExt for Shell {
             (Shell::Nu, When::Post) => include_str!("scripts/post.nu"),
         }
     }
+
+    fn get_inline_shell_completion_source(&self) -> Option<&'static str> {
+        match self {
+            Shell::Zsh => Some(inline_shell_completion_plugin::ZSH_SCRIPT),
+            Shell::Bash => Some(inline_shell_completion_plugin::BASH_SCRIPT),
+            Shell::Fish => Some(inline_shell_completion_plugin::FISH_SCRIPT),
+            Shell::Nu => None,
+        }
+    }
 }
 
 pub trait ShellIntegration: Send + Sync + Integration + ShellIntegrationClone {
@@ -282,6 +377,12 @@ impl ShellScriptShellIntegration {
     #[allow(clippy::needless_return)]
     fn get_contents(&self) -> String {
         let Self { shell, when, path } = self;
+
+        // Nushell can't eval the output of `q init`, so the script is installed as is
+        if shell.is_nu() {
+            return shell.get_fig_integration_source(when).to_owned();
+        }
+
         let rcfile = match path.file_name().and_then(|x| x.to_str()) {
             Some(name) => format!(" --rcfile {}", get_prefix(name)),
             None => "".into(),
@@ -502,10 +603,21 @@ impl DotfileShellIntegration {
     fn source_text(&self, when: When) -> Result<String> {
         let home = directories::home_dir()?;
         let integration_path = self.script_integration(when)?.path;
-        let path = format!("\"${{HOME}}/{}\"", integration_path.strip_prefix(home)?.display());
+        let relative_path = integration_path.strip_prefix(home)?.display();
+        let path = format!("\"${{HOME}}/{relative_path}\"");
 
         match self.shell {
             Shell::Fish => Ok(format!("test -f {path}; and builtin source {path}")),
+            // Nushell sources files at parse time, so the path must be a constant. Sourcing `null`
+            // does nothing, such that config.nu still loads if the integration is missing.
+            Shell::Nu => {
+                let name = format!("amazon_q_{when}");
+                Ok(format!(
+                    "const {name}_path = ($nu.home-path | path join \"{relative_path}\")\n\
+                     const {name} = if (${name}_path | path exists) {{ ${name}_path }} else {{ null }}\n\
+                     source ${name}"
+                ))
+            },
             _ => Ok(format!("[[ -f {path} ]] && builtin source {path}")),
         }
     }
@@ -932,10 +1044,55 @@ mod test {
         }
     }
 
+    #[test]
+    fn nu_dotfile_integrations() {
+        let files = Shell::Nu
+            .get_script_integrations()
+            .unwrap()
+            .into_iter()
+            .map(|integration| (integration.when, integration.file_name().to_owned()))
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![
+            (When::Pre, "env.nu.pre.nu".to_owned()),
+            (When::Post, "config.nu.post.nu".to_owned())
+        ]);
+
+        let integration = DotfileShellIntegration {
+            pre: false,
+            post: true,
+            shell: Shell::Nu,
+            dotfile_directory: "".into(),
+            dotfile_name: NU_CONFIG_FILE,
+        };
+        let source_text = integration.source_text(When::Post).unwrap();
+        assert!(source_text.starts_with("const amazon_q_post_path = ($nu.home-path | path join \""));
+        assert!(source_text.contains("shell/config.nu.post.nu\")\n"));
+        assert!(source_text.contains("const amazon_q_post = if ($amazon_q_post_path | path exists)"));
+        assert!(source_text.ends_with("source $amazon_q_post"));
+
+        // The script is installed as is, since nushell can't eval the output of `q init`
+        assert_eq!(
+            integration.script_integration(When::Post).unwrap().get_contents(),
+            include_str!("scripts/post.nu")
+        );
+    }
+
+    #[test]
+    fn nu_version() {
+        let version = parse_nu_version("0.101.0\n").unwrap();
+        assert!(version >= NU_MIN_VERSION);
+        assert!(parse_nu_version("0.100.0").unwrap() < NU_MIN_VERSION);
+        assert!(parse_nu_version("0.102.1").unwrap() >= NU_MIN_VERSION);
+        assert!(parse_nu_version("nu").is_none());
+    }
+
     #[test]
     #[cfg(target_os = "linux")]
     fn dotfile_shell_integrations_shellcheck() {
         for integration in all_dotfile_shell_integrations() {
+            if integration.shell.is_nu() {
+                continue;
+            }
             run_shellcheck(integration.get_contents());
         }
     }
@@ -66,6 +66,7 @@ function __fig_pre_prompt () {
   fig_osc "ExitCode=%s" "$__fig_ret_value"
   fig_osc "TTY=%s" "${TTY}"
   fig_osc "Log=%s" "${Q_LOG_LEVEL}"
+  fig_osc "FigAutosuggestionColor=%s" "${Q_AUTOSUGGEST_HIGHLIGHT_STYLE}"
   fig_osc "User=%s" "${USER:-root}"
 
   if command -v q >/dev/null 2>&1; then
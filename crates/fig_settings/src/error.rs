@@ -30,6 +30,8 @@ pub enum Error {
     DbOpenError(#[from] DbOpenError),
     #[error("{}", .0)]
     PoisonError(String),
+    #[error("invalid value for {key}: {reason}")]
+    InvalidSettingValue { key: String, reason: String },
 }
 
 impl<T> From<PoisonError<T>> for Error {
@@ -56,6 +58,10 @@ mod tests {
             // r2d2::Error
             DbOpenError("oops".into()).into(),
             PoisonError::<()>::new(()).into(),
+            Error::InvalidSettingValue {
+                key: "key".into(),
+                reason: "oops".into(),
+            },
         ]
     }
 
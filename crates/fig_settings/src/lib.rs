@@ -2,6 +2,7 @@ pub mod error;
 pub mod history;
 pub mod keybindings;
 pub mod keys;
+pub mod schema;
 pub mod settings;
 pub mod sqlite;
 pub mod state;
@@ -0,0 +1,988 @@
+//! Declarative registry of every setting, with its type, default value and the component that
+//! reads it.
+//!
+//! [`SettingDefinition`]s add validation and discoverability on top of the untyped settings file.
+//! Settings that are not in the registry can still be read, but `q settings` refuses to write them
+//! unless their key has one of the [`DYNAMIC_PREFIXES`].
+
+use std::fmt::Display;
+
+use serde_json::Value;
+
+use crate::{
+    Error,
+    Result,
+};
+
+/// The type of the value of a setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingType {
+    Bool,
+    Int,
+    String,
+    /// A list of strings
+    StringList,
+    /// A json object
+    Object,
+}
+
+impl Display for SettingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SettingType::Bool => "boolean",
+            SettingType::Int => "integer",
+            SettingType::String => "string",
+            SettingType::StringList => "list of strings",
+            SettingType::Object => "object",
+        })
+    }
+}
+
+/// The component that reads a setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Api,
+    App,
+    Autocomplete,
+    Chat,
+    Inline,
+    Integrations,
+    Qterm,
+    Telemetry,
+    Translate,
+}
+
+impl Display for Component {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Component::Api => "api",
+            Component::App => "app",
+            Component::Autocomplete => "autocomplete",
+            Component::Chat => "chat",
+            Component::Inline => "inline",
+            Component::Integrations => "integrations",
+            Component::Qterm => "qterm",
+            Component::Telemetry => "telemetry",
+            Component::Translate => "translate",
+        })
+    }
+}
+
+/// The value used when a setting is not set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingDefault {
+    /// The setting is unset by default, or its default depends on the environment
+    None,
+    Bool(bool),
+    Int(i64),
+    String(&'static str),
+    StringList(&'static [&'static str]),
+}
+
+impl SettingDefault {
+    pub fn to_value(&self) -> Option<Value> {
+        match self {
+            SettingDefault::None => None,
+            SettingDefault::Bool(value) => Some(Value::Bool(*value)),
+            SettingDefault::Int(value) => Some(Value::from(*value)),
+            SettingDefault::String(value) => Some(Value::from(*value)),
+            SettingDefault::StringList(values) => Some(Value::from(values.to_vec())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub setting_type: SettingType,
+    pub default: SettingDefault,
+    pub description: &'static str,
+    /// If not empty, the only values a string setting may be set to
+    pub allowed_values: &'static [&'static str],
+    pub component: Component,
+}
+
+impl SettingDefinition {
+    const fn new(
+        key: &'static str,
+        setting_type: SettingType,
+        default: SettingDefault,
+        component: Component,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            key,
+            setting_type,
+            default,
+            description,
+            allowed_values: &[],
+            component,
+        }
+    }
+
+    const fn allowed_values(mut self, allowed_values: &'static [&'static str]) -> Self {
+        self.allowed_values = allowed_values;
+        self
+    }
+
+    /// Check that `value` has the type of the setting and is one of the allowed values
+    pub fn validate(&self, value: &Value) -> Result<()> {
+        let valid_type = match self.setting_type {
+            SettingType::Bool => value.is_boolean(),
+            SettingType::Int => value.is_i64(),
+            SettingType::String => value.is_string(),
+            SettingType::StringList => value
+                .as_array()
+                .is_some_and(|values| values.iter().all(Value::is_string)),
+            SettingType::Object => value.is_object(),
+        };
+
+        if !valid_type {
+            return Err(Error::InvalidSettingValue {
+                key: self.key.into(),
+                reason: format!("expected a {}, found {value}", self.setting_type),
+            });
+        }
+
+        if let Some(value) = value.as_str() {
+            if !self.allowed_values.is_empty() && !self.allowed_values.contains(&value) {
+                return Err(Error::InvalidSettingValue {
+                    key: self.key.into(),
+                    reason: format!("expected one of {}, found {value:?}", self.allowed_values.join(", ")),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse a value given on the command line, values that are not valid json are strings
+    ///
+    /// Values of string settings are always strings, so `q settings key 123` sets `"123"`
+    pub fn parse_value(&self, value: &str) -> Value {
+        match self.setting_type {
+            SettingType::String => Value::from(value),
+            _ => serde_json::from_str(value).unwrap_or_else(|_| Value::from(value)),
+        }
+    }
+}
+
+/// Prefixes of settings whose keys are built at runtime, e.g. `autocomplete.keybindings.control+r`
+pub const DYNAMIC_PREFIXES: &[&str] = &["autocomplete.keybindings.", "developer."];
+
+macro_rules! setting {
+    ($key:literal, $setting_type:ident, $default:expr, $component:ident, $description:literal) => {
+        SettingDefinition::new(
+            $key,
+            SettingType::$setting_type,
+            $default,
+            Component::$component,
+            $description,
+        )
+    };
+}
+
+use SettingDefault::{
+    Bool,
+    Int,
+    None as NoDefault,
+    String as Str,
+    StringList,
+};
+
+/// All the settings, ordered by key
+pub const SETTINGS: &[SettingDefinition] = &[
+    setting!(
+        "ai.menu-actions",
+        StringList,
+        StringList(&["execute", "edit", "regenerate", "ask", "cancel"]),
+        Translate,
+        "Actions offered for a translated command"
+    ),
+    setting!(
+        "ai.terminal-hash-sub",
+        Bool,
+        Bool(true),
+        Translate,
+        "Substitute the `#` prefix for `q translate`"
+    ),
+    setting!(
+        "api.codewhisperer.service",
+        Object,
+        NoDefault,
+        Api,
+        "Override the CodeWhisperer endpoint and region"
+    ),
+    setting!(
+        "api.q.service",
+        Object,
+        NoDefault,
+        Api,
+        "Override the Q endpoint and region"
+    ),
+    setting!(
+        "api.timeout",
+        Int,
+        NoDefault,
+        Api,
+        "Timeout of API requests in milliseconds"
+    ),
+    setting!(
+        "app.autoupdate.check-period",
+        Int,
+        Int(60 * 60 * 3),
+        App,
+        "Seconds between checks for updates"
+    ),
+    setting!("app.beta", Bool, Bool(false), App, "Use the beta update channel"),
+    setting!(
+        "app.disableAutolaunch",
+        Bool,
+        Bool(false),
+        App,
+        "Do not launch the app when a shell starts"
+    ),
+    setting!(
+        "app.disableAutoupdates",
+        Bool,
+        Bool(false),
+        App,
+        "Do not install updates automatically"
+    ),
+    setting!(
+        "app.hideMenubarIcon",
+        Bool,
+        Bool(false),
+        App,
+        "Hide the icon in the menu bar"
+    ),
+    setting!(
+        "app.launchOnStartup",
+        Bool,
+        Bool(true),
+        App,
+        "Launch the app when you log in"
+    ),
+    setting!("app.theme", String, NoDefault, App, "Theme of the app windows")
+        .allowed_values(&["system", "dark", "light"]),
+    setting!(
+        "autocomplete.alwaysShowDescription",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Always show the description of the selected suggestion"
+    ),
+    setting!(
+        "autocomplete.alwaysSuggestCurrentToken",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Always suggest the token being typed"
+    ),
+    setting!(
+        "autocomplete.decreaseSize",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to decrease the size of autocomplete"
+    ),
+    setting!(
+        "autocomplete.devCompletionsFolder",
+        String,
+        NoDefault,
+        Autocomplete,
+        "Folder to load completion specs from in developer mode"
+    ),
+    setting!(
+        "autocomplete.devCompletionsServerPort",
+        Int,
+        NoDefault,
+        Autocomplete,
+        "Port of the server to load completion specs from in developer mode"
+    ),
+    setting!(
+        "autocomplete.developerMode",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Load completion specs from the developer folder"
+    ),
+    setting!(
+        "autocomplete.developerModeNPM",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Load completion specs from the development server"
+    ),
+    setting!(
+        "autocomplete.developerModeNPMInvalidateCache",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Reload the completion specs from the development server once"
+    ),
+    setting!(
+        "autocomplete.disable",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Disable autocomplete"
+    ),
+    setting!(
+        "autocomplete.disableForCommands",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Commands autocomplete does not show for"
+    ),
+    setting!(
+        "autocomplete.execute",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to execute the current command"
+    ),
+    setting!(
+        "autocomplete.firstTokenCompletion",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Suggest commands for the first token"
+    ),
+    setting!(
+        "autocomplete.fontFamily",
+        String,
+        NoDefault,
+        Autocomplete,
+        "Font family of autocomplete"
+    ),
+    setting!(
+        "autocomplete.fontSize",
+        Int,
+        NoDefault,
+        Autocomplete,
+        "Font size of autocomplete"
+    ),
+    setting!(
+        "autocomplete.fuzzySearch",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Match suggestions with fuzzy search"
+    ),
+    setting!(
+        "autocomplete.height",
+        Int,
+        Int(140),
+        Autocomplete,
+        "Maximum height of autocomplete in pixels"
+    ),
+    setting!(
+        "autocomplete.hideAutoExecuteSuggestion",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Hide the suggestion to execute the command"
+    ),
+    setting!(
+        "autocomplete.hidePreviewWindow",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Hide the preview window next to autocomplete"
+    ),
+    setting!(
+        "autocomplete.history.disableLoading",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Do not load the shell history"
+    ),
+    setting!(
+        "autocomplete.iconTheme",
+        String,
+        NoDefault,
+        Autocomplete,
+        "Icon theme used for file icons on Linux"
+    ),
+    setting!(
+        "autocomplete.immediatelyExecuteAfterSpace",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Execute the command after inserting a space"
+    ),
+    setting!(
+        "autocomplete.immediatelyRunDangerousCommands",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Run dangerous commands without confirmation"
+    ),
+    setting!(
+        "autocomplete.immediatelyRunGitAliases",
+        Bool,
+        Bool(true),
+        Autocomplete,
+        "Run git aliases without confirmation"
+    ),
+    setting!(
+        "autocomplete.increaseSize",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to increase the size of autocomplete"
+    ),
+    setting!(
+        "autocomplete.insertCommonPrefix",
+        StringList,
+        StringList(&["tab"]),
+        Autocomplete,
+        "Keybindings to insert the common prefix"
+    ),
+    setting!(
+        "autocomplete.insertCommonPrefixOrInsertSelected",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to insert the common prefix or the selected suggestion"
+    ),
+    setting!(
+        "autocomplete.insertCommonPrefixOrNavigateDown",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to insert the common prefix or navigate down"
+    ),
+    setting!(
+        "autocomplete.insertSelected",
+        StringList,
+        StringList(&["enter"]),
+        Autocomplete,
+        "Keybindings to insert the selected suggestion"
+    ),
+    setting!(
+        "autocomplete.insertSelectedAndExecute",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to insert the selected suggestion and execute"
+    ),
+    setting!(
+        "autocomplete.insertSpaceAutomatically",
+        Bool,
+        Bool(true),
+        Autocomplete,
+        "Insert a space after suggestions that take a mandatory argument"
+    ),
+    setting!(
+        "autocomplete.navigateDown",
+        StringList,
+        StringList(&["down", "control+n"]),
+        Autocomplete,
+        "Keybindings to navigate down"
+    ),
+    setting!(
+        "autocomplete.navigateToHistory",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Toggle history mode when navigating up from the first suggestion"
+    ),
+    setting!(
+        "autocomplete.navigateUp",
+        StringList,
+        StringList(&["shift+tab", "up", "control+p"]),
+        Autocomplete,
+        "Keybindings to navigate up"
+    ),
+    setting!(
+        "autocomplete.onlyShowOnTab",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Only show autocomplete after pressing tab"
+    ),
+    setting!(
+        "autocomplete.personalShortcutsToken",
+        String,
+        Str("+"),
+        Autocomplete,
+        "Token that shows personal shortcuts"
+    ),
+    setting!(
+        "autocomplete.preferVerboseSuggestions",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Show the full form of suggestions"
+    ),
+    setting!(
+        "autocomplete.scriptTimeout",
+        Int,
+        Int(5000),
+        Autocomplete,
+        "Timeout of completion scripts in milliseconds"
+    ),
+    setting!(
+        "autocomplete.scrollWrapAround",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Wrap around when scrolling past the last suggestion"
+    ),
+    setting!(
+        "autocomplete.sortMethod",
+        String,
+        Str("most recent"),
+        Autocomplete,
+        "Order of the suggestions"
+    )
+    .allowed_values(&["most recent", "alphabetical"]),
+    setting!(
+        "autocomplete.theme",
+        String,
+        Str("system"),
+        Autocomplete,
+        "Theme of autocomplete"
+    ),
+    setting!(
+        "autocomplete.toggleAutocomplete",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to toggle autocomplete"
+    ),
+    setting!(
+        "autocomplete.toggleDescription",
+        StringList,
+        StringList(&["control+k"]),
+        Autocomplete,
+        "Keybindings to toggle the description"
+    ),
+    setting!(
+        "autocomplete.toggleFuzzySearch",
+        StringList,
+        StringList(&[]),
+        Autocomplete,
+        "Keybindings to toggle fuzzy search"
+    ),
+    setting!(
+        "autocomplete.toggleHistoryMode",
+        StringList,
+        StringList(&["control+r"]),
+        Autocomplete,
+        "Keybindings to toggle history mode"
+    ),
+    setting!(
+        "autocomplete.userStyles",
+        Object,
+        NoDefault,
+        Autocomplete,
+        "Custom styles of autocomplete"
+    ),
+    setting!(
+        "autocomplete.width",
+        Int,
+        Int(320),
+        Autocomplete,
+        "Width of autocomplete in pixels"
+    ),
+    setting!(
+        "beta.autocomplete.auto-cache",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Cache the results of all generators"
+    ),
+    setting!(
+        "beta.history.allShells",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Suggest the history of all shells rather than the current one"
+    ),
+    setting!(
+        "beta.history.ctrl-r",
+        Bool,
+        Bool(false),
+        Autocomplete,
+        "Toggle history mode with control+r"
+    ),
+    setting!(
+        "beta.history.customCommand",
+        String,
+        NoDefault,
+        Autocomplete,
+        "Command printing the history to suggest"
+    ),
+    setting!(
+        "beta.history.mode",
+        String,
+        Str("off"),
+        Autocomplete,
+        "How the history is suggested"
+    )
+    .allowed_values(&["off", "history_only", "show"]),
+    setting!(
+        "chat.defaultModel",
+        String,
+        NoDefault,
+        Chat,
+        "Model used for new conversations"
+    ),
+    setting!(
+        "chat.disableAutoCompaction",
+        Bool,
+        Bool(false),
+        Chat,
+        "Do not compact the conversation when the context window is full"
+    ),
+    setting!(
+        "chat.editMode",
+        String,
+        Str("emacs"),
+        Chat,
+        "Line editing mode of the chat prompt"
+    )
+    .allowed_values(&["emacs", "vi", "vim"]),
+    setting!(
+        "chat.enableHistoryHints",
+        Bool,
+        Bool(false),
+        Chat,
+        "Show hints from the prompt history"
+    ),
+    setting!(
+        "chat.enableKnowledge",
+        Bool,
+        Bool(false),
+        Chat,
+        "Enable the knowledge tool"
+    ),
+    setting!(
+        "chat.enableNotifications",
+        Bool,
+        Bool(false),
+        Chat,
+        "Notify when a response is ready"
+    ),
+    setting!(
+        "chat.enableTerminalContext",
+        Bool,
+        Bool(false),
+        Chat,
+        "Add the last failed command to the context"
+    ),
+    setting!(
+        "chat.enableThinking",
+        Bool,
+        Bool(false),
+        Chat,
+        "Enable the thinking tool"
+    ),
+    setting!(
+        "chat.greeting.enabled",
+        Bool,
+        Bool(true),
+        Chat,
+        "Show the greeting when starting a chat"
+    ),
+    setting!(
+        "chat.sandbox.allowNetwork",
+        Bool,
+        Bool(false),
+        Chat,
+        "Allow network access in the sandbox"
+    ),
+    setting!(
+        "chat.sandbox.cpuTimeLimit",
+        Int,
+        Int(600),
+        Chat,
+        "CPU time limit of sandboxed commands in seconds, 0 for no limit"
+    ),
+    setting!(
+        "chat.sandbox.enabled",
+        Bool,
+        Bool(false),
+        Chat,
+        "Run shell commands in a sandbox"
+    ),
+    setting!(
+        "chat.sandbox.memoryLimit",
+        Int,
+        Int(4096),
+        Chat,
+        "Memory limit of sandboxed commands in MiB, 0 for no limit"
+    ),
+    setting!(
+        "chat.sandbox.timeout",
+        Int,
+        Int(900),
+        Chat,
+        "Timeout of sandboxed commands in seconds, 0 for no limit"
+    ),
+    setting!(
+        "chat.sandbox.writablePaths",
+        StringList,
+        StringList(&[]),
+        Chat,
+        "Paths sandboxed commands may write to"
+    ),
+    setting!(
+        "chat.skimCommandKey",
+        String,
+        Str("s"),
+        Chat,
+        "Key to open the fuzzy command search"
+    ),
+    setting!(
+        "codeWhisperer.shareCodeWhispererContentWithAWS",
+        Bool,
+        Bool(true),
+        Telemetry,
+        "Share content with AWS to improve the service"
+    ),
+    setting!(
+        "inline.enabled",
+        Bool,
+        Bool(true),
+        Inline,
+        "Enable inline suggestions in new shell sessions"
+    ),
+    setting!(
+        "inline.localProvider",
+        String,
+        Str("fallback"),
+        Inline,
+        "How suggestions from the shell history are used"
+    )
+    .allowed_values(&["primary", "fallback", "disabled"]),
+    setting!(
+        "install.releaseUrl",
+        String,
+        NoDefault,
+        App,
+        "Override the URL updates are downloaded from"
+    ),
+    setting!(
+        "integrations.hyper.disabled",
+        Bool,
+        Bool(false),
+        Integrations,
+        "Disable the Hyper integration"
+    ),
+    setting!(
+        "integrations.iterm.disabled",
+        Bool,
+        Bool(false),
+        Integrations,
+        "Disable the iTerm integration"
+    ),
+    setting!(
+        "integrations.terminal.disabled",
+        Bool,
+        Bool(false),
+        Integrations,
+        "Disable the Terminal integration"
+    ),
+    setting!(
+        "integrations.vscode.disabled",
+        Bool,
+        Bool(false),
+        Integrations,
+        "Disable the VSCode integration"
+    ),
+    setting!(
+        "mcp.initTimeout",
+        Int,
+        Int(5000),
+        Chat,
+        "Milliseconds to wait for MCP servers to load in interactive chats"
+    ),
+    setting!(
+        "mcp.loadedBefore",
+        Bool,
+        Bool(false),
+        Chat,
+        "Whether MCP servers have been loaded before"
+    ),
+    setting!(
+        "mcp.noInteractiveTimeout",
+        Int,
+        Int(30_000),
+        Chat,
+        "Milliseconds to wait for MCP servers to load in non-interactive chats"
+    ),
+    setting!(
+        "qterm.csi-u.enabled",
+        Bool,
+        Bool(false),
+        Qterm,
+        "Enable the CSI u keyboard protocol"
+    ),
+    setting!(
+        "qterm.path",
+        String,
+        NoDefault,
+        Qterm,
+        "Path of the qterm binary launched by the shell integrations"
+    ),
+    setting!(
+        "ssh.remote-prompt",
+        String,
+        Str("ask"),
+        Qterm,
+        "Whether to prompt to install on remote hosts"
+    )
+    .allowed_values(&["ask", "never"]),
+    setting!(
+        "ssh.remote-prompt.timeout",
+        Int,
+        Int(2000),
+        Qterm,
+        "Milliseconds to wait for a remote session before prompting"
+    ),
+    setting!("telemetry.enabled", Bool, Bool(true), Telemetry, "Send usage telemetry"),
+    setting!(
+        "telemetryClientId",
+        String,
+        NoDefault,
+        Telemetry,
+        "Client id of previous versions, used for telemetry"
+    ),
+];
+
+/// Get the definition of the setting `key`
+pub fn get(key: impl AsRef<str>) -> Option<&'static SettingDefinition> {
+    let key = key.as_ref();
+    SETTINGS.iter().find(|definition| definition.key == key)
+}
+
+/// Whether `key` is a setting that is either registered or has a dynamic prefix
+pub fn is_known(key: impl AsRef<str>) -> bool {
+    let key = key.as_ref();
+    get(key).is_some() || DYNAMIC_PREFIXES.iter().any(|prefix| key.starts_with(prefix))
+}
+
+/// Validate `value` if `key` is a registered setting
+pub fn validate(key: impl AsRef<str>, value: &Value) -> Result<()> {
+    match get(key) {
+        Some(definition) => definition.validate(value),
+        None => Ok(()),
+    }
+}
+
+/// Registered settings with a key close to `key`, most likely first
+pub fn suggestions(key: impl AsRef<str>) -> Vec<&'static str> {
+    let key = key.as_ref().to_lowercase();
+    let max_distance = (key.len() / 4).max(2);
+
+    let mut suggestions = SETTINGS
+        .iter()
+        .map(|definition| (edit_distance(&key, &definition.key.to_lowercase()), definition.key))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    suggestions.sort();
+    suggestions.into_iter().map(|(_, key)| key).collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_settings_sorted_and_unique() {
+        for window in SETTINGS.windows(2) {
+            assert!(window[0].key < window[1].key, "{} >= {}", window[0].key, window[1].key);
+        }
+    }
+
+    #[test]
+    fn test_settings_read_by_autocomplete_are_registered() {
+        let settings_ts = include_str!("../../../packages/api-bindings-wrappers/src/settings.ts");
+        let (_, enum_body) = settings_ts.split_once("export enum SETTINGS {").unwrap();
+        let (enum_body, _) = enum_body.split_once('}').unwrap();
+
+        let keys = enum_body
+            .lines()
+            .filter_map(|line| line.split_once(" = \"").map(|(_, key)| key.trim_end_matches("\",")))
+            .collect::<Vec<_>>();
+        assert!(keys.contains(&"autocomplete.developerMode"));
+
+        for key in keys {
+            assert!(get(key).is_some(), "{key} is not registered");
+        }
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        for definition in SETTINGS {
+            if let Some(default) = definition.default.to_value() {
+                definition.validate(&default).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        validate("inline.enabled", &json!(false)).unwrap();
+        validate("inline.enabled", &json!("false")).unwrap_err();
+        validate("autocomplete.height", &json!(200)).unwrap();
+        validate("autocomplete.height", &json!(1.5)).unwrap_err();
+        validate("inline.localProvider", &json!("primary")).unwrap();
+        validate("inline.localProvider", &json!("local")).unwrap_err();
+        validate("autocomplete.disableForCommands", &json!(["git"])).unwrap();
+        validate("autocomplete.disableForCommands", &json!([1])).unwrap_err();
+        validate("unknown.setting", &json!(1)).unwrap();
+    }
+
+    #[test]
+    fn test_parse_value() {
+        assert_eq!(get("autocomplete.height").unwrap().parse_value("200"), json!(200));
+        assert_eq!(get("inline.enabled").unwrap().parse_value("true"), json!(true));
+        assert_eq!(get("chat.defaultModel").unwrap().parse_value("123"), json!("123"));
+        assert_eq!(get("api.timeout").unwrap().parse_value("soon"), json!("soon"));
+    }
+
+    #[test]
+    fn test_is_known() {
+        assert!(is_known("chat.enableKnowledge"));
+        assert!(is_known("autocomplete.keybindings.control+r"));
+        assert!(!is_known("chat.enableKnowlege"));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(suggestions("chat.enableKnowlege"), vec!["chat.enableKnowledge"]);
+        assert_eq!(suggestions("inline.Enabled")[0], "inline.enabled");
+        assert!(suggestions("something.else.entirely").is_empty());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+}
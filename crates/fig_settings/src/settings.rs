@@ -13,6 +13,7 @@ use crate::{
     JsonStore,
     OldSettings,
     Result,
+    schema,
 };
 
 #[derive(Debug, Clone, Default)]
@@ -52,7 +53,12 @@ impl Settings {
         ))))
     }
 
+    /// Set `key` to `value`, values of registered settings are validated against the [`schema`]
     pub fn set_value(&self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Result<()> {
+        let key = key.into();
+        let value = value.into();
+        schema::validate(&key, &value)?;
+
         match &self.0 {
             inner::Inner::Real => {
                 let mut settings = OldSettings::load()?;
@@ -61,7 +67,7 @@ impl Settings {
                 Ok(())
             },
             inner::Inner::Fake(map) => {
-                map.lock()?.insert(key.into(), value.into());
+                map.lock()?.insert(key, value);
                 Ok(())
             },
         }
@@ -244,4 +250,16 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_set_value_validation() {
+        let settings = Settings::new_fake();
+
+        settings.set_value("inline.enabled", false).unwrap();
+        assert!(settings.set_value("inline.enabled", "false").is_err());
+        assert!(!settings.get_bool("inline.enabled").unwrap().unwrap());
+
+        assert!(settings.set_value("inline.localProvider", "local").is_err());
+        assert!(settings.get_value("inline.localProvider").unwrap().is_none());
+    }
 }
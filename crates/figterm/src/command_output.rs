@@ -0,0 +1,155 @@
+//! Output of the last commands run in the terminal, for clients of the figterm socket
+
+use std::collections::VecDeque;
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use alacritty_terminal::term::CommandInfo;
+use fig_proto::figterm::{
+    CommandOutput,
+    CommandOutputRequest,
+    CommandOutputResponse,
+};
+
+/// Number of commands whose output is kept
+const MAX_COMMANDS: usize = 20;
+
+/// Maximum length of the output kept for a command, in bytes
+const MAX_OUTPUT_LEN: usize = 16 * 1024;
+
+/// Ring buffer of the output of the last commands, most recent first
+#[derive(Debug, Default)]
+pub struct CommandOutputs {
+    commands: VecDeque<CommandOutput>,
+}
+
+impl CommandOutputs {
+    pub const fn new() -> Self {
+        Self {
+            commands: VecDeque::new(),
+        }
+    }
+
+    /// Record a command that finished, dropping the oldest one if the buffer is full
+    pub fn push(&mut self, command: &CommandInfo) {
+        let mut output = command.output.clone().unwrap_or_default();
+        let mut truncated = command.output_truncated;
+
+        // Keep the end of the output, where errors usually are
+        if output.len() > MAX_OUTPUT_LEN {
+            let mut start = output.len() - MAX_OUTPUT_LEN;
+            while !output.is_char_boundary(start) {
+                start += 1;
+            }
+            if let Some(newline) = output[start..].find('\n') {
+                start += newline + 1;
+            }
+            output.drain(..start);
+            truncated = true;
+        }
+
+        if self.commands.len() == MAX_COMMANDS {
+            self.commands.pop_back();
+        }
+        self.commands.push_front(CommandOutput {
+            command: command.command.clone(),
+            exit_code: command.exit_code,
+            cwd: command.cwd.clone(),
+            start_time: unix_millis(command.start_time),
+            end_time: unix_millis(command.end_time),
+            output,
+            truncated,
+        });
+    }
+
+    pub fn query(&self, request: &CommandOutputRequest) -> CommandOutputResponse {
+        let limit = request.limit.map_or(usize::MAX, |limit| limit as usize);
+        let commands = self
+            .commands
+            .iter()
+            .filter(|command| !request.failed_only || command.exit_code.is_some_and(|code| code != 0))
+            .take(limit)
+            .cloned()
+            .collect();
+
+        CommandOutputResponse { commands }
+    }
+}
+
+fn unix_millis(time: Option<SystemTime>) -> Option<u64> {
+    time?.duration_since(UNIX_EPOCH).ok()?.as_millis().try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command_info(command: &str, exit_code: Option<i32>, output: &str) -> CommandInfo {
+        CommandInfo {
+            command: Some(command.into()),
+            shell: Some("bash".into()),
+            pid: None,
+            session_id: None,
+            cwd: Some("/home/user".into()),
+            start_time: Some(SystemTime::now()),
+            end_time: Some(SystemTime::now()),
+            username: None,
+            exit_code,
+            output: Some(output.into()),
+            output_truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_query() {
+        let mut outputs = CommandOutputs::new();
+        outputs.push(&command_info(
+            "cargo build",
+            Some(101),
+            "error[E0308]: mismatched types",
+        ));
+        outputs.push(&command_info("ls", Some(0), "Cargo.toml"));
+        outputs.push(&command_info("vim", None, ""));
+
+        let response = outputs.query(&CommandOutputRequest::default());
+        let commands = response
+            .commands
+            .iter()
+            .map(|c| c.command.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(commands, vec![Some("vim"), Some("ls"), Some("cargo build")]);
+
+        let response = outputs.query(&CommandOutputRequest {
+            limit: Some(1),
+            failed_only: true,
+        });
+        assert_eq!(response.commands.len(), 1);
+        assert_eq!(response.commands[0].command.as_deref(), Some("cargo build"));
+        assert_eq!(response.commands[0].exit_code, Some(101));
+        assert_eq!(response.commands[0].output, "error[E0308]: mismatched types");
+        assert!(response.commands[0].end_time.is_some());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut outputs = CommandOutputs::new();
+        for i in 0..MAX_COMMANDS + 5 {
+            outputs.push(&command_info(&format!("echo {i}"), Some(0), ""));
+        }
+        let response = outputs.query(&CommandOutputRequest::default());
+        assert_eq!(response.commands.len(), MAX_COMMANDS);
+        assert_eq!(response.commands[0].command, Some(format!("echo {}", MAX_COMMANDS + 4)));
+
+        let mut output = "é line\n".repeat(MAX_OUTPUT_LEN / 4);
+        output.push_str("error");
+        outputs.push(&command_info("make", Some(2), &output));
+        let response = outputs.query(&CommandOutputRequest::default());
+        let command = &response.commands[0];
+        assert!(command.truncated);
+        assert!(command.output.len() <= MAX_OUTPUT_LEN);
+        assert!(command.output.starts_with("é line\n"));
+        assert!(command.output.ends_with("\nerror"));
+    }
+}
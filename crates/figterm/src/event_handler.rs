@@ -24,6 +24,7 @@ use crate::history::{
 };
 use crate::inline::on_prompt;
 use crate::{
+    COMMAND_OUTPUTS,
     INSERT_ON_NEW_CMD,
     MainLoopEvent,
     shell_state_to_context,
@@ -123,6 +124,8 @@ impl EventListener for EventHandler {
                     error!(%err, "Sender error");
                 }
 
+                COMMAND_OUTPUTS.lock().unwrap().push(command_info);
+
                 if let Err(err) = self.history_sender.send(HistoryCommand::Insert(command_info.clone())) {
                     error!(%err, "Sender error");
                 }
@@ -0,0 +1,201 @@
+//! Suggestions from the local shell history, ranked by frecency, the current working
+//! directory, exit status and the preceding command.
+
+use std::collections::HashMap;
+use std::time::{
+    Duration,
+    SystemTime,
+};
+
+use fig_settings::history::CommandInfo;
+
+const LOCAL_PROVIDER_SETTINGS_KEY: &str = "inline.localProvider";
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Multiplier for commands run in the current working directory
+const CWD_WEIGHT: f64 = 2.0;
+/// Multiplier for commands that followed the command run before the current one
+const PRECEDING_COMMAND_WEIGHT: f64 = 3.0;
+/// Multiplier for commands that failed, they are likely to have been mistyped
+const FAILED_WEIGHT: f64 = 0.25;
+
+/// How the local provider is used alongside the remote one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalProvider {
+    /// Suggest from history, and only ask the remote provider if there is no suggestion
+    Primary,
+    /// Suggest from history when the remote provider is unavailable or has no suggestion
+    Fallback,
+    /// Only use the remote provider
+    Disabled,
+}
+
+impl LocalProvider {
+    pub fn from_settings() -> Self {
+        match fig_settings::settings::get_string_or(LOCAL_PROVIDER_SETTINGS_KEY, "fallback".into()).as_str() {
+            "primary" => Self::Primary,
+            "disabled" => Self::Disabled,
+            _ => Self::Fallback,
+        }
+    }
+}
+
+/// Context of the command line a suggestion is made for
+#[derive(Debug, Clone, Default)]
+pub struct LocalContext<'a> {
+    pub cwd: Option<&'a str>,
+    pub session_id: Option<&'a str>,
+}
+
+/// Weight of a command run at `time`, more recent commands weigh more
+fn recency_weight(now: SystemTime, time: Option<SystemTime>) -> f64 {
+    match time.map(|time| now.duration_since(time).unwrap_or_default()) {
+        Some(age) if age < HOUR => 4.0,
+        Some(age) if age < DAY => 2.0,
+        Some(age) if age < WEEK => 1.0,
+        _ => 0.5,
+    }
+}
+
+/// Get the text to insert after `buffer`, from the history ordered from the most recent command
+pub fn insert_text(
+    history: &[CommandInfo],
+    buffer: &str,
+    context: &LocalContext<'_>,
+    now: SystemTime,
+) -> Option<String> {
+    if buffer.trim().is_empty() {
+        return None;
+    }
+
+    // The command run before the current command line in this session
+    let preceding_command = history
+        .iter()
+        .find(|info| context.session_id.is_some() && info.session_id.as_deref() == context.session_id)
+        .and_then(|info| info.command.as_deref());
+
+    let mut scores: HashMap<&str, (f64, usize)> = HashMap::new();
+    // The command that was run last in each session while going from the oldest to the most
+    // recent command
+    let mut last_in_session: HashMap<Option<&str>, &str> = HashMap::new();
+
+    for (index, info) in history.iter().enumerate().rev() {
+        let Some(command) = info.command.as_deref() else {
+            continue;
+        };
+        let previous = last_in_session.insert(info.session_id.as_deref(), command);
+
+        if command.len() <= buffer.len() || !command.starts_with(buffer) || command.contains('\n') {
+            continue;
+        }
+
+        let mut score = recency_weight(now, info.end_time.or(info.start_time));
+        if context.cwd.is_some() && info.cwd.as_deref() == context.cwd {
+            score *= CWD_WEIGHT;
+        }
+        if preceding_command.is_some() && previous == preceding_command {
+            score *= PRECEDING_COMMAND_WEIGHT;
+        }
+        if info.exit_code.is_some_and(|exit_code| exit_code != 0) {
+            score *= FAILED_WEIGHT;
+        }
+
+        // Lower indices are more recent, keep the most recent one to break ties
+        let entry = scores.entry(command).or_insert((0.0, index));
+        entry.0 += score;
+        entry.1 = entry.1.min(index);
+    }
+
+    scores
+        .into_iter()
+        .max_by(|(_, (a, a_index)), (_, (b, b_index))| a.total_cmp(b).then(b_index.cmp(a_index)))
+        .map(|(command, _)| command[buffer.len()..].to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(
+        command: &str,
+        cwd: &str,
+        exit_code: i32,
+        session_id: &str,
+        age: Duration,
+        now: SystemTime,
+    ) -> CommandInfo {
+        CommandInfo {
+            command: Some(command.into()),
+            cwd: Some(cwd.into()),
+            exit_code: Some(exit_code),
+            session_id: Some(session_id.into()),
+            end_time: Some(now - age),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_insert_text() {
+        let now = SystemTime::now();
+        let minutes = |n: u64| Duration::from_secs(n * 60);
+
+        // Most recent first
+        let history = vec![
+            command("cd repo", "/", 0, "a", minutes(1), now),
+            command("git push", "/other", 0, "b", minutes(2), now),
+            command("git status", "/repo", 0, "a", minutes(3), now),
+            command("git pull", "/repo", 0, "a", minutes(4), now),
+            command("git psuh", "/other", 1, "b", minutes(5), now),
+            command("cd repo", "/", 0, "a", minutes(6), now),
+            command("cargo build", "/repo", 0, "a", minutes(7), now),
+            command(
+                "git status",
+                "/old",
+                0,
+                "c",
+                Duration::from_secs(60 * 60 * 24 * 30),
+                now,
+            ),
+        ];
+
+        let context = LocalContext {
+            cwd: Some("/repo"),
+            session_id: Some("a"),
+        };
+
+        // "git pull" follows "cd repo" in session "a", which was the last command run
+        assert_eq!(insert_text(&history, "git ", &context, now).as_deref(), Some("pull"));
+
+        // Without the preceding command, the most used command in the cwd wins
+        let context = LocalContext {
+            cwd: Some("/repo"),
+            session_id: Some("d"),
+        };
+        assert_eq!(insert_text(&history, "git ", &context, now).as_deref(), Some("status"));
+
+        // Failed commands rank below successful ones
+        let context = LocalContext {
+            cwd: Some("/other"),
+            session_id: None,
+        };
+        assert_eq!(insert_text(&history, "git p", &context, now).as_deref(), Some("ush"));
+
+        // Only commands extending the buffer are suggested
+        assert_eq!(insert_text(&history, "git status", &context, now), None);
+        assert_eq!(insert_text(&history, "ls", &context, now), None);
+        assert_eq!(insert_text(&history, " ", &context, now), None);
+    }
+
+    #[test]
+    fn test_recency_weight() {
+        let now = SystemTime::now();
+        assert_eq!(recency_weight(now, Some(now)), 4.0);
+        assert_eq!(recency_weight(now, Some(now - 2 * HOUR)), 2.0);
+        assert_eq!(recency_weight(now, Some(now - 2 * DAY)), 1.0);
+        assert_eq!(recency_weight(now, Some(now - 2 * WEEK)), 0.5);
+        assert_eq!(recency_weight(now, None), 0.5);
+    }
+}
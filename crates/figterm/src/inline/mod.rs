@@ -1,4 +1,5 @@
 mod completion_cache;
+mod local;
 mod validate;
 
 use std::fmt::Write;
@@ -46,6 +47,10 @@ use tracing::{
 use validate::validate;
 
 use self::completion_cache::CompletionCache;
+use self::local::{
+    LocalContext,
+    LocalProvider,
+};
 use crate::history::{
     self,
     HistoryQueryParams,
@@ -53,6 +58,7 @@ use crate::history::{
 };
 
 const HISTORY_COUNT_DEFAULT: usize = 49;
+const LOCAL_HISTORY_COUNT: usize = 1000;
 const DEBOUNCE_DURATION_DEFAULT: Duration = Duration::from_millis(300);
 
 static INLINE_ENABLED: Mutex<bool> = Mutex::const_new(true);
@@ -112,6 +118,7 @@ impl TelemetryQueue {
                 suggested_chars_len,
                 number_of_recommendations,
                 latency,
+                shell,
                 ..
             } = item;
 
@@ -129,8 +136,7 @@ impl TelemetryQueue {
                         latency,
                         terminal: current_terminal().map(|s| s.internal_id().into_owned()),
                         terminal_version: current_terminal_version().map(Into::into),
-                        // The only supported shell currently is Zsh
-                        shell: Some(Shell::Zsh.as_str().into()),
+                        shell,
                         shell_version: None,
                     },
                 })
@@ -157,11 +163,77 @@ struct TelemetryQueueItem {
     suggested_chars_len: i32,
     number_of_recommendations: i32,
     latency: Duration,
+    shell: Option<String>,
+}
+
+/// Suggest from the local history
+async fn local_insert_text(
+    history_sender: &HistorySender,
+    buffer: &str,
+    cwd: Option<&str>,
+    session_id: &str,
+) -> Option<String> {
+    let (history_query_tx, history_query_rx) = flume::bounded(1);
+    if let Err(err) = history_sender
+        .send_async(history::HistoryCommand::Query(
+            HistoryQueryParams {
+                limit: LOCAL_HISTORY_COUNT,
+            },
+            history_query_tx,
+        ))
+        .await
+    {
+        error!(%err, "Failed to send history query");
+        return None;
+    }
+
+    let history = history_query_rx.recv_async().await.ok().flatten()?;
+    local::insert_text(
+        &history,
+        buffer,
+        &LocalContext {
+            cwd,
+            session_id: Some(session_id),
+        },
+        SystemTime::now(),
+    )
+}
+
+/// Suggest from the local history when the remote provider had no suggestion, if it is the fallback
+async fn fallback_insert_text(
+    local_provider: LocalProvider,
+    remote_insert_text: Option<String>,
+    history_sender: &HistorySender,
+    buffer: &str,
+    cwd: Option<&str>,
+    session_id: &str,
+) -> Option<String> {
+    match remote_insert_text {
+        None if local_provider == LocalProvider::Fallback => {
+            local_insert_text(history_sender, buffer, cwd, session_id).await
+        },
+        insert_text => insert_text,
+    }
+}
+
+async fn send_insert_text(response_tx: &Sender<FigtermResponseMessage>, insert_text: Option<String>) {
+    if let Err(err) = response_tx
+        .send_async(FigtermResponseMessage {
+            response: Some(FigtermResponse::InlineShellCompletion(InlineShellCompletionResponse {
+                insert_text,
+            })),
+        })
+        .await
+    {
+        error!(%err, "Failed to send inline_shell_completion completion");
+    }
 }
 
 pub async fn handle_request(
     figterm_request: InlineShellCompletionRequest,
-    _session_id: String,
+    session_id: String,
+    shell: Option<Shell>,
+    cwd: Option<String>,
     response_tx: Sender<FigtermResponseMessage>,
     history_sender: HistorySender,
 ) {
@@ -190,14 +262,34 @@ pub async fn handle_request(
         }
     }
 
+    let local_provider = LocalProvider::from_settings();
+    if local_provider == LocalProvider::Primary {
+        if let Some(insert_text) = local_insert_text(&history_sender, buffer, cwd.as_deref(), &session_id).await {
+            send_insert_text(&response_tx, Some(insert_text)).await;
+            return;
+        }
+    }
+
     // debounce requests
     let now = SystemTime::now();
     LAST_RECEIVED.lock().await.replace(now);
 
     let Ok(client) = Client::new().await else {
+        // The remote provider is unavailable, e.g. when logged out
+        let insert_text = fallback_insert_text(
+            local_provider,
+            None,
+            &history_sender,
+            buffer,
+            cwd.as_deref(),
+            &session_id,
+        )
+        .await;
+        send_insert_text(&response_tx, insert_text).await;
         return;
     };
 
+    let mut insert_text = None;
     for _ in 0..3 {
         tokio::time::sleep(*DEBOUNCE_DURATION).await;
         if *LAST_RECEIVED.lock().await == Some(now) {
@@ -241,7 +333,7 @@ pub async fn handle_request(
         };
 
         let Some(prompt) = prompt(&history, buffer) else {
-            return;
+            break;
         };
 
         let input = RecommendationsInput {
@@ -268,7 +360,7 @@ pub async fn handle_request(
             other => other,
         };
 
-        let insert_text = match response {
+        insert_text = match response {
             Ok(output) => {
                 let request_id = output.request_id.unwrap_or_default();
                 let session_id = output.session_id.unwrap_or_default();
@@ -308,6 +400,7 @@ pub async fn handle_request(
                     tokio::spawn({
                         let completion = completion.clone();
                         let buffer = buffer.to_owned();
+                        let shell = shell.map(|shell| shell.as_str().into());
                         async move {
                             let mut queue = TELEMETRY_QUEUE.lock().await;
                             queue.items.push(TelemetryQueueItem {
@@ -321,6 +414,7 @@ pub async fn handle_request(
                                 suggestion_state,
                                 edit_buffer_len: buffer.chars().count().try_into().ok(),
                                 buffer,
+                                shell,
                             });
                             // flush all but 4 messages, this is to retain messages that might have
                             // an accept waiting
@@ -340,26 +434,36 @@ pub async fn handle_request(
         };
 
         info!(?insert_text, "Got inline_shell_completion completion");
-
-        match response_tx
-            .send_async(FigtermResponseMessage {
-                response: Some(FigtermResponse::InlineShellCompletion(InlineShellCompletionResponse {
-                    insert_text,
-                })),
-            })
-            .await
-        {
-            Ok(()) => {},
-            Err(err) => {
-                // This means the user typed something else before we got a response
-                // We want to bump the debounce timer
-
-                error!(%err, "Failed to send inline_shell_completion completion");
-            },
-        }
-
         break;
     }
+
+    // e.g. when offline, still throttled after retrying, or the suggestion was discarded
+    let insert_text = fallback_insert_text(
+        local_provider,
+        insert_text,
+        &history_sender,
+        buffer,
+        cwd.as_deref(),
+        &session_id,
+    )
+    .await;
+
+    match response_tx
+        .send_async(FigtermResponseMessage {
+            response: Some(FigtermResponse::InlineShellCompletion(InlineShellCompletionResponse {
+                insert_text,
+            })),
+        })
+        .await
+    {
+        Ok(()) => {},
+        Err(err) => {
+            // This means the user typed something else before we got a response
+            // We want to bump the debounce timer
+
+            error!(%err, "Failed to send inline_shell_completion completion");
+        },
+    }
 }
 
 pub async fn handle_accept(figterm_request: InlineShellCompletionAcceptRequest, _session_id: String) {
@@ -478,6 +582,46 @@ mod tests {
         assert!(prompt(&history, "echo ").is_none());
     }
 
+    #[tokio::test]
+    async fn test_fallback_insert_text() {
+        let (history_sender, history_receiver) = flume::bounded(1);
+        tokio::spawn(async move {
+            while let Ok(command) = history_receiver.recv_async().await {
+                if let history::HistoryCommand::Query(_, sender) = command {
+                    let history = vec![CommandInfo {
+                        command: Some("git status".into()),
+                        ..Default::default()
+                    }];
+                    sender.send(Some(history)).unwrap();
+                }
+            }
+        });
+
+        let fallback = |local_provider, remote_insert_text: Option<&str>| {
+            let history_sender = history_sender.clone();
+            async move {
+                fallback_insert_text(
+                    local_provider,
+                    remote_insert_text.map(Into::into),
+                    &history_sender,
+                    "git ",
+                    None,
+                    "session",
+                )
+                .await
+            }
+        };
+
+        // Any remote failure falls back to the history
+        assert_eq!(fallback(LocalProvider::Fallback, None).await.as_deref(), Some("status"));
+        assert_eq!(
+            fallback(LocalProvider::Fallback, Some("stash")).await.as_deref(),
+            Some("stash")
+        );
+        assert_eq!(fallback(LocalProvider::Disabled, None).await, None);
+        assert_eq!(fallback(LocalProvider::Primary, None).await, None);
+    }
+
     #[ignore = "not in CI"]
     #[tokio::test]
     async fn test_inline_suggestion_prompt() {
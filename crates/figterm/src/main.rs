@@ -1,6 +1,7 @@
 #[cfg(target_os = "linux")]
 mod cleanup;
 pub mod cli;
+mod command_output;
 mod event_handler;
 pub mod history;
 pub mod inline;
@@ -108,6 +109,7 @@ use tracing::{
     warn,
 };
 
+use crate::command_output::CommandOutputs;
 use crate::event_handler::EventHandler;
 use crate::input::{
     InputEvent,
@@ -149,6 +151,7 @@ static EXPECTED_BUFFER: Mutex<String> = Mutex::new(String::new());
 
 static SHELL_ENVIRONMENT_VARIABLES: Mutex<Vec<EnvironmentVariable>> = Mutex::new(Vec::new());
 static SHELL_ALIAS: Mutex<Option<String>> = Mutex::new(None);
+static COMMAND_OUTPUTS: Mutex<CommandOutputs> = Mutex::new(CommandOutputs::new());
 
 static USER_ENABLED_SHELLS: LazyLock<Vec<String>> = LazyLock::new(|| {
     fig_settings::state::get("user.enabled-shells")
@@ -33,6 +33,7 @@ use fig_proto::remote::{
     clientbound,
     hostbound,
 };
+use fig_util::Shell;
 use fig_util::env_var::PROCESS_LAUNCHED_BY_Q;
 use flume::Sender;
 use tokio::process::Command;
@@ -48,6 +49,7 @@ use crate::history::HistorySender;
 use crate::interceptor::KeyInterceptor;
 use crate::pty::AsyncMasterPty;
 use crate::{
+    COMMAND_OUTPUTS,
     EXPECTED_BUFFER,
     INSERT_ON_NEW_CMD,
     INSERTION_LOCKED_AT,
@@ -262,6 +264,10 @@ pub async fn process_figterm_request(
 
             Ok(Some(response))
         },
+        FigtermRequest::CommandOutput(request) => {
+            let response = COMMAND_OUTPUTS.lock().unwrap().query(&request);
+            Ok(Some(FigtermResponse::CommandOutput(response)))
+        },
         FigtermRequest::InsertOnNewCmd(command) => {
             *INSERT_ON_NEW_CMD.lock().unwrap() = Some((command.text, command.bracketed, command.execute));
             Ok(None)
@@ -312,8 +318,21 @@ pub async fn process_figterm_message(
         Some(FigtermRequest::InlineShellCompletion(request)) => {
             let history_sender = history_sender.clone();
             let session_id = session_id.to_owned();
-
-            tokio::spawn(inline::handle_request(request, session_id, response_tx, history_sender));
+            let context = term.shell_state().get_context();
+            let shell = context.shell.as_deref().and_then(|shell| shell.parse::<Shell>().ok());
+            let cwd = context
+                .current_working_directory
+                .as_ref()
+                .map(|cwd| cwd.to_string_lossy().into_owned());
+
+            tokio::spawn(inline::handle_request(
+                request,
+                session_id,
+                shell,
+                cwd,
+                response_tx,
+                history_sender,
+            ));
         },
         Some(FigtermRequest::InlineShellCompletionAccept(request)) => {
             tokio::spawn(inline::handle_accept(request, session_id.to_owned()));
@@ -361,7 +380,7 @@ async fn send_figterm_response_hostbound(
                 nonce,
                 response: Some(match response {
                     FigtermResponse::Diagnostics(diagnostics) => Response::Diagnostics(diagnostics),
-                    FigtermResponse::InlineShellCompletion(_) => unreachable!(),
+                    FigtermResponse::InlineShellCompletion(_) | FigtermResponse::CommandOutput(_) => unreachable!(),
                 }),
             })),
         };
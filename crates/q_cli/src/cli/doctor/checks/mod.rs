@@ -3,9 +3,13 @@ mod fish_version;
 #[cfg(target_os = "linux")]
 pub mod linux;
 mod midway;
+mod nu_version;
+mod settings_schema;
 mod sshd_config;
 
 pub use bash_version::BashVersionCheck;
 pub use fish_version::FishVersionCheck;
 pub use midway::MidwayCheck;
+pub use nu_version::NuVersionCheck;
+pub use settings_schema::SettingsSchemaCheck;
 pub use sshd_config::SshdConfigCheck;
@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+
+use async_trait::async_trait;
+use eyre::Context;
+use fig_integrations::shell::{
+    NU_MIN_VERSION,
+    parse_nu_version,
+};
+use tokio::process::Command;
+
+use crate::cli::doctor::{
+    DoctorCheck,
+    DoctorError,
+};
+
+pub struct NuVersionCheck;
+
+#[async_trait]
+impl DoctorCheck for NuVersionCheck {
+    fn name(&self) -> Cow<'static, str> {
+        "Nushell is up to date".into()
+    }
+
+    async fn check(&self, _: &()) -> Result<(), DoctorError> {
+        if which::which("nu").is_err() {
+            // nushell is not installed, so we shouldn't check it
+            return Ok(());
+        }
+
+        let output = Command::new("nu")
+            .arg("--version")
+            .output()
+            .await
+            .context("failed getting nushell version")?;
+
+        let Some(version) = parse_nu_version(&String::from_utf8_lossy(&output.stdout)) else {
+            return Err(DoctorError::error("failed parsing nushell version"));
+        };
+
+        // The integration is not installed for older versions
+        if version < NU_MIN_VERSION {
+            return Err(DoctorError::error(format!(
+                "your nushell version is outdated (need at least {NU_MIN_VERSION}, found {version})"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::doctor::Platform;
+
+    #[tokio::test]
+    async fn test_nu_version_check() {
+        let check = NuVersionCheck;
+        let name = check.name();
+        let doctor_type = check.get_type(&(), Platform::current()).await;
+        let result = check.check(&()).await;
+        println!("{name}: {doctor_type:?} {result:?}");
+    }
+}
@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+
+use async_trait::async_trait;
+use fig_settings::{
+    JsonStore,
+    Map,
+    schema,
+};
+use fig_util::CLI_BINARY_NAME;
+
+use crate::cli::doctor::{
+    DoctorCheck,
+    DoctorError,
+};
+
+pub struct SettingsSchemaCheck;
+
+/// Unknown keys and invalid values in the settings file
+fn settings_issues(settings: &Map) -> Vec<String> {
+    settings
+        .iter()
+        .filter_map(|(key, value)| {
+            if !schema::is_known(key) {
+                let suggestions = schema::suggestions(key);
+                Some(match suggestions.first() {
+                    Some(suggestion) => format!("{key} is not a setting, did you mean {suggestion}?"),
+                    None => format!("{key} is not a setting"),
+                })
+            } else {
+                schema::validate(key, value).err().map(|err| err.to_string())
+            }
+        })
+        .collect()
+}
+
+#[async_trait]
+impl DoctorCheck for SettingsSchemaCheck {
+    fn name(&self) -> Cow<'static, str> {
+        "Settings are valid".into()
+    }
+
+    async fn check(&self, _: &()) -> Result<(), DoctorError> {
+        // A corrupted settings file is reported by the corruption check
+        let Ok(settings) = fig_settings::OldSettings::load() else {
+            return Ok(());
+        };
+
+        let issues = settings_issues(&settings.map());
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = String::from("Some settings are unknown or invalid:");
+        for issue in issues {
+            message.push_str(&format!("\n    - {issue}"));
+        }
+        message.push_str(&format!(
+            "\n  Run `{CLI_BINARY_NAME} settings list --all` to see all settings"
+        ));
+
+        Err(DoctorError::Warning(message.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_settings_issues() {
+        let settings = json!({
+            "inline.enabled": false,
+            "autocomplete.keybindings.control+r": "toggleHistoryMode",
+            "chat.enableKnowlege": true,
+            "inline.localProvider": "local",
+        });
+
+        assert_eq!(settings_issues(settings.as_object().unwrap()), vec![
+            "chat.enableKnowlege is not a setting, did you mean chat.enableKnowledge?".to_owned(),
+            "invalid value for inline.localProvider: expected one of primary, fallback, disabled, found \"local\""
+                .to_owned(),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_settings_schema_check() {
+        let check = SettingsSchemaCheck;
+        let name = check.name();
+        let result = check.check(&()).await;
+        println!("{name}: {result:?}");
+    }
+}
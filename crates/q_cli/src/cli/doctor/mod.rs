@@ -27,6 +27,8 @@ use checks::{
     BashVersionCheck,
     FishVersionCheck,
     MidwayCheck,
+    NuVersionCheck,
+    SettingsSchemaCheck,
     SshdConfigCheck,
 };
 use clap::Args;
@@ -722,11 +724,11 @@ impl DoctorCheck for InlineCheck {
     async fn get_type(&self, _: &(), _: Platform) -> DoctorCheckType {
         let shell = get_shell_context().await;
         let inline_enabled = fig_settings::settings::get_bool_or("inline.enabled", true);
-        let is_zsh = matches!(shell, Ok(Some(Shell::Zsh)));
+        let is_supported = matches!(shell, Ok(Some(Shell::Zsh | Shell::Bash | Shell::Fish | Shell::Nu)));
 
-        if is_zsh && inline_enabled {
+        if is_supported && inline_enabled {
             DoctorCheckType::NormalCheck
-        } else if !is_zsh {
+        } else if !is_supported {
             DoctorCheckType::NoCheck
         } else {
             DoctorCheckType::SoftCheck
@@ -2129,8 +2131,8 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
     })
     .ok();
 
-    let shell_integrations: Vec<_> = [Shell::Bash, Shell::Zsh, Shell::Fish]
-        .into_iter()
+    let shell_integrations: Vec<_> = Shell::all()
+        .iter()
         .map(|shell| shell.get_shell_integrations(&Env::new()))
         .collect::<Result<Vec<_>, fig_integrations::Error>>()?
         .into_iter()
@@ -2160,6 +2162,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
                 #[cfg(target_os = "windows")]
                 &WindowsConsoleCheck,
                 &SettingsCorruptionCheck,
+                &SettingsSchemaCheck,
                 &SshdConfigCheck,
                 &FigIntegrationsCheck,
                 // &SshIntegrationCheck,
@@ -2202,6 +2205,7 @@ pub async fn doctor_cli(all: bool, strict: bool) -> Result<ExitCode> {
                 &SystemVersionCheck,
                 &BashVersionCheck,
                 &FishVersionCheck,
+                &NuVersionCheck,
                 #[cfg(target_os = "macos")]
                 &ToolboxInstalledCheck,
             ],
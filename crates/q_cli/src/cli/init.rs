@@ -71,7 +71,7 @@ fn assign_shell_variable(shell: &Shell, name: impl Display, value: impl Display,
         (Shell::Bash | Shell::Zsh, true) => format!("export {name}=\"{value}\""),
         (Shell::Fish, false) => format!("set -g {name} \"{value}\""),
         (Shell::Fish, true) => format!("set -gx {name} \"{value}\""),
-        (Shell::Nu, _) => format!("let-env {name} = \"{value}\";"),
+        (Shell::Nu, _) => format!("$env.{name} = \"{value}\""),
     }
 }
 
@@ -86,7 +86,7 @@ fn guard_source(
     output.push(match shell {
         Shell::Bash | Shell::Zsh => format!("if [ -z \"${{{guard_var}}}\" ]; then").into(),
         Shell::Fish => format!("if test -z \"${guard_var}\"").into(),
-        Shell::Nu => format!("if env | any name == '{guard_var}' {{").into(),
+        Shell::Nu => format!("if ($env.{guard_var}? | is-empty) {{").into(),
     });
 
     let shell_var = assign_shell_variable(shell, guard_var, "1", export);
@@ -155,22 +155,22 @@ async fn shell_init(shell: &Shell, when: &When, rcfile: &Option<String>) -> Resu
     let inline_enabled = fig_settings::settings::get_bool_or(INLINE_ENABLED_SETTINGS_KEY, true);
 
     if let When::Post = when {
-        if !matches!(
-            (shell, rcfile.as_deref()),
-            (Shell::Zsh, Some("zprofile")) | (Shell::Bash, Some("profile" | "bash_profile"))
-        ) && fig_settings::state::get_bool_or("dotfiles.enabled", true)
-            && shell == &Shell::Zsh
-            && when == &When::Post
-            && inline_enabled
-            && !*IS_SNAPSHOT_TEST
-        {
-            to_source.push(guard_source(
-                shell,
-                false,
-                "Q_DOTFILES_SOURCED",
-                GuardAssignment::AfterSourcing,
-                fig_integrations::shell::inline_shell_completion_plugin::ZSH_SCRIPT,
-            ));
+        if let Some(inline_shell_completion) = shell.get_inline_shell_completion_source() {
+            if !matches!(
+                (shell, rcfile.as_deref()),
+                (Shell::Zsh, Some("zprofile")) | (Shell::Bash, Some("profile" | "bash_profile"))
+            ) && fig_settings::state::get_bool_or("dotfiles.enabled", true)
+                && inline_enabled
+                && !*IS_SNAPSHOT_TEST
+            {
+                to_source.push(guard_source(
+                    shell,
+                    false,
+                    "Q_DOTFILES_SOURCED",
+                    GuardAssignment::AfterSourcing,
+                    inline_shell_completion,
+                ));
+            }
         }
 
         // if stdin().is_tty() && env::var_os(PROCESS_LAUNCHED_BY_Q).is_none() {
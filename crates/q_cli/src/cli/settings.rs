@@ -1,12 +1,19 @@
+use std::ffi::OsStr;
 use std::process::ExitCode;
 
 use anstream::println;
+use clap::builder::{
+    PossibleValue,
+    StringValueParser,
+    TypedValueParser,
+};
 use clap::{
     ArgGroup,
     Args,
     Parser,
     Subcommand,
 };
+use crossterm::style::Stylize;
 use eyre::{
     Result,
     WrapErr,
@@ -17,6 +24,10 @@ use fig_ipc::local::open_ui_element;
 use fig_os_shim::Os;
 use fig_proto::local::UiElement;
 use fig_settings::JsonStore;
+use fig_settings::schema::{
+    self,
+    SettingDefinition,
+};
 use fig_util::{
     CLI_BINARY_NAME,
     directories,
@@ -47,6 +58,63 @@ pub enum SettingsSubcommands {
         #[arg(long, short, value_enum, default_value_t)]
         format: OutputFormat,
     },
+    /// List the settings that are set
+    List {
+        /// Also list the settings that are not set, with their defaults and descriptions
+        #[arg(long, short)]
+        all: bool,
+        /// Format of the output
+        #[arg(long, short, value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+}
+
+/// Accepts any key, but offers the registered settings for shell completion
+#[derive(Debug, Clone, Copy)]
+struct SettingKeyParser;
+
+impl TypedValueParser for SettingKeyParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(schema::SETTINGS.iter().map(|definition| {
+            PossibleValue::new(definition.key).help(definition.description)
+        })))
+    }
+}
+
+/// Error for a key that is neither registered nor set, with the registered keys close to it
+fn unknown_key_error(key: &str) -> eyre::Report {
+    let mut message = format!("Unknown setting {key}");
+    let suggestions = schema::suggestions(key);
+    if !suggestions.is_empty() {
+        message.push_str(&format!(", did you mean {}?", suggestions.join(" or ")));
+    }
+    message.push_str(&format!(
+        "\nRun `{CLI_BINARY_NAME} settings list --all` to see all settings"
+    ));
+    eyre::eyre!(message)
+}
+
+fn setting_json(definition: &SettingDefinition, value: Option<&serde_json::Value>) -> serde_json::Value {
+    json!({
+        "key": definition.key,
+        "type": definition.setting_type.to_string(),
+        "default": definition.default.to_value(),
+        "value": value,
+        "description": definition.description,
+        "allowedValues": definition.allowed_values,
+        "component": definition.component.to_string(),
+    })
 }
 
 #[derive(Debug, Args, PartialEq, Eq)]
@@ -57,6 +125,7 @@ pub struct SettingsArgs {
     #[command(subcommand)]
     cmd: Option<SettingsSubcommands>,
     /// key
+    #[arg(value_parser = SettingKeyParser, hide_possible_values = true)]
     key: Option<String>,
     /// value
     value: Option<String>,
@@ -89,7 +158,7 @@ impl SettingsArgs {
                     bail!("The EDITOR environment variable is not set")
                 }
             },
-            Some(SettingsSubcommands::All { format }) => {
+            Some(SettingsSubcommands::All { format } | SettingsSubcommands::List { all: false, format }) => {
                 let settings = fig_settings::OldSettings::load()?.map().clone();
 
                 match format {
@@ -106,6 +175,60 @@ impl SettingsArgs {
 
                 Ok(ExitCode::SUCCESS)
             },
+            Some(SettingsSubcommands::List { all: true, format }) => {
+                let settings = fig_settings::OldSettings::load()?.map().clone();
+
+                match format {
+                    OutputFormat::Plain => {
+                        for definition in schema::SETTINGS {
+                            match settings.get(definition.key) {
+                                Some(value) => println!("{} = {value}", definition.key.bold()),
+                                None => match definition.default.to_value() {
+                                    Some(default) => {
+                                        println!("{} = {}", definition.key.bold(), default.to_string().grey());
+                                    },
+                                    None => println!("{}", definition.key.bold()),
+                                },
+                            }
+
+                            let mut details = format!("{}, {}", definition.setting_type, definition.component);
+                            if let Some(default) = definition.default.to_value() {
+                                details.push_str(&format!(", default {default}"));
+                            }
+                            if !definition.allowed_values.is_empty() {
+                                details.push_str(&format!(", one of {}", definition.allowed_values.join(", ")));
+                            }
+                            println!("    {}", definition.description);
+                            println!("    {}", details.grey());
+                        }
+
+                        let unknown = settings
+                            .iter()
+                            .filter(|(key, _)| schema::get(key).is_none())
+                            .collect::<Vec<_>>();
+                        if !unknown.is_empty() {
+                            println!();
+                            println!("{}", "Other settings".bold());
+                            for (key, value) in unknown {
+                                println!("{key} = {value}");
+                            }
+                        }
+                    },
+                    OutputFormat::Json | OutputFormat::JsonPretty => {
+                        let list = schema::SETTINGS
+                            .iter()
+                            .map(|definition| setting_json(definition, settings.get(definition.key)))
+                            .collect::<Vec<_>>();
+                        if format == OutputFormat::Json {
+                            println!("{}", serde_json::to_string(&list)?);
+                        } else {
+                            println!("{}", serde_json::to_string_pretty(&list)?);
+                        }
+                    },
+                }
+
+                Ok(ExitCode::SUCCESS)
+            },
             None => match &self.key {
                 Some(key) => match (&self.value, self.delete) {
                     (None, false) => match fig_settings::settings::get_value(key)? {
@@ -121,6 +244,7 @@ impl SettingsArgs {
                             Ok(ExitCode::SUCCESS)
                         },
                         None => match self.format {
+                            OutputFormat::Plain if !schema::is_known(key) => Err(unknown_key_error(key)),
                             OutputFormat::Plain => Err(eyre::eyre!("No value associated with {key}")),
                             OutputFormat::Json | OutputFormat::JsonPretty => {
                                 println!("null");
@@ -129,7 +253,13 @@ impl SettingsArgs {
                         },
                     },
                     (Some(value_str), false) => {
-                        let value = serde_json::from_str(value_str).unwrap_or_else(|_| json!(value_str));
+                        let value = match schema::get(key) {
+                            Some(definition) => definition.parse_value(value_str),
+                            None if schema::is_known(key) => {
+                                serde_json::from_str(value_str).unwrap_or_else(|_| json!(value_str))
+                            },
+                            None => return Err(unknown_key_error(key)),
+                        };
                         fig_settings::settings::set_value(key, value)?;
                         Ok(ExitCode::SUCCESS)
                     },
@@ -192,3 +322,33 @@ impl SettingsArgs {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setting_key_parser() {
+        let possible_values = SettingKeyParser.possible_values().unwrap().collect::<Vec<_>>();
+        assert_eq!(possible_values.len(), schema::SETTINGS.len());
+        assert!(possible_values.iter().any(|value| value.get_name() == "inline.enabled"));
+
+        // Keys that are not registered are still accepted, e.g. globs for --delete
+        let cmd = clap::Command::new("settings");
+        assert_eq!(
+            SettingKeyParser
+                .parse_ref(&cmd, None, OsStr::new("autocomplete.*"))
+                .unwrap(),
+            "autocomplete.*"
+        );
+    }
+
+    #[test]
+    fn test_unknown_key_error() {
+        let message = unknown_key_error("chat.enableKnowlege").to_string();
+        assert!(message.contains("did you mean chat.enableKnowledge?"), "{message}");
+
+        let message = unknown_key_error("something.else.entirely").to_string();
+        assert!(!message.contains("did you mean"), "{message}");
+    }
+}
@@ -1,4 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 use std::path::{
     Path,
     PathBuf,
@@ -6,16 +9,21 @@ use std::path::{
 use std::sync::Arc;
 use std::time::SystemTime;
 
+use chrono::Utc;
+use serde_json::Value;
 use tokio::sync::{
     Mutex,
     RwLock,
     Semaphore,
+    SemaphorePermit,
     mpsc,
 };
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
+use crate::client::filter::PointFilter;
 use crate::client::semantic_context::SemanticContext;
+use crate::client::watcher::ContextWatcher;
 use crate::client::{
     embedder_factory,
     utils,
@@ -29,16 +37,24 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::processing::{
+    FileMatcher,
+    hash_file,
+    process_file,
+    walk_files,
+};
 use crate::types::{
     ContextId,
     DataPoint,
     IndexingJob,
+    IndexingOptions,
     KnowledgeContext,
     OperationHandle,
     OperationStatus,
     OperationType,
     ProgressInfo,
     ProgressStatus,
+    SearchFilter,
     SearchResults,
     SystemStatus,
 };
@@ -65,6 +81,8 @@ pub struct AsyncSemanticSearchClient {
     job_tx: mpsc::UnboundedSender<IndexingJob>,
     /// Active operations tracking
     pub active_operations: Arc<RwLock<HashMap<Uuid, OperationHandle>>>,
+    /// Scheduler of context updates, watching the contexts to keep fresh
+    watcher: Arc<ContextWatcher>,
 }
 
 /// Background worker for processing indexing jobs
@@ -77,6 +95,7 @@ struct BackgroundWorker {
     config: SemanticSearchConfig,
     base_dir: PathBuf,
     indexing_semaphore: Arc<Semaphore>,
+    watcher: Arc<ContextWatcher>,
 }
 
 const MAX_CONCURRENT_OPERATIONS: usize = 3;
@@ -133,6 +152,7 @@ impl AsyncSemanticSearchClient {
         let volatile_contexts = Arc::new(RwLock::new(HashMap::new()));
         let active_operations = Arc::new(RwLock::new(HashMap::new()));
         let (job_tx, job_rx) = mpsc::unbounded_channel();
+        let watcher = Arc::new(ContextWatcher::new(&job_tx, active_operations.clone()));
 
         // Start background worker - we'll need to create a new embedder for the worker
         let worker_embedder = embedder_factory::create_embedder(embedding_type)?;
@@ -145,6 +165,7 @@ impl AsyncSemanticSearchClient {
             config: config.clone(),
             base_dir: base_dir.clone(),
             indexing_semaphore: Arc::new(Semaphore::new(MAX_CONCURRENT_OPERATIONS)),
+            watcher: watcher.clone(),
         };
 
         tokio::spawn(worker.run());
@@ -157,11 +178,21 @@ impl AsyncSemanticSearchClient {
             config,
             job_tx,
             active_operations,
+            watcher,
         };
 
         // Load all persistent contexts
         client.load_persistent_contexts().await?;
 
+        // Resume watching the contexts to keep fresh
+        for context in client.contexts.read().await.values() {
+            if context.indexing.watch {
+                if let Err(e) = client.watcher.watch(context) {
+                    tracing::error!("Failed to watch context {}: {}", context.id, e);
+                }
+            }
+        }
+
         Ok(client)
     }
 
@@ -172,12 +203,28 @@ impl AsyncSemanticSearchClient {
         name: &str,
         description: &str,
         persistent: bool,
+    ) -> Result<(Uuid, CancellationToken)> {
+        self.add_context_from_path_with_options(path, name, description, persistent, IndexingOptions::default())
+            .await
+    }
+
+    /// Add a context from a path, indexing the files selected by `options` (async, cancellable)
+    pub async fn add_context_from_path_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        name: &str,
+        description: &str,
+        persistent: bool,
+        options: IndexingOptions,
     ) -> Result<(Uuid, CancellationToken)> {
         let path = path.as_ref();
         let canonical_path = path.canonicalize().map_err(|_e| {
             SemanticSearchError::InvalidPath(format!("Path does not exist or is not accessible: {}", path.display()))
         })?;
 
+        // Validate the patterns before queueing the job
+        FileMatcher::new(&canonical_path, &options)?;
+
         // Check for conflicts
         self.check_path_exists(&canonical_path).await?;
 
@@ -203,6 +250,7 @@ impl AsyncSemanticSearchClient {
             name: name.to_string(),
             description: description.to_string(),
             persistent,
+            options,
         };
 
         self.job_tx
@@ -212,6 +260,50 @@ impl AsyncSemanticSearchClient {
         Ok((operation_id, cancel_token))
     }
 
+    /// Update a context from its source path (async, cancellable)
+    ///
+    /// Only the files added or changed since the context was last indexed are re-indexed, and the
+    /// data points of deleted files are dropped.
+    pub async fn update_context(&self, context_id: &str) -> Result<(Uuid, CancellationToken)> {
+        let context = self.get_context_with_source(context_id).await?;
+        self.watcher.schedule_update(&context).await
+    }
+
+    /// Start or stop watching the source path of a context, such that it is updated in the
+    /// background whenever its files change
+    pub async fn set_watch(&self, context_id: &str, watch: bool) -> Result<()> {
+        let context = self.get_context_with_source(context_id).await?;
+        if watch {
+            self.watcher.watch(&context)?;
+        } else {
+            self.watcher.unwatch(context_id);
+        }
+
+        if let Some(context) = self.contexts.write().await.get_mut(context_id) {
+            context.indexing.watch = watch;
+        }
+        self.save_contexts_metadata_sync()
+            .await
+            .map_err(SemanticSearchError::OperationFailed)
+    }
+
+    async fn get_context_with_source(&self, context_id: &str) -> Result<KnowledgeContext> {
+        let context = self
+            .contexts
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| SemanticSearchError::ContextNotFound(context_id.to_string()))?;
+        if context.source_path.is_none() {
+            return Err(SemanticSearchError::InvalidArgument(format!(
+                "Context '{}' was not created from a path",
+                context.name
+            )));
+        }
+        Ok(context)
+    }
+
     /// Get all contexts (concurrent with indexing)
     pub async fn get_contexts(&self) -> Vec<KnowledgeContext> {
         // Try to get a read lock with timeout
@@ -235,6 +327,28 @@ impl AsyncSemanticSearchClient {
         &self,
         query_text: &str,
         result_limit: Option<usize>,
+    ) -> Result<Vec<(ContextId, SearchResults)>> {
+        self.search_filtered(query_text, result_limit, &SearchFilter::default())
+            .await
+    }
+
+    /// Search the contexts and the data points matching a filter (concurrent with indexing)
+    ///
+    /// # Arguments
+    ///
+    /// * `query_text` - The query
+    /// * `result_limit` - Maximum number of results per context
+    /// * `filter` - The contexts to search and the criteria the data points must match
+    ///
+    /// # Returns
+    ///
+    /// The results of every context with results, or an error if a context of the filter doesn't
+    /// exist or its path pattern is invalid
+    pub async fn search_filtered(
+        &self,
+        query_text: &str,
+        result_limit: Option<usize>,
+        filter: &SearchFilter,
     ) -> Result<Vec<(ContextId, SearchResults)>> {
         if query_text.is_empty() {
             return Err(SemanticSearchError::InvalidArgument(
@@ -242,6 +356,16 @@ impl AsyncSemanticSearchClient {
             ));
         }
 
+        let contexts = self.get_contexts().await;
+        if let Some(scope) = filter.missing_context(&contexts) {
+            return Err(SemanticSearchError::ContextNotFound(scope.to_string()));
+        }
+        let mut point_filters = HashMap::new();
+        for context in contexts.iter().filter(|context| filter.includes_context(context)) {
+            let point_filter = PointFilter::new(filter, context.source_path.as_deref())?;
+            point_filters.insert(context.id.clone(), point_filter);
+        }
+
         let effective_limit = result_limit.unwrap_or(self.config.default_results);
         let query_vector = self.embedder.embed(query_text)?;
 
@@ -262,8 +386,23 @@ impl AsyncSemanticSearchClient {
         let mut all_results = Vec::new();
 
         for (context_id, context) in volatile_contexts.iter() {
+            let Some(point_filter) = point_filters.get(context_id) else {
+                continue;
+            };
+            let is_match = point_filter
+                .as_ref()
+                .map(|point_filter| |point: &DataPoint| point_filter.is_match(point));
+
             if let Ok(context_guard) = context.try_lock() {
-                match context_guard.search(&query_vector, effective_limit) {
+                match context_guard.search_with_filter(
+                    self.config.search_mode,
+                    query_text,
+                    &query_vector,
+                    effective_limit,
+                    is_match
+                        .as_ref()
+                        .map(|is_match| is_match as &dyn Fn(&DataPoint) -> bool),
+                ) {
                     Ok(results) => {
                         if !results.is_empty() {
                             all_results.push((context_id.clone(), results));
@@ -281,9 +420,7 @@ impl AsyncSemanticSearchClient {
             if a.is_empty() || b.is_empty() {
                 return std::cmp::Ordering::Equal;
             }
-            a[0].distance
-                .partial_cmp(&b[0].distance)
-                .unwrap_or(std::cmp::Ordering::Equal)
+            b[0].score.partial_cmp(&a[0].score).unwrap_or(std::cmp::Ordering::Equal)
         });
 
         Ok(all_results)
@@ -473,6 +610,8 @@ impl AsyncSemanticSearchClient {
             contexts.len()
         };
 
+        self.watcher.unwatch_all();
+
         // Clear all contexts
         {
             let mut contexts = self.contexts.write().await;
@@ -553,16 +692,7 @@ impl AsyncSemanticSearchClient {
         operation_type: OperationType,
         cancel_token: CancellationToken,
     ) {
-        let handle = OperationHandle {
-            operation_type,
-            started_at: SystemTime::now(),
-            progress: Arc::new(Mutex::new(ProgressInfo::new())),
-            cancel_token,
-            task_handle: None,
-        };
-
-        let mut operations = self.active_operations.write().await;
-        operations.insert(operation_id, handle);
+        register_operation(&self.active_operations, operation_id, operation_type, cancel_token).await;
     }
 
     async fn load_persistent_contexts(&mut self) -> Result<()> {
@@ -621,6 +751,8 @@ impl AsyncSemanticSearchClient {
 
     /// Remove context by ID
     pub async fn remove_context_by_id(&self, context_id: &str) -> Result<()> {
+        self.watcher.unwatch(context_id);
+
         // Remove from contexts map
         {
             let mut contexts = self.contexts.write().await;
@@ -715,6 +847,31 @@ impl AsyncSemanticSearchClient {
     }
 }
 
+impl Drop for AsyncSemanticSearchClient {
+    fn drop(&mut self) {
+        self.watcher.unwatch_all();
+    }
+}
+
+/// Register an operation for tracking
+pub(crate) async fn register_operation(
+    active_operations: &RwLock<HashMap<Uuid, OperationHandle>>,
+    operation_id: Uuid,
+    operation_type: OperationType,
+    cancel_token: CancellationToken,
+) {
+    let handle = OperationHandle {
+        operation_type,
+        started_at: SystemTime::now(),
+        progress: Arc::new(Mutex::new(ProgressInfo::new())),
+        cancel_token,
+        task_handle: None,
+    };
+
+    let mut operations = active_operations.write().await;
+    operations.insert(operation_id, handle);
+}
+
 // Background Worker Implementation
 impl BackgroundWorker {
     async fn run(mut self) {
@@ -729,10 +886,14 @@ impl BackgroundWorker {
                     name,
                     description,
                     persistent,
+                    options,
                 } => {
-                    self.process_add_directory(id, path, name, description, persistent, cancel)
+                    self.process_add_directory(id, path, name, description, persistent, options, cancel)
                         .await;
                 },
+                IndexingJob::UpdateContext { id, cancel, context_id } => {
+                    self.process_update_context(id, context_id, cancel).await;
+                },
                 IndexingJob::Clear { id, cancel } => {
                     self.process_clear(id, cancel).await;
                 },
@@ -742,6 +903,7 @@ impl BackgroundWorker {
         tracing::info!("Background worker stopped");
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn process_add_directory(
         &self,
         operation_id: Uuid,
@@ -749,6 +911,7 @@ impl BackgroundWorker {
         name: String,
         description: String,
         persistent: bool,
+        options: IndexingOptions,
         cancel_token: CancellationToken,
     ) {
         tracing::info!("Processing AddDirectory job: {} -> {}", name, path.display());
@@ -758,15 +921,37 @@ impl BackgroundWorker {
             return;
         }
 
-        // Update status and acquire semaphore
+        let Some(_permit) = self.acquire_indexing_slot(operation_id).await else {
+            return;
+        };
+
+        // Perform actual indexing
+        let result = self
+            .perform_indexing(operation_id, path, name, description, persistent, options, cancel_token)
+            .await;
+
+        match result {
+            Ok(context_id) => {
+                tracing::info!("Successfully indexed context: {}", context_id);
+                self.mark_operation_completed(operation_id).await;
+            },
+            Err(e) => {
+                tracing::error!("Indexing failed: {}", e);
+                self.mark_operation_failed(operation_id, e).await;
+            },
+        }
+    }
+
+    /// Update status and acquire semaphore, marking the operation as failed if unavailable
+    async fn acquire_indexing_slot(&self, operation_id: Uuid) -> Option<SemaphorePermit<'_>> {
         self.update_operation_status(operation_id, "Waiting in queue...".to_string())
             .await;
 
-        let _permit = match self.indexing_semaphore.try_acquire() {
+        match self.indexing_semaphore.try_acquire() {
             Ok(permit) => {
                 self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
                     .await;
-                permit
+                Some(permit)
             },
             Err(_) => {
                 self.update_operation_status(
@@ -781,34 +966,19 @@ impl BackgroundWorker {
                     Ok(permit) => {
                         self.update_operation_status(operation_id, "Acquired slot, starting indexing...".to_string())
                             .await;
-                        permit
+                        Some(permit)
                     },
                     Err(_) => {
                         self.mark_operation_failed(operation_id, "Semaphore unavailable".to_string())
                             .await;
-                        return;
+                        None
                     },
                 }
             },
-        };
-
-        // Perform actual indexing
-        let result = self
-            .perform_indexing(operation_id, path, name, description, persistent, cancel_token)
-            .await;
-
-        match result {
-            Ok(context_id) => {
-                tracing::info!("Successfully indexed context: {}", context_id);
-                self.mark_operation_completed(operation_id).await;
-            },
-            Err(e) => {
-                tracing::error!("Indexing failed: {}", e);
-                self.mark_operation_failed(operation_id, e).await;
-            },
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn perform_indexing(
         &self,
         operation_id: Uuid,
@@ -816,6 +986,7 @@ impl BackgroundWorker {
         name: String,
         description: String,
         persistent: bool,
+        options: IndexingOptions,
         cancel_token: CancellationToken,
     ) -> std::result::Result<String, String> {
         if !path.exists() {
@@ -856,7 +1027,7 @@ impl BackgroundWorker {
         }
 
         // Count files and notify progress
-        let file_count = self.count_files_in_directory(&path, operation_id).await?;
+        let file_count = self.count_files_in_directory(&path, &options, operation_id).await?;
 
         // Check if file count exceeds the configured limit
         if file_count > config.max_files {
@@ -888,7 +1059,7 @@ impl BackgroundWorker {
 
         // Process files with cancellation checks
         let items = self
-            .process_directory_files(&path, file_count, operation_id, &cancel_token_clone)
+            .process_directory_files(&path, &options, file_count, operation_id, &cancel_token_clone)
             .await?;
 
         // Check cancellation before creating semantic context
@@ -932,6 +1103,7 @@ impl BackgroundWorker {
             &description,
             persistent,
             Some(path.to_string_lossy().to_string()),
+            options,
             semantic_context,
             file_count,
         )
@@ -940,6 +1112,171 @@ impl BackgroundWorker {
         Ok(context_id)
     }
 
+    async fn process_update_context(&self, operation_id: Uuid, context_id: ContextId, cancel_token: CancellationToken) {
+        tracing::info!("Processing UpdateContext job: {}", context_id);
+
+        if cancel_token.is_cancelled() {
+            self.mark_operation_cancelled(operation_id).await;
+            return;
+        }
+
+        let Some(_permit) = self.acquire_indexing_slot(operation_id).await else {
+            return;
+        };
+
+        match self.perform_update(operation_id, &context_id, &cancel_token).await {
+            Ok(summary) => {
+                tracing::info!("Successfully updated context {}: {}", context_id, summary);
+                self.mark_operation_completed(operation_id).await;
+            },
+            Err(e) => {
+                tracing::error!("Update failed: {}", e);
+                self.mark_operation_failed(operation_id, e).await;
+            },
+        }
+    }
+
+    /// Re-index the files of a context that were added or changed since it was last indexed, and
+    /// drop the data points of the files that were deleted
+    async fn perform_update(
+        &self,
+        operation_id: Uuid,
+        context_id: &str,
+        cancel_token: &CancellationToken,
+    ) -> std::result::Result<String, String> {
+        let context = self
+            .contexts
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| format!("Failed: Context '{}' not found", context_id))?;
+        let semantic_context = self
+            .volatile_contexts
+            .read()
+            .await
+            .get(context_id)
+            .cloned()
+            .ok_or_else(|| format!("Failed: Context '{}' is not loaded", context_id))?;
+        let path = context
+            .source_path
+            .as_ref()
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("Failed: Context '{}' was not created from a path", context.name))?;
+        if !path.exists() {
+            return Err(format!("Failed: Path '{}' does not exist", path.display()));
+        }
+
+        // Hash the files to index, which is much cheaper than embedding them again
+        self.update_operation_status(operation_id, "Checking for changed files...".to_string())
+            .await;
+        let options = context.indexing.clone();
+        let max_files = self.config.max_files;
+        let hash_cancel_token = cancel_token.clone();
+        let current_hashes = tokio::task::spawn_blocking(move || {
+            let mut hashes = HashMap::new();
+            for file in walk_files(&path, &options).map_err(|e| format!("Failed: {}", e))? {
+                if hash_cancel_token.is_cancelled() {
+                    return Err("Operation was cancelled while checking for changed files".to_string());
+                }
+                if hashes.len() == max_files {
+                    return Err(format!(
+                        "Failed: Directory contains more than {} files, which exceeds the maximum limit",
+                        max_files
+                    ));
+                }
+                // Skip files that fail to read, as indexing does
+                if let Ok(hash) = hash_file(&file) {
+                    hashes.insert(file.to_string_lossy().to_string(), (file, hash));
+                }
+            }
+            Ok(hashes)
+        })
+        .await
+        .map_err(|e| format!("Failed: Checking for changed files failed: {}", e))??;
+
+        let indexed_hashes = semantic_context.lock().await.file_hashes();
+        let changed_files = current_hashes
+            .iter()
+            .filter(|(key, (_, hash))| indexed_hashes.get(*key) != Some(hash))
+            .map(|(key, (file, hash))| (key.clone(), file.clone(), hash.clone()))
+            .collect::<Vec<_>>();
+        let deleted_files = indexed_hashes
+            .keys()
+            .filter(|key| !current_hashes.contains_key(*key))
+            .cloned()
+            .collect::<Vec<_>>();
+        let added_count = changed_files
+            .iter()
+            .filter(|(key, ..)| !indexed_hashes.contains_key(key))
+            .count();
+
+        if changed_files.is_empty() && deleted_files.is_empty() {
+            return Ok("no files changed".to_string());
+        }
+
+        // Process the added and changed files
+        let mut items = Vec::new();
+        for (i, (_, file, hash)) in changed_files.iter().enumerate() {
+            if cancel_token.is_cancelled() {
+                return Err("Operation was cancelled during file processing".to_string());
+            }
+            if let Ok(mut file_items) = Self::process_tracked_file(file, hash) {
+                items.append(&mut file_items);
+            }
+            if (i + 1) % 10 == 0 {
+                self.update_operation_progress(
+                    operation_id,
+                    (i + 1) as u64,
+                    changed_files.len() as u64,
+                    format!("Indexing changed files ({}/{})", i + 1, changed_files.len()),
+                )
+                .await;
+            }
+        }
+        let data_points = self
+            .embed_items(&items, &*self.embedder, operation_id, cancel_token)
+            .await?;
+
+        if cancel_token.is_cancelled() {
+            return Err("Cancelled: Operation was cancelled before saving".to_string());
+        }
+
+        self.update_operation_status(operation_id, "Building vector index...".to_string())
+            .await;
+        let stale_files = changed_files
+            .iter()
+            .map(|(key, ..)| key.clone())
+            .chain(deleted_files.iter().cloned())
+            .collect::<HashSet<_>>();
+        {
+            let mut semantic_context = semantic_context.lock().await;
+            semantic_context
+                .replace_files(&stale_files, data_points)
+                .map_err(|e| format!("Failed to update data points: {}", e))?;
+            if context.persistent {
+                semantic_context
+                    .save()
+                    .map_err(|e| format!("Failed to save context: {}", e))?;
+            }
+        }
+
+        if let Some(context) = self.contexts.write().await.get_mut(context_id) {
+            context.item_count = current_hashes.len();
+            context.updated_at = Utc::now();
+        }
+        if context.persistent {
+            self.save_contexts_metadata().await?;
+        }
+
+        Ok(format!(
+            "{} files added, {} changed, {} deleted",
+            added_count,
+            changed_files.len() - added_count,
+            deleted_files.len()
+        ))
+    }
+
     async fn process_clear(&self, operation_id: Uuid, cancel_token: CancellationToken) {
         tracing::info!("Processing Clear job");
 
@@ -968,6 +1305,8 @@ impl BackgroundWorker {
         let mut removed = 0;
 
         for (index, context) in contexts.iter().enumerate() {
+            self.watcher.unwatch(&context.id);
+
             // Check for cancellation before each context removal
             if cancel_token.is_cancelled() {
                 self.update_operation_status(
@@ -1168,11 +1507,12 @@ impl BackgroundWorker {
         description: &str,
         persistent: bool,
         source_path: Option<String>,
+        options: IndexingOptions,
         semantic_context: SemanticContext,
         item_count: usize,
     ) -> std::result::Result<(), String> {
         // Create the context metadata
-        let context = KnowledgeContext::new(
+        let mut context = KnowledgeContext::new(
             context_id.to_string(),
             name,
             description,
@@ -1180,6 +1520,14 @@ impl BackgroundWorker {
             source_path,
             item_count,
         );
+        context.indexing = options;
+
+        // Keep the context fresh if requested
+        if context.indexing.watch {
+            if let Err(e) = self.watcher.watch(&context) {
+                tracing::error!("Failed to watch context {}: {}", context_id, e);
+            }
+        }
 
         // Store in contexts map
         {
@@ -1205,32 +1553,21 @@ impl BackgroundWorker {
     async fn count_files_in_directory(
         &self,
         dir_path: &Path,
+        options: &IndexingOptions,
         operation_id: Uuid,
     ) -> std::result::Result<usize, String> {
         self.update_operation_status(operation_id, "Counting files...".to_string())
             .await;
 
-        // Use tokio::task::spawn_blocking to make the synchronous walkdir operation non-blocking
-        let dir_path = dir_path.to_path_buf();
+        // Use tokio::task::spawn_blocking to make the synchronous walk non-blocking
+        let files = walk_files(dir_path, options).map_err(|e| format!("Failed: {}", e))?;
         let active_operations = self.active_operations.clone();
 
         let count_result = tokio::task::spawn_blocking(move || {
             let mut count = 0;
             let mut checked = 0;
 
-            for _entry in walkdir::WalkDir::new(&dir_path)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.file_type().is_file())
-                .filter(|e| {
-                    // Skip hidden files
-                    !e.path()
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .is_some_and(|s| s.starts_with('.'))
-                })
-            {
+            for _file in files {
                 count += 1;
                 checked += 1;
 
@@ -1271,42 +1608,26 @@ impl BackgroundWorker {
     async fn process_directory_files(
         &self,
         dir_path: &Path,
+        options: &IndexingOptions,
         file_count: usize,
         operation_id: Uuid,
         cancel_token: &CancellationToken,
     ) -> std::result::Result<Vec<serde_json::Value>, String> {
-        use crate::processing::process_file;
-
         self.update_operation_status(operation_id, format!("Starting indexing ({} files)", file_count))
             .await;
 
         let mut processed_files = 0;
         let mut items = Vec::new();
 
-        for entry in walkdir::WalkDir::new(dir_path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
+        for path in walk_files(dir_path, options).map_err(|e| format!("Failed: {}", e))? {
             // Check for cancellation frequently
             if cancel_token.is_cancelled() {
                 return Err("Operation was cancelled during file processing".to_string());
             }
 
-            let path = entry.path();
-
-            // Skip hidden files
-            if path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .is_some_and(|s| s.starts_with('.'))
-            {
-                continue;
-            }
-
             // Process the file
-            match process_file(path) {
+            let processed = hash_file(&path).and_then(|hash| Self::process_tracked_file(&path, &hash));
+            match processed {
                 Ok(mut file_items) => items.append(&mut file_items),
                 Err(_) => continue, // Skip files that fail to process
             }
@@ -1328,6 +1649,37 @@ impl BackgroundWorker {
         Ok(items)
     }
 
+    /// Process a file into items tagged with its path, its modification time and the hash of its
+    /// content, such that updates can tell which items to replace when the file changes
+    fn process_tracked_file(path: &Path, hash: &str) -> Result<Vec<Value>> {
+        let path_str = path.to_string_lossy().to_string();
+        let modified_at = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(|modified| chrono::DateTime::<Utc>::from(modified).to_rfc3339());
+        let items = process_file(path)?
+            .into_iter()
+            .map(|item| {
+                let mut metadata = match item {
+                    Value::Object(metadata) => metadata,
+                    item => {
+                        let mut metadata = serde_json::Map::new();
+                        metadata.insert("text".to_string(), item);
+                        metadata
+                    },
+                };
+                metadata.insert("path".to_string(), Value::String(path_str.clone()));
+                metadata.insert("file_hash".to_string(), Value::String(hash.to_string()));
+                if let Some(modified_at) = &modified_at {
+                    metadata.insert("modified_at".to_string(), Value::String(modified_at.clone()));
+                }
+                Value::Object(metadata)
+            })
+            .collect();
+
+        Ok(items)
+    }
+
     async fn create_semantic_context_impl(
         &self,
         context_dir: &Path,
@@ -1347,7 +1699,32 @@ impl BackgroundWorker {
         let mut semantic_context = SemanticContext::new(context_dir.join("data.json"))
             .map_err(|e| format!("Failed to create semantic context: {}", e))?;
 
-        // Process items to data points with cancellation checks
+        let data_points = self.embed_items(items, embedder, operation_id, cancel_token).await?;
+
+        // Check for cancellation before building index
+        if cancel_token.is_cancelled() {
+            return Err("Operation was cancelled before building index".to_string());
+        }
+
+        self.update_operation_status(operation_id, "Building vector index...".to_string())
+            .await;
+
+        // Add the data points to the context
+        semantic_context
+            .add_data_points(data_points)
+            .map_err(|e| format!("Failed to add data points: {}", e))?;
+
+        Ok(semantic_context)
+    }
+
+    /// Process items to data points with cancellation checks
+    async fn embed_items(
+        &self,
+        items: &[serde_json::Value],
+        embedder: &dyn TextEmbedderTrait,
+        operation_id: Uuid,
+        cancel_token: &CancellationToken,
+    ) -> std::result::Result<Vec<DataPoint>, String> {
         let mut data_points = Vec::new();
         let total_items = items.len();
 
@@ -1374,20 +1751,7 @@ impl BackgroundWorker {
             data_points.push(data_point);
         }
 
-        // Check for cancellation before building index
-        if cancel_token.is_cancelled() {
-            return Err("Operation was cancelled before building index".to_string());
-        }
-
-        self.update_operation_status(operation_id, "Building vector index...".to_string())
-            .await;
-
-        // Add the data points to the context
-        semantic_context
-            .add_data_points(data_points)
-            .map_err(|e| format!("Failed to add data points: {}", e))?;
-
-        Ok(semantic_context)
+        Ok(data_points)
     }
 
     fn create_data_point_from_item(
@@ -1430,3 +1794,175 @@ impl BackgroundWorker {
             .map_err(|e| format!("Failed to save contexts metadata: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    async fn wait_for_operations(client: &AsyncSemanticSearchClient) {
+        for _ in 0..500 {
+            if client.active_operations.read().await.is_empty() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!(
+            "Operations did not complete: {:?}",
+            client.get_status_data().await.unwrap().operations
+        );
+    }
+
+    async fn indexed_files(client: &AsyncSemanticSearchClient, context_id: &str, root: &Path) -> Vec<String> {
+        let context = client.volatile_contexts.read().await.get(context_id).cloned().unwrap();
+        let mut files = context
+            .lock()
+            .await
+            .file_hashes()
+            .into_keys()
+            .map(|path| {
+                Path::new(&path)
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_update_context() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let source_dir = tempfile::tempdir().unwrap();
+        let root = source_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("target/out.txt"), "Build output").unwrap();
+        fs::write(root.join("a.txt"), "The first file").unwrap();
+        fs::write(root.join("b.txt"), "The second file").unwrap();
+        fs::write(root.join("c.txt"), "The third file").unwrap();
+
+        let client = AsyncSemanticSearchClient::with_config_and_embedding_type(
+            base_dir.path(),
+            SemanticSearchConfig::default(),
+            EmbeddingType::Mock,
+        )
+        .await
+        .unwrap();
+
+        client
+            .add_context_from_path(&root, "source", "Source files", true)
+            .await
+            .unwrap();
+        wait_for_operations(&client).await;
+        let context = client.get_contexts().await.pop().unwrap();
+        assert_eq!(context.item_count, 3);
+        assert_eq!(indexed_files(&client, &context.id, &root).await, vec![
+            "a.txt", "b.txt", "c.txt"
+        ]);
+
+        fs::write(root.join("a.txt"), "The first file, changed").unwrap();
+        fs::remove_file(root.join("b.txt")).unwrap();
+        fs::write(root.join("d.txt"), "The fourth file").unwrap();
+
+        client.update_context(&context.id).await.unwrap();
+        wait_for_operations(&client).await;
+        let context = client.get_contexts().await.pop().unwrap();
+        assert_eq!(context.item_count, 3);
+        assert_eq!(indexed_files(&client, &context.id, &root).await, vec![
+            "a.txt", "c.txt", "d.txt"
+        ]);
+
+        let results = client.search_all("changed", None).await.unwrap();
+        let texts = results
+            .iter()
+            .flat_map(|(_, results)| results.iter().filter_map(|result| result.text()))
+            .collect::<Vec<_>>();
+        assert!(texts.contains(&"The first file, changed"));
+        assert!(!texts.contains(&"The second file"));
+
+        assert!(client.update_context("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_filtered() {
+        let base_dir = tempfile::tempdir().unwrap();
+        let docs_dir = tempfile::tempdir().unwrap();
+        let src_dir = tempfile::tempdir().unwrap();
+        let docs = docs_dir.path().canonicalize().unwrap();
+        let src = src_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(docs.join("api")).unwrap();
+        fs::write(docs.join("api/client.md"), "The client sends requests").unwrap();
+        fs::write(docs.join("guide.txt"), "The client is configured in settings").unwrap();
+        fs::write(src.join("client.py"), "client = Client()").unwrap();
+
+        let client = AsyncSemanticSearchClient::with_config_and_embedding_type(
+            base_dir.path(),
+            SemanticSearchConfig::default(),
+            EmbeddingType::Mock,
+        )
+        .await
+        .unwrap();
+        client
+            .add_context_from_path(&docs, "API docs", "Documentation", true)
+            .await
+            .unwrap();
+        client
+            .add_context_from_path(&src, "source", "Source files", true)
+            .await
+            .unwrap();
+        wait_for_operations(&client).await;
+
+        let search = |filter: SearchFilter| {
+            let client = &client;
+            async move {
+                let results = client.search_filtered("client", None, &filter).await?;
+                let mut paths = results
+                    .iter()
+                    .flat_map(|(_, results)| results.iter().filter_map(|result| result.path()))
+                    .map(|path| Path::new(path).file_name().unwrap().to_string_lossy().to_string())
+                    .collect::<Vec<_>>();
+                paths.sort();
+                Ok::<_, SemanticSearchError>(paths)
+            }
+        };
+
+        assert_eq!(search(SearchFilter::default()).await.unwrap(), vec![
+            "client.md",
+            "client.py",
+            "guide.txt"
+        ]);
+        let filter = SearchFilter {
+            contexts: vec!["api docs".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(search(filter).await.unwrap(), vec!["client.md", "guide.txt"]);
+        let filter = SearchFilter {
+            path: Some("api/*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(filter).await.unwrap(), vec!["client.md"]);
+        let filter = SearchFilter {
+            file_type: Some("py".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(filter).await.unwrap(), vec!["client.py"]);
+        let filter = SearchFilter {
+            modified_after: Some(Utc::now() + chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(search(filter).await.unwrap().is_empty());
+
+        let filter = SearchFilter {
+            contexts: vec!["missing".to_string()],
+            ..Default::default()
+        };
+        assert!(matches!(
+            search(filter).await,
+            Err(SemanticSearchError::ContextNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,191 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use chrono::{
+    DateTime,
+    Utc,
+};
+use globset::{
+    Glob,
+    GlobMatcher,
+};
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::types::{
+    DataPoint,
+    SearchFilter,
+};
+
+/// Matcher of the data points of a context against the criteria of a [SearchFilter]
+#[derive(Debug)]
+pub(crate) struct PointFilter {
+    /// Source path of the context, which relative path patterns are matched against
+    root: Option<PathBuf>,
+    path: Option<GlobMatcher>,
+    file_type: Option<String>,
+    modified_after: Option<DateTime<Utc>>,
+}
+
+impl PointFilter {
+    /// Create a matcher of the data points of a context
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The filter to match the data points against
+    /// * `source_path` - Source path of the context, if any
+    ///
+    /// # Returns
+    ///
+    /// The matcher, `None` if the filter doesn't restrict the data points, or an error if the
+    /// path pattern is invalid
+    pub(crate) fn new(filter: &SearchFilter, source_path: Option<&str>) -> Result<Option<Self>> {
+        if !filter.filters_data_points() {
+            return Ok(None);
+        }
+
+        let path = filter
+            .path
+            .as_deref()
+            .map(|pattern| {
+                Glob::new(pattern)
+                    .map(|glob| glob.compile_matcher())
+                    .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid pattern '{}': {}", pattern, e)))
+            })
+            .transpose()?;
+
+        Ok(Some(Self {
+            root: source_path.map(PathBuf::from),
+            path,
+            file_type: filter.file_type.clone(),
+            modified_after: filter.modified_after,
+        }))
+    }
+
+    /// Check whether a data point matches every criterion of the filter
+    pub(crate) fn is_match(&self, point: &DataPoint) -> bool {
+        let payload_str = |key: &str| point.payload.get(key).and_then(|v| v.as_str());
+
+        if let Some(pattern) = &self.path {
+            let Some(path) = payload_str("path").map(Path::new) else {
+                return false;
+            };
+            let relative = self.root.as_deref().and_then(|root| path.strip_prefix(root).ok());
+            if !pattern.is_match(path) && !relative.is_some_and(|relative| pattern.is_match(relative)) {
+                return false;
+            }
+        }
+
+        if let Some(file_type) = &self.file_type {
+            let matches_type = ["file_type", "language"]
+                .into_iter()
+                .any(|key| payload_str(key).is_some_and(|value| value.eq_ignore_ascii_case(file_type)));
+            if !matches_type {
+                return false;
+            }
+        }
+
+        if let Some(modified_after) = self.modified_after {
+            let modified_at = payload_str("modified_at").and_then(|value| DateTime::parse_from_rfc3339(value).ok());
+            if modified_at.is_none_or(|modified_at| modified_at <= modified_after) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::Value;
+
+    use super::*;
+
+    fn data_point(path: &str, file_type: &str, language: Option<&str>, modified_at: Option<&str>) -> DataPoint {
+        let mut payload = HashMap::from([
+            ("path".to_string(), Value::String(path.to_string())),
+            ("file_type".to_string(), Value::String(file_type.to_string())),
+        ]);
+        if let Some(language) = language {
+            payload.insert("language".to_string(), Value::String(language.to_string()));
+        }
+        if let Some(modified_at) = modified_at {
+            payload.insert("modified_at".to_string(), Value::String(modified_at.to_string()));
+        }
+        DataPoint {
+            id: 0,
+            payload,
+            vector: vec![],
+        }
+    }
+
+    #[test]
+    fn test_point_filter() {
+        let main = data_point(
+            "/repo/src/main.rs",
+            "Code",
+            Some("rs"),
+            Some("2025-06-01T12:00:00+00:00"),
+        );
+        let readme = data_point("/repo/README.md", "Markdown", None, None);
+
+        let filter = SearchFilter {
+            contexts: vec!["repo".to_string()],
+            ..Default::default()
+        };
+        assert!(PointFilter::new(&filter, Some("/repo")).unwrap().is_none());
+
+        let filter = SearchFilter {
+            path: Some("src/**/*.rs".to_string()),
+            ..Default::default()
+        };
+        let matcher = PointFilter::new(&filter, Some("/repo")).unwrap().unwrap();
+        assert!(matcher.is_match(&main));
+        assert!(!matcher.is_match(&readme));
+
+        let filter = SearchFilter {
+            path: Some("*.md".to_string()),
+            ..Default::default()
+        };
+        let matcher = PointFilter::new(&filter, None).unwrap().unwrap();
+        assert!(!matcher.is_match(&main));
+        assert!(matcher.is_match(&readme));
+
+        let filter = SearchFilter {
+            file_type: Some("markdown".to_string()),
+            ..Default::default()
+        };
+        let matcher = PointFilter::new(&filter, Some("/repo")).unwrap().unwrap();
+        assert!(!matcher.is_match(&main));
+        assert!(matcher.is_match(&readme));
+
+        let filter = SearchFilter {
+            file_type: Some("RS".to_string()),
+            modified_after: Some("2025-01-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let matcher = PointFilter::new(&filter, Some("/repo")).unwrap().unwrap();
+        assert!(matcher.is_match(&main));
+        assert!(!matcher.is_match(&readme));
+
+        let filter = SearchFilter {
+            modified_after: Some("2025-07-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let matcher = PointFilter::new(&filter, Some("/repo")).unwrap().unwrap();
+        assert!(!matcher.is_match(&main));
+
+        let filter = SearchFilter {
+            path: Some("[".to_string()),
+            ..Default::default()
+        };
+        assert!(PointFilter::new(&filter, Some("/repo")).is_err());
+    }
+}
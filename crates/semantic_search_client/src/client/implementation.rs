@@ -25,11 +25,15 @@ use crate::error::{
     Result,
     SemanticSearchError,
 };
-use crate::processing::process_file;
+use crate::processing::{
+    process_file,
+    walk_files,
+};
 use crate::types::{
     ContextId,
     ContextMap,
     DataPoint,
+    IndexingOptions,
     KnowledgeContext,
     ProgressStatus,
     SearchResults,
@@ -467,25 +471,9 @@ impl SemanticSearchClient {
         let mut processed_files = 0;
         let mut items = Vec::new();
 
-        for entry in walkdir::WalkDir::new(dir_path)
-            .follow_links(true)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.file_type().is_file())
-        {
-            let path = entry.path();
-
-            // Skip hidden files
-            if path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .is_some_and(|s| s.starts_with('.'))
-            {
-                continue;
-            }
-
+        for path in walk_files(dir_path, &IndexingOptions::default())? {
             // Process the file
-            match process_file(path) {
+            match process_file(&path) {
                 Ok(mut file_items) => items.append(&mut file_items),
                 Err(_) => continue, // Skip files that fail to process
             }
@@ -759,6 +747,7 @@ impl SemanticSearchClient {
 
         // Use the configured default_results if limit is None
         let effective_limit = result_limit.unwrap_or_else(|| config::get_config().default_results);
+        let search_mode = config::get_config().search_mode;
 
         // Generate an embedding for the query
         let query_vector = self.embedder.embed(query_text)?;
@@ -771,7 +760,7 @@ impl SemanticSearchClient {
                 SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e))
             })?;
 
-            match context_guard.search(&query_vector, effective_limit) {
+            match context_guard.search_with_mode(search_mode, query_text, &query_vector, effective_limit) {
                 Ok(results) => {
                     if !results.is_empty() {
                         all_results.push((context_id.clone(), results));
@@ -791,9 +780,7 @@ impl SemanticSearchClient {
             if b.is_empty() {
                 return std::cmp::Ordering::Less;
             }
-            a[0].distance
-                .partial_cmp(&b[0].distance)
-                .unwrap_or(std::cmp::Ordering::Equal)
+            b[0].score.partial_cmp(&a[0].score).unwrap_or(std::cmp::Ordering::Equal)
         });
 
         Ok(all_results)
@@ -845,7 +832,12 @@ impl SemanticSearchClient {
             .lock()
             .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?;
 
-        context_guard.search(&query_vector, effective_limit)
+        context_guard.search_with_mode(
+            config::get_config().search_mode,
+            query_text,
+            &query_vector,
+            effective_limit,
+        )
     }
 
     /// Get all contexts
@@ -898,8 +890,7 @@ impl SemanticSearchClient {
             .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to acquire lock on context: {}", e)))?;
 
         // Save the data to the persistent directory
-        let data_path = persistent_dir.join("data.json");
-        utils::save_json_to_file(&data_path, context_guard.get_data_points())?;
+        context_guard.save_as(&persistent_dir.join("data.json"))?;
 
         // Create the context metadata
         let context_meta = KnowledgeContext::new(
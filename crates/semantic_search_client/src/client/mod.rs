@@ -2,12 +2,18 @@
 mod async_implementation;
 /// Factory for creating embedders
 pub mod embedder_factory;
+/// Matching of data points against search filters
+mod filter;
 /// Client implementation for semantic search operations
 mod implementation;
 /// Semantic context implementation for search operations
 pub mod semantic_context;
+/// Binary on-disk format of the data points of semantic contexts
+mod storage;
 /// Utility functions for semantic search operations
 pub mod utils;
+/// Scheduling of context updates, when requested or when their files change
+mod watcher;
 
 // Re-export types for external use
 pub use async_implementation::AsyncSemanticSearchClient;
@@ -1,27 +1,48 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
 use std::fs::{
     self,
     File,
 };
-use std::io::{
-    BufReader,
-    BufWriter,
+use std::io::BufReader;
+use std::path::{
+    Path,
+    PathBuf,
 };
-use std::path::PathBuf;
 
+use crate::client::storage;
 use crate::error::Result;
-use crate::index::VectorIndex;
+use crate::index::{
+    BM25Index,
+    VectorIndex,
+    reciprocal_rank_fusion,
+};
 use crate::types::{
     DataPoint,
+    SearchMode,
     SearchResult,
 };
 
+/// Number of candidates retrieved by each index for every result of a hybrid search, such that
+/// items ranked lower by one of the indexes can still make it into the fused results
+const HYBRID_CANDIDATES_PER_RESULT: usize = 4;
+
+/// Maximum number of data points matching a filter that are compared to the query exhaustively,
+/// as the graph of the vector index connects few of them when most data points are filtered out
+const EXACT_SEARCH_MAX_POINTS: usize = 2048;
+
 /// A semantic context containing data points and a vector index
 pub struct SemanticContext {
     /// The data points stored in the index
     pub(crate) data_points: Vec<DataPoint>,
     /// The vector index for fast approximate nearest neighbor search
     index: Option<VectorIndex>,
-    /// Path to save/load the data points
+    /// The lexical index of the text of the data points
+    lexical_index: Option<BM25Index>,
+    /// Path the data points were saved to as JSON by previous versions, next to which they are
+    /// now saved in the binary format of [storage]
     data_path: PathBuf,
 }
 
@@ -37,30 +58,90 @@ impl SemanticContext {
         let mut context = Self {
             data_points: Vec::new(),
             index: None,
+            lexical_index: None,
             data_path: data_path.clone(),
         };
 
-        // Load data points if the file exists
-        if data_path.exists() {
+        let binary_path = binary_path(&data_path);
+        if binary_path.exists() {
+            let stored = storage::read_data_points(&binary_path)?;
+            context.data_points = stored.data_points;
+
+            // Load the persisted vector index rather than building it again
+            let index = stored.index_id.and_then(|index_id| {
+                let dir = data_path.parent().unwrap_or(Path::new("."));
+                VectorIndex::load(dir, &index_basename(&data_path, index_id))
+                    .inspect_err(|e| tracing::warn!("Rebuilding vector index of {}: {}", binary_path.display(), e))
+                    .ok()
+            });
+            match index {
+                Some(index) if index.len() == context.data_points.len() => {
+                    context.index = Some(index);
+                    context.rebuild_lexical_index();
+                },
+                _ if !context.data_points.is_empty() => context.rebuild_index()?,
+                _ => {},
+            }
+        } else if data_path.exists() {
+            // Migrate data points saved as JSON by previous versions
             let file = File::open(&data_path)?;
             let reader = BufReader::new(file);
             context.data_points = serde_json::from_reader(reader)?;
-        }
+            if !context.data_points.is_empty() {
+                context.rebuild_index()?;
+            }
 
-        // If we have data points, rebuild the index
-        if !context.data_points.is_empty() {
-            context.rebuild_index()?;
+            match context.save() {
+                Ok(()) => {
+                    tracing::info!("Migrated {} to {}", data_path.display(), binary_path.display());
+                    fs::remove_file(&data_path)?;
+                },
+                Err(e) => tracing::warn!("Failed to migrate {}: {}", data_path.display(), e),
+            }
         }
 
         Ok(context)
     }
 
-    /// Save data points to disk
+    /// Save data points and the vector index to disk
     pub fn save(&self) -> Result<()> {
-        // Save the data points as JSON
-        let file = File::create(&self.data_path)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer(writer, &self.data_points)?;
+        self.save_as(&self.data_path)
+    }
+
+    /// Save data points and the vector index next to the given path, e.g. to persist a volatile
+    /// context
+    pub fn save_as(&self, data_path: &Path) -> Result<()> {
+        let dir = data_path.parent().unwrap_or(Path::new("."));
+        fs::create_dir_all(dir)?;
+
+        // The index is saved under a new name, such that the data points never refer to an index
+        // that is partially written or out of date
+        let index_id = match &self.index {
+            Some(index) if !index.is_empty() => {
+                let index_id = uuid::Uuid::new_v4().as_u64_pair().0.max(1);
+                index.save(dir, &index_basename(data_path, index_id))?;
+                Some(index_id)
+            },
+            _ => None,
+        };
+        storage::write_data_points(&binary_path(data_path), &self.data_points, index_id)?;
+
+        // Remove the indexes saved previously
+        let prefix = format!("{}-", file_stem(data_path));
+        let current = index_id.map(|index_id| index_basename(data_path, index_id));
+        for entry in fs::read_dir(dir)?.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(basename) = name
+                .strip_suffix(".hnsw.graph")
+                .or_else(|| name.strip_suffix(".hnsw.data"))
+                .or_else(|| name.strip_suffix(".hnsw.sha256"))
+            else {
+                continue;
+            };
+            if basename.starts_with(&prefix) && current.as_deref() != Some(basename) {
+                fs::remove_file(entry.path())?;
+            }
+        }
 
         Ok(())
     }
@@ -77,10 +158,18 @@ impl SemanticContext {
 
         // Set the new index
         self.index = Some(index);
+        self.rebuild_lexical_index();
 
         Ok(())
     }
 
+    /// Rebuild the lexical index from the current data points, which isn't persisted as it is
+    /// much cheaper to build than the vector index
+    fn rebuild_lexical_index(&mut self) {
+        let texts = self.data_points.iter().map(point_text).collect::<Vec<_>>();
+        self.lexical_index = Some(BM25Index::new(&texts));
+    }
+
     /// Add data points to the context
     pub fn add_data_points(&mut self, data_points: Vec<DataPoint>) -> Result<usize> {
         // Store the count before extending the data points
@@ -116,6 +205,9 @@ impl SemanticContext {
         // Add only the points in the specified range to the index
         for i in start_idx..end_idx {
             index.insert(&self.data_points[i].vector, i);
+            if let Some(lexical_index) = &mut self.lexical_index {
+                lexical_index.insert(point_text(&self.data_points[i]), i);
+            }
         }
 
         Ok(())
@@ -143,8 +235,216 @@ impl SemanticContext {
         Ok(search_results)
     }
 
+    /// Search for the items most relevant to the given query, retrieved according to `mode`
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - How to retrieve the items
+    /// * `query_text` - The query, used by the lexical index
+    /// * `query_vector` - The embedding of the query, used by the vector index
+    /// * `limit` - Maximum number of results to return
+    pub fn search_with_mode(
+        &self,
+        mode: SearchMode,
+        query_text: &str,
+        query_vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_with_filter(mode, query_text, query_vector, limit, None)
+    }
+
+    /// Search for the items most relevant to the given query among the items accepted by a filter,
+    /// retrieved according to `mode`
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - How to retrieve the items
+    /// * `query_text` - The query, used by the lexical index
+    /// * `query_vector` - The embedding of the query, used by the vector index
+    /// * `limit` - Maximum number of results to return
+    /// * `filter` - Whether to consider a data point, all data points are considered if `None`
+    pub fn search_with_filter(
+        &self,
+        mode: SearchMode,
+        query_text: &str,
+        query_vector: &[f32],
+        limit: usize,
+        filter: Option<&dyn Fn(&DataPoint) -> bool>,
+    ) -> Result<Vec<SearchResult>> {
+        let (Some(index), Some(lexical_index)) = (&self.index, &self.lexical_index) else {
+            return Ok(Vec::new()); // Return empty results if no index
+        };
+
+        // IDs of the data points accepted by the filter, in ascending order
+        let allowed = filter.map(|filter| {
+            (0..self.data_points.len())
+                .filter(|id| filter(&self.data_points[*id]))
+                .collect::<Vec<_>>()
+        });
+        let allowed = allowed.as_deref();
+        if allowed.is_some_and(|allowed| allowed.is_empty()) {
+            return Ok(Vec::new());
+        }
+
+        let search_lexical = |limit| match allowed {
+            Some(allowed) => lexical_index.search_filter(query_text, limit, |id| allowed.binary_search(&id).is_ok()),
+            None => lexical_index.search(query_text, limit),
+        };
+        let lexical_results = match mode {
+            SearchMode::Semantic => {
+                let search_results = self
+                    .search_vectors(index, query_vector, limit, allowed)
+                    .into_iter()
+                    .map(|(id, distance)| SearchResult::new(self.data_points[id].clone(), distance))
+                    .collect();
+                return Ok(search_results);
+            },
+            SearchMode::Lexical => search_lexical(limit),
+            SearchMode::Hybrid => search_lexical(limit * HYBRID_CANDIDATES_PER_RESULT),
+        };
+        let lexical_scores = lexical_results.iter().copied().collect::<HashMap<_, _>>();
+
+        let ranked = match mode {
+            SearchMode::Hybrid => {
+                let vector_ranking = self
+                    .search_vectors(index, query_vector, limit * HYBRID_CANDIDATES_PER_RESULT, allowed)
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>();
+                let lexical_ranking = lexical_results.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+                reciprocal_rank_fusion(&[&vector_ranking, &lexical_ranking])
+            },
+            _ => lexical_results,
+        };
+
+        let search_results = ranked
+            .into_iter()
+            .take(limit)
+            .map(|(id, score)| {
+                let point = self.data_points[id].clone();
+                let distance = cosine_distance(query_vector, &point.vector);
+                SearchResult {
+                    point,
+                    distance,
+                    score,
+                    lexical_score: lexical_scores.get(&id).copied(),
+                }
+            })
+            .collect();
+
+        Ok(search_results)
+    }
+
+    /// Search the vector index for the nearest neighbors of a vector, among the given data points
+    /// if any
+    fn search_vectors(
+        &self,
+        index: &VectorIndex,
+        query_vector: &[f32],
+        limit: usize,
+        allowed: Option<&[usize]>,
+    ) -> Vec<(usize, f32)> {
+        match allowed {
+            None => index.search(query_vector, limit, 100),
+            Some(allowed) if allowed.len() <= EXACT_SEARCH_MAX_POINTS => {
+                let mut results = allowed
+                    .iter()
+                    .map(|id| (*id, cosine_distance(query_vector, &self.data_points[*id].vector)))
+                    .collect::<Vec<_>>();
+                results.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+                results.truncate(limit);
+                results
+            },
+            Some(allowed) => index.search_filter(query_vector, limit, 100, allowed),
+        }
+    }
+
     /// Get the data points for serialization
     pub fn get_data_points(&self) -> &Vec<DataPoint> {
         &self.data_points
     }
+
+    /// Get the hash of every file the data points were extracted from, as of when it was indexed
+    ///
+    /// Files indexed before their hashes were recorded have an empty hash, such that they are
+    /// considered changed.
+    pub fn file_hashes(&self) -> HashMap<String, String> {
+        self.data_points
+            .iter()
+            .filter_map(|point| {
+                let path = point_path(point)?;
+                let hash = point
+                    .payload
+                    .get("file_hash")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                Some((path.to_string(), hash.to_string()))
+            })
+            .collect()
+    }
+
+    /// Replace the data points extracted from the given files, e.g. because they changed or were
+    /// deleted, with new data points
+    ///
+    /// The remaining data points keep their embeddings, only the indexes are rebuilt.
+    ///
+    /// # Arguments
+    ///
+    /// * `paths` - Paths of the files whose data points to remove
+    /// * `data_points` - Data points to add, whose IDs are reassigned
+    ///
+    /// # Returns
+    ///
+    /// The number of data points removed
+    pub fn replace_files(&mut self, paths: &HashSet<String>, data_points: Vec<DataPoint>) -> Result<usize> {
+        let count = self.data_points.len();
+        self.data_points
+            .retain(|point| !point_path(point).is_some_and(|path| paths.contains(path)));
+        let removed = count - self.data_points.len();
+
+        self.data_points.extend(data_points);
+        for (id, point) in self.data_points.iter_mut().enumerate() {
+            point.id = id;
+        }
+        self.rebuild_index()?;
+
+        Ok(removed)
+    }
+}
+
+/// Path of the binary file of the data points of a context
+fn binary_path(data_path: &Path) -> PathBuf {
+    data_path.with_extension("bin")
+}
+
+/// Base name of the files of a vector index saved next to the data points of a context
+fn index_basename(data_path: &Path, index_id: u64) -> String {
+    format!("{}-{:016x}", file_stem(data_path), index_id)
+}
+
+fn file_stem(data_path: &Path) -> String {
+    data_path
+        .file_stem()
+        .map_or_else(|| "data".to_string(), |stem| stem.to_string_lossy().to_string())
+}
+
+/// The path of the file a data point was extracted from
+fn point_path(point: &DataPoint) -> Option<&str> {
+    point.payload.get("path").and_then(|v| v.as_str())
+}
+
+/// The text of a data point, as indexed by the lexical index
+fn point_text(point: &DataPoint) -> &str {
+    point.payload.get("text").and_then(|v| v.as_str()).unwrap_or_default()
+}
+
+/// Cosine distance between two vectors, as computed by the vector index
+fn cosine_distance(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 1.0;
+    }
+    1.0 - dot / (norm_a * norm_b)
 }
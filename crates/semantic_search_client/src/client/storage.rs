@@ -0,0 +1,236 @@
+//! Binary on-disk format of the data points of semantic contexts
+//!
+//! All integers and floats are little endian. A file is laid out as:
+//!
+//! | Offset            | Content                                                   |
+//! |-------------------|-----------------------------------------------------------|
+//! | 0                 | Magic number `SSCX`                                       |
+//! | 4                 | Format version (u32)                                      |
+//! | 8                 | Dimension of the vectors (u32)                            |
+//! | 12                | Reserved (u32)                                            |
+//! | 16                | Number of data points (u64)                               |
+//! | 24                | ID of the persisted vector index, 0 if none (u64)         |
+//! | 32                | Offset of the payloads (u64)                              |
+//! | 40                | Length of the payloads (u64)                              |
+//! | 48                | IDs of the data points (u64 each)                         |
+//! | 48 + 8 * count    | Vectors of the data points, back to back (f32 each)       |
+//! | payloads offset   | Payloads of the data points, as a JSON array              |
+//!
+//! The vectors are decoded from their binary representation, while the payloads are still parsed as
+//! JSON.
+
+use std::collections::HashMap;
+use std::fs::{
+    self,
+    File,
+};
+use std::io::{
+    BufWriter,
+    Write,
+};
+use std::path::Path;
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::types::DataPoint;
+
+/// Magic number at the start of the files
+const MAGIC: &[u8; 4] = b"SSCX";
+
+/// Version of the format, to be incremented on incompatible changes
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// Length of the header preceding the IDs of the data points
+const HEADER_LEN: usize = 48;
+
+/// Data points read from a file
+pub struct StoredDataPoints {
+    /// The data points, in the order they were written
+    pub data_points: Vec<DataPoint>,
+    /// ID of the vector index persisted along with the data points, if any
+    pub index_id: Option<u64>,
+}
+
+/// Write data points to a file, replacing it atomically
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+/// * `data_points` - The data points, whose vectors must all have the same dimension
+/// * `index_id` - ID of the vector index persisted along with the data points, if any
+pub fn write_data_points(path: &Path, data_points: &[DataPoint], index_id: Option<u64>) -> Result<()> {
+    let dimension = data_points.first().map_or(0, |point| point.vector.len());
+    if data_points.iter().any(|point| point.vector.len() != dimension) {
+        return Err(SemanticSearchError::InvalidArgument(
+            "All vectors must have the same dimension".to_string(),
+        ));
+    }
+
+    let payloads = data_points.iter().map(|point| &point.payload).collect::<Vec<_>>();
+    let payloads = serde_json::to_vec(&payloads)?;
+    let payloads_offset = HEADER_LEN + data_points.len() * (8 + dimension * 4);
+
+    // Write to a temporary file first, such that a crash can't leave a truncated file behind
+    let tmp_path = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&(dimension as u32).to_le_bytes())?;
+    writer.write_all(&0u32.to_le_bytes())?;
+    writer.write_all(&(data_points.len() as u64).to_le_bytes())?;
+    writer.write_all(&index_id.unwrap_or(0).to_le_bytes())?;
+    writer.write_all(&(payloads_offset as u64).to_le_bytes())?;
+    writer.write_all(&(payloads.len() as u64).to_le_bytes())?;
+    for point in data_points {
+        writer.write_all(&(point.id as u64).to_le_bytes())?;
+    }
+    for point in data_points {
+        for value in &point.vector {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+    writer.write_all(&payloads)?;
+    writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Read data points from a file written by [write_data_points]
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+///
+/// # Returns
+///
+/// The data points, or an error if the file is corrupt or was written by a newer version
+pub fn read_data_points(path: &Path) -> Result<StoredDataPoints> {
+    let bytes = fs::read(path)?;
+    let bytes = &bytes[..];
+
+    let corrupt = |reason: &str| {
+        SemanticSearchError::SerializationError(format!("Corrupt context file {}: {}", path.display(), reason))
+    };
+    let u32_at = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+
+    if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+        return Err(corrupt("not a context file"));
+    }
+    let version = u32_at(4);
+    if version > FORMAT_VERSION {
+        return Err(SemanticSearchError::SerializationError(format!(
+            "Context file {} was written by a newer version (format {})",
+            path.display(),
+            version
+        )));
+    }
+    let dimension = u32_at(8) as usize;
+    let count = u64_at(16) as usize;
+    let index_id = u64_at(24);
+    let payloads_offset = u64_at(32) as usize;
+    let payloads_len = u64_at(40) as usize;
+
+    let vectors_offset = count
+        .checked_mul(8)
+        .and_then(|len| len.checked_add(HEADER_LEN))
+        .ok_or_else(|| corrupt("invalid number of data points"))?;
+    let vectors_len = count
+        .checked_mul(dimension)
+        .and_then(|len| len.checked_mul(4))
+        .ok_or_else(|| corrupt("invalid dimension"))?;
+    if vectors_offset.checked_add(vectors_len) != Some(payloads_offset)
+        || payloads_offset.checked_add(payloads_len) != Some(bytes.len())
+    {
+        return Err(corrupt("invalid offsets"));
+    }
+
+    let payloads: Vec<HashMap<String, serde_json::Value>> =
+        serde_json::from_slice(&bytes[payloads_offset..payloads_offset + payloads_len])?;
+    if payloads.len() != count {
+        return Err(corrupt("number of payloads does not match the number of data points"));
+    }
+
+    let ids = bytes[HEADER_LEN..vectors_offset]
+        .chunks_exact(8)
+        .map(|id| u64::from_le_bytes(id.try_into().unwrap()) as usize);
+    let mut vectors = bytes[vectors_offset..payloads_offset]
+        .chunks_exact(4)
+        .map(|value| f32::from_le_bytes(value.try_into().unwrap()));
+    let data_points = ids
+        .zip(payloads)
+        .map(|(id, payload)| DataPoint {
+            id,
+            payload,
+            vector: vectors.by_ref().take(dimension).collect(),
+        })
+        .collect();
+
+    Ok(StoredDataPoints {
+        data_points,
+        index_id: (index_id != 0).then_some(index_id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data_point(id: usize, text: &str, vector: Vec<f32>) -> DataPoint {
+        let mut payload = HashMap::new();
+        payload.insert("text".to_string(), serde_json::Value::String(text.to_string()));
+        DataPoint { id, payload, vector }
+    }
+
+    #[test]
+    fn test_write_and_read_data_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+
+        let data_points = vec![
+            data_point(0, "first", vec![0.1, -0.2, 0.3]),
+            data_point(7, "second", vec![1.0, f32::MIN_POSITIVE, -4.5]),
+        ];
+        write_data_points(&path, &data_points, Some(42)).unwrap();
+
+        let stored = read_data_points(&path).unwrap();
+        assert_eq!(stored.index_id, Some(42));
+        assert_eq!(stored.data_points.len(), 2);
+        for (read, written) in stored.data_points.iter().zip(&data_points) {
+            assert_eq!(read.id, written.id);
+            assert_eq!(read.payload, written.payload);
+            assert_eq!(read.vector, written.vector);
+        }
+
+        write_data_points(&path, &[], None).unwrap();
+        let stored = read_data_points(&path).unwrap();
+        assert!(stored.data_points.is_empty());
+        assert_eq!(stored.index_id, None);
+
+        let mismatched = vec![data_point(0, "a", vec![0.1]), data_point(1, "b", vec![0.1, 0.2])];
+        assert!(write_data_points(&path, &mismatched, None).is_err());
+    }
+
+    #[test]
+    fn test_read_corrupt_data_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+
+        fs::write(&path, b"[]").unwrap();
+        assert!(read_data_points(&path).is_err());
+
+        write_data_points(&path, &[data_point(0, "text", vec![0.5; 4])], None).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        fs::write(&path, &bytes).unwrap();
+        assert!(read_data_points(&path).is_err());
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        assert!(read_data_points(&path).is_err());
+    }
+}
@@ -7,7 +7,11 @@ use std::path::{
 use uuid::Uuid;
 
 use crate::error::Result;
-use crate::types::ProgressStatus;
+use crate::processing::walk_files;
+use crate::types::{
+    IndexingOptions,
+    ProgressStatus,
+};
 
 /// Create a context directory based on persistence setting
 ///
@@ -64,26 +68,7 @@ where
     }
 
     // Count files first to provide progress information
-    let mut file_count = 0;
-    for entry in walkdir::WalkDir::new(dir_path)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Skip hidden files
-        if path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .is_some_and(|s| s.starts_with('.'))
-        {
-            continue;
-        }
-
-        file_count += 1;
-    }
+    let file_count = walk_files(dir_path, &IndexingOptions::default())?.count();
 
     Ok(file_count)
 }
@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{
+    Arc,
+    Mutex as StdMutex,
+};
+use std::time::Duration;
+
+use notify::{
+    Event,
+    EventKind,
+    RecommendedWatcher,
+    RecursiveMode,
+    Watcher,
+};
+use tokio::sync::{
+    RwLock,
+    mpsc,
+};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::client::async_implementation::register_operation;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::processing::FileMatcher;
+use crate::types::{
+    ContextId,
+    IndexingJob,
+    KnowledgeContext,
+    OperationHandle,
+    OperationType,
+};
+
+/// How long the files of a watched directory must be left alone before the context is updated,
+/// such that e.g. switching branches results in a single update
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Schedules updates of contexts on the background worker, either on request or when the files
+/// of their source path change
+pub(crate) struct ContextWatcher {
+    /// Background job processor, which must not be kept alive by the watcher
+    job_tx: mpsc::WeakUnboundedSender<IndexingJob>,
+    /// Active operations tracking
+    active_operations: Arc<RwLock<HashMap<Uuid, OperationHandle>>>,
+    /// Cancellation tokens of the watches, by context ID
+    watches: StdMutex<HashMap<ContextId, CancellationToken>>,
+}
+
+impl ContextWatcher {
+    pub(crate) fn new(
+        job_tx: &mpsc::UnboundedSender<IndexingJob>,
+        active_operations: Arc<RwLock<HashMap<Uuid, OperationHandle>>>,
+    ) -> Self {
+        Self {
+            job_tx: job_tx.downgrade(),
+            active_operations,
+            watches: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    /// Schedule the update of a context, re-indexing only the files that changed
+    pub(crate) async fn schedule_update(&self, context: &KnowledgeContext) -> Result<(Uuid, CancellationToken)> {
+        let job_tx = self
+            .job_tx
+            .upgrade()
+            .ok_or_else(|| SemanticSearchError::OperationFailed("Background worker unavailable".to_string()))?;
+
+        let operation_id = Uuid::new_v4();
+        let cancel_token = CancellationToken::new();
+
+        register_operation(
+            &self.active_operations,
+            operation_id,
+            OperationType::Updating {
+                name: context.name.clone(),
+                path: context.source_path.clone().unwrap_or_default(),
+            },
+            cancel_token.clone(),
+        )
+        .await;
+
+        let job = IndexingJob::UpdateContext {
+            id: operation_id,
+            cancel: cancel_token.clone(),
+            context_id: context.id.clone(),
+        };
+        job_tx
+            .send(job)
+            .map_err(|_send_error| SemanticSearchError::OperationFailed("Background worker unavailable".to_string()))?;
+
+        Ok((operation_id, cancel_token))
+    }
+
+    /// Watch the source path of a context, scheduling an update whenever its files change
+    ///
+    /// Watching a context that is already watched restarts the watch.
+    pub(crate) fn watch(self: &Arc<Self>, context: &KnowledgeContext) -> Result<()> {
+        let root = context.source_path.as_ref().map(PathBuf::from).ok_or_else(|| {
+            SemanticSearchError::InvalidArgument(format!("Context '{}' has no source path to watch", context.name))
+        })?;
+        let matcher = FileMatcher::new(&root, &context.indexing)?;
+
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                let _ = event_tx.send(event);
+            }
+        })
+        .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to watch {}: {}", root.display(), e)))?;
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to watch {}: {}", root.display(), e)))?;
+
+        let cancel_token = CancellationToken::new();
+        if let Some(previous) = self
+            .watches
+            .lock()
+            .unwrap()
+            .insert(context.id.clone(), cancel_token.clone())
+        {
+            previous.cancel();
+        }
+
+        tracing::info!("Watching {} for context {}", root.display(), context.id);
+        tokio::spawn(Arc::clone(self).run(context.clone(), watcher, matcher, event_rx, cancel_token));
+
+        Ok(())
+    }
+
+    /// Stop watching the source path of a context
+    ///
+    /// # Returns
+    ///
+    /// `true` if the context was watched, `false` otherwise
+    pub(crate) fn unwatch(&self, context_id: &str) -> bool {
+        match self.watches.lock().unwrap().remove(context_id) {
+            Some(cancel_token) => {
+                cancel_token.cancel();
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Stop watching the source paths of all contexts
+    pub(crate) fn unwatch_all(&self) {
+        for (_, cancel_token) in self.watches.lock().unwrap().drain() {
+            cancel_token.cancel();
+        }
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        context: KnowledgeContext,
+        // Dropping the watcher stops the events
+        _watcher: RecommendedWatcher,
+        matcher: FileMatcher,
+        mut events: mpsc::UnboundedReceiver<Event>,
+        cancel_token: CancellationToken,
+    ) {
+        loop {
+            // Wait for a change to one of the indexed files
+            tokio::select! {
+                _ = cancel_token.cancelled() => break,
+                event = events.recv() => match event {
+                    Some(event) if is_relevant(&event, &matcher) => {},
+                    Some(_) => continue,
+                    None => break,
+                },
+            }
+
+            // Wait for the changes to settle
+            loop {
+                tokio::select! {
+                    _ = cancel_token.cancelled() => return,
+                    event = tokio::time::timeout(WATCH_DEBOUNCE, events.recv()) => match event {
+                        Ok(Some(_)) => {},
+                        Ok(None) => return,
+                        Err(_) => break,
+                    },
+                }
+            }
+
+            tracing::debug!("Files of context {} changed, scheduling an update", context.id);
+            if let Err(e) = self.schedule_update(&context).await {
+                tracing::warn!("Failed to schedule the update of context {}: {}", context.id, e);
+            }
+        }
+
+        tracing::info!("Stopped watching context {}", context.id);
+    }
+}
+
+/// Whether an event changed one of the indexed files
+fn is_relevant(event: &Event, matcher: &FileMatcher) -> bool {
+    !matches!(event.kind, EventKind::Access(_)) && event.paths.iter().any(|path| matcher.is_match(path))
+}
@@ -16,6 +16,8 @@ use serde::{
     Serialize,
 };
 
+use crate::types::SearchMode;
+
 /// Main configuration structure for the semantic search client.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SemanticSearchConfig {
@@ -39,6 +41,10 @@ pub struct SemanticSearchConfig {
 
     /// Maximum number of files allowed for indexing (default: 5000)
     pub max_files: usize,
+
+    /// How search results are retrieved (default: hybrid)
+    #[serde(default)]
+    pub search_mode: SearchMode,
 }
 
 impl SemanticSearchConfig {
@@ -83,6 +89,7 @@ impl Default for SemanticSearchConfig {
             timeout: 30000, // 30 seconds
             base_dir: get_default_base_dir(),
             max_files: 5000, // Default limit of 5000 files
+            search_mode: SearchMode::default(),
         }
     }
 }
@@ -333,6 +340,7 @@ mod tests {
             timeout: 30000,
             base_dir: temp_dir.path().to_path_buf(),
             max_files: 10000,
+            search_mode: SearchMode::Lexical,
         };
 
         // Update the config
@@ -347,6 +355,7 @@ mod tests {
         assert_eq!(loaded_config.chunk_overlap, 256);
         assert_eq!(loaded_config.default_results, 10);
         assert_eq!(loaded_config.model_name, "different-model");
+        assert_eq!(loaded_config.search_mode, SearchMode::Lexical);
     }
 
     #[test]
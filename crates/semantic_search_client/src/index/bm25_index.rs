@@ -0,0 +1,145 @@
+use bm25::{
+    Embedder,
+    EmbedderBuilder,
+    Language,
+    Scorer,
+};
+use tracing::{
+    debug,
+    info,
+};
+
+/// Lexical index ranking texts by their BM25 score for a query
+///
+/// Unlike [crate::embedding::BM25TextEmbedder], which folds term weights into a fixed size vector
+/// to fit in a [super::VectorIndex], this keeps an inverted index of the tokens, so scores account
+/// for how rare a token is across the indexed texts.
+pub struct BM25Index {
+    /// Tokenizes texts and computes the term weights of their tokens
+    embedder: Embedder,
+    /// Inverted index of the tokens of the texts
+    scorer: Scorer<usize>,
+    /// Number of texts in the index
+    count: usize,
+}
+
+impl BM25Index {
+    /// Create a new lexical index of the given texts, where the ID of a text is its position
+    ///
+    /// # Arguments
+    ///
+    /// * `corpus` - The texts to index, also used to compute the average text length
+    ///
+    /// # Returns
+    ///
+    /// A new BM25Index instance
+    pub fn new(corpus: &[&str]) -> Self {
+        info!("Creating new BM25 index with {} texts", corpus.len());
+
+        // The language is fixed rather than detected, since texts and queries are tokenized
+        // separately and could otherwise be stemmed differently.
+        let embedder = EmbedderBuilder::with_fit_to_corpus(Language::English, corpus).build();
+        let mut index = Self {
+            embedder,
+            scorer: Scorer::new(),
+            count: 0,
+        };
+        for (id, text) in corpus.iter().enumerate() {
+            index.insert(text, id);
+        }
+
+        debug!("BM25 index created with avgdl: {}", index.embedder.avgdl());
+        index
+    }
+
+    /// Insert a text into the index
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to insert
+    /// * `id` - The ID associated with the text
+    pub fn insert(&mut self, text: &str, id: usize) {
+        self.scorer.upsert(&id, self.embedder.embed(text));
+        self.count += 1;
+    }
+
+    /// Search for the texts sharing tokens with the query
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query text
+    /// * `limit` - Maximum number of results to return
+    ///
+    /// # Returns
+    ///
+    /// A vector of (id, score) pairs, sorted by descending score
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(usize, f32)> {
+        self.search_filter(query, limit, |_| true)
+    }
+
+    /// Search for the texts sharing tokens with the query, among the texts accepted by a filter
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query text
+    /// * `limit` - Maximum number of results to return
+    /// * `filter` - Whether to consider the text with the given ID
+    ///
+    /// # Returns
+    ///
+    /// A vector of (id, score) pairs, sorted by descending score
+    pub fn search_filter(&self, query: &str, limit: usize, filter: impl Fn(usize) -> bool) -> Vec<(usize, f32)> {
+        self.scorer
+            .matches(&self.embedder.embed(query))
+            .into_iter()
+            .filter(|document| filter(document.id))
+            .take(limit)
+            .map(|document| (document.id, document.score))
+            .collect()
+    }
+
+    /// Get the number of texts in the index
+    ///
+    /// # Returns
+    ///
+    /// The number of texts in the index
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    /// Check if the index is empty
+    ///
+    /// # Returns
+    ///
+    /// `true` if the index is empty, `false` otherwise
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bm25_index_exact_tokens() {
+        let mut index = BM25Index::new(&[
+            "let builder = ToolManagerBuilder::default();",
+            "The tool manager loads the tools of every server",
+            "fn main() { println!(\"hello\"); }",
+        ]);
+        assert_eq!(index.len(), 3);
+
+        let results = index.search("ToolManagerBuilder", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, 0);
+
+        let results = index.search("tool manager", 10);
+        assert_eq!(results[0].0, 1);
+
+        index.insert("impl ToolManagerBuilder { fn build(self) }", 3);
+        let results = index.search("ToolManagerBuilder", 1);
+        assert_eq!(results.len(), 1);
+        assert!(index.search("unrelated", 10).is_empty());
+    }
+}
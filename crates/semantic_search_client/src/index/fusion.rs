@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+/// Constant of reciprocal rank fusion dampening the weight of the top ranks, such that an item
+/// ranked well by several retrievers beats an item ranked first by only one of them
+pub const RRF_K: f32 = 60.0;
+
+/// Fuse rankings of the same items with reciprocal rank fusion
+///
+/// Every item scores `1 / (RRF_K + rank)` for each ranking it appears in, with the first rank
+/// being 1. Only ranks are used, so rankings with incomparable scores (e.g. cosine similarities
+/// and BM25 scores) can be fused.
+///
+/// # Arguments
+///
+/// * `rankings` - Lists of IDs, each sorted from the most to the least relevant
+///
+/// # Returns
+///
+/// A vector of (id, score) pairs, sorted by descending score
+pub fn reciprocal_rank_fusion(rankings: &[&[usize]]) -> Vec<(usize, f32)> {
+    let mut scores: HashMap<usize, f32> = HashMap::new();
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            *scores.entry(*id).or_default() += 1.0 / (RRF_K + (rank + 1) as f32);
+        }
+    }
+
+    let mut fused = scores.into_iter().collect::<Vec<_>>();
+    // Ties are broken by ID to keep results stable
+    fused.sort_by(|(a_id, a), (b_id, b)| b.total_cmp(a).then(a_id.cmp(b_id)));
+    fused
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        let fused = reciprocal_rank_fusion(&[&[1, 2, 3], &[3, 1, 4]]);
+        let ids = fused.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        // 1 and 3 are found by both rankings, 1 is ranked better on average
+        assert_eq!(ids, vec![1, 3, 2, 4]);
+        assert!((fused[0].1 - (1.0 / 61.0 + 1.0 / 62.0)).abs() < 1e-6);
+
+        assert!(reciprocal_rank_fusion(&[&[], &[]]).is_empty());
+    }
+}
@@ -1,3 +1,10 @@
+mod bm25_index;
+mod fusion;
 mod vector_index;
 
+pub use bm25_index::BM25Index;
+pub use fusion::{
+    RRF_K,
+    reciprocal_rank_fusion,
+};
 pub use vector_index::VectorIndex;
@@ -1,18 +1,82 @@
+use std::fs::{
+    self,
+    File,
+};
+use std::path::Path;
+use std::ptr::NonNull;
+
+use hnsw_rs::api::AnnT;
 use hnsw_rs::hnsw::Hnsw;
+use hnsw_rs::hnswio::HnswIo;
 use hnsw_rs::prelude::DistCosine;
+use sha2::{
+    Digest,
+    Sha256,
+};
 use tracing::{
     debug,
     info,
 };
 
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// Extensions of the files written by [Hnsw::file_dump]
+const INDEX_EXTENSIONS: [&str; 2] = ["hnsw.graph", "hnsw.data"];
+
+/// Extension of the file holding the checksum of the index files
+const CHECKSUM_EXTENSION: &str = "hnsw.sha256";
+
 /// Vector index for fast approximate nearest neighbor search
 pub struct VectorIndex {
-    /// The HNSW index
+    /// The HNSW index. This is declared before the reloader it may borrow from, such that it is
+    /// dropped first.
     index: Hnsw<'static, f32, DistCosine>,
+    /// The reloader an index loaded from disk borrows from
+    _reloader: Option<Reloader>,
     /// Counter to track the number of elements
     count: std::sync::atomic::AtomicUsize,
 }
 
+/// Owns the [HnswIo] a loaded index borrows from for as long as the index lives
+struct Reloader(NonNull<HnswIo>);
+
+impl Reloader {
+    fn new(reloader: HnswIo) -> Self {
+        // SAFETY: `Box::into_raw` never returns a null pointer.
+        Self(unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(reloader))) })
+    }
+
+    /// Load the index, which borrows from the reloader
+    ///
+    /// # Safety
+    ///
+    /// The index must be dropped before the reloader, and this must be called at most once.
+    unsafe fn load(&self) -> Result<Hnsw<'static, f32, DistCosine>> {
+        // SAFETY: The reloader lives at a stable address until it is dropped, and is not referred
+        // to by anything else.
+        let reloader: &'static mut HnswIo = unsafe { &mut *self.0.as_ptr() };
+        reloader
+            .load_hnsw::<f32, DistCosine>()
+            .map_err(|e| SemanticSearchError::SerializationError(format!("Failed to load vector index: {}", e)))
+    }
+}
+
+// SAFETY: The reloader is not accessed once the index is loaded, other than to be dropped.
+unsafe impl Send for Reloader {}
+// SAFETY: See above.
+unsafe impl Sync for Reloader {}
+
+impl Drop for Reloader {
+    fn drop(&mut self) {
+        // SAFETY: The pointer was created by `Box::into_raw` and the index borrowing from it has
+        // already been dropped.
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
 impl VectorIndex {
     /// Create a new empty vector index
     ///
@@ -37,10 +101,78 @@ impl VectorIndex {
         debug!("Vector index created successfully");
         Self {
             index,
+            _reloader: None,
             count: std::sync::atomic::AtomicUsize::new(0),
         }
     }
 
+    /// Load an index saved with [Self::save]
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory the index was saved to
+    /// * `basename` - Base name of the files of the index
+    ///
+    /// # Returns
+    ///
+    /// The loaded VectorIndex instance, or an error if the files are missing or corrupt
+    pub fn load(dir: &Path, basename: &str) -> Result<Self> {
+        // The reloader asserts on unexpected content rather than failing, so the files are checked
+        // against the checksum written along with them first
+        let checksum_path = dir.join(format!("{}.{}", basename, CHECKSUM_EXTENSION));
+        let expected = fs::read_to_string(&checksum_path).map_err(|e| {
+            SemanticSearchError::InvalidPath(format!(
+                "Failed to read vector index checksum {}: {}",
+                checksum_path.display(),
+                e
+            ))
+        })?;
+        if checksum(dir, basename)? != expected.trim() {
+            return Err(SemanticSearchError::SerializationError(format!(
+                "Corrupt vector index: {}",
+                basename
+            )));
+        }
+
+        // The reloader is freed along with the index, or right away if loading fails
+        let reloader = Reloader::new(HnswIo::new(dir, basename));
+        // SAFETY: The index is stored before the reloader, so it is dropped first.
+        let index = unsafe { reloader.load() }?;
+
+        let count = index.get_nb_point();
+        debug!("Vector index loaded with {} elements", count);
+        Ok(Self {
+            index,
+            _reloader: Some(reloader),
+            count: std::sync::atomic::AtomicUsize::new(count),
+        })
+    }
+
+    /// Save the index to `<basename>.hnsw.graph` and `<basename>.hnsw.data` files, along with
+    /// their checksum in `<basename>.hnsw.sha256`
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory to save the index to
+    /// * `basename` - Base name of the files of the index, which must not exist yet
+    pub fn save(&self, dir: &Path, basename: &str) -> Result<()> {
+        let dumped = self
+            .index
+            .file_dump(dir, basename)
+            .map_err(|e| SemanticSearchError::OperationFailed(format!("Failed to save vector index: {}", e)))?;
+        if dumped != basename {
+            return Err(SemanticSearchError::OperationFailed(format!(
+                "Vector index files already exist: {}",
+                basename
+            )));
+        }
+        fs::write(
+            dir.join(format!("{}.{}", basename, CHECKSUM_EXTENSION)),
+            checksum(dir, basename)?,
+        )?;
+        Ok(())
+    }
+
     /// Insert a vector into the index
     ///
     /// # Arguments
@@ -72,6 +204,28 @@ impl VectorIndex {
             .collect()
     }
 
+    /// Search for the nearest neighbors of a vector, among the given elements only
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query vector
+    /// * `limit` - Maximum number of results to return
+    /// * `ef_search` - Size of the dynamic candidate list
+    /// * `ids` - IDs of the elements to consider, sorted in ascending order
+    ///
+    /// # Returns
+    ///
+    /// A vector of (id, distance) pairs
+    pub fn search_filter(&self, query: &[f32], limit: usize, ef_search: usize, ids: &[usize]) -> Vec<(usize, f32)> {
+        let filter = |id: &usize| ids.binary_search(id).is_ok();
+        let results = self.index.search_filter(query, limit, ef_search, Some(&filter));
+
+        results
+            .into_iter()
+            .map(|neighbor| (neighbor.d_id, neighbor.distance))
+            .collect()
+    }
+
     /// Get the number of elements in the index
     ///
     /// # Returns
@@ -90,3 +244,16 @@ impl VectorIndex {
         self.len() == 0
     }
 }
+
+/// Compute the checksum of the files of a saved index
+fn checksum(dir: &Path, basename: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for extension in INDEX_EXTENSIONS {
+        let path = dir.join(format!("{}.{}", basename, extension));
+        let mut file = File::open(&path).map_err(|e| {
+            SemanticSearchError::InvalidPath(format!("Failed to open vector index file {}: {}", path.display(), e))
+        })?;
+        std::io::copy(&mut file, &mut hasher)?;
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
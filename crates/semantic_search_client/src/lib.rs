@@ -31,11 +31,14 @@ pub use error::{
 pub use types::{
     DataPoint,
     FileType,
+    IndexingOptions,
     KnowledgeContext,
     OperationStatus,
     OperationType,
     ProgressInfo,
     ProgressStatus,
+    SearchFilter,
+    SearchMode,
     SearchResult,
     SystemStatus,
 };
@@ -0,0 +1,439 @@
+use std::path::Path;
+
+use tree_sitter::{
+    Language,
+    Node,
+    Parser,
+};
+
+use crate::config;
+
+/// A chunk of a source file, split on the boundaries of its definitions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeChunk {
+    /// The text of the chunk
+    pub text: String,
+    /// Path of the definition the chunk belongs to, e.g. `ToolManager::load_tools`, or [None] for
+    /// code outside of any definition such as imports
+    pub symbol: Option<String>,
+    /// First line of the chunk, starting at 1
+    pub start_line: usize,
+    /// Last line of the chunk (inclusive)
+    pub end_line: usize,
+}
+
+/// Languages supported by [chunk_code]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLanguage {
+    /// Rust
+    Rust,
+    /// TypeScript
+    TypeScript,
+    /// TypeScript with JSX, also used for JavaScript
+    Tsx,
+    /// Python
+    Python,
+    /// Go
+    Go,
+    /// Bash and other POSIX shells
+    Shell,
+}
+
+impl CodeLanguage {
+    /// Determine the language of a source file based on its extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str())? {
+            "rs" => Some(Self::Rust),
+            "ts" | "mts" | "cts" => Some(Self::TypeScript),
+            "tsx" | "js" | "jsx" | "mjs" | "cjs" => Some(Self::Tsx),
+            "py" => Some(Self::Python),
+            "go" => Some(Self::Go),
+            "sh" | "bash" | "zsh" => Some(Self::Shell),
+            _ => None,
+        }
+    }
+
+    fn grammar(self) -> Language {
+        match self {
+            Self::Rust => tree_sitter_rust::LANGUAGE.into(),
+            Self::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            Self::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+            Self::Python => tree_sitter_python::LANGUAGE.into(),
+            Self::Go => tree_sitter_go::LANGUAGE.into(),
+            Self::Shell => tree_sitter_bash::LANGUAGE.into(),
+        }
+    }
+
+    /// Separator between the names of a symbol path
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Rust => "::",
+            _ => ".",
+        }
+    }
+
+    /// Whether nodes of `kind` are definitions that get chunks of their own
+    fn is_definition(self, kind: &str) -> bool {
+        let kinds: &[&str] = match self {
+            Self::Rust => &[
+                "function_item",
+                "impl_item",
+                "trait_item",
+                "struct_item",
+                "enum_item",
+                "union_item",
+                "mod_item",
+                "macro_definition",
+            ],
+            Self::TypeScript | Self::Tsx => &[
+                "function_declaration",
+                "generator_function_declaration",
+                "class_declaration",
+                "abstract_class_declaration",
+                "interface_declaration",
+                "enum_declaration",
+                "internal_module",
+                "method_definition",
+                "lexical_declaration",
+            ],
+            Self::Python => &["function_definition", "class_definition"],
+            Self::Go => &["function_declaration", "method_declaration", "type_declaration"],
+            Self::Shell => &["function_definition"],
+        };
+        kinds.contains(&kind)
+    }
+
+    /// Whether definitions of `kind` have a body containing more definitions, which is chunked
+    /// separately when the definition is too large for a single chunk
+    fn is_container(self, kind: &str) -> bool {
+        let kinds: &[&str] = match self {
+            Self::Rust => &["impl_item", "trait_item", "mod_item"],
+            Self::TypeScript | Self::Tsx => &[
+                "class_declaration",
+                "abstract_class_declaration",
+                "interface_declaration",
+                "internal_module",
+            ],
+            Self::Python => &["class_definition"],
+            Self::Go | Self::Shell => &[],
+        };
+        kinds.contains(&kind)
+    }
+}
+
+/// Split source code into chunks on the boundaries of its definitions (functions, classes, impls
+/// and so on), such that every chunk can be traced back to a symbol and a line range
+///
+/// Definitions larger than `max_words` are split into their member definitions if they have any,
+/// and into windows of lines otherwise. Code between definitions, like imports, forms chunks of its
+/// own.
+///
+/// # Arguments
+///
+/// * `source` - The source code
+/// * `language` - The language of the source code
+/// * `max_words` - Optional maximum number of words of a chunk (if None, uses the chunk size of the
+///   config)
+///
+/// # Returns
+///
+/// The chunks in the order they appear in the source, or [None] if the source could not be parsed
+pub fn chunk_code(source: &str, language: CodeLanguage, max_words: Option<usize>) -> Option<Vec<CodeChunk>> {
+    let max_words = max_words.unwrap_or_else(|| config::get_config().chunk_size).max(1);
+
+    let mut parser = Parser::new();
+    parser.set_language(&language.grammar()).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let lines = source.lines().collect::<Vec<_>>();
+    let mut chunker = Chunker {
+        lines: &lines,
+        source,
+        language,
+        max_words,
+        chunks: Vec::new(),
+    };
+    if !lines.is_empty() {
+        chunker.chunk_children(tree.root_node(), None, 0, lines.len() - 1);
+    }
+    Some(chunker.chunks)
+}
+
+struct Chunker<'a> {
+    source: &'a str,
+    lines: &'a [&'a str],
+    language: CodeLanguage,
+    max_words: usize,
+    chunks: Vec<CodeChunk>,
+}
+
+impl Chunker<'_> {
+    /// Chunks the lines `first..=last`, which contain the children of `node`
+    fn chunk_children(&mut self, node: Node<'_>, symbol: Option<&str>, first: usize, last: usize) {
+        // First line that has not been chunked yet
+        let mut next = first;
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            let Some(definition) = self.definition(child) else {
+                continue;
+            };
+
+            // Comments and attributes right above a definition belong to it
+            let mut start = child.start_position().row;
+            let mut sibling = child.prev_sibling();
+            while let Some(prev) = sibling.filter(|prev| {
+                (prev.kind().contains("comment") || prev.kind() == "attribute_item")
+                    && prev.end_position().row + 1 >= start
+            }) {
+                start = prev.start_position().row;
+                sibling = prev.prev_sibling();
+            }
+            let start = start.max(next);
+            let end = child.end_position().row.min(last);
+            if end < start {
+                continue;
+            }
+
+            if start > next {
+                self.push_lines(next, start - 1, symbol);
+            }
+            let name = self.definition_name(definition);
+            let path = match (symbol, &name) {
+                (Some(parent), Some(name)) => Some(format!("{parent}{}{name}", self.language.separator())),
+                (None, Some(name)) => Some(name.clone()),
+                (parent, None) => parent.map(str::to_string),
+            };
+
+            let body = definition.child_by_field_name("body");
+            match body {
+                Some(body)
+                    if self.language.is_container(definition.kind())
+                        && self.word_count(start, end) > self.max_words =>
+                {
+                    self.chunk_children(body, path.as_deref(), start, end);
+                },
+                _ => self.push_lines(start, end, path.as_deref()),
+            }
+            next = end + 1;
+        }
+
+        if next <= last {
+            self.push_lines(next, last, symbol);
+        }
+    }
+
+    /// The definition `node` consists of, if any. Exports and decorators wrap the definition they
+    /// apply to, and are chunked along with it.
+    fn definition<'t>(&self, node: Node<'t>) -> Option<Node<'t>> {
+        let definition = match node.kind() {
+            "export_statement" => node.child_by_field_name("declaration")?,
+            "decorated_definition" => node.child_by_field_name("definition")?,
+            _ => node,
+        };
+        self.language.is_definition(definition.kind()).then_some(definition)
+    }
+
+    /// Name of a definition, as shown in symbol paths
+    fn definition_name(&self, node: Node<'_>) -> Option<String> {
+        let text = |node: Node<'_>| node.utf8_text(self.source.as_bytes()).ok().map(str::to_string);
+        match node.kind() {
+            // Named after the type, e.g. `ToolManager` for `impl Clone for ToolManager`
+            "impl_item" => node.child_by_field_name("type").and_then(text),
+            // `const handler = () => {}`
+            "lexical_declaration" => node
+                .named_child(0)
+                .and_then(|declarator| declarator.child_by_field_name("name"))
+                .and_then(text),
+            // Named `Receiver.method`
+            "method_declaration" => {
+                let name = node.child_by_field_name("name").and_then(text)?;
+                let receiver = node
+                    .child_by_field_name("receiver")
+                    .and_then(text)
+                    .and_then(|receiver| {
+                        receiver
+                            .trim_matches(|c| c == '(' || c == ')')
+                            .split_whitespace()
+                            .last()
+                            .map(|ty| ty.trim_start_matches('*').to_string())
+                    });
+                Some(match receiver {
+                    Some(receiver) => format!("{receiver}.{name}"),
+                    None => name,
+                })
+            },
+            "type_declaration" => {
+                let mut cursor = node.walk();
+                let spec = node
+                    .named_children(&mut cursor)
+                    .find(|child| child.kind() == "type_spec" || child.kind() == "type_alias");
+                spec.and_then(|spec| spec.child_by_field_name("name")).and_then(text)
+            },
+            _ => node.child_by_field_name("name").and_then(text),
+        }
+    }
+
+    /// Pushes the lines `first..=last` as chunks, split into windows of at most `max_words` words
+    fn push_lines(&mut self, first: usize, last: usize, symbol: Option<&str>) {
+        let mut start = first;
+        let mut words = 0;
+        for line in first..=last {
+            let line_words = self.lines[line].split_whitespace().count();
+            if words > 0 && words + line_words > self.max_words {
+                self.push_chunk(start, line - 1, symbol);
+                start = line;
+                words = 0;
+            }
+            words += line_words;
+        }
+        self.push_chunk(start, last, symbol);
+    }
+
+    fn push_chunk(&mut self, mut first: usize, mut last: usize, symbol: Option<&str>) {
+        while first < last && self.lines[first].trim().is_empty() {
+            first += 1;
+        }
+        while last > first && self.lines[last].trim().is_empty() {
+            last -= 1;
+        }
+        let text = self.lines[first..=last].join("\n");
+        // Skip chunks without any content, e.g. blank lines or the closing brace of a block
+        if !text.chars().any(char::is_alphanumeric) {
+            return;
+        }
+        self.chunks.push(CodeChunk {
+            text,
+            symbol: symbol.map(str::to_string),
+            start_line: first + 1,
+            end_line: last + 1,
+        });
+    }
+
+    fn word_count(&self, first: usize, last: usize) -> usize {
+        self.lines[first..=last]
+            .iter()
+            .map(|line| line.split_whitespace().count())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(chunks: &[CodeChunk]) -> Vec<Option<&str>> {
+        chunks.iter().map(|chunk| chunk.symbol.as_deref()).collect()
+    }
+
+    #[test]
+    fn test_chunk_rust() {
+        let source = r#"use std::fmt;
+
+/// A builder
+#[derive(Default)]
+pub struct ToolManagerBuilder {
+    name: String,
+}
+
+impl ToolManagerBuilder {
+    pub fn name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn build(self) -> ToolManager {
+        ToolManager { name: self.name }
+    }
+}
+"#;
+        let chunks = chunk_code(source, CodeLanguage::Rust, Some(100)).unwrap();
+        assert_eq!(symbols(&chunks), vec![
+            None,
+            Some("ToolManagerBuilder"),
+            Some("ToolManagerBuilder")
+        ]);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 1));
+        // The doc comment and attribute are part of the struct
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 7));
+        assert!(chunks[1].text.starts_with("/// A builder\n#[derive(Default)]"));
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (9, 18));
+
+        // The impl is too large for a single chunk, so its methods are chunked separately
+        let chunks = chunk_code(source, CodeLanguage::Rust, Some(20)).unwrap();
+        assert_eq!(symbols(&chunks), vec![
+            None,
+            Some("ToolManagerBuilder"),
+            Some("ToolManagerBuilder"),
+            Some("ToolManagerBuilder::name"),
+            Some("ToolManagerBuilder::build"),
+        ]);
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (9, 9));
+        assert_eq!((chunks[3].start_line, chunks[3].end_line), (10, 13));
+        assert_eq!((chunks[4].start_line, chunks[4].end_line), (15, 17));
+    }
+
+    #[test]
+    fn test_chunk_large_function() {
+        let body = (0..30).map(|i| format!("    let x{i} = {i};")).collect::<Vec<_>>();
+        let source = format!("fn large() {{\n{}\n}}\n", body.join("\n"));
+        let chunks = chunk_code(&source, CodeLanguage::Rust, Some(40)).unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.symbol.as_deref() == Some("large")));
+        // The windows cover the function without overlapping
+        for window in chunks.windows(2) {
+            assert_eq!(window[0].end_line + 1, window[1].start_line);
+        }
+        assert_eq!(chunks.first().unwrap().start_line, 1);
+        assert_eq!(chunks.last().unwrap().end_line, 32);
+    }
+
+    #[test]
+    fn test_chunk_other_languages() {
+        let python = "import os\n\n@cache\ndef load(path):\n    return open(path)\n\nclass Store:\n    def get(self, key):\n        return key\n";
+        let chunks = chunk_code(python, CodeLanguage::Python, Some(6)).unwrap();
+        assert_eq!(symbols(&chunks), vec![
+            None,
+            Some("load"),
+            Some("Store"),
+            Some("Store.get")
+        ]);
+        assert_eq!((chunks[1].start_line, chunks[1].end_line), (3, 5));
+
+        let typescript = "import { x } from 'y';\n\nexport function run(): void {\n  x();\n}\n\nexport const handler = () => run();\n\nclass Server {\n  start() {\n    run();\n  }\n}\n";
+        let chunks = chunk_code(typescript, CodeLanguage::TypeScript, Some(100)).unwrap();
+        assert_eq!(symbols(&chunks), vec![
+            None,
+            Some("run"),
+            Some("handler"),
+            Some("Server")
+        ]);
+
+        let go = "package main\n\ntype Server struct {\n\tname string\n}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n\nfunc main() {\n}\n";
+        let chunks = chunk_code(go, CodeLanguage::Go, Some(100)).unwrap();
+        assert_eq!(symbols(&chunks), vec![
+            None,
+            Some("Server"),
+            Some("Server.Start"),
+            Some("main")
+        ]);
+
+        let shell = "#!/bin/bash\nset -e\n\nbuild() {\n  cargo build\n}\n\nbuild\n";
+        let chunks = chunk_code(shell, CodeLanguage::Shell, Some(100)).unwrap();
+        assert_eq!(symbols(&chunks), vec![None, Some("build"), None]);
+        assert_eq!((chunks[2].start_line, chunks[2].end_line), (8, 8));
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            CodeLanguage::from_path(Path::new("src/main.rs")),
+            Some(CodeLanguage::Rust)
+        );
+        assert_eq!(CodeLanguage::from_path(Path::new("app.tsx")), Some(CodeLanguage::Tsx));
+        assert_eq!(
+            CodeLanguage::from_path(Path::new("install.sh")),
+            Some(CodeLanguage::Shell)
+        );
+        assert_eq!(CodeLanguage::from_path(Path::new("README.md")), None);
+    }
+}
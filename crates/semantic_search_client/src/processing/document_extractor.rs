@@ -0,0 +1,398 @@
+use std::path::Path;
+
+use kuchikiki::NodeRef;
+use kuchikiki::traits::TendrilSink;
+use serde_json::Value;
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+
+/// A part of a document whose text is chunked on its own, such that chunks never span several
+/// pages, sections or cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSection {
+    /// The text of the section
+    pub text: String,
+    /// Where the section is in the document
+    pub location: SectionLocation,
+}
+
+/// Location of a section within a document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SectionLocation {
+    /// Page of a PDF document, starting at 1
+    Page(usize),
+    /// Section of an HTML page under the given headings, e.g. `Installation > Linux`, or [None]
+    /// for the content preceding the first heading
+    Section(Option<String>),
+    /// Cell of a Jupyter notebook
+    Cell {
+        /// Index of the cell, starting at 0
+        index: usize,
+        /// Type of the cell, e.g. `markdown` or `code`
+        cell_type: String,
+    },
+}
+
+/// Elements that hold navigation, scripts and the like rather than content
+const HTML_BOILERPLATE: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside", "form",
+];
+
+/// Elements whose content starts on a new line
+const HTML_BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "hr",
+    "li",
+    "main",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "td",
+    "th",
+    "tr",
+    "ul",
+];
+
+/// Extract the text of every page of a PDF document
+///
+/// # Arguments
+///
+/// * `path` - Path to the document
+///
+/// # Returns
+///
+/// The non-empty pages, or an error if the document can't be read
+pub fn extract_pdf(path: &Path) -> Result<Vec<DocumentSection>> {
+    let content = std::fs::read(path)?;
+    let failed = |reason: String| {
+        SemanticSearchError::OperationFailed(format!("Failed to extract text from {}: {}", path.display(), reason))
+    };
+
+    // The extractor panics on some malformed documents rather than failing
+    let pages = std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem_by_pages(&content))
+        .map_err(|_panic| failed("malformed document".to_string()))?
+        .map_err(|e| failed(e.to_string()))?;
+
+    Ok(pages
+        .into_iter()
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(i, text)| DocumentSection {
+            text: text.trim().to_string(),
+            location: SectionLocation::Page(i + 1),
+        })
+        .collect())
+}
+
+/// Extract the text of an HTML page, split into sections on its headings
+///
+/// Navigation, scripts and other boilerplate are stripped, and only the main content is kept if
+/// the page marks it up. Headings are kept as Markdown headings at the start of their sections.
+///
+/// # Arguments
+///
+/// * `content` - The HTML page
+///
+/// # Returns
+///
+/// The non-empty sections, in document order
+pub fn extract_html(content: &str) -> Vec<DocumentSection> {
+    let document = kuchikiki::parse_html().one(content);
+    for selector in HTML_BOILERPLATE {
+        if let Ok(elements) = document.select(selector) {
+            for element in elements.collect::<Vec<_>>() {
+                element.as_node().detach();
+            }
+        }
+    }
+
+    let root = ["main", "article", "body"]
+        .into_iter()
+        .find_map(|selector| document.select_first(selector).ok())
+        .map_or(document, |element| element.as_node().clone());
+
+    let mut sections = HtmlSections::default();
+    sections.walk(&root);
+    sections.finish()
+}
+
+/// Extract the cells of a Jupyter notebook
+///
+/// # Arguments
+///
+/// * `content` - The notebook, as JSON
+///
+/// # Returns
+///
+/// The non-empty cells, or an error if the notebook is invalid
+pub fn extract_notebook(content: &str) -> Result<Vec<DocumentSection>> {
+    let notebook: Value = serde_json::from_str(content)?;
+    let cells = notebook
+        .get("cells")
+        .and_then(|cells| cells.as_array())
+        .ok_or_else(|| SemanticSearchError::SerializationError("Notebook has no cells".to_string()))?;
+
+    Ok(cells
+        .iter()
+        .enumerate()
+        .filter_map(|(index, cell)| {
+            // The source is either a string or a list of lines
+            let text = match cell.get("source")? {
+                Value::String(source) => source.clone(),
+                Value::Array(lines) => lines.iter().filter_map(|line| line.as_str()).collect(),
+                _ => return None,
+            };
+            if text.trim().is_empty() {
+                return None;
+            }
+
+            let cell_type = cell.get("cell_type").and_then(|v| v.as_str()).unwrap_or("code");
+            Some(DocumentSection {
+                text,
+                location: SectionLocation::Cell {
+                    index,
+                    cell_type: cell_type.to_string(),
+                },
+            })
+        })
+        .collect())
+}
+
+/// Get the language of the code cells of a Jupyter notebook, e.g. `python`
+pub fn notebook_language(content: &str) -> Option<String> {
+    let notebook: Value = serde_json::from_str(content).ok()?;
+    let metadata = notebook.get("metadata")?;
+    metadata
+        .pointer("/language_info/name")
+        .or_else(|| metadata.pointer("/kernelspec/language"))
+        .and_then(|v| v.as_str())
+        .map(str::to_lowercase)
+}
+
+/// Sections of an HTML page, built while walking its nodes in document order
+#[derive(Default)]
+struct HtmlSections {
+    sections: Vec<DocumentSection>,
+    /// Headings enclosing the current section, with their levels
+    headings: Vec<(usize, String)>,
+    text: String,
+}
+
+impl HtmlSections {
+    fn walk(&mut self, node: &NodeRef) {
+        for child in node.children() {
+            if let Some(text) = child.as_text() {
+                self.push_text(&text.borrow());
+                continue;
+            }
+            let Some(element) = child.as_element() else {
+                continue;
+            };
+
+            let name = element.name.local.as_ref();
+            if let Some(level) = heading_level(name) {
+                self.start_section(level, &child.text_contents());
+                continue;
+            }
+
+            let is_block = HTML_BLOCKS.contains(&name);
+            if is_block {
+                self.break_line();
+            }
+            if name == "pre" {
+                // Preformatted text keeps its whitespace
+                self.text.push_str(child.text_contents().trim_end());
+            } else {
+                self.walk(&child);
+            }
+            if is_block {
+                self.break_line();
+            }
+        }
+    }
+
+    /// Append text, collapsing whitespace as browsers do
+    fn push_text(&mut self, text: &str) {
+        let at_line_start = self.text.is_empty() || self.text.ends_with('\n');
+        let ends_with_space = self.text.ends_with(' ');
+        for (i, word) in text.split_whitespace().enumerate() {
+            let needs_space = if i == 0 {
+                !at_line_start && !ends_with_space && text.starts_with(char::is_whitespace)
+            } else {
+                true
+            };
+            if needs_space {
+                self.text.push(' ');
+            }
+            self.text.push_str(word);
+        }
+        if text.ends_with(char::is_whitespace) && !self.text.is_empty() && !self.text.ends_with(['\n', ' ']) {
+            self.text.push(' ');
+        }
+    }
+
+    fn break_line(&mut self) {
+        let trimmed = self.text.trim_end_matches(' ').len();
+        self.text.truncate(trimmed);
+        if !self.text.is_empty() && !self.text.ends_with('\n') {
+            self.text.push('\n');
+        }
+    }
+
+    fn start_section(&mut self, level: usize, heading: &str) {
+        // A heading directly followed by a subheading stays at the start of the subsection
+        let has_parent = self.headings.last().is_some_and(|(parent, _)| *parent < level);
+        if !(has_parent && self.has_headings_only()) {
+            self.flush();
+        }
+
+        let heading = heading.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.headings.retain(|(enclosing, _)| *enclosing < level);
+        self.text.push_str(&format!("{} {}\n", "#".repeat(level), heading));
+        self.headings.push((level, heading));
+    }
+
+    /// Whether the current section holds nothing but headings
+    fn has_headings_only(&self) -> bool {
+        self.text
+            .lines()
+            .all(|line| line.trim().is_empty() || line.starts_with('#'))
+    }
+
+    fn flush(&mut self) {
+        if self.has_headings_only() {
+            self.text.clear();
+            return;
+        }
+
+        // Drop the blank lines left by empty elements
+        let text = std::mem::take(&mut self.text)
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let section = (!self.headings.is_empty()).then(|| {
+            self.headings
+                .iter()
+                .map(|(_, heading)| heading.as_str())
+                .collect::<Vec<_>>()
+                .join(" > ")
+        });
+        self.sections.push(DocumentSection {
+            text,
+            location: SectionLocation::Section(section),
+        });
+    }
+
+    fn finish(mut self) -> Vec<DocumentSection> {
+        self.flush();
+        self.sections
+    }
+}
+
+/// Level of a heading element, e.g. 2 for `h2`
+fn heading_level(name: &str) -> Option<usize> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(text: &str, location: Option<&str>) -> DocumentSection {
+        DocumentSection {
+            text: text.to_string(),
+            location: SectionLocation::Section(location.map(str::to_string)),
+        }
+    }
+
+    #[test]
+    fn test_extract_html() {
+        let sections = extract_html(
+            "<html><body>
+               <header><h1>Site</h1></header>
+               <main>
+                 <p>Read   this <em>first</em>.</p>
+                 <h1>API</h1>
+                 <h2>Client</h2>
+                 <p>Sends requests.</p>
+                 <ul><li>Retries</li><li>Timeouts</li></ul>
+                 <h3>Errors</h3>
+                 <pre>match result {
+    Err(e) =&gt; fail(e),
+}</pre>
+                 <h2>Server</h2>
+                 <div>Handles requests.<script>track()</script></div>
+               </main>
+               <footer>Copyright</footer>
+             </body></html>",
+        );
+
+        assert_eq!(sections, vec![
+            section("Read this first.", None),
+            section(
+                "# API\n## Client\nSends requests.\nRetries\nTimeouts",
+                Some("API > Client")
+            ),
+            section(
+                "### Errors\nmatch result {\n    Err(e) => fail(e),\n}",
+                Some("API > Client > Errors")
+            ),
+            section("## Server\nHandles requests.", Some("API > Server")),
+        ]);
+    }
+
+    #[test]
+    fn test_extract_notebook() {
+        let notebook = r#"{
+            "cells": [
+                {"cell_type": "markdown", "source": "Intro"},
+                {"cell_type": "code", "source": []},
+                {"cell_type": "code", "source": ["import os\n", "os.getcwd()"]}
+            ],
+            "metadata": {"kernelspec": {"language": "Python"}}
+        }"#;
+
+        assert_eq!(extract_notebook(notebook).unwrap(), vec![
+            DocumentSection {
+                text: "Intro".to_string(),
+                location: SectionLocation::Cell {
+                    index: 0,
+                    cell_type: "markdown".to_string(),
+                },
+            },
+            DocumentSection {
+                text: "import os\nos.getcwd()".to_string(),
+                location: SectionLocation::Cell {
+                    index: 2,
+                    cell_type: "code".to_string(),
+                },
+            },
+        ]);
+        assert_eq!(notebook_language(notebook), Some("python".to_string()));
+        assert!(extract_notebook("{}").is_err());
+    }
+}
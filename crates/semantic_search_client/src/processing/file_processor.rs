@@ -2,13 +2,33 @@ use std::fs;
 use std::path::Path;
 
 use serde_json::Value;
+use sha2::{
+    Digest,
+    Sha256,
+};
 
 use crate::error::{
     Result,
     SemanticSearchError,
 };
+use crate::processing::code_chunker::{
+    CodeLanguage,
+    chunk_code,
+};
+use crate::processing::document_extractor::{
+    DocumentSection,
+    SectionLocation,
+    extract_html,
+    extract_notebook,
+    extract_pdf,
+    notebook_language,
+};
+use crate::processing::file_walker::walk_files;
 use crate::processing::text_chunker::chunk_text;
-use crate::types::FileType;
+use crate::types::{
+    FileType,
+    IndexingOptions,
+};
 
 /// Determine the file type based on extension
 pub fn get_file_type(path: &Path) -> FileType {
@@ -16,6 +36,9 @@ pub fn get_file_type(path: &Path) -> FileType {
         Some("txt") => FileType::Text,
         Some("md" | "markdown") => FileType::Markdown,
         Some("json") => FileType::Json,
+        Some("pdf") => FileType::Pdf,
+        Some("html" | "htm" | "xhtml") => FileType::Html,
+        Some("ipynb") => FileType::Notebook,
         // Code file extensions
         Some("rs") => FileType::Code,
         Some("py") => FileType::Code,
@@ -29,7 +52,7 @@ pub fn get_file_type(path: &Path) -> FileType {
         Some("kt" | "kts") => FileType::Code,
         Some("cs") => FileType::Code,
         Some("sh" | "bash" | "zsh") => FileType::Code,
-        Some("html" | "htm" | "xml") => FileType::Code,
+        Some("xml") => FileType::Code,
         Some("css" | "scss" | "sass" | "less") => FileType::Code,
         Some("sql") => FileType::Code,
         Some("yaml" | "yml") => FileType::Code,
@@ -57,14 +80,53 @@ pub fn process_file(path: &Path) -> Result<Vec<Value>> {
     }
 
     let file_type = get_file_type(path);
-    let content = fs::read_to_string(path).map_err(|e| {
-        SemanticSearchError::IoError(std::io::Error::new(
-            e.kind(),
-            format!("Failed to read file {}: {}", path.display(), e),
-        ))
-    })?;
+    // PDF documents are binary, and read by their extractor
+    let content = match file_type {
+        FileType::Pdf => String::new(),
+        _ => fs::read_to_string(path).map_err(|e| {
+            SemanticSearchError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("Failed to read file {}: {}", path.display(), e),
+            ))
+        })?,
+    };
+
+    let code_chunks = match file_type {
+        FileType::Code => CodeLanguage::from_path(path).and_then(|language| chunk_code(&content, language, None)),
+        _ => None,
+    };
 
     match file_type {
+        FileType::Code if code_chunks.as_ref().is_some_and(|chunks| !chunks.is_empty()) => {
+            let chunks = code_chunks.unwrap_or_default();
+            let path_str = path.to_string_lossy().to_string();
+            let language = path.extension().and_then(|ext| ext.to_str()).unwrap_or("unknown");
+
+            let results = chunks
+                .iter()
+                .enumerate()
+                .map(|(i, chunk)| {
+                    let mut metadata = serde_json::Map::new();
+                    metadata.insert("text".to_string(), Value::String(chunk.text.clone()));
+                    metadata.insert("path".to_string(), Value::String(path_str.clone()));
+                    metadata.insert("file_type".to_string(), Value::String(format!("{:?}", file_type)));
+                    metadata.insert("chunk_index".to_string(), Value::Number((i as u64).into()));
+                    metadata.insert("total_chunks".to_string(), Value::Number((chunks.len() as u64).into()));
+                    metadata.insert("language".to_string(), Value::String(language.to_string()));
+                    if let Some(symbol) = &chunk.symbol {
+                        metadata.insert("symbol".to_string(), Value::String(symbol.clone()));
+                    }
+                    metadata.insert(
+                        "start_line".to_string(),
+                        Value::Number((chunk.start_line as u64).into()),
+                    );
+                    metadata.insert("end_line".to_string(), Value::Number((chunk.end_line as u64).into()));
+                    Value::Object(metadata)
+                })
+                .collect();
+
+            Ok(results)
+        },
         FileType::Text | FileType::Markdown | FileType::Code => {
             // For text-based files, chunk the content and create multiple data points
             // Use the configured chunk size and overlap
@@ -112,6 +174,17 @@ pub fn process_file(path: &Path) -> Result<Vec<Value>> {
 
             Ok(results)
         },
+        FileType::Pdf => Ok(document_items(path, file_type, extract_pdf(path)?, None)),
+        FileType::Html => Ok(document_items(path, file_type, extract_html(&content), None)),
+        FileType::Notebook => {
+            let language = notebook_language(&content);
+            Ok(document_items(
+                path,
+                file_type,
+                extract_notebook(&content)?,
+                language.as_deref(),
+            ))
+        },
         FileType::Json => {
             // For JSON files, parse the content
             let json: Value =
@@ -139,6 +212,68 @@ pub fn process_file(path: &Path) -> Result<Vec<Value>> {
     }
 }
 
+/// Chunk the sections of a document into items tagged with their location in the document
+///
+/// # Arguments
+///
+/// * `path` - Path to the document
+/// * `file_type` - Type of the document
+/// * `sections` - The sections extracted from the document
+/// * `language` - Language of the code cells of a notebook, if known
+///
+/// # Returns
+///
+/// A vector of JSON objects, one per chunk, which is empty for documents without text
+fn document_items(
+    path: &Path,
+    file_type: FileType,
+    sections: Vec<DocumentSection>,
+    language: Option<&str>,
+) -> Vec<Value> {
+    let path_str = path.to_string_lossy().to_string();
+    let file_type_str = format!("{:?}", file_type);
+
+    let chunks = sections
+        .iter()
+        .flat_map(|section| {
+            chunk_text(&section.text, None, None)
+                .into_iter()
+                .map(|chunk| (chunk, &section.location))
+        })
+        .collect::<Vec<_>>();
+
+    let total_chunks = chunks.len();
+    chunks
+        .into_iter()
+        .enumerate()
+        .map(|(i, (chunk, location))| {
+            let mut metadata = serde_json::Map::new();
+            metadata.insert("text".to_string(), Value::String(chunk));
+            metadata.insert("path".to_string(), Value::String(path_str.clone()));
+            metadata.insert("file_type".to_string(), Value::String(file_type_str.clone()));
+            metadata.insert("chunk_index".to_string(), Value::Number((i as u64).into()));
+            metadata.insert("total_chunks".to_string(), Value::Number((total_chunks as u64).into()));
+            match location {
+                SectionLocation::Page(page) => {
+                    metadata.insert("page".to_string(), Value::Number((*page as u64).into()));
+                },
+                SectionLocation::Section(Some(section)) => {
+                    metadata.insert("section".to_string(), Value::String(section.clone()));
+                },
+                SectionLocation::Cell { index, cell_type } => {
+                    metadata.insert("cell_index".to_string(), Value::Number((*index as u64).into()));
+                    metadata.insert("cell_type".to_string(), Value::String(cell_type.clone()));
+                    if let (Some(language), "code") = (language, cell_type.as_str()) {
+                        metadata.insert("language".to_string(), Value::String(language.to_string()));
+                    }
+                },
+                SectionLocation::Section(None) => {},
+            }
+            Value::Object(metadata)
+        })
+        .collect()
+}
+
 /// Process a directory and extract content from all files
 ///
 /// # Arguments
@@ -151,28 +286,26 @@ pub fn process_file(path: &Path) -> Result<Vec<Value>> {
 pub fn process_directory(dir_path: &Path) -> Result<Vec<Value>> {
     let mut results = Vec::new();
 
-    for entry in walkdir::WalkDir::new(dir_path)
-        .follow_links(true)
-        .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-    {
-        let path = entry.path();
-
-        // Skip hidden files
-        if path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .is_some_and(|s| s.starts_with('.'))
-        {
-            continue;
-        }
-
+    for path in walk_files(dir_path, &IndexingOptions::default())? {
         // Process the file
-        if let Ok(mut items) = process_file(path) {
+        if let Ok(mut items) = process_file(&path) {
             results.append(&mut items);
         }
     }
 
     Ok(results)
 }
+
+/// Hash the content of a file, to tell whether it changed since it was indexed
+///
+/// # Arguments
+///
+/// * `path` - Path to the file
+///
+/// # Returns
+///
+/// The hex encoded SHA-256 digest of the content of the file
+pub fn hash_file(path: &Path) -> Result<String> {
+    let content = fs::read(path)?;
+    Ok(hex::encode(Sha256::digest(&content)))
+}
@@ -0,0 +1,237 @@
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use ignore::gitignore::{
+    Gitignore,
+    GitignoreBuilder,
+};
+use ignore::overrides::{
+    Override,
+    OverrideBuilder,
+};
+use ignore::{
+    Match,
+    WalkBuilder,
+};
+
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
+use crate::types::IndexingOptions;
+
+/// Walk the files of a directory to index
+///
+/// Files are skipped if they are hidden, ignored by a `.gitignore` or `.ignore` file (even
+/// outside of a git repository), or filtered out by the include and exclude patterns of
+/// `options`. Skipped directories are not descended into, so that e.g. `target/` or
+/// `node_modules/` cost nothing.
+///
+/// # Arguments
+///
+/// * `dir_path` - Path to the directory, or to a single file
+/// * `options` - Include and exclude patterns, relative to `dir_path`
+///
+/// # Returns
+///
+/// An iterator over the paths of the files, or an error if a pattern is invalid
+pub fn walk_files(dir_path: &Path, options: &IndexingOptions) -> Result<impl Iterator<Item = PathBuf> + use<>> {
+    let walker = WalkBuilder::new(dir_path)
+        .follow_links(true)
+        .require_git(false)
+        .overrides(build_overrides(dir_path, options)?)
+        .build();
+
+    Ok(walker
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|file_type| file_type.is_file()))
+        .map(|entry| entry.into_path()))
+}
+
+/// Matcher of the files of a directory to index, for checking paths one at a time (e.g. as they
+/// change) rather than walking the whole directory
+///
+/// Unlike [walk_files], only the `.gitignore` and `.ignore` files at the root of the directory
+/// are taken into account.
+#[derive(Debug)]
+pub struct FileMatcher {
+    root: PathBuf,
+    ignores: Gitignore,
+    overrides: Override,
+}
+
+impl FileMatcher {
+    /// Create a matcher of the files of a directory to index
+    ///
+    /// # Arguments
+    ///
+    /// * `dir_path` - Path to the directory, or to a single file
+    /// * `options` - Include and exclude patterns, relative to `dir_path`
+    ///
+    /// # Returns
+    ///
+    /// A new FileMatcher instance, or an error if a pattern is invalid
+    pub fn new(dir_path: &Path, options: &IndexingOptions) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(dir_path);
+        for ignore_file in [".gitignore", ".ignore"] {
+            let ignore_path = dir_path.join(ignore_file);
+            if ignore_path.is_file() {
+                // Invalid lines are skipped, as they are when walking the directory
+                builder.add(ignore_path);
+            }
+        }
+        let ignores = builder.build().unwrap_or_else(|_| Gitignore::empty());
+
+        Ok(Self {
+            root: dir_path.to_path_buf(),
+            ignores,
+            overrides: build_overrides(dir_path, options)?,
+        })
+    }
+
+    /// Check whether a file would be indexed, which may no longer exist
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the file
+    ///
+    /// # Returns
+    ///
+    /// `true` if the file is in the directory and is not skipped, `false` otherwise
+    pub fn is_match(&self, path: &Path) -> bool {
+        if path == self.root {
+            return true;
+        }
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        // Parent directories are checked first, as skipping them skips everything inside
+        let mut current = self.root.clone();
+        for component in relative.components() {
+            current.push(component);
+            let is_dir = current != path || path.is_dir();
+
+            if component.as_os_str().to_string_lossy().starts_with('.') {
+                return false;
+            }
+            match self.overrides.matched(&current, is_dir) {
+                Match::Ignore(_) => return false,
+                Match::Whitelist(_) => continue,
+                Match::None => {},
+            }
+            if self.ignores.matched(&current, is_dir).is_ignore() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn build_overrides(dir_path: &Path, options: &IndexingOptions) -> Result<Override> {
+    let invalid_pattern = |pattern: &str, err: ignore::Error| {
+        SemanticSearchError::InvalidArgument(format!("Invalid pattern '{}': {}", pattern, err))
+    };
+
+    let mut builder = OverrideBuilder::new(dir_path);
+    for pattern in &options.include {
+        builder.add(pattern).map_err(|e| invalid_pattern(pattern, e))?;
+    }
+    for pattern in &options.exclude {
+        builder
+            .add(&format!("!{}", pattern))
+            .map_err(|e| invalid_pattern(pattern, e))?;
+    }
+
+    builder
+        .build()
+        .map_err(|e| SemanticSearchError::InvalidArgument(format!("Invalid patterns: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    fn create_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in [
+            (".gitignore", "target/\n*.log\n"),
+            (".env", "SECRET=1"),
+            ("README.md", "# Readme"),
+            ("build.log", "Compiling"),
+            ("src/main.rs", "fn main() {}"),
+            ("src/lib.rs", "pub mod a;"),
+            ("target/debug/main.d", "src/main.rs"),
+            ("node_modules/pkg/index.js", "module.exports = {}"),
+            (".git/HEAD", "ref: refs/heads/main"),
+        ] {
+            let path = dir.path().join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn walk(dir: &Path, options: &IndexingOptions) -> Vec<String> {
+        let mut files = walk_files(dir, options)
+            .unwrap()
+            .map(|path| path.strip_prefix(dir).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_walk_files() {
+        let dir = create_tree();
+        let options = IndexingOptions::default();
+        assert_eq!(walk(dir.path(), &options), vec![
+            "README.md",
+            "node_modules/pkg/index.js",
+            "src/lib.rs",
+            "src/main.rs"
+        ]);
+
+        let options = IndexingOptions {
+            exclude: vec!["node_modules".to_string(), "*.md".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(walk(dir.path(), &options), vec!["src/lib.rs", "src/main.rs"]);
+
+        let options = IndexingOptions {
+            include: vec!["*.rs".to_string()],
+            exclude: vec!["lib.rs".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(walk(dir.path(), &options), vec!["src/main.rs"]);
+
+        let options = IndexingOptions {
+            include: vec!["[".to_string()],
+            ..Default::default()
+        };
+        assert!(walk_files(dir.path(), &options).is_err());
+    }
+
+    #[test]
+    fn test_file_matcher() {
+        let dir = create_tree();
+        let options = IndexingOptions {
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+        let matcher = FileMatcher::new(dir.path(), &options).unwrap();
+
+        assert!(matcher.is_match(&dir.path().join("src/main.rs")));
+        assert!(matcher.is_match(&dir.path().join("src/deleted.rs")));
+        assert!(!matcher.is_match(&dir.path().join("target/debug/main.d")));
+        assert!(!matcher.is_match(&dir.path().join("build.log")));
+        assert!(!matcher.is_match(&dir.path().join("node_modules/pkg/index.js")));
+        assert!(!matcher.is_match(&dir.path().join(".git/HEAD")));
+        assert!(!matcher.is_match(Path::new("/elsewhere/main.rs")));
+    }
+}
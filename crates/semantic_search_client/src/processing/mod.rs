@@ -1,11 +1,34 @@
+/// Syntax aware chunking of source code
+pub mod code_chunker;
+/// Text extraction from PDF documents, HTML pages and Jupyter notebooks
+pub mod document_extractor;
 /// File processing utilities for handling different file types and extracting content
 pub mod file_processor;
+/// Walking the files of a directory to index, skipping ignored files
+pub mod file_walker;
 /// Text chunking utilities for breaking down text into manageable pieces for embedding
 pub mod text_chunker;
 
+pub use code_chunker::{
+    CodeChunk,
+    CodeLanguage,
+    chunk_code,
+};
+pub use document_extractor::{
+    DocumentSection,
+    SectionLocation,
+    extract_html,
+    extract_notebook,
+    extract_pdf,
+};
 pub use file_processor::{
     get_file_type,
+    hash_file,
     process_directory,
     process_file,
 };
+pub use file_walker::{
+    FileMatcher,
+    walk_files,
+};
 pub use text_chunker::chunk_text;
@@ -8,6 +8,8 @@ use std::time::SystemTime;
 
 use chrono::{
     DateTime,
+    NaiveDate,
+    NaiveTime,
     Utc,
 };
 use serde::{
@@ -18,6 +20,10 @@ use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use crate::client::SemanticContext;
+use crate::error::{
+    Result,
+    SemanticSearchError,
+};
 
 /// Type alias for context ID
 pub type ContextId = String;
@@ -54,6 +60,10 @@ pub struct KnowledgeContext {
 
     /// Number of items in the context
     pub item_count: usize,
+
+    /// Which files of the source path are indexed, and whether they are watched for changes
+    #[serde(default)]
+    pub indexing: IndexingOptions,
 }
 
 impl KnowledgeContext {
@@ -76,10 +86,30 @@ impl KnowledgeContext {
             source_path,
             persistent,
             item_count,
+            indexing: IndexingOptions::default(),
         }
     }
 }
 
+/// Options controlling which files of a directory are indexed into a context
+///
+/// Files ignored by `.gitignore` and `.ignore` files, as well as hidden files, are always skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexingOptions {
+    /// Glob patterns of the files to index, relative to the indexed directory. All files are
+    /// indexed if empty.
+    #[serde(default)]
+    pub include: Vec<String>,
+
+    /// Glob patterns of the files and directories to skip, relative to the indexed directory
+    #[serde(default)]
+    pub exclude: Vec<String>,
+
+    /// Whether the directory is watched, such that changed files are re-indexed in the background
+    #[serde(default)]
+    pub watch: bool,
+}
+
 /// A data point in the semantic index
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataPoint {
@@ -93,26 +123,160 @@ pub struct DataPoint {
     pub vector: Vec<f32>,
 }
 
+/// How the data points matching a query are retrieved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Nearest neighbors of the query embedding
+    Semantic,
+    /// BM25 ranking of the tokens of the query
+    Lexical,
+    /// Both of the above, fused with reciprocal rank fusion. Queries for identifiers such as type
+    /// names are dominated by exact token matches that embeddings tend to miss.
+    #[default]
+    Hybrid,
+}
+
+/// Restricts a search to some of the contexts, and to the data points of the files matching every
+/// given criterion
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchFilter {
+    /// IDs or names of the contexts to search. All contexts are searched if empty.
+    #[serde(default)]
+    pub contexts: Vec<String>,
+
+    /// Glob pattern the path of the file must match, either relative to the indexed directory
+    /// (e.g. `src/**/*.rs`) or absolute
+    #[serde(default)]
+    pub path: Option<String>,
+
+    /// Type of the file (e.g. `markdown`), or language of the source code (e.g. `rs`)
+    #[serde(default)]
+    pub file_type: Option<String>,
+
+    /// Only search the files modified after this time. Files indexed before their modification
+    /// time was recorded never match.
+    #[serde(default)]
+    pub modified_after: Option<DateTime<Utc>>,
+}
+
+impl SearchFilter {
+    /// Check whether the filter restricts the data points of the searched contexts
+    pub fn filters_data_points(&self) -> bool {
+        self.path.is_some() || self.file_type.is_some() || self.modified_after.is_some()
+    }
+
+    /// Check whether a context is to be searched
+    pub fn includes_context(&self, context: &KnowledgeContext) -> bool {
+        self.contexts.is_empty() || self.contexts.iter().any(|scope| is_in_scope(scope, context))
+    }
+
+    /// Parse a time to filter by, either a date (e.g. `2025-01-31`, at midnight UTC) or an RFC 3339
+    /// timestamp (e.g. `2025-01-31T12:00:00Z`)
+    pub fn parse_time(value: &str) -> Result<DateTime<Utc>> {
+        if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            return Ok(date.and_time(NaiveTime::MIN).and_utc());
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(|_err| {
+                SemanticSearchError::InvalidArgument(format!(
+                    "Invalid time '{}', expected a date such as 2025-01-31 or an RFC 3339 timestamp",
+                    value
+                ))
+            })
+    }
+
+    /// Find a context of the filter that matches none of the given contexts
+    pub fn missing_context(&self, contexts: &[KnowledgeContext]) -> Option<&str> {
+        self.contexts
+            .iter()
+            .find(|scope| !contexts.iter().any(|context| is_in_scope(scope, context)))
+            .map(String::as_str)
+    }
+}
+
+/// Whether a context is identified by its ID or, ignoring case, by its name
+fn is_in_scope(scope: &str, context: &KnowledgeContext) -> bool {
+    scope == context.id || scope.eq_ignore_ascii_case(&context.name)
+}
+
 /// A search result from the semantic index
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
     /// The data point that matched
     pub point: DataPoint,
 
-    /// Distance/similarity score (lower is better)
+    /// Cosine distance between the query and the data point embeddings (lower is better)
     pub distance: f32,
+
+    /// Relevance of the result according to the [SearchMode] used (higher is better): the cosine
+    /// similarity for [SearchMode::Semantic], the BM25 score for [SearchMode::Lexical] and the
+    /// reciprocal rank fusion score for [SearchMode::Hybrid]
+    #[serde(default)]
+    pub score: f32,
+
+    /// BM25 score of the data point, if it contains tokens of the query
+    #[serde(default)]
+    pub lexical_score: Option<f32>,
 }
 
 impl SearchResult {
     /// Create a new search result
     pub fn new(point: DataPoint, distance: f32) -> Self {
-        Self { point, distance }
+        Self {
+            point,
+            distance,
+            score: 1.0 - distance,
+            lexical_score: None,
+        }
     }
 
     /// Get the text content of this result
     pub fn text(&self) -> Option<&str> {
         self.point.payload.get("text").and_then(|v| v.as_str())
     }
+
+    /// Get the path of the file this result was found in
+    pub fn path(&self) -> Option<&str> {
+        self.point.payload.get("path").and_then(|v| v.as_str())
+    }
+
+    /// Get the path of the definition this result belongs to, e.g. `ToolManager::load_tools`, for
+    /// source files chunked along their syntax
+    pub fn symbol(&self) -> Option<&str> {
+        self.point.payload.get("symbol").and_then(|v| v.as_str())
+    }
+
+    /// Get the first and last lines of this result within its file, starting at 1
+    pub fn line_range(&self) -> Option<(usize, usize)> {
+        let line = |key: &str| self.point.payload.get(key).and_then(|v| v.as_u64()).map(|v| v as usize);
+        Some((line("start_line")?, line("end_line")?))
+    }
+
+    /// Get the location of this result as `path:start-end`, or as the path followed by the page,
+    /// cell or section of the document, or just the path if neither is known
+    pub fn location(&self) -> Option<String> {
+        let path = self.path()?;
+        let payload = &self.point.payload;
+        if let Some((start, end)) = self.line_range() {
+            return Some(if start == end {
+                format!("{path}:{start}")
+            } else {
+                format!("{path}:{start}-{end}")
+            });
+        }
+        if let Some(page) = payload.get("page").and_then(|v| v.as_u64()) {
+            return Some(format!("{path}, page {page}"));
+        }
+        if let Some(cell) = payload.get("cell_index").and_then(|v| v.as_u64()) {
+            return Some(format!("{path}, cell {cell}"));
+        }
+        if let Some(section) = payload.get("section").and_then(|v| v.as_str()) {
+            return Some(format!("{path}, {section}"));
+        }
+        Some(path.to_string())
+    }
 }
 
 /// File type for processing
@@ -126,6 +290,12 @@ pub enum FileType {
     Json,
     /// Source code file (programming languages)
     Code,
+    /// PDF document
+    Pdf,
+    /// HTML page
+    Html,
+    /// Jupyter notebook
+    Notebook,
     /// Unknown file type
     Unknown,
 }
@@ -172,6 +342,13 @@ pub enum OperationType {
         /// Path being indexed
         path: String,
     },
+    /// Re-indexing the files of a context that changed since it was last indexed
+    Updating {
+        /// Display name for the operation
+        name: String,
+        /// Path being indexed
+        path: String,
+    },
     /// Clearing all contexts
     Clearing,
 }
@@ -181,6 +358,7 @@ impl OperationType {
     pub fn display_name(&self) -> String {
         match self {
             OperationType::Indexing { name, .. } => format!("Indexing '{}'", name),
+            OperationType::Updating { name, .. } => format!("Updating '{}'", name),
             OperationType::Clearing => "Clearing all".to_string(),
         }
     }
@@ -302,6 +480,12 @@ pub(crate) enum IndexingJob {
         name: String,
         description: String,
         persistent: bool,
+        options: IndexingOptions,
+    },
+    UpdateContext {
+        id: Uuid,
+        cancel: CancellationToken,
+        context_id: ContextId,
     },
     Clear {
         id: Uuid,
@@ -353,3 +537,23 @@ mod progress_tests {
         assert!(progress.calculate_eta().is_none());
     }
 }
+
+#[cfg(test)]
+mod search_filter_tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(
+            SearchFilter::parse_time("2025-01-31").unwrap().to_rfc3339(),
+            "2025-01-31T00:00:00+00:00"
+        );
+        assert_eq!(
+            SearchFilter::parse_time("2025-01-31T12:00:00+02:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2025-01-31T10:00:00+00:00"
+        );
+        assert!(SearchFilter::parse_time("last week").is_err());
+    }
+}
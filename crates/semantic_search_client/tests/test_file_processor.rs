@@ -119,3 +119,172 @@ fn test_process_binary_file() {
     // Clean up
     fs::remove_dir_all(temp_dir).unwrap_or(());
 }
+
+#[test]
+fn test_process_code_file() {
+    // Create a temporary directory for the test
+    let temp_dir = env::temp_dir().join("memory_bank_test_process_code");
+    fs::create_dir_all(&temp_dir).unwrap();
+
+    // Initialize config
+    config::init_config(&temp_dir).unwrap();
+
+    // Create a test Python file
+    let test_file = temp_dir.join("store.py");
+    fs::write(
+        &test_file,
+        "import os\n\n\ndef load(path):\n    return open(path)\n\n\nclass Store:\n    pass\n",
+    )
+    .unwrap();
+
+    // Process the file
+    let items = process_file(&test_file).unwrap();
+
+    // The file is chunked along its definitions, with their symbols and line ranges
+    let chunks = items
+        .iter()
+        .map(|item| {
+            (
+                item.get("symbol").and_then(|v| v.as_str()),
+                item.get("start_line").and_then(|v| v.as_u64()),
+                item.get("end_line").and_then(|v| v.as_u64()),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(chunks, vec![
+        (None, Some(1), Some(1)),
+        (Some("load"), Some(4), Some(5)),
+        (Some("Store"), Some(8), Some(9)),
+    ]);
+    assert_eq!(items[1].get("language").and_then(|v| v.as_str()), Some("py"));
+
+    // Clean up
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
+
+/// Write a PDF document with a page per text
+fn write_pdf(path: &Path, pages: &[&str]) {
+    use lopdf::content::{
+        Content,
+        Operation,
+    };
+    use lopdf::{
+        Document,
+        Object,
+        Stream,
+        dictionary,
+    };
+
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font_id = doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => "Helvetica",
+    });
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! { "F1" => font_id },
+    });
+
+    let page_ids = pages
+        .iter()
+        .map(|text| {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![72.into(), 720.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(*text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            })
+            .into()
+        })
+        .collect::<Vec<Object>>();
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => page_ids.len() as i64,
+            "Kids" => page_ids,
+            "Resources" => resources_id,
+            "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.save(path).unwrap();
+}
+
+#[test]
+fn test_process_document_files() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    config::init_config(temp_dir.path()).unwrap();
+
+    let pdf = temp_dir.path().join("spec.pdf");
+    write_pdf(&pdf, &["The first page of the spec", "", "The third page of the spec"]);
+    let items = process_file(&pdf).unwrap();
+    let pages = items
+        .iter()
+        .map(|item| {
+            (
+                item["page"].as_u64().unwrap(),
+                item["text"].as_str().unwrap().trim().to_string(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(pages, vec![
+        (1, "The first page of the spec".to_string()),
+        (3, "The third page of the spec".to_string())
+    ]);
+    assert_eq!(items[0]["file_type"], "Pdf");
+
+    let html = temp_dir.path().join("guide.html");
+    fs::write(
+        &html,
+        "<html><head><title>Guide</title><script>track()</script></head><body>\
+         <nav><a href=\"/\">Home</a></nav>\
+         <h1>Guide</h1><h2>Installation</h2><p>Run the installer.</p>\
+         <footer>Copyright</footer></body></html>",
+    )
+    .unwrap();
+    let items = process_file(&html).unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["section"], "Guide > Installation");
+    assert_eq!(items[0]["text"], "# Guide ## Installation Run the installer.");
+
+    let notebook = temp_dir.path().join("analysis.ipynb");
+    fs::write(
+        &notebook,
+        r##"{
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Analysis\n", "Load the data"]},
+                {"cell_type": "code", "source": "df = load()", "outputs": []}
+            ],
+            "metadata": {"language_info": {"name": "python"}}
+        }"##,
+    )
+    .unwrap();
+    let items = process_file(&notebook).unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["cell_type"], "markdown");
+    assert_eq!(items[0]["text"], "# Analysis Load the data");
+    assert!(items[0].get("language").is_none());
+    assert_eq!(items[1]["cell_index"], 1);
+    assert_eq!(items[1]["cell_type"], "code");
+    assert_eq!(items[1]["language"], "python");
+
+    let blank = temp_dir.path().join("blank.html");
+    fs::write(&blank, "<html><body><script>track()</script></body></html>").unwrap();
+    assert!(process_file(&blank).unwrap().is_empty());
+}
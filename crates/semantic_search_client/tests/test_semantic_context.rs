@@ -5,7 +5,10 @@ use std::{
 };
 
 use semantic_search_client::client::SemanticContext;
-use semantic_search_client::types::DataPoint;
+use semantic_search_client::types::{
+    DataPoint,
+    SearchMode,
+};
 use serde_json::Value;
 
 #[test]
@@ -98,3 +101,127 @@ fn test_add_data_points() {
     // Clean up
     fs::remove_dir_all(temp_dir).unwrap_or(());
 }
+
+#[test]
+fn test_search_modes() {
+    let temp_dir = env::temp_dir().join("memory_bank_test_search_modes");
+    fs::create_dir_all(&temp_dir).unwrap();
+    let mut semantic_context = SemanticContext::new(temp_dir.join("data.json")).unwrap();
+
+    let texts = [
+        "let builder = ToolManagerBuilder::default();",
+        "The tool manager loads the tools of every MCP server",
+        "Conversations are summarized once the context window is full",
+    ];
+    let data_points = texts
+        .iter()
+        .enumerate()
+        .map(|(id, text)| {
+            // Orthogonal vectors, such that the query vector decides the semantic ranking
+            let mut vector = vec![0.0; 384];
+            vector[id] = 1.0;
+            DataPoint {
+                id,
+                payload: HashMap::from([("text".to_string(), Value::String((*text).to_string()))]),
+                vector,
+            }
+        })
+        .collect();
+    semantic_context.add_data_points(data_points).unwrap();
+
+    // The embedding of the query is closest to the prose about the tool manager, while only the
+    // first data point contains the identifier
+    let query = "ToolManagerBuilder";
+    let mut query_vector = vec![0.0; 384];
+    query_vector[0] = 0.1;
+    query_vector[1] = 0.9;
+
+    let results = semantic_context
+        .search_with_mode(SearchMode::Semantic, query, &query_vector, 1)
+        .unwrap();
+    assert_eq!(results[0].point.id, 1);
+    assert!(results[0].score > 0.9);
+
+    let results = semantic_context
+        .search_with_mode(SearchMode::Lexical, query, &query_vector, 3)
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].point.id, 0);
+    assert_eq!(results[0].lexical_score, Some(results[0].score));
+
+    let results = semantic_context
+        .search_with_mode(SearchMode::Hybrid, query, &query_vector, 2)
+        .unwrap();
+    let ids = results.iter().map(|result| result.point.id).collect::<Vec<_>>();
+    assert_eq!(ids, vec![0, 1]);
+    assert!(results[0].score > results[1].score);
+    assert!(results[0].lexical_score.is_some());
+    assert!(results[1].lexical_score.is_none());
+    // Distances are reported for the results found by the lexical index only as well
+    assert!(results[0].distance > results[1].distance);
+
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
+
+#[test]
+fn test_migrate_json_context() {
+    let temp_dir = env::temp_dir().join("memory_bank_test_migrate_json");
+    fs::remove_dir_all(&temp_dir).unwrap_or(());
+    fs::create_dir_all(&temp_dir).unwrap();
+    let data_path = temp_dir.join("data.json");
+
+    // Data points saved as JSON by previous versions
+    let data_points = (0..20)
+        .map(|id| {
+            let mut vector = vec![0.0; 384];
+            vector[id] = 1.0;
+            DataPoint {
+                id,
+                payload: HashMap::from([("text".to_string(), Value::String(format!("Data point {}", id)))]),
+                vector,
+            }
+        })
+        .collect::<Vec<_>>();
+    fs::write(&data_path, serde_json::to_string(&data_points).unwrap()).unwrap();
+
+    let mut query_vector = vec![0.0; 384];
+    query_vector[7] = 1.0;
+
+    // Loading the context migrates it to the binary format
+    let semantic_context = SemanticContext::new(data_path.clone()).unwrap();
+    assert_eq!(semantic_context.get_data_points().len(), 20);
+    assert!(!data_path.exists());
+    assert!(temp_dir.join("data.bin").exists());
+    let results = semantic_context.search(&query_vector, 1).unwrap();
+    assert_eq!(results[0].point.id, 7);
+
+    // The vector index is persisted along with the data points, and replaced on every save
+    let count_index_files = || {
+        fs::read_dir(&temp_dir)
+            .unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().contains(".hnsw."))
+            .count()
+    };
+    assert_eq!(count_index_files(), 3);
+    semantic_context.save().unwrap();
+    assert_eq!(count_index_files(), 3);
+
+    let loaded_context = SemanticContext::new(data_path).unwrap();
+    for (loaded, saved) in loaded_context
+        .get_data_points()
+        .iter()
+        .zip(semantic_context.get_data_points())
+    {
+        assert_eq!(loaded.id, saved.id);
+        assert_eq!(loaded.payload, saved.payload);
+        assert_eq!(loaded.vector, saved.vector);
+    }
+    let results = loaded_context.search(&query_vector, 1).unwrap();
+    assert_eq!(results[0].point.id, 7);
+    let results = loaded_context
+        .search_with_mode(SearchMode::Lexical, "point", &query_vector, 3)
+        .unwrap();
+    assert_eq!(results.len(), 3);
+
+    fs::remove_dir_all(temp_dir).unwrap_or(());
+}
@@ -53,3 +53,31 @@ fn test_search() {
         assert!(results[0].0 <= 2);
     }
 }
+
+#[test]
+fn test_save_and_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let index = VectorIndex::new(100);
+    for id in 0..10 {
+        let mut vector = vec![0.0; 16];
+        vector[id] = 1.0;
+        index.insert(&vector, id);
+    }
+    index.save(dir.path(), "index").unwrap();
+
+    let loaded = VectorIndex::load(dir.path(), "index").unwrap();
+    assert_eq!(loaded.len(), 10);
+    let mut query = vec![0.0; 16];
+    query[3] = 1.0;
+    assert_eq!(loaded.search(&query, 1, 100)[0].0, 3);
+    drop(loaded);
+
+    // Corrupt files are reported as errors rather than panicking in the reloader
+    let graph_path = dir.path().join("index.hnsw.graph");
+    let mut graph = std::fs::read(&graph_path).unwrap();
+    graph.truncate(graph.len() / 2);
+    std::fs::write(&graph_path, graph).unwrap();
+    assert!(VectorIndex::load(dir.path(), "index").is_err());
+
+    assert!(VectorIndex::load(dir.path(), "missing").is_err());
+}
@@ -12,6 +12,16 @@ const inlineShellCompletionSettings: PrefSection[] = [
         type: "boolean",
         default: true,
       },
+      {
+        id: "inline.localProvider",
+        title: "Suggestions from history",
+        description:
+          "Suggest commands from your shell history, before (primary) or when there is no suggestion from Amazon Q (fallback).",
+        type: "select",
+        default: "fallback",
+        options: ["primary", "fallback", "disabled"],
+        popular: false,
+      },
     ],
   },
 ];